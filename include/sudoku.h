@@ -0,0 +1,57 @@
+/* The C interface of the sudoku crate, built with the `ffi` feature.
+ *
+ * Grids are buffers of 81 bytes in row-major order, 0 for blanks and 1 to
+ * 9 for digits. Kept in sync with src/ffi.rs by hand, in the layout
+ * cbindgen generates with `prefix_with_name` for enums. */
+
+#ifndef SUDOKU_H
+#define SUDOKU_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum SudokuStatus {
+  SudokuStatus_Ok = 0,
+  SudokuStatus_NullPointer = 1,
+  /* A byte above 9 or a digit repeated in a unit. */
+  SudokuStatus_InvalidGrid = 2,
+  SudokuStatus_NoSolution = 3,
+  SudokuStatus_InvalidDifficulty = 4,
+} SudokuStatus;
+
+typedef struct SudokuRating {
+  /* From 0 for easy to 3 for diabolical. */
+  uint32_t tier;
+  float score;
+  /* Index of the hardest technique for sudoku_technique_name, -1 if there
+   * were no steps. */
+  int32_t hardest;
+  uint32_t steps;
+  bool solved;
+  uint32_t clues;
+} SudokuRating;
+
+/* Writes the solution of puzzle to solution, the first if there are
+ * several. */
+SudokuStatus sudoku_solve_grid(const uint8_t *puzzle, uint8_t *solution);
+
+/* Writes a new proper puzzle of a difficulty from 0 for beginner to 5 for
+ * diabolical to puzzle, the same seed always giving the same one. */
+SudokuStatus sudoku_generate_grid(uint32_t difficulty, uint64_t seed, uint8_t *puzzle);
+
+/* Rates puzzle by the techniques a person would use. */
+SudokuStatus sudoku_rate_grid(const uint8_t *puzzle, SudokuRating *rating);
+
+/* The id of a technique like "x-wing", NULL if the index is out of range.
+ * The string is static. */
+const char *sudoku_technique_name(int32_t index);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* SUDOKU_H */
@@ -0,0 +1,2 @@
+//! Links in `sudoku::ffi`, whose functions the bindings call.
+pub use sudoku::ffi::*;
@@ -0,0 +1,169 @@
+"""Python bindings of the sudoku crate over its C interface.
+
+The functions come from the ``ffi`` feature, loaded with ctypes from the
+shared library the ``sudoku-python`` crate in this directory builds:
+
+    cargo build --release -p sudoku-python
+
+Point ``SUDOKU_LIB`` at ``target/release/libsudoku_ffi.so`` (or the
+``.dylib``/``.dll``), or copy it next to this file. The smoke tests next to
+it run with ``pytest python``.
+"""
+
+import ctypes
+import os
+import random
+import sys
+from collections import namedtuple
+
+__all__ = ["Sudoku", "Rating", "SudokuError", "solve", "generate", "rate"]
+
+DIFFICULTIES = ["beginner", "easy", "medium", "hard", "expert", "diabolical"]
+TIERS = ["easy", "medium", "hard", "diabolical"]
+
+_STATUS = {
+    1: "null pointer",
+    2: "invalid grid",
+    3: "no solution",
+    4: "invalid difficulty",
+}
+
+
+class SudokuError(Exception):
+    pass
+
+
+class _Rating(ctypes.Structure):
+    _fields_ = [
+        ("tier", ctypes.c_uint32),
+        ("score", ctypes.c_float),
+        ("hardest", ctypes.c_int32),
+        ("steps", ctypes.c_uint32),
+        ("solved", ctypes.c_bool),
+        ("clues", ctypes.c_uint32),
+    ]
+
+
+Rating = namedtuple("Rating", "tier score hardest steps solved clues")
+
+_Grid = ctypes.c_uint8 * 81
+
+
+def _library_path():
+    path = os.environ.get("SUDOKU_LIB")
+    if path:
+        return path
+    name = {"darwin": "libsudoku_ffi.dylib", "win32": "sudoku_ffi.dll"}.get(
+        sys.platform, "libsudoku_ffi.so"
+    )
+    here = os.path.dirname(os.path.abspath(__file__))
+    for directory in (here, os.path.join(here, "..", "target", "release")):
+        candidate = os.path.join(directory, name)
+        if os.path.exists(candidate):
+            return candidate
+    raise SudokuError("libsudoku_ffi not found, set SUDOKU_LIB")
+
+
+_lib = ctypes.CDLL(_library_path())
+_lib.sudoku_solve_grid.argtypes = [_Grid, _Grid]
+_lib.sudoku_solve_grid.restype = ctypes.c_int
+_lib.sudoku_generate_grid.argtypes = [ctypes.c_uint32, ctypes.c_uint64, _Grid]
+_lib.sudoku_generate_grid.restype = ctypes.c_int
+_lib.sudoku_rate_grid.argtypes = [_Grid, ctypes.POINTER(_Rating)]
+_lib.sudoku_rate_grid.restype = ctypes.c_int
+_lib.sudoku_technique_name.argtypes = [ctypes.c_int32]
+_lib.sudoku_technique_name.restype = ctypes.c_char_p
+
+
+def _check(status):
+    if status != 0:
+        raise SudokuError(_STATUS.get(status, "error %d" % status))
+
+
+class Sudoku:
+    """A grid of 81 cells in row-major order, 0 for blanks."""
+
+    def __init__(self, cells):
+        if isinstance(cells, str):
+            line = "".join(cells.split())
+            if not all(c in ".0123456789" for c in line):
+                raise SudokuError("expected digits and dots, got %r" % line)
+            cells = [0 if c == "." else int(c) for c in line]
+        cells = list(cells)
+        if len(cells) != 81 or not all(
+            isinstance(c, int) and 0 <= c <= 9 for c in cells
+        ):
+            raise SudokuError("expected 81 cells from 0 to 9")
+        self.cells = cells
+
+    def _grid(self):
+        return _Grid(*self.cells)
+
+    def __getitem__(self, position):
+        row, col = position
+        return self.cells[row * 9 + col]
+
+    def __eq__(self, other):
+        return isinstance(other, Sudoku) and self.cells == other.cells
+
+    def __hash__(self):
+        return hash(tuple(self.cells))
+
+    def __str__(self):
+        return "".join(str(c) if c else "." for c in self.cells)
+
+    def __repr__(self):
+        return "Sudoku(%r)" % str(self)
+
+    @property
+    def clues(self):
+        return sum(1 for c in self.cells if c)
+
+    def solve(self):
+        """The solution, the first if there are several, or None."""
+        solution = _Grid()
+        status = _lib.sudoku_solve_grid(self._grid(), solution)
+        if status == 3:
+            return None
+        _check(status)
+        return Sudoku(solution)
+
+    def rate(self):
+        """The difficulty by the techniques a person would use."""
+        rating = _Rating()
+        _check(_lib.sudoku_rate_grid(self._grid(), ctypes.byref(rating)))
+        hardest = None
+        if rating.hardest >= 0:
+            hardest = _lib.sudoku_technique_name(rating.hardest).decode()
+        return Rating(
+            TIERS[rating.tier],
+            rating.score,
+            hardest,
+            rating.steps,
+            rating.solved,
+            rating.clues,
+        )
+
+
+def _sudoku(puzzle):
+    return puzzle if isinstance(puzzle, Sudoku) else Sudoku(puzzle)
+
+
+def solve(puzzle):
+    """Solves a `Sudoku`, a line of 81 cells or a list of them."""
+    return _sudoku(puzzle).solve()
+
+
+def rate(puzzle):
+    return _sudoku(puzzle).rate()
+
+
+def generate(difficulty="medium", seed=None):
+    """A new proper puzzle, the same seed always giving the same one."""
+    if difficulty not in DIFFICULTIES:
+        raise SudokuError("unknown difficulty %r" % difficulty)
+    if seed is None:
+        seed = random.getrandbits(64)
+    puzzle = _Grid()
+    _check(_lib.sudoku_generate_grid(DIFFICULTIES.index(difficulty), seed, puzzle))
+    return Sudoku(puzzle)
@@ -0,0 +1,49 @@
+"""Smoke tests of the bindings, run with ``pytest python`` after building
+the library as described in ``sudoku.py``."""
+
+import pytest
+
+import sudoku
+
+PUZZLE = (
+    "53..7...."
+    "6..195..."
+    ".98....6."
+    "8...6...3"
+    "4..8.3..1"
+    "7...2...6"
+    ".6....28."
+    "...419..5"
+    "....8..79"
+)
+
+
+def test_solve():
+    solution = sudoku.solve(PUZZLE)
+    assert solution.clues == 81
+    assert str(solution).startswith("534678912")
+    assert sudoku.Sudoku(PUZZLE).solve() == solution
+    assert sudoku.solve(solution.cells) == solution
+
+
+def test_invalid_grid():
+    broken = "55" + PUZZLE[2:]
+    with pytest.raises(sudoku.SudokuError):
+        sudoku.solve(broken)
+
+
+def test_generate_and_rate():
+    puzzle = sudoku.generate("easy", seed=7)
+    assert puzzle == sudoku.generate("easy", seed=7)
+    assert puzzle.solve() is not None
+    rating = sudoku.rate(puzzle)
+    assert rating.tier in sudoku.TIERS
+    assert rating.clues == puzzle.clues
+    with pytest.raises(sudoku.SudokuError):
+        sudoku.generate("impossible")
+
+
+def test_parse_errors():
+    for cells in ["x" * 81, PUZZLE[:80], [0] * 80, ["1"] * 81, [10] * 81]:
+        with pytest.raises(sudoku.SudokuError):
+            sudoku.Sudoku(cells)
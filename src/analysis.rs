@@ -0,0 +1,603 @@
+use crate::solver::{propagate, solutions, solve_unique};
+use crate::{peers, Error, PuzzleSet, Sudoku};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    /// Point symmetry around the center cell.
+    Rotational180,
+    Rotational90,
+    /// Mirrored across the horizontal center line.
+    Horizontal,
+    /// Mirrored across the vertical center line.
+    Vertical,
+    /// Mirrored across the main diagonal.
+    Diagonal,
+    /// Mirrored across the anti diagonal.
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    pub fn all() -> [Symmetry; 6] {
+        [
+            Symmetry::Rotational180,
+            Symmetry::Rotational90,
+            Symmetry::Horizontal,
+            Symmetry::Vertical,
+            Symmetry::Diagonal,
+            Symmetry::AntiDiagonal,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::Rotational180 => "rotational",
+            Symmetry::Rotational90 => "rotational90",
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::Diagonal => "diagonal",
+            Symmetry::AntiDiagonal => "anti-diagonal",
+        }
+    }
+
+    /// Returns the cell that `(x, y)` maps to.
+    pub fn map(&self, x: usize, y: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Rotational180 => (8 - x, 8 - y),
+            Symmetry::Rotational90 => (y, 8 - x),
+            Symmetry::Horizontal => (8 - x, y),
+            Symmetry::Vertical => (x, 8 - y),
+            Symmetry::Diagonal => (y, x),
+            Symmetry::AntiDiagonal => (8 - y, 8 - x),
+        }
+    }
+}
+
+impl std::str::FromStr for Symmetry {
+    type Err = Error;
+
+    /// Parses a symmetry by its `name`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Symmetry::all()
+            .iter()
+            .copied()
+            .find(|symmetry| symmetry.name() == s)
+            .ok_or_else(|| Error::UnknownSymmetry(s.into()))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymmetryReport {
+    pub symmetries: Vec<Symmetry>,
+}
+
+impl SymmetryReport {
+    pub fn has(&self, symmetry: Symmetry) -> bool {
+        self.symmetries.contains(&symmetry)
+    }
+
+    pub fn is_asymmetric(&self) -> bool {
+        self.symmetries.is_empty()
+    }
+
+    /// Whether the pattern has all symmetries of the square, the rotations
+    /// and the four mirrors.
+    pub fn is_dihedral(&self) -> bool {
+        Symmetry::all().iter().all(|&symmetry| self.has(symmetry))
+    }
+}
+
+impl Sudoku {
+    /// The strongest symmetry of the pattern of givens, quarter turns
+    /// before half turns before the mirrors, `None` if it has none. See
+    /// [`detect_symmetry`] for all of them.
+    pub fn symmetry(&self) -> Option<Symmetry> {
+        let report = detect_symmetry(self);
+        [
+            Symmetry::Rotational90,
+            Symmetry::Rotational180,
+            Symmetry::Diagonal,
+            Symmetry::AntiDiagonal,
+            Symmetry::Horizontal,
+            Symmetry::Vertical,
+        ]
+        .iter()
+        .copied()
+        .find(|&symmetry| report.has(symmetry))
+    }
+}
+
+/// Checks which symmetries the pattern of the given cells has.
+pub fn detect_symmetry(sudoku: &Sudoku) -> SymmetryReport {
+    let given = |x: usize, y: usize| sudoku.get(x, y).unwrap().is_final();
+    let symmetries = Symmetry::all()
+        .iter()
+        .copied()
+        .filter(|symmetry| {
+            (0..81).all(|i| {
+                let (x, y) = (i / 9, i % 9);
+                let (mx, my) = symmetry.map(x, y);
+                given(x, y) == given(mx, my)
+            })
+        })
+        .collect();
+    SymmetryReport { symmetries }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClueStats {
+    pub clues: usize,
+    /// How often each digit is given, indexed by `digit - 1`.
+    pub digits: [usize; 9],
+    pub rows: [usize; 9],
+    pub cols: [usize; 9],
+    pub blocks: [usize; 9],
+    pub empty_rows: usize,
+    pub empty_cols: usize,
+    pub empty_blocks: usize,
+}
+
+pub fn clue_stats(sudoku: &Sudoku) -> ClueStats {
+    let mut stats = ClueStats::default();
+    for x in 0..9 {
+        for y in 0..9 {
+            if let Some(value) = sudoku.get(x, y).unwrap().value() {
+                stats.clues += 1;
+                stats.digits[value.0 as usize] += 1;
+                stats.rows[x] += 1;
+                stats.cols[y] += 1;
+                stats.blocks[(x / 3) * 3 + y / 3] += 1;
+            }
+        }
+    }
+    let empty = |counts: &[usize; 9]| counts.iter().filter(|&&n| n == 0).count();
+    stats.empty_rows = empty(&stats.rows);
+    stats.empty_cols = empty(&stats.cols);
+    stats.empty_blocks = empty(&stats.blocks);
+    stats
+}
+
+/// The layout of a puzzle, for filtering pools before grading.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PuzzleStats {
+    pub givens: ClueStats,
+    pub empty: usize,
+    /// Empty cells of each row, column and block, the complement of the
+    /// counts of `givens`.
+    pub empty_per_row: [usize; 9],
+    pub empty_per_col: [usize; 9],
+    pub empty_per_block: [usize; 9],
+    /// Empty cells by how many candidates the givens leave them, from 0 to
+    /// 9, as counted by `heatmap`.
+    pub candidate_counts: [usize; 10],
+}
+
+impl Sudoku {
+    pub fn stats(&self) -> PuzzleStats {
+        let givens = clue_stats(self);
+        let complement = |counts: &[usize; 9]| counts.map(|n| 9 - n);
+        let heatmap = heatmap(self);
+        let mut candidate_counts = [0; 10];
+        for coord in self.iter_empty() {
+            candidate_counts[heatmap[coord.row()][coord.col()] as usize] += 1;
+        }
+        PuzzleStats {
+            empty: 81 - givens.clues,
+            empty_per_row: complement(&givens.rows),
+            empty_per_col: complement(&givens.cols),
+            empty_per_block: complement(&givens.blocks),
+            candidate_counts,
+            givens,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SetClueStats {
+    pub puzzles: usize,
+    pub min_clues: usize,
+    pub max_clues: usize,
+    /// The sum of the statistics of all puzzles.
+    pub total: ClueStats,
+}
+
+impl SetClueStats {
+    pub fn mean_clues(&self) -> f64 {
+        if self.puzzles == 0 {
+            return 0.0;
+        }
+        self.total.clues as f64 / self.puzzles as f64
+    }
+}
+
+pub fn set_clue_stats(set: &PuzzleSet) -> SetClueStats {
+    let mut agg = SetClueStats::default();
+    for sudoku in set {
+        let stats = clue_stats(sudoku);
+        if agg.puzzles == 0 || stats.clues < agg.min_clues {
+            agg.min_clues = stats.clues;
+        }
+        agg.max_clues = agg.max_clues.max(stats.clues);
+        agg.puzzles += 1;
+        let total = &mut agg.total;
+        total.clues += stats.clues;
+        for i in 0..9 {
+            total.digits[i] += stats.digits[i];
+            total.rows[i] += stats.rows[i];
+            total.cols[i] += stats.cols[i];
+            total.blocks[i] += stats.blocks[i];
+        }
+        total.empty_rows += stats.empty_rows;
+        total.empty_cols += stats.empty_cols;
+        total.empty_blocks += stats.empty_blocks;
+    }
+    agg
+}
+
+/// Finds a smallest set of at most `max` cells that, when given, make the
+/// puzzle solvable with singles only. Returns `None` if there is no such set
+/// or the puzzle doesn't have a unique solution.
+pub fn backdoor_size(sudoku: &Sudoku, max: usize) -> Option<Vec<(usize, usize)>> {
+    let solution = solve_unique(sudoku).ok()?;
+    let empty: Vec<_> = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(x, y)| !sudoku.get(x, y).unwrap().is_final())
+        .collect();
+    let mut cells = Vec::new();
+    for size in 0..=max.min(empty.len()) {
+        if find_backdoor(sudoku, &solution, &empty, size, &mut cells) {
+            return Some(cells);
+        }
+    }
+    None
+}
+
+fn find_backdoor(
+    sudoku: &Sudoku,
+    solution: &Sudoku,
+    empty: &[(usize, usize)],
+    size: usize,
+    cells: &mut Vec<(usize, usize)>,
+) -> bool {
+    if size == 0 {
+        let mut sudoku = *sudoku;
+        for &(x, y) in cells.iter() {
+            *sudoku.get_mut(x, y).unwrap() = *solution.get(x, y).unwrap();
+        }
+        return propagate(&mut sudoku) && sudoku.valid();
+    }
+    for (i, &cell) in empty.iter().enumerate() {
+        cells.push(cell);
+        if find_backdoor(sudoku, solution, &empty[i + 1..], size - 1, cells) {
+            return true;
+        }
+        cells.pop();
+    }
+    false
+}
+
+/// Finds unavoidable sets of at most `max_size` cells in a solved grid. Each
+/// set can be rearranged into a different valid grid, so every proper puzzle
+/// with this solution has a clue in each of them. Sets are found by clearing
+/// all cells of two or three digits and comparing the alternative solutions.
+pub fn unavoidable_sets(solution: &Sudoku, max_size: usize) -> Vec<Vec<(usize, usize)>> {
+    let digits = solution.digits();
+    let mut sets: Vec<Vec<(usize, usize)>> = Vec::new();
+    for a in 1..=9 {
+        for b in a + 1..=9 {
+            // `c == b` clears just two digits
+            for c in b..=9 {
+                let mut puzzle = *solution;
+                for (i, &digit) in digits.iter().enumerate() {
+                    if digit == a || digit == b || digit == c {
+                        puzzle.0[i] = Default::default();
+                    }
+                }
+                for other in solutions(&puzzle, 1000) {
+                    let set: Vec<_> = (0..81)
+                        .filter(|&i| other.0[i] != solution.0[i])
+                        .map(|i| (i / 9, i % 9))
+                        .collect();
+                    if !set.is_empty() && set.len() <= max_size && !sets.contains(&set) {
+                        sets.push(set);
+                    }
+                }
+            }
+        }
+    }
+    sets.sort_by_key(|set| set.len());
+    let mut minimal: Vec<Vec<(usize, usize)>> = Vec::new();
+    for set in sets {
+        if !minimal
+            .iter()
+            .any(|m| m.iter().all(|cell| set.contains(cell)))
+        {
+            minimal.push(set);
+        }
+    }
+    minimal
+}
+
+/// Returns the unavoidable sets that the puzzle has no clue in. A puzzle
+/// with unhit sets has more than one solution.
+pub fn unhit_sets<'a>(
+    puzzle: &Sudoku,
+    sets: &'a [Vec<(usize, usize)>],
+) -> Vec<&'a Vec<(usize, usize)>> {
+    sets.iter()
+        .filter(|set| {
+            set.iter()
+                .all(|&(x, y)| !puzzle.get(x, y).unwrap().is_final())
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BandKind {
+    /// The minirows of the first box reappear as minirows of the other boxes.
+    Pure,
+    Mixed,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridStats {
+    /// Validity preserving transformations, including the identity, that map
+    /// the grid onto itself after relabeling the digits.
+    pub automorphisms: usize,
+    /// Distinct digit permutations among the automorphisms.
+    pub digit_automorphisms: usize,
+    pub bands: [BandKind; 3],
+    pub stacks: [BandKind; 3],
+}
+
+/// Statistics of a complete grid, `None` if the grid isn't solved.
+pub fn grid_stats(sudoku: &Sudoku) -> Option<GridStats> {
+    if !sudoku.valid() {
+        return None;
+    }
+    let digits = sudoku.digits();
+    let mut transposed = [0; 81];
+    for (i, digit) in transposed.iter_mut().enumerate() {
+        *digit = digits[(i % 9) * 9 + i / 9];
+    }
+    let mut automorphisms = 0;
+    let mut relabelings: Vec<[u8; 10]> = Vec::new();
+    for grid in [digits, transposed].iter() {
+        for cols in column_permutations() {
+            for first in 0..9 {
+                let mut labels = [0; 10];
+                for j in 0..9 {
+                    labels[grid[first * 9 + cols[j]] as usize] = digits[j];
+                }
+                if maps_onto(grid, &digits, &cols, &labels, first) {
+                    automorphisms += 1;
+                    if !relabelings.contains(&labels) {
+                        relabelings.push(labels);
+                    }
+                }
+            }
+        }
+    }
+    let kind = |grid: &[u8; 81], band: usize| {
+        let row = band * 3;
+        let top = &grid[row * 9..row * 9 + 3];
+        let middle = &grid[(row + 1) * 9 + 3..(row + 1) * 9 + 6];
+        match top.iter().filter(|d| middle.contains(d)).count() {
+            0 | 3 => BandKind::Pure,
+            _ => BandKind::Mixed,
+        }
+    };
+    Some(GridStats {
+        automorphisms,
+        digit_automorphisms: relabelings.len(),
+        bands: [kind(&digits, 0), kind(&digits, 1), kind(&digits, 2)],
+        stacks: [
+            kind(&transposed, 0),
+            kind(&transposed, 1),
+            kind(&transposed, 2),
+        ],
+    })
+}
+
+fn column_permutations() -> impl Iterator<Item = [usize; 9]> {
+    const PERMS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    (0..6 * 6 * 6 * 6).map(|n| {
+        let stacks = PERMS[n % 6];
+        let mut cols = [0; 9];
+        for (i, &stack) in stacks.iter().enumerate() {
+            let perm = PERMS[(n / 6usize.pow(i as u32 + 1)) % 6];
+            for (j, col) in perm.iter().enumerate() {
+                cols[i * 3 + j] = stack * 3 + col;
+            }
+        }
+        cols
+    })
+}
+
+/// Checks whether some row permutation, starting with source row `first`,
+/// maps the column permuted and relabeled `grid` onto `target`.
+fn maps_onto(
+    grid: &[u8; 81],
+    target: &[u8; 81],
+    cols: &[usize; 9],
+    labels: &[u8; 10],
+    first: usize,
+) -> bool {
+    let mut rows = [first; 9];
+    for (k, row) in rows.iter_mut().enumerate().skip(1) {
+        let found = (0..9)
+            .find(|&r| (0..9).all(|j| labels[grid[r * 9 + cols[j]] as usize] == target[k * 9 + j]));
+        match found {
+            Some(r) => *row = r,
+            None => return false,
+        }
+    }
+    rows.chunks(3)
+        .all(|band| band.iter().all(|r| r / 3 == band[0] / 3))
+}
+
+/// Number of candidates left by the peers of each empty cell, `0` for cells
+/// with a value.
+pub fn heatmap(sudoku: &Sudoku) -> [[u8; 9]; 9] {
+    let mut heatmap = [[0; 9]; 9];
+    for coord in sudoku.iter_empty() {
+        let (x, y) = coord.into();
+        let mut cell = *sudoku.get(x, y).unwrap();
+        for (px, py) in peers(x, y) {
+            if let Some(value) = sudoku.get(px, py).unwrap().value() {
+                cell.0 &= !value.mask();
+            }
+        }
+        heatmap[x][y] = cell.len() as u8;
+    }
+    heatmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle() -> Sudoku {
+        "53..7....\n\
+         6..195...\n\
+         .98....6.\n\
+         8...6...3\n\
+         4..8.3..1\n\
+         7...2...6\n\
+         .6....28.\n\
+         ...419..5\n\
+         ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_detect_symmetry() {
+        let sudoku = puzzle();
+        let report = detect_symmetry(&sudoku);
+        assert_eq!(report.symmetries, [Symmetry::Rotational180]);
+
+        let report = detect_symmetry(&Sudoku::new());
+        assert_eq!(report.symmetries.len(), 6);
+        assert!(report.is_dihedral());
+        assert_eq!(sudoku.symmetry(), Some(Symmetry::Rotational180));
+        assert_eq!(Sudoku::new().symmetry(), Some(Symmetry::Rotational90));
+
+        let mut sudoku = Sudoku::new();
+        sudoku
+            .get_mut(0, 1)
+            .unwrap()
+            .set(crate::Value::new(1).unwrap());
+        assert!(detect_symmetry(&sudoku).is_asymmetric());
+        assert_eq!(sudoku.symmetry(), None);
+
+        for symmetry in Symmetry::all() {
+            assert_eq!(symmetry.name().parse::<Symmetry>().unwrap(), symmetry);
+        }
+        assert!("spiral".parse::<Symmetry>().is_err());
+    }
+
+    #[test]
+    fn test_clue_stats() {
+        let stats = clue_stats(&puzzle());
+        assert_eq!(stats.clues, 30);
+        assert_eq!(stats.digits, [3, 2, 3, 2, 3, 5, 3, 5, 4]);
+        assert_eq!(stats.rows, [3, 4, 3, 3, 4, 3, 3, 4, 3]);
+        assert_eq!(stats.blocks[0], 5);
+        assert_eq!(stats.empty_rows, 0);
+
+        let set: PuzzleSet = vec![puzzle(), Sudoku::new()].into_iter().collect();
+        let agg = set_clue_stats(&set);
+        assert_eq!(agg.puzzles, 2);
+        assert_eq!(agg.min_clues, 0);
+        assert_eq!(agg.max_clues, 30);
+        assert_eq!(agg.total.empty_rows, 9);
+        assert_eq!(agg.mean_clues(), 15.0);
+    }
+
+    #[test]
+    fn test_puzzle_stats() {
+        let stats = puzzle().stats();
+        assert_eq!(stats.givens, clue_stats(&puzzle()));
+        assert_eq!(stats.empty, 51);
+        assert_eq!(stats.empty_per_row, [6, 5, 6, 6, 5, 6, 6, 5, 6]);
+        assert_eq!(stats.empty_per_block[0], 4);
+        assert_eq!(stats.candidate_counts.iter().sum::<usize>(), 51);
+        assert_eq!(stats.candidate_counts[0], 0);
+
+        let empty = Sudoku::new().stats();
+        assert_eq!(empty.empty_per_col, [9; 9]);
+        assert_eq!(empty.candidate_counts[9], 81);
+    }
+
+    #[test]
+    fn test_backdoor_size() {
+        assert_eq!(backdoor_size(&puzzle(), 2), Some(vec![]));
+        let hard: Sudoku = "5..6..9.2\n\
+             ...1.53.8\n\
+             ......5..\n\
+             8....1.2.\n\
+             .....3...\n\
+             .1.92.8..\n\
+             .6.5....4\n\
+             28.......\n\
+             3.5....7."
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        assert_eq!(backdoor_size(&hard, 2).unwrap().len(), 1);
+        assert_eq!(backdoor_size(&Sudoku::new(), 1), None);
+    }
+
+    #[test]
+    fn test_unavoidable_sets() {
+        let solution = crate::solve(&puzzle()).unwrap();
+        let sets = unavoidable_sets(&solution, 6);
+        assert!(!sets.is_empty());
+        for set in &sets {
+            assert!(set.len() >= 4 && set.len() <= 6);
+        }
+        assert!(unhit_sets(&puzzle(), &sets).is_empty());
+        assert_eq!(unhit_sets(&Sudoku::new(), &sets).len(), sets.len());
+    }
+
+    #[test]
+    fn test_grid_stats() {
+        assert_eq!(grid_stats(&puzzle()), None);
+        let solution = crate::solve(&puzzle()).unwrap();
+        let stats = grid_stats(&solution).unwrap();
+        assert_eq!(stats.automorphisms, 1);
+        assert_eq!(stats.digit_automorphisms, 1);
+
+        let pattern: Sudoku = "123456789\n\
+             456789123\n\
+             789123456\n\
+             234567891\n\
+             567891234\n\
+             891234567\n\
+             345678912\n\
+             678912345\n\
+             912345678"
+            .parse()
+            .unwrap();
+        let stats = grid_stats(&pattern).unwrap();
+        assert!(stats.automorphisms > 1);
+        assert_eq!(stats.bands, [BandKind::Pure; 3]);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let map = heatmap(&puzzle());
+        assert_eq!(map[0][0], 0);
+        // only 1, 2 and 4 remain for r1c3
+        assert_eq!(map[0][2], 3);
+        assert_eq!(heatmap(&Sudoku::new()), [[9; 9]; 9]);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{Cell, Coord, Sudoku};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Annotation {
+    /// Index into an application defined palette.
+    pub color: Option<u8>,
+    pub note: Option<String>,
+    pub highlighted: bool,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A grid with an annotation for every cell.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnnotatedGrid {
+    pub grid: Sudoku,
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GridChange {
+    Cell {
+        coord: Coord,
+        old: Cell,
+        new: Cell,
+    },
+    Annotation {
+        coord: Coord,
+        old: Annotation,
+        new: Annotation,
+    },
+}
+
+impl AnnotatedGrid {
+    pub fn new(grid: Sudoku) -> Self {
+        Self {
+            grid,
+            annotations: vec![Annotation::default(); 81],
+        }
+    }
+
+    pub fn annotation(&self, coord: Coord) -> &Annotation {
+        &self.annotations[coord.index()]
+    }
+
+    pub fn annotation_mut(&mut self, coord: Coord) -> &mut Annotation {
+        &mut self.annotations[coord.index()]
+    }
+
+    /// Iterates over the cells carrying a non empty annotation.
+    pub fn annotated(&self) -> impl Iterator<Item = (Coord, &Annotation)> {
+        self.annotations
+            .iter()
+            .enumerate()
+            .filter(|(_, annotation)| !annotation.is_empty())
+            .map(|(i, annotation)| (Coord::from_index(i), annotation))
+    }
+
+    pub fn clear_annotations(&mut self) {
+        for annotation in &mut self.annotations {
+            *annotation = Annotation::default();
+        }
+    }
+
+    /// Lists the changes turning `self` into `other`, cells before
+    /// annotations in row-major order.
+    pub fn diff(&self, other: &AnnotatedGrid) -> Vec<GridChange> {
+        let mut changes = Vec::new();
+        for i in 0..81 {
+            let (old, new) = (self.grid.0[i], other.grid.0[i]);
+            if old != new {
+                let coord = Coord::from_index(i);
+                changes.push(GridChange::Cell { coord, old, new });
+            }
+        }
+        for (i, (old, new)) in self.annotations.iter().zip(&other.annotations).enumerate() {
+            if old != new {
+                changes.push(GridChange::Annotation {
+                    coord: Coord::from_index(i),
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+        changes
+    }
+}
+
+impl From<Sudoku> for AnnotatedGrid {
+    fn from(grid: Sudoku) -> Self {
+        Self::new(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    #[test]
+    fn test_annotated_grid_diff() {
+        let grid = AnnotatedGrid::new(Sudoku::new());
+        let mut other = grid.clone();
+        let coord = Coord::new(2, 3);
+        other.annotation_mut(coord).color = Some(1);
+        other
+            .grid
+            .get_mut(0, 0)
+            .unwrap()
+            .set(Value::new(4).unwrap());
+        assert_eq!(other.annotated().count(), 1);
+
+        let changes = grid.diff(&other);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(changes[0], GridChange::Cell { coord, .. } if coord == Coord::new(0, 0)));
+        match &changes[1] {
+            GridChange::Annotation { coord: c, old, new } => {
+                assert_eq!(*c, coord);
+                assert!(old.is_empty());
+                assert_eq!(new.color, Some(1));
+            }
+            change => panic!("unexpected {:?}", change),
+        }
+        other.clear_annotations();
+        assert_eq!(grid.diff(&other).len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_annotated_grid_serde() {
+        let mut grid = AnnotatedGrid::new(Sudoku::new());
+        grid.annotation_mut(Coord::new(8, 8)).note = Some("x".into());
+        grid.grid.get_mut(1, 1).unwrap().set(Value::new(9).unwrap());
+        let json = serde_json::to_string(&grid).unwrap();
+        let parsed: AnnotatedGrid = serde_json::from_str(&json).unwrap();
+        assert!(grid.diff(&parsed).is_empty());
+    }
+}
@@ -0,0 +1,345 @@
+//! A configured solver choosing between the backends, built once and
+//! reused for many puzzles.
+use crate::logic::{LogicState, LogicalSolver};
+use crate::solver::{search_with_events, FRAME_BYTES};
+use crate::verified::SolvedSudoku;
+use crate::{Heuristic, SolveEvent, SolveOptions, SolveOutcome, SolveStats, SolverConfig, Sudoku};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How a [`Solver`] searches.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Backend {
+    /// Propagation and guessing, see `SolverConfig`.
+    Backtracking,
+    /// Dancing links over the exact cover problem, see `solve_dlx`.
+    Dlx,
+    /// The embedded DPLL solver, see `solve_sat`.
+    #[cfg(feature = "sat")]
+    Sat,
+    /// The techniques of `LogicalSolver` alone. Gives up with
+    /// `SolveOutcome::Aborted` when they run out instead of guessing.
+    Logic,
+}
+
+impl Backend {
+    /// The backends compiled in.
+    pub fn all() -> Vec<Backend> {
+        vec![
+            Backend::Backtracking,
+            Backend::Dlx,
+            #[cfg(feature = "sat")]
+            Backend::Sat,
+            Backend::Logic,
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SolverBuilder {
+    backend: Backend,
+    config: SolverConfig,
+    options: SolveOptions,
+    stats: bool,
+    trace: bool,
+}
+
+impl SolverBuilder {
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// The cell to guess at, for backtracking.
+    pub fn heuristic(mut self, heuristic: Heuristic) -> Self {
+        self.config.heuristic = heuristic;
+        self
+    }
+
+    /// Limits of the search, honoured by every backend, see `SolveOptions`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    pub fn max_nodes(mut self, max_nodes: u64) -> Self {
+        self.options.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.options.cancel = cancel;
+        self
+    }
+
+    /// Counts the work of a backtracking search in `SolveReport::stats`.
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Records the steps in `SolveReport::trace`. Backtracking then
+    /// searches like `solve_with_events`, applying the techniques before
+    /// each guess, which is much slower.
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    pub fn build(self) -> Solver {
+        Solver {
+            backend: self.backend,
+            config: self.config,
+            options: self.options,
+            stats: self.stats,
+            trace: self.trace,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Solver {
+    backend: Backend,
+    config: SolverConfig,
+    options: SolveOptions,
+    stats: bool,
+    trace: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct SolveReport {
+    pub outcome: SolveOutcome,
+    /// Only for backtracking, when asked for.
+    pub stats: Option<SolveStats>,
+    /// Only for backtracking and logic, when asked for.
+    pub trace: Option<Vec<SolveEvent>>,
+}
+
+impl Solver {
+    /// Backtracking with the default heuristic, no limits, no stats and no
+    /// trace, `Solver::default()`.
+    pub fn builder() -> SolverBuilder {
+        SolverBuilder {
+            backend: Backend::Backtracking,
+            config: SolverConfig::default(),
+            options: SolveOptions::default(),
+            stats: false,
+            trace: false,
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn solve(&self, sudoku: &Sudoku) -> SolveReport {
+        let mut report = SolveReport {
+            outcome: SolveOutcome::NoSolution,
+            stats: None,
+            trace: None,
+        };
+        let mut abort = self.options.limits();
+        match self.backend {
+            Backend::Backtracking if self.trace => {
+                let (tx, rx) = std::sync::mpsc::channel();
+                let solver = LogicalSolver::default();
+                if crate::solver::consistent(sudoku) {
+                    let state = LogicState::new(sudoku);
+                    report.outcome = match search_with_events(&solver, state, &tx, &mut abort) {
+                        Ok(Some(solution)) => {
+                            SolveOutcome::Solved(SolvedSudoku::from_solver(solution))
+                        }
+                        Ok(None) => SolveOutcome::NoSolution,
+                        Err(()) => SolveOutcome::Aborted,
+                    };
+                }
+                drop(tx);
+                report.trace = Some(rx.into_iter().collect());
+            }
+            Backend::Backtracking => {
+                let mut stats = SolveStats::default();
+                report.outcome = self.config.solve_limited(sudoku, &self.options, &mut stats);
+                report.stats = Some(stats).filter(|_| self.stats);
+            }
+            Backend::Dlx => report.outcome = crate::dlx::solve_dlx_limited(sudoku, &mut abort),
+            #[cfg(feature = "sat")]
+            Backend::Sat => report.outcome = crate::sat::solve_sat_limited(sudoku, &mut abort),
+            Backend::Logic => {
+                let solver = LogicalSolver::default();
+                let mut state = LogicState::new(sudoku);
+                let mut steps = Vec::new();
+                while !state.is_solved() && !abort() {
+                    match solver.next_step(&state) {
+                        Some(step) => {
+                            state.apply(&step);
+                            steps.push(step);
+                        }
+                        None => break,
+                    }
+                }
+                report.outcome = if state.is_broken() {
+                    SolveOutcome::NoSolution
+                } else if state.is_solved() && state.grid().valid() {
+                    SolveOutcome::Solved(SolvedSudoku::from_solver(*state.grid()))
+                } else {
+                    SolveOutcome::Aborted
+                };
+                if self.trace {
+                    report.trace = Some(steps.into_iter().map(SolveEvent::Step).collect());
+                }
+            }
+        }
+        report
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// How one backend did on a puzzle of `benchmark`.
+#[derive(Clone, Debug)]
+pub struct BackendStats {
+    pub backend: Backend,
+    pub outcome: SolveOutcome,
+    pub elapsed: Duration,
+    /// Propagations of a backtracking search, steps of the logic solver,
+    /// `None` for dancing links and SAT.
+    pub nodes: Option<u64>,
+    /// The stack a backtracking search fills at its deepest, in bytes,
+    /// `None` for the other backends.
+    pub memory: Option<usize>,
+}
+
+/// Solves the puzzle with each backend in turn, with the default settings.
+/// A single run, so time puzzles that take microseconds over several.
+pub fn benchmark(puzzle: &Sudoku, backends: &[Backend]) -> Vec<BackendStats> {
+    backends
+        .iter()
+        .map(|&backend| {
+            let solver = Solver::builder()
+                .backend(backend)
+                .stats(backend == Backend::Backtracking)
+                .trace(backend == Backend::Logic)
+                .build();
+            let start = Instant::now();
+            let report = solver.solve(puzzle);
+            let elapsed = start.elapsed();
+            let (nodes, memory) = match (report.stats, report.trace) {
+                (Some(stats), _) => (Some(stats.nodes), Some((stats.max_depth + 1) * FRAME_BYTES)),
+                (None, Some(trace)) => (Some(trace.len() as u64), None),
+                (None, None) => (None, None),
+            };
+            BackendStats {
+                backend,
+                outcome: report.outcome,
+                elapsed,
+                nodes,
+                memory,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solver_builder() {
+        let corpus = crate::Corpus::top1465_sample();
+        let hard = *corpus.puzzles.iter().next().unwrap();
+        let expected = crate::solve(&hard).unwrap();
+
+        let report = Solver::builder().stats(true).build().solve(&hard);
+        assert!(matches!(report.outcome, SolveOutcome::Solved(s) if s == expected));
+        assert!(report.stats.unwrap().guesses > 0);
+        assert!(report.trace.is_none());
+
+        let limited = Solver::builder()
+            .heuristic(Heuristic::FirstEmpty)
+            .max_nodes(1)
+            .build();
+        assert!(matches!(
+            limited.solve(&hard).outcome,
+            SolveOutcome::Aborted
+        ));
+
+        let traced = Solver::builder().trace(true).build().solve(&hard);
+        assert!(matches!(traced.outcome, SolveOutcome::Solved(s) if s == expected));
+        assert!(!traced.trace.unwrap().is_empty());
+        // the techniques can't start on an empty grid
+        let traced = Solver::builder().trace(true).build().solve(&Sudoku::new());
+        let trace = traced.trace.unwrap();
+        assert!(trace.iter().any(|e| matches!(e, SolveEvent::Guess(_, _))));
+
+        let logic = Solver::builder()
+            .backend(Backend::Logic)
+            .trace(true)
+            .build();
+        assert!(matches!(
+            logic.solve(&Sudoku::new()).outcome,
+            SolveOutcome::Aborted
+        ));
+        let easy = crate::Corpus::seventeen_sample()
+            .puzzles
+            .iter()
+            .last()
+            .copied();
+        let report = logic.solve(&easy.unwrap());
+        assert!(matches!(report.outcome, SolveOutcome::Solved(_)));
+        assert!(!report.trace.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let corpus = crate::Corpus::top1465_sample();
+        let hard = *corpus.puzzles.iter().next().unwrap();
+        let cancel = Arc::new(AtomicBool::new(true));
+        for backend in Backend::all() {
+            for trace in [false, true] {
+                let builder = Solver::builder().backend(backend).trace(trace);
+                let limited = builder.clone().max_nodes(1).build();
+                assert!(matches!(
+                    limited.solve(&hard).outcome,
+                    SolveOutcome::Aborted
+                ));
+                let cancelled = builder.clone().cancel(cancel.clone()).build();
+                assert!(matches!(
+                    cancelled.solve(&hard).outcome,
+                    SolveOutcome::Aborted
+                ));
+                let timed = builder.timeout(Duration::ZERO).build();
+                assert!(matches!(timed.solve(&hard).outcome, SolveOutcome::Aborted));
+            }
+        }
+        let dlx = Solver::builder().backend(Backend::Dlx).max_nodes(1000);
+        assert!(matches!(
+            dlx.build().solve(&hard).outcome,
+            SolveOutcome::Solved(_)
+        ));
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let corpus = crate::Corpus::seventeen_sample();
+        let puzzle = *corpus.puzzles.iter().next().unwrap();
+        let expected = crate::solve(&puzzle).unwrap();
+        let results = benchmark(&puzzle, &Backend::all());
+        assert_eq!(results.len(), Backend::all().len());
+        for result in &results {
+            assert!(matches!(result.outcome, SolveOutcome::Solved(s) if s == expected));
+        }
+        let backtracking = &results[0];
+        assert_eq!(backtracking.backend, Backend::Backtracking);
+        assert!(backtracking.nodes.unwrap() > 0);
+        assert!(backtracking.memory.unwrap() >= FRAME_BYTES);
+        let logic = results.last().unwrap();
+        assert_eq!(logic.backend, Backend::Logic);
+        assert!(logic.nodes.unwrap() > 0 && logic.memory.is_none());
+        assert!(benchmark(&puzzle, &[]).is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::logic::{LogicalSolver, Technique};
+use crate::rating::{se_rating, Bucket, BucketThresholds, TechniqueWeights};
+use crate::Sudoku;
+
+/// Puzzles labeled with the level players give the hardest technique they
+/// need: hidden singles, naked singles, intersections, subsets with the
+/// basic fish and wings, chains, and beyond the solver.
+const LABELED: &str = "\
+8.5..2..3.........3....954....98.7..72..1.8..1.4...9..43.....9.6....3......4.6... beginner
+2...........1...9.1..7.3.5..4.....2..9..52..1.....6.....3...97...4.9.1.8..5...3.. beginner
+..8.....2..67.4...5......6....5..73..3...72..49........5..7.8..6..1..........84.3 easy
+4...52..9.........827.1.6....8..32..2.......6.7...43....5....1..4.......1.....724 easy
+.68.........8.6.1..294....8..1..54.24...6..7..9....5..5...1..2....3.9.....35....1 medium
+.5...7.....4....6..81.96.....56.84..1.....3.....7.2.........63.9.3....52.7...59.. medium
+8...2..........64.5....1........8....3...4.7....1...98.4....3591...67.8...9..3... medium
+..62..59.....8...4...6.5.3...71......8........135..7...38...6...9.4.7.8..5....3.. hard
+.1...2.4.9...3....8.3.7...........6.5..7...3..91.25....74.....9........5...8.9... hard
+1......68.7...13.........4554............98.4.19.7.....6...5.2.3..71.....27.64... hard
+4..35..9..1.7..4..65..9..........17...12....8..5..4..9..6...35....56..47.....7.1. hard
+...6.2...3....4.8..26..1..99.782..3.8..............95......6..71.5.3........7.8.. hard
+1....86.3......2...8..54.....1.7.4..3.......9.......6.89.6....4..5.2.8....24...1. expert
+.19.843......3.5..7..........5...7.92......3..8...3.26....9.....6..42.7.8..1..... expert
+4...3.......6..8..........1....5..9..8....6...7.2........1.27..5.3....4.9........ diabolical
+7.8...3.....2.1...5.........4.....263...8.......1...9..9.6....4....7.5........... diabolical
+.4..1.2.......9.7..1..........43.6..8......5....2.....7.5..8......6..3..9........ diabolical
+";
+
+/// The bundled reference sample [`BucketThresholds::default`] is fitted to.
+pub fn labeled_sample() -> Vec<(Sudoku, Bucket)> {
+    LABELED
+        .lines()
+        .map(|line| {
+            let (puzzle, label) = line.split_once(' ').expect("puzzle and label; qed");
+            let sudoku = puzzle.parse().expect("bundled puzzles are valid; qed");
+            let bucket = Bucket::all()
+                .iter()
+                .copied()
+                .find(|bucket| bucket.name() == label)
+                .expect("bundled labels are bucket names; qed");
+            (sudoku, bucket)
+        })
+        .collect()
+}
+
+/// A sample whose predicted score is far from its known rating.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outlier {
+    /// Index into the samples.
+    pub index: usize,
+    pub expected: f32,
+    pub predicted: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calibration {
+    pub weights: TechniqueWeights,
+    /// Pearson correlation between predicted scores and known ratings.
+    pub correlation: f32,
+    /// Root mean squared error of the predicted scores.
+    pub rmse: f32,
+    /// Samples with a residual of more than two standard deviations.
+    pub outliers: Vec<Outlier>,
+}
+
+/// Evaluates how well `weights` predict the known ratings of `samples`.
+pub fn validate_weights(samples: &[(Sudoku, f32)], weights: &TechniqueWeights) -> Calibration {
+    let solver = LogicalSolver::default();
+    let predicted: Vec<f32> = samples
+        .iter()
+        .map(|(sudoku, _)| weights.score(&solver.solve(sudoku)))
+        .collect();
+    report(samples, weights.clone(), &predicted)
+}
+
+/// Fits one weight per technique to the known ratings of `samples`. Each
+/// technique is weighted with the mean rating of the samples it is the
+/// hardest technique of, pooled so that harder techniques never weigh less
+/// than easier ones. Techniques without samples keep their default weight.
+pub fn calibrate(samples: &[(Sudoku, f32)]) -> Calibration {
+    let solver = LogicalSolver::default();
+    let techniques = Technique::all();
+    // one group per technique and a last one for unsolved puzzles
+    let groups: Vec<usize> = samples
+        .iter()
+        .map(|(sudoku, _)| {
+            let solution = solver.solve(sudoku);
+            if !solution.solved {
+                return techniques.len();
+            }
+            solution
+                .steps
+                .iter()
+                .filter_map(|step| techniques.iter().position(|&t| t == step.technique))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut sums = vec![(0.0, 0); techniques.len() + 1];
+    for (&group, (_, rating)) in groups.iter().zip(samples) {
+        sums[group].0 += rating;
+        sums[group].1 += 1;
+    }
+    // pool adjacent violators
+    let mut blocks: Vec<(f32, usize, Vec<usize>)> = Vec::new();
+    for (group, &(sum, count)) in sums.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        blocks.push((sum, count, vec![group]));
+        while blocks.len() > 1 {
+            let (sum, count, _) = &blocks[blocks.len() - 1];
+            let (prev_sum, prev_count, _) = &blocks[blocks.len() - 2];
+            if prev_sum / *prev_count as f32 <= sum / *count as f32 {
+                break;
+            }
+            let (sum, count, groups) = blocks.pop().unwrap();
+            let prev = blocks.last_mut().unwrap();
+            prev.0 += sum;
+            prev.1 += count;
+            prev.2.extend(groups);
+        }
+    }
+
+    let mut weights = TechniqueWeights::default();
+    for (sum, count, groups) in blocks {
+        let mean = sum / count as f32;
+        for group in groups {
+            match techniques.get(group) {
+                Some(&technique) => weights.set(technique, mean),
+                None => weights.unsolved = mean,
+            }
+        }
+    }
+    let predicted: Vec<f32> = groups
+        .iter()
+        .map(|&group| match techniques.get(group) {
+            Some(&technique) => weights.get(technique),
+            None => weights.unsolved,
+        })
+        .collect();
+    report(samples, weights, &predicted)
+}
+
+/// Fits the bucket bounds to labeled `samples`. Each bound is the Sudoku
+/// Explainer rating that misplaces the fewest samples on either side of
+/// it, the lowest one on a tie. Bounds without samples on both sides keep
+/// their default.
+pub fn calibrate_thresholds(samples: &[(Sudoku, Bucket)]) -> BucketThresholds {
+    let scored: Vec<(f32, Bucket)> = samples
+        .iter()
+        .map(|(sudoku, bucket)| (se_rating(sudoku).er, *bucket))
+        .collect();
+    let mut thresholds = BucketThresholds::default();
+    for (i, upper) in thresholds.0.iter_mut().enumerate() {
+        let below = |bucket: Bucket| bucket <= Bucket::all()[i];
+        if !scored.iter().any(|&(_, b)| below(b)) || scored.iter().all(|&(_, b)| below(b)) {
+            continue;
+        }
+        let misplaced = |cut: f32| {
+            scored
+                .iter()
+                .filter(|&&(score, bucket)| (score <= cut) != below(bucket))
+                .count()
+        };
+        *upper = scored
+            .iter()
+            .map(|&(score, _)| score)
+            .min_by(|&a, &b| misplaced(a).cmp(&misplaced(b)).then(a.total_cmp(&b)))
+            .expect("samples on both sides; qed");
+    }
+    for i in 1..thresholds.0.len() {
+        thresholds.0[i] = thresholds.0[i].max(thresholds.0[i - 1]);
+    }
+    thresholds
+}
+
+fn report(samples: &[(Sudoku, f32)], weights: TechniqueWeights, predicted: &[f32]) -> Calibration {
+    let n = samples.len().max(1) as f32;
+    let expected: Vec<f32> = samples.iter().map(|(_, rating)| *rating).collect();
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / n;
+    let (me, mp) = (mean(&expected), mean(predicted));
+    let mut cov = 0.0;
+    let mut var_e = 0.0;
+    let mut var_p = 0.0;
+    let mut sq = 0.0;
+    for (e, p) in expected.iter().zip(predicted) {
+        cov += (e - me) * (p - mp);
+        var_e += (e - me) * (e - me);
+        var_p += (p - mp) * (p - mp);
+        sq += (p - e) * (p - e);
+    }
+    let correlation = if var_e == 0.0 || var_p == 0.0 {
+        0.0
+    } else {
+        cov / (var_e.sqrt() * var_p.sqrt())
+    };
+    let rmse = (sq / n).sqrt();
+    let outliers = expected
+        .iter()
+        .zip(predicted)
+        .enumerate()
+        .filter(|(_, (e, p))| rmse > 0.0 && (*p - *e).abs() > 2.0 * rmse)
+        .map(|(index, (&expected, &predicted))| Outlier {
+            index,
+            expected,
+            predicted,
+        })
+        .collect();
+    Calibration {
+        weights,
+        correlation,
+        rmse,
+        outliers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibrate() {
+        let easy: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let hard = Sudoku::new();
+        let samples = vec![(easy, 1.0), (easy, 2.0), (hard, 5.0), (hard, 5.0)];
+        let calibration = calibrate(&samples);
+        assert_eq!(calibration.weights.get(Technique::HiddenSingle), 1.5);
+        assert_eq!(calibration.weights.unsolved, 5.0);
+        assert!(calibration.correlation > 0.9);
+        assert!(calibration.outliers.is_empty());
+
+        let default = validate_weights(&samples, &TechniqueWeights::default());
+        assert!(default.rmse > calibration.rmse);
+    }
+
+    #[test]
+    fn test_calibrate_thresholds() {
+        let samples = labeled_sample();
+        assert!(Bucket::all()
+            .iter()
+            .all(|bucket| samples.iter().any(|(_, b)| b == bucket)));
+        assert_eq!(calibrate_thresholds(&samples), BucketThresholds::default());
+
+        // an outlier left misplaced, no samples above easy and a bound
+        // raised to the one below it
+        let easy = samples[0].0;
+        let hard = samples[7].0;
+        let mut samples = vec![
+            (easy, Bucket::Beginner),
+            (easy, Bucket::Easy),
+            (hard, Bucket::Easy),
+            (hard, Bucket::Easy),
+        ];
+        assert_eq!(calibrate_thresholds(&samples).0[..2], [1.5, 2.3]);
+        samples.push((easy, Bucket::Medium));
+        assert_eq!(calibrate_thresholds(&samples).0[..3], [1.5, 3.0, 3.0]);
+    }
+}
@@ -0,0 +1,162 @@
+//! A set of digits as a bitmask.
+use crate::{Coord, Sudoku, Value};
+use core::ops::{BitAnd, BitOr};
+
+const ALL: u16 = 0b1_1111_1111;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CandidateSet(u16);
+
+impl CandidateSet {
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// The digits 1 to 9.
+    pub fn all() -> Self {
+        Self(ALL)
+    }
+
+    /// The set of the digits in a mask as `Value::mask` builds them, bits
+    /// above the ninth are dropped.
+    pub fn from_mask(mask: u16) -> Self {
+        Self(mask & ALL)
+    }
+
+    pub fn mask(&self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(&self, value: Value) -> bool {
+        self.0 & value.mask() != 0
+    }
+
+    pub fn insert(&mut self, value: Value) {
+        self.0 |= value.mask();
+    }
+
+    pub fn remove(&mut self, value: Value) {
+        self.0 &= !value.mask();
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The digits in ascending order.
+    pub fn iter(&self) -> Iter {
+        Iter(self.0)
+    }
+}
+
+impl BitOr for CandidateSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl BitAnd for CandidateSet {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(other)
+    }
+}
+
+impl core::iter::FromIterator<Value> for CandidateSet {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        let mut set = Self::empty();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+impl IntoIterator for CandidateSet {
+    type Item = Value;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+pub struct Iter(u16);
+
+impl Iterator for Iter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        if self.0 == 0 {
+            return None;
+        }
+        let digit = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+        Some(Value(digit))
+    }
+}
+
+impl Sudoku {
+    /// The digits no peer of the empty cell at `(x, y)` has, empty if the
+    /// cell has a value. Panics if `x` or `y` is out of range.
+    pub fn candidates(&self, x: usize, y: usize) -> CandidateSet {
+        let coord = Coord::new(x, y);
+        if self[coord].is_final() {
+            return CandidateSet::empty();
+        }
+        let mut set = CandidateSet::all();
+        for peer in coord.peers() {
+            if let Some(value) = self[peer].value() {
+                set.remove(value);
+            }
+        }
+        set
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn values(digits: &[u8]) -> CandidateSet {
+        digits.iter().map(|&d| Value::new(d).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_candidate_set() {
+        let odd = values(&[1, 3, 5, 7, 9]);
+        let low = values(&[1, 2, 3]);
+        assert_eq!(odd.len(), 5);
+        assert_eq!(odd & low, values(&[1, 3]));
+        assert_eq!((odd | low).len(), 6);
+        let digits: Vec<String> = odd.iter().map(|v| v.to_string()).collect();
+        assert_eq!(digits, ["1", "3", "5", "7", "9"]);
+        assert!(CandidateSet::empty().is_empty());
+        assert_eq!(CandidateSet::all().len(), 9);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let sudoku: Sudoku =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+                .parse()
+                .unwrap();
+        assert_eq!(sudoku.candidates(0, 0), values(&[4, 5]));
+        assert!(sudoku.candidates(0, 2).is_empty());
+    }
+}
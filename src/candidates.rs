@@ -0,0 +1,231 @@
+//! Puzzles given as candidate lists per cell, known as sukaku or pencil
+//! mark puzzles.
+use crate::solver::solutions;
+use crate::{CandidateSet, Cell, Coord, Error, NotedSudoku, Sudoku};
+
+#[derive(Clone, Copy, Debug)]
+pub struct CandidateGrid(Sudoku);
+
+impl CandidateGrid {
+    /// A grid with all candidates in every cell.
+    pub fn new() -> Self {
+        Self(Sudoku::new())
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.0.get(x, y)
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Cell> {
+        self.0.get_mut(x, y)
+    }
+
+    /// Solves the puzzle, only ever placing a digit in a cell that lists it.
+    pub fn solve(&self) -> Option<Sudoku> {
+        self.solutions(1).pop()
+    }
+
+    /// Enumerates up to `limit` solutions.
+    pub fn solutions(&self, limit: usize) -> Vec<Sudoku> {
+        solutions(&self.0, limit)
+    }
+
+    /// The 729 character form, each cell listing the digits 1 to 9 in
+    /// place with a `.` for every candidate it lacks.
+    pub fn to_sukaku(&self) -> String {
+        self.0
+             .0
+            .iter()
+            .flat_map(|cell| {
+                (0..9u8).map(move |d| match cell.0 & 1 << d {
+                    0 => '.',
+                    _ => (b'1' + d) as char,
+                })
+            })
+            .collect()
+    }
+}
+
+impl NotedSudoku {
+    /// Imports a sukaku, placing the cells listing a single candidate and
+    /// noting the candidates of the others.
+    pub fn from_sukaku(s: &str) -> Result<Self, Error> {
+        let grid: CandidateGrid = s.parse()?;
+        let mut sudoku = Sudoku::new();
+        for (cell, candidates) in sudoku.0.iter_mut().zip(grid.0 .0.iter()) {
+            if candidates.is_final() {
+                *cell = *candidates;
+            }
+        }
+        let mut noted = NotedSudoku::new(sudoku);
+        for (i, candidates) in grid.0 .0.iter().enumerate() {
+            noted.set_notes(Coord::from_index(i), CandidateSet::from_mask(candidates.0));
+        }
+        Ok(noted)
+    }
+
+    /// Exports the placed digits and the marks as a 729 character sukaku.
+    /// An empty cell without marks lists no candidates.
+    pub fn to_sukaku(&self) -> String {
+        let mut grid = CandidateGrid::new();
+        for (i, cell) in grid.0 .0.iter_mut().enumerate() {
+            let coord = Coord::from_index(i);
+            cell.0 = match self.sudoku()[coord].is_final() {
+                true => self.sudoku()[coord].0,
+                false => self.notes(coord).mask(),
+            };
+        }
+        grid.to_sukaku()
+    }
+}
+
+impl Default for CandidateGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Sudoku> for CandidateGrid {
+    fn from(sudoku: Sudoku) -> Self {
+        Self(sudoku)
+    }
+}
+
+impl std::str::FromStr for CandidateGrid {
+    type Err = Error;
+
+    /// Parses either 81 whitespace separated candidate lists, or 729
+    /// characters with a digit or `.` for each candidate of each cell.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let masks: Vec<u16> = if tokens.len() == 81 {
+            tokens
+                .iter()
+                .map(|token| token.chars().try_fold(0, |mask, c| Ok(mask | bit(c)?)))
+                .collect::<Result<_, Error>>()?
+        } else {
+            let chars: Vec<char> = tokens.concat().chars().collect();
+            if chars.len() != 729 {
+                return Err(Error::WrongLength {
+                    expected: 729,
+                    found: chars.len(),
+                });
+            }
+            chars
+                .chunks(9)
+                .map(|cell| {
+                    cell.iter().try_fold(0, |mask, &c| match c {
+                        '.' | '0' => Ok(mask),
+                        _ => Ok(mask | bit(c)?),
+                    })
+                })
+                .collect::<Result<_, Error>>()?
+        };
+        let mut grid = Sudoku::new();
+        for (cell, mask) in grid.0.iter_mut().zip(masks) {
+            cell.0 = mask;
+        }
+        Ok(Self(grid))
+    }
+}
+
+fn bit(c: char) -> Result<u16, Error> {
+    match c.to_digit(10) {
+        Some(d @ 1..=9) => Ok(1 << (d - 1)),
+        _ => Err(Error::InvalidSymbol(c)),
+    }
+}
+
+impl std::fmt::Display for CandidateGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for row in self.0 .0.chunks(9) {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| {
+                    let digits: String = cell.values().map(|v| v.to_string()).collect();
+                    format!("{:<9}", digits)
+                })
+                .collect();
+            writeln!(f, "{}", cells.join(" ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sukaku() {
+        let solution: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        // every cell lists its answer and the following digit
+        let mut grid = CandidateGrid::new();
+        for (i, cell) in grid.0 .0.iter_mut().enumerate() {
+            let value = solution.0[i].value().unwrap().0;
+            cell.0 = (1 << value) | (1 << ((value + 1) % 9));
+        }
+        let parsed: CandidateGrid = grid.to_string().parse().unwrap();
+        assert_eq!(parsed.0 .0, grid.0 .0);
+
+        let solutions = grid.solutions(10);
+        assert!(solutions
+            .iter()
+            .any(|s| s.to_string() == solution.to_string()));
+        for s in &solutions {
+            assert!(s.valid());
+            for (i, cell) in s.0.iter().enumerate() {
+                assert!(grid.0 .0[i].contains(cell.value().unwrap()));
+            }
+        }
+
+        let sukaku = grid.to_sukaku();
+        assert_eq!(sukaku.len(), 729);
+        assert_eq!(&sukaku[..9], "....56...");
+        assert_eq!(sukaku.parse::<CandidateGrid>().unwrap().0 .0, grid.0 .0);
+
+        let dense = "1........".repeat(81);
+        assert!(dense.parse::<CandidateGrid>().unwrap().solve().is_none());
+        assert!("12 3".parse::<CandidateGrid>().is_err());
+    }
+
+    #[test]
+    fn test_noted_sukaku() {
+        // the sample puzzle of the notes module, noted and exported
+        let puzzle: Sudoku =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+                .parse()
+                .unwrap();
+        let mut noted = NotedSudoku::new(puzzle);
+        let unmarked = noted.to_sukaku();
+        assert_eq!(unmarked.len(), 729);
+        // an empty cell without marks lists no candidates
+        assert_eq!(&unmarked[..9], ".........");
+        assert_eq!(&unmarked[18..27], "..3......");
+        noted.fill_notes();
+        let sukaku = noted.to_sukaku();
+        assert_eq!(&sukaku[18..27], "..3......");
+        let imported = NotedSudoku::from_sukaku(&sukaku).unwrap();
+        // a single mark reads as a placed digit
+        for i in 0..81 {
+            let coord = Coord::from_index(i);
+            let placed = imported.sudoku()[coord];
+            match noted.notes(coord).len() {
+                1 => assert_eq!(placed.0, noted.notes(coord).mask()),
+                _ => assert_eq!(placed, noted.sudoku()[coord]),
+            }
+        }
+        assert_eq!(imported.to_sukaku(), sukaku);
+        assert!(NotedSudoku::from_sukaku(&sukaku[1..]).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::Sudoku;
+
+/// Computes the minimal lexicographic representative of the puzzle over all
+/// validity preserving transformations: transposition, band and stack
+/// permutations, row and column permutations within bands and stacks and
+/// digit relabeling. Empty cells are treated as `0`.
+pub fn canonical_form(sudoku: &Sudoku) -> Sudoku {
+    let digits = sudoku.digits();
+    let mut transposed = [0; 81];
+    for (i, digit) in transposed.iter_mut().enumerate() {
+        *digit = digits[(i % 9) * 9 + i / 9];
+    }
+    let mut search = Search {
+        grid: digits,
+        cols: [0; 9],
+        rows: [0; 9],
+        cur: [0; 81],
+        best: [u8::MAX; 81],
+    };
+    for &grid in &[digits, transposed] {
+        search.grid = grid;
+        for stacks in PERMS.iter() {
+            for a in PERMS.iter() {
+                for b in PERMS.iter() {
+                    for c in PERMS.iter() {
+                        for (i, &stack) in stacks.iter().enumerate() {
+                            let perm = [a, b, c][i];
+                            for (j, col) in perm.iter().enumerate() {
+                                search.cols[i * 3 + j] = stack * 3 + col;
+                            }
+                        }
+                        search.rows(0, [0; 10], 1);
+                    }
+                }
+            }
+        }
+    }
+    Sudoku::from_digits(&search.best)
+}
+
+impl Sudoku {
+    /// See [`canonical_form`].
+    pub fn canonical_form(&self) -> Sudoku {
+        canonical_form(self)
+    }
+
+    /// Whether the puzzles are the same up to the transformations of
+    /// [`canonical_form`], with empty cells mapped onto empty cells.
+    pub fn is_isomorphic_to(&self, other: &Sudoku) -> bool {
+        self.iter_filled().count() == other.iter_filled().count()
+            && self.canonical_form().digits() == other.canonical_form().digits()
+    }
+}
+
+const PERMS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+struct Search {
+    grid: [u8; 81],
+    cols: [usize; 9],
+    /// Source row of each target row picked so far.
+    rows: [usize; 9],
+    cur: [u8; 81],
+    best: [u8; 81],
+}
+
+impl Search {
+    /// Picks the source row for target row `k`, keeping the rows of a band
+    /// together. Branches are cut as soon as the rows so far are greater
+    /// than those of `best`, which may have been lowered by a sibling.
+    fn rows(&mut self, k: usize, labels: [u8; 10], next: u8) {
+        if k == 9 {
+            if self.cur < self.best {
+                self.best = self.cur;
+            }
+            return;
+        }
+        for row in 0..9 {
+            let used = |r: usize| self.rows[..k].contains(&r);
+            let allowed = if k.is_multiple_of(3) {
+                !self.rows[..k].iter().any(|r| r / 3 == row / 3)
+            } else {
+                !used(row) && self.rows[k - 1] / 3 == row / 3
+            };
+            if !allowed {
+                continue;
+            }
+            let mut labels = labels;
+            let mut next = next;
+            for j in 0..9 {
+                let digit = self.grid[row * 9 + self.cols[j]] as usize;
+                if digit != 0 && labels[digit] == 0 {
+                    labels[digit] = next;
+                    next += 1;
+                }
+                self.cur[k * 9 + j] = labels[digit];
+            }
+            let end = k * 9 + 9;
+            if self.cur[..end] > self.best[..end] {
+                continue;
+            }
+            self.rows[k] = row;
+            self.rows(k + 1, labels, next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_form() {
+        let sudoku: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let canonical = canonical_form(&sudoku);
+        assert_eq!(canonical.digits().iter().filter(|&&d| d != 0).count(), 30);
+
+        let mut transposed = [0; 81];
+        let digits = sudoku.digits();
+        for (i, digit) in transposed.iter_mut().enumerate() {
+            // transpose and swap the first two bands and the digits 1 and 2
+            let (x, y) = (i / 9, i % 9);
+            let x = if x < 6 { (x + 3) % 6 } else { x };
+            *digit = match digits[y * 9 + x] {
+                1 => 2,
+                2 => 1,
+                d => d,
+            };
+        }
+        let other = Sudoku::from_digits(&transposed);
+        assert_eq!(canonical_form(&other).digits()[..], canonical.digits()[..]);
+        assert!(sudoku.is_isomorphic_to(&other));
+        assert!(sudoku.is_isomorphic_to(&sudoku.scrambled(3)));
+
+        let mut fewer = sudoku;
+        *fewer.get_mut(0, 0).unwrap() = crate::Cell::new();
+        assert!(!sudoku.is_isomorphic_to(&fewer));
+        let mut moved = fewer;
+        moved
+            .get_mut(0, 2)
+            .unwrap()
+            .set(crate::Value::new(5).unwrap());
+        assert!(!sudoku.is_isomorphic_to(&moved));
+    }
+
+    #[test]
+    fn test_canonical_solution() {
+        let sudoku: Sudoku =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+                .parse()
+                .unwrap();
+        let canonical = sudoku.canonical_form();
+        assert!(canonical.valid());
+        for seed in 0..8 {
+            let scrambled = sudoku.scrambled(seed);
+            assert_eq!(scrambled.canonical_form().to_line(), canonical.to_line());
+        }
+    }
+
+    #[test]
+    fn test_canonical_tied_rows() {
+        // the first rows of many branches tie with `best`; once one of them
+        // went lower, its later rows were no longer compared and any leaf
+        // replaced `best`, so equivalent puzzles canonicalized differently
+        let sudoku: Sudoku =
+            "4...3.......6..8..........1....5..9..8....6...7.2........1.27..5.3....4.9........"
+                .replace('.', " ")
+                .parse()
+                .unwrap();
+        let canonical = canonical_form(&sudoku);
+        let digits = sudoku.digits();
+        for shift in 1..9 {
+            // swap the first two rows, shift the stacks and relabel the digits
+            let mut other = [0; 81];
+            for (i, digit) in other.iter_mut().enumerate() {
+                let (x, y) = (i / 9, i % 9);
+                let x = match x {
+                    0 => 1,
+                    1 => 0,
+                    x => x,
+                };
+                *digit = match digits[x * 9 + (y + 3 * shift) % 9] {
+                    0 => 0,
+                    d => (d - 1 + shift as u8) % 9 + 1,
+                };
+            }
+            let other = canonical_form(&Sudoku::from_digits(&other));
+            assert_eq!(other.digits()[..], canonical.digits()[..]);
+        }
+        assert_eq!(
+            canonical_form(&canonical).digits()[..],
+            canonical.digits()[..]
+        );
+        assert!(canonical.digits() <= digits);
+    }
+}
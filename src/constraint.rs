@@ -0,0 +1,664 @@
+//! Rules on the digits of a [`Grid`](crate::Grid), so that variants beyond
+//! the built-in ones don't need changes to the crate.
+//!
+//! Constraints see the cells of a grid as row-major digits, `0` for blanks,
+//! and the candidates of the blanks as masks with bit `d` set if digit `d`
+//! is still possible.
+use crate::Error;
+use std::fmt::Debug;
+
+pub trait Constraint: Debug + Send + Sync {
+    /// Whether the digits break no rule. Blanks may still take any digit,
+    /// so a rule on cells some of which are blank only fails if no digits
+    /// for the blanks could satisfy it.
+    fn is_satisfied(&self, cells: &[u8]) -> bool;
+
+    /// Removes candidates of blanks the rule rules out. Only candidates of
+    /// blanks may be touched, a blank left without any is a dead end.
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        let _ = (cells, candidates);
+    }
+}
+
+/// Cells no digit may repeat in, the rule of the rows, columns, boxes and
+/// extra regions of a grid.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Distinct {
+    /// Row-major indices.
+    cells: Vec<usize>,
+}
+
+impl Distinct {
+    /// Cells of a grid `size` cells wide.
+    pub fn new(cells: &[(usize, usize)], size: usize) -> Self {
+        Self::from_indices(cells.iter().map(|&(row, col)| row * size + col).collect())
+    }
+
+    pub(crate) fn from_indices(cells: Vec<usize>) -> Self {
+        Self { cells }
+    }
+}
+
+impl Constraint for Distinct {
+    fn is_satisfied(&self, cells: &[u8]) -> bool {
+        let mut used = 0u64;
+        for &i in &self.cells {
+            let digit = cells[i];
+            if digit != 0 {
+                if used & 1 << digit != 0 {
+                    return false;
+                }
+                used |= 1 << digit;
+            }
+        }
+        true
+    }
+
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        let used = self
+            .cells
+            .iter()
+            .fold(0u64, |mask, &i| mask | 1 << cells[i]);
+        for &i in &self.cells {
+            if cells[i] == 0 {
+                candidates[i] &= !used;
+            }
+        }
+    }
+}
+
+/// The smallest and largest digit a cell can still take, `None` for a
+/// blank without candidates.
+fn bounds(cells: &[u8], candidates: &[u64], i: usize) -> Option<(u32, u32)> {
+    match (cells[i], candidates[i]) {
+        (0, 0) => None,
+        (0, mask) => Some((mask.trailing_zeros(), 63 - mask.leading_zeros())),
+        (digit, _) => Some((digit as u32, digit as u32)),
+    }
+}
+
+/// Whether the row-major indices of a grid `size` cells wide follow each
+/// other a king's move apart, without visiting a cell twice.
+fn is_path(cells: &[usize], size: usize) -> bool {
+    let adjacent = |a: usize, b: usize| {
+        let (dr, dc) = ((a / size).abs_diff(b / size), (a % size).abs_diff(b % size));
+        dr <= 1 && dc <= 1 && a != b
+    };
+    cells.windows(2).all(|pair| adjacent(pair[0], pair[1]))
+        && cells
+            .iter()
+            .enumerate()
+            .all(|(j, a)| !cells[j + 1..].contains(a))
+}
+
+/// Row-major indices of cells of a grid `size` cells wide, failing with
+/// `Error::InvalidShape` if one is outside.
+fn indices(cells: &[(usize, usize)], size: usize) -> Result<Vec<usize>, Error> {
+    cells
+        .iter()
+        .map(|&(row, col)| match row < size && col < size {
+            true => Ok(row * size + col),
+            false => Err(Error::InvalidShape),
+        })
+        .collect()
+}
+
+/// The digits along an arrow sum to the digit in its circle. Digits may
+/// repeat on an arrow unless another rule forbids it.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Arrow {
+    /// Row-major indices, the circle first.
+    cells: Vec<usize>,
+    /// The largest digit.
+    size: u32,
+}
+
+impl Arrow {
+    /// An arrow of a grid `size` cells wide from the circle along the
+    /// path, each cell a king's move from the one before.
+    pub fn new(
+        circle: (usize, usize),
+        path: &[(usize, usize)],
+        size: usize,
+    ) -> Result<Self, Error> {
+        let mut cells = indices(&[circle], size)?;
+        cells.extend(indices(path, size)?);
+        if path.is_empty() || !is_path(&cells, size) {
+            return Err(Error::InvalidShape);
+        }
+        Ok(Self {
+            cells,
+            size: size as u32,
+        })
+    }
+
+    pub fn circle(&self) -> usize {
+        self.cells[0]
+    }
+
+    pub fn path(&self) -> &[usize] {
+        &self.cells[1..]
+    }
+}
+
+impl Constraint for Arrow {
+    fn is_satisfied(&self, cells: &[u8]) -> bool {
+        let digits = || self.path().iter().map(|&i| cells[i] as u32);
+        let sum: u32 = digits().sum();
+        let blanks = digits().filter(|&d| d == 0).count() as u32;
+        match cells[self.circle()] as u32 {
+            // each blank adds at least 1
+            0 => sum + blanks <= self.size,
+            circle if blanks == 0 => sum == circle,
+            circle => sum + blanks <= circle,
+        }
+    }
+
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        let bounds: Option<Vec<(u32, u32)>> = self
+            .cells
+            .iter()
+            .map(|&i| bounds(cells, candidates, i))
+            .collect();
+        let bounds = match bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (circle, path) = (bounds[0], &bounds[1..]);
+        let (lo, hi) = path
+            .iter()
+            .fold((0, 0), |(lo, hi), &(a, b)| (lo + a, hi + b));
+        let keep = |lo: u32, hi: u32| match (lo, hi.min(63)) {
+            (lo, hi) if lo > hi => 0,
+            (lo, hi) => (u64::MAX >> (63 - hi)) & !((1u64 << lo) - 1),
+        };
+        let i = self.circle();
+        if cells[i] == 0 {
+            candidates[i] &= keep(lo, hi);
+        }
+        for (&i, &(a, b)) in self.path().iter().zip(path) {
+            if cells[i] == 0 {
+                // the other cells take between lo - a and hi - b
+                let low = circle.0.saturating_sub(hi - b);
+                let high = circle.1.saturating_sub(lo - a);
+                candidates[i] &= keep(low, high);
+            }
+        }
+    }
+}
+
+/// Row-major indices of a line of at least two cells, each a king's move
+/// from the one before.
+fn line(cells: &[(usize, usize)], size: usize) -> Result<Vec<usize>, Error> {
+    let cells = indices(cells, size)?;
+    if cells.len() < 2 || !is_path(&cells, size) {
+        return Err(Error::InvalidShape);
+    }
+    Ok(cells)
+}
+
+/// The digits of a cell, its own for a filled one.
+fn mask(cells: &[u8], candidates: &[u64], i: usize) -> u64 {
+    match cells[i] {
+        0 => candidates[i],
+        digit => 1 << digit,
+    }
+}
+
+/// A line reading the same from both ends, each digit equal to the one
+/// as far from the other end.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Palindrome {
+    /// Row-major indices, in order along the line.
+    cells: Vec<usize>,
+}
+
+impl Palindrome {
+    /// A line of a grid `size` cells wide, each cell a king's move from
+    /// the one before.
+    pub fn new(line: &[(usize, usize)], size: usize) -> Result<Self, Error> {
+        Ok(Self {
+            cells: self::line(line, size)?,
+        })
+    }
+
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    /// The cells that mirror each other.
+    fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let n = self.cells.len();
+        (0..n / 2).map(move |k| (self.cells[k], self.cells[n - 1 - k]))
+    }
+}
+
+impl Constraint for Palindrome {
+    fn is_satisfied(&self, cells: &[u8]) -> bool {
+        self.pairs()
+            .all(|(a, b)| cells[a] == 0 || cells[b] == 0 || cells[a] == cells[b])
+    }
+
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        for (a, b) in self.pairs() {
+            let both = mask(cells, candidates, a) & mask(cells, candidates, b);
+            for i in [a, b] {
+                if cells[i] == 0 {
+                    candidates[i] &= both;
+                }
+            }
+        }
+    }
+}
+
+/// A line along which neighbouring digits differ by at least some amount,
+/// 5 on the German whispers of a 9x9 grid.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Whisper {
+    /// Row-major indices, in order along the line.
+    cells: Vec<usize>,
+    difference: u32,
+    /// The largest digit.
+    size: u32,
+}
+
+impl Whisper {
+    /// A line of a grid `size` cells wide, each cell a king's move from
+    /// the one before.
+    pub fn new(line: &[(usize, usize)], difference: u32, size: usize) -> Result<Self, Error> {
+        Ok(Self {
+            cells: self::line(line, size)?,
+            difference,
+            size: size as u32,
+        })
+    }
+
+    /// A German whisper, the difference more than half the largest digit.
+    pub fn german(line: &[(usize, usize)], size: usize) -> Result<Self, Error> {
+        Self::new(line, size as u32 / 2 + 1, size)
+    }
+
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    pub fn difference(&self) -> u32 {
+        self.difference
+    }
+
+    /// The digits far enough from one of those of `mask`.
+    fn reach(&self, mask: u64) -> u64 {
+        (1..=self.size)
+            .filter(|&d| mask & 1 << d != 0)
+            .fold(0, |reach, d| {
+                (1..=self.size)
+                    .filter(|&e| d.abs_diff(e) >= self.difference)
+                    .fold(reach, |reach, e| reach | 1 << e)
+            })
+    }
+}
+
+impl Constraint for Whisper {
+    fn is_satisfied(&self, cells: &[u8]) -> bool {
+        let all = ((1u64 << self.size) - 1) << 1;
+        self.cells.windows(2).all(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            match (cells[a], cells[b]) {
+                (0, 0) => true,
+                (0, digit) | (digit, 0) => self.reach(1 << digit) & all != 0,
+                (x, y) => (x as u32).abs_diff(y as u32) >= self.difference,
+            }
+        })
+    }
+
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        let n = self.cells.len();
+        for k in 0..n {
+            let i = self.cells[k];
+            if cells[i] != 0 {
+                continue;
+            }
+            for j in [k.wrapping_sub(1), k + 1] {
+                if let Some(&neighbour) = self.cells.get(j) {
+                    candidates[i] &= self.reach(mask(cells, candidates, neighbour));
+                }
+            }
+        }
+    }
+}
+
+/// The digits strictly between the smallest and the largest digit of a
+/// row or column sum to a clue, the 1 and the 9 of a 9x9 grid.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Sandwich {
+    /// Row-major indices in order along the line.
+    cells: Vec<usize>,
+    sum: u32,
+}
+
+impl Sandwich {
+    /// The clue of a row of a grid `size` cells wide.
+    pub fn row(row: usize, sum: u32, size: usize) -> Self {
+        Self {
+            cells: (0..size).map(|col| row * size + col).collect(),
+            sum,
+        }
+    }
+
+    pub fn col(col: usize, sum: u32, size: usize) -> Self {
+        Self {
+            cells: (0..size).map(|row| row * size + col).collect(),
+            sum,
+        }
+    }
+
+    pub fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    /// The candidates each cell of the line keeps over all placements of
+    /// the crusts that leave the clue reachable, in the order of the line,
+    /// `None` if none do. Filled cells keep their digit.
+    fn allowed(&self, cells: &[u8], candidates: &[u64]) -> Option<Vec<u64>> {
+        let size = self.cells.len() as u32;
+        let (low, high) = (1u64 << 1, 1u64 << size);
+        let inner = (2..size).fold(0, |mask, d| mask | 1u64 << d);
+        let masks: Vec<u64> = self
+            .cells
+            .iter()
+            .map(|&i| match cells[i] {
+                0 => candidates[i],
+                digit => 1 << digit,
+            })
+            .collect();
+        let mut allowed = vec![0; masks.len()];
+        let mut found = false;
+        for a in (0..masks.len()).filter(|&a| masks[a] & low != 0) {
+            for b in (0..masks.len()).filter(|&b| b != a && masks[b] & high != 0) {
+                let gap = a.min(b) + 1..a.max(b);
+                let mut ranges = Vec::with_capacity(gap.len());
+                for j in gap.clone() {
+                    let mask = masks[j] & inner;
+                    if mask == 0 {
+                        break;
+                    }
+                    ranges.push((mask.trailing_zeros(), 63 - mask.leading_zeros()));
+                }
+                if ranges.len() != gap.len() {
+                    continue;
+                }
+                let lo: u32 = ranges.iter().map(|r| r.0).sum();
+                let hi: u32 = ranges.iter().map(|r| r.1).sum();
+                if self.sum < lo || self.sum > hi {
+                    continue;
+                }
+                found = true;
+                for (j, mask) in masks.iter().enumerate() {
+                    allowed[j] |= if j == a {
+                        low
+                    } else if j == b {
+                        high
+                    } else if gap.contains(&j) {
+                        let (x, y) = ranges[j - gap.start];
+                        // the others take between lo - x and hi - y
+                        let from = self.sum.saturating_sub(hi - y).max(x);
+                        let to = (self.sum + x).saturating_sub(lo).min(y);
+                        let range = (from..=to).fold(0, |m, d| m | 1u64 << d);
+                        mask & range
+                    } else {
+                        mask & inner
+                    };
+                }
+            }
+        }
+        Some(allowed).filter(|_| found)
+    }
+}
+
+impl Constraint for Sandwich {
+    fn is_satisfied(&self, cells: &[u8]) -> bool {
+        // the line is a row or column, so blanks can't repeat its digits
+        let size = self.cells.len() as u8;
+        let used = self
+            .cells
+            .iter()
+            .fold(0u64, |mask, &i| mask | 1 << cells[i]);
+        let all = (1..=size).fold(0u64, |mask, d| mask | 1 << d);
+        let blanks = vec![all & !used; cells.len()];
+        self.allowed(cells, &blanks).is_some()
+    }
+
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        let allowed = self.allowed(cells, candidates);
+        for (j, &i) in self.cells.iter().enumerate() {
+            if cells[i] == 0 {
+                // no placement works, a dead end
+                candidates[i] &= allowed.as_ref().map_or(0, |allowed| allowed[j]);
+            }
+        }
+    }
+}
+
+/// Signs between cells, each pair with the smaller digit first. Bounds
+/// carry along chains of signs, so in `a < b < c` the `a` can't take the
+/// two largest digits even while `b` and `c` are blank.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct Inequalities {
+    /// Pairs of row-major indices.
+    pub(crate) pairs: Vec<(usize, usize)>,
+    pub(crate) size: usize,
+}
+
+impl Inequalities {
+    /// Narrows the masks of the cells to the digits the signs leave them,
+    /// `false` once a cell has none left.
+    fn narrow(&self, masks: &mut [u64]) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(a, b) in &self.pairs {
+                if masks[a] == 0 || masks[b] == 0 {
+                    return false;
+                }
+                let below = (1u64 << (63 - masks[b].leading_zeros())) - 1;
+                let above = !((1u64 << (masks[a].trailing_zeros() + 1)) - 1);
+                let (x, y) = (masks[a] & below, masks[b] & above);
+                changed |= (x, y) != (masks[a], masks[b]);
+                masks[a] = x;
+                masks[b] = y;
+            }
+        }
+        self.pairs
+            .iter()
+            .all(|&(a, b)| masks[a] != 0 && masks[b] != 0)
+    }
+}
+
+impl Constraint for Inequalities {
+    fn is_satisfied(&self, cells: &[u8]) -> bool {
+        let all = ((1u64 << self.size) - 1) << 1;
+        let mut masks: Vec<u64> = cells
+            .iter()
+            .map(|&digit| match digit {
+                0 => all,
+                digit => 1 << digit,
+            })
+            .collect();
+        self.narrow(&mut masks)
+    }
+
+    fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+        let mut masks: Vec<u64> = cells
+            .iter()
+            .zip(candidates.iter())
+            .map(|(&digit, &mask)| match digit {
+                0 => mask,
+                digit => 1 << digit,
+            })
+            .collect();
+        let consistent = self.narrow(&mut masks);
+        for &(a, b) in &self.pairs {
+            for i in [a, b] {
+                if cells[i] == 0 {
+                    // a dead end leaves the blank without candidates
+                    candidates[i] = if consistent { masks[i] } else { 0 };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct() {
+        let distinct = Distinct::new(&[(0, 0), (0, 1), (1, 0)], 4);
+        let mut cells = vec![0; 16];
+        cells[0] = 3;
+        assert!(distinct.is_satisfied(&cells));
+        let mut candidates = vec![0b11110; 16];
+        distinct.prune_candidates(&cells, &mut candidates);
+        assert_eq!(
+            &candidates[..5],
+            &[0b11110, 0b10110, 0b11110, 0b11110, 0b10110]
+        );
+        cells[4] = 3;
+        assert!(!distinct.is_satisfied(&cells));
+    }
+
+    #[test]
+    fn test_arrow() {
+        assert!(Arrow::new((0, 0), &[(0, 2)], 9).is_err());
+        assert!(Arrow::new((0, 0), &[], 9).is_err());
+        assert!(Arrow::new((0, 0), &[(1, 1), (0, 0)], 9).is_err());
+        assert!(Arrow::new((8, 8), &[(8, 9)], 9).is_err());
+        let arrow = Arrow::new((0, 0), &[(1, 1), (2, 2)], 9).unwrap();
+        assert_eq!((arrow.circle(), arrow.path()), (0, &[10, 20][..]));
+
+        let mut cells = vec![0; 81];
+        cells[10] = 3;
+        assert!(arrow.is_satisfied(&cells));
+        let mut candidates = vec![0b11_1111_1110; 81];
+        arrow.prune_candidates(&cells, &mut candidates);
+        // the circle is 4 or more, the other cell of the path at most 6
+        assert_eq!(candidates[0], 0b11_1111_0000);
+        assert_eq!(candidates[20], 0b111_1110);
+        cells[0] = 2;
+        assert!(!arrow.is_satisfied(&cells));
+        cells[0] = 5;
+        cells[20] = 2;
+        assert!(arrow.is_satisfied(&cells));
+        cells[20] = 3;
+        assert!(!arrow.is_satisfied(&cells));
+    }
+
+    #[test]
+    fn test_palindrome() {
+        assert!(Palindrome::new(&[(0, 0)], 9).is_err());
+        assert!(Palindrome::new(&[(0, 0), (0, 2)], 9).is_err());
+        let palindrome = Palindrome::new(&[(0, 0), (1, 1), (2, 1), (2, 0)], 9).unwrap();
+        assert_eq!(palindrome.cells(), &[0, 10, 19, 18]);
+
+        let mut cells = vec![0; 81];
+        cells[0] = 4;
+        assert!(palindrome.is_satisfied(&cells));
+        let mut candidates = vec![0b11_1111_1110; 81];
+        candidates[10] = 0b1100;
+        candidates[19] = 0b1010;
+        palindrome.prune_candidates(&cells, &mut candidates);
+        assert_eq!(candidates[18], 0b1_0000);
+        assert_eq!((candidates[10], candidates[19]), (0b1000, 0b1000));
+        cells[18] = 5;
+        assert!(!palindrome.is_satisfied(&cells));
+        cells[18] = 4;
+        assert!(palindrome.is_satisfied(&cells));
+    }
+
+    #[test]
+    fn test_whisper() {
+        assert!(Whisper::german(&[(0, 0), (2, 2)], 9).is_err());
+        let whisper = Whisper::german(&[(0, 0), (0, 1), (0, 2)], 9).unwrap();
+        assert_eq!(whisper.difference(), 5);
+
+        let mut cells = vec![0; 81];
+        cells[1] = 3;
+        assert!(whisper.is_satisfied(&cells));
+        let mut candidates = vec![0b11_1111_1110; 81];
+        whisper.prune_candidates(&cells, &mut candidates);
+        // 8 and 9 are the only digits 5 from a 3
+        assert_eq!(
+            (candidates[0], candidates[2]),
+            (0b11_0000_0000, 0b11_0000_0000)
+        );
+        cells[1] = 5;
+        assert!(!whisper.is_satisfied(&cells));
+        cells[1] = 1;
+        cells[2] = 5;
+        assert!(!whisper.is_satisfied(&cells));
+        cells[2] = 6;
+        assert!(whisper.is_satisfied(&cells));
+    }
+
+    #[test]
+    fn test_sandwich() {
+        let row = Sandwich::row(0, 5, 9);
+        let mut cells = vec![0; 81];
+        let all = vec![0b11_1111_1110; 81];
+        assert!(row.is_satisfied(&cells));
+        // 1 _ _ with room for 2 + 3 only right after it
+        cells[..3].copy_from_slice(&[1, 2, 3]);
+        assert!(row.is_satisfied(&cells));
+        // the rest of the row can't repeat the 1, 2 and 3
+        let mut candidates = all.clone();
+        for mask in &mut candidates[3..9] {
+            *mask &= !0b1110;
+        }
+        row.prune_candidates(&cells, &mut candidates);
+        assert_eq!(candidates[3], 1 << 9);
+        assert_eq!(candidates[4], 0b1_1111_0000);
+        cells[3] = 9;
+        assert!(row.is_satisfied(&cells));
+        cells[3] = 4;
+        assert!(!row.is_satisfied(&cells));
+
+        // no digits between the crusts
+        let col = Sandwich::col(0, 0, 9);
+        let mut cells = vec![0; 81];
+        cells[0] = 1;
+        cells[27] = 9;
+        assert!(!col.is_satisfied(&cells));
+        cells[27] = 0;
+        cells[9] = 9;
+        assert!(col.is_satisfied(&cells));
+        let mut candidates = all;
+        candidates[9] &= !(1 << 9);
+        col.prune_candidates(&[0; 81], &mut candidates);
+        assert_eq!(candidates[0] & 1 << 9, 1 << 9);
+        assert_eq!(candidates[9] & 0b10, 0b10);
+    }
+
+    #[test]
+    fn test_inequalities() {
+        // a chain 0 < 1 < 2 along the top of a 4x4 grid
+        let signs = Inequalities {
+            pairs: vec![(0, 1), (1, 2)],
+            size: 4,
+        };
+        let mut cells = vec![0; 16];
+        assert!(signs.is_satisfied(&cells));
+        let mut candidates = vec![0b11110; 16];
+        signs.prune_candidates(&cells, &mut candidates);
+        assert_eq!(&candidates[..4], &[0b00110, 0b01100, 0b11000, 0b11110]);
+        cells[1] = 2;
+        assert!(signs.is_satisfied(&cells));
+        cells[0] = 2;
+        assert!(!signs.is_satisfied(&cells));
+        cells[0] = 0;
+        cells[2] = 1;
+        assert!(!signs.is_satisfied(&cells));
+        signs.prune_candidates(&cells, &mut candidates);
+        assert_eq!(candidates[0], 0);
+    }
+}
@@ -0,0 +1,211 @@
+//! Benchmark puzzle sets, a small subset of each is bundled with the crate.
+use crate::set::PuzzleSet;
+use crate::{Error, Sudoku, Value};
+use std::io::BufRead;
+use std::path::Path;
+
+/// The first puzzles of Guenter Stertenbrink's top1465 list.
+const TOP1465: &str = "\
+4...3.......6..8..........1....5..9..8....6...7.2........1.27..5.3....4.9........
+7.8...3.....2.1...5.........4.....263...8.......1...9..9.6....4....7.5...........
+7.8...3.....6.1...5.........4.....263...8.......1...9..9.2....4....7.5...........
+3.7.4...........918........4.....7.....16.......25..........38..9....5...2.6.....
+5..7..6....38...........2..62.4............917............35.8.4.....1......9....
+4..7..6....38...........2..62.5............917............43.8.5.....1......9....
+.4..1.2.......9.7..1..........43.6..8......5....2.....7.5..8......6..3..9........
+7.5.....2...4.1...3.........1.6..4..2...5...........9....37.....8....6...9.....8.
+......41.9..3.....3...5.....48..7..........62.1.......6..2....5.7....8......9....
+7.5.....2...4.1...3.........1.6..4..2...5...........9....37.....9....8...8.....6.
+";
+
+/// The first puzzles of Gordon Royle's list of 17 clue puzzles, followed by
+/// the 17 clue puzzles from the start of the top95 list.
+const SEVENTEEN: &str = "\
+000000010400000000020000000000050407008000300001090000300400200050100000000806000
+000000010400000000020000000000050604008000300001090000300400200050100000000807000
+000000012000035000000600070700000300000400800100000000000120000080000040050000600
+000000012003600000000007000410020000000500300700000600280000040000300500000000000
+000000012008030000000000040120500000000004700060000000507000300000620000000100000
+4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......
+52...6.........7.13...........4..8..6......5...........418.........3..2...87.....
+6.....8.3.4.7.................5.4.7.3..2.....1.6.......2.....5.....8.6......1....
+48.3............71.2.......7.5....6....2..8.............1.76...3.....4......5....
+";
+
+/// Properties every puzzle of a corpus is known to have.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Expected {
+    /// The puzzle has exactly one solution.
+    pub unique: bool,
+    /// The exact number of clues.
+    pub clues: Option<usize>,
+}
+
+impl Expected {
+    /// Whether `sudoku` has the expected properties.
+    pub fn matches(&self, sudoku: &Sudoku) -> bool {
+        if let Some(clues) = self.clues {
+            if sudoku.iter_filled().count() != clues {
+                return false;
+            }
+        }
+        !self.unique || sudoku.has_unique_solution()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Corpus {
+    pub name: &'static str,
+    pub expected: Expected,
+    pub puzzles: PuzzleSet,
+}
+
+impl Corpus {
+    /// The bundled subset of top1465, hard puzzles for backtracking solvers.
+    pub fn top1465_sample() -> Self {
+        Self::bundled("top1465", TOP1465, TOP1465_EXPECTED)
+    }
+
+    /// The bundled sample of minimum clue puzzles.
+    pub fn seventeen_sample() -> Self {
+        Self::bundled("17-clue", SEVENTEEN, SEVENTEEN_EXPECTED)
+    }
+
+    /// Loads the full top1465 file.
+    pub fn load_top1465<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::load("top1465", path, TOP1465_EXPECTED)
+    }
+
+    /// Loads a full list of 17 clue puzzles like Gordon Royle's.
+    pub fn load_seventeen<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::load("17-clue", path, SEVENTEEN_EXPECTED)
+    }
+
+    /// Loads a file in the format of `read_lines`.
+    pub fn load<P: AsRef<Path>>(
+        name: &'static str,
+        path: P,
+        expected: Expected,
+    ) -> Result<Self, Error> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(Self {
+            name,
+            expected,
+            puzzles: read_lines(file)?,
+        })
+    }
+
+    fn bundled(name: &'static str, lines: &str, expected: Expected) -> Self {
+        Self {
+            name,
+            expected,
+            puzzles: read_lines(lines.as_bytes()).expect("bundled puzzles are valid; qed"),
+        }
+    }
+
+    /// Indices of the puzzles that don't have the expected properties.
+    pub fn violations(&self) -> Vec<usize> {
+        self.puzzles
+            .iter()
+            .enumerate()
+            .filter(|(_, puzzle)| !self.expected.matches(puzzle))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+const TOP1465_EXPECTED: Expected = Expected {
+    unique: true,
+    clues: None,
+};
+
+const SEVENTEEN_EXPECTED: Expected = Expected {
+    unique: true,
+    clues: Some(17),
+};
+
+/// Reads one puzzle of 81 characters per line, with `.` or `0` for blanks.
+/// Anything after the puzzle separated by whitespace is ignored, as are
+/// empty lines and lines starting with `#`.
+pub fn read_lines<R: BufRead>(reader: R) -> Result<PuzzleSet, Error> {
+    let mut set = PuzzleSet::new();
+    for puzzle in parse_collection(reader) {
+        set.push(puzzle?);
+    }
+    Ok(set)
+}
+
+/// Like [`read_lines`], yielding the puzzles as they are read, so that
+/// large collections don't need to fit in memory. A malformed line yields
+/// an error and reading goes on with the next one.
+pub fn parse_collection<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Sudoku, Error>> {
+    reader.lines().filter_map(|line| {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(err.into())),
+        };
+        let puzzle = line
+            .split_whitespace()
+            .next()
+            .filter(|p| !p.starts_with('#'))?;
+        if puzzle.len() != 81 {
+            return Some(Err(Error::WrongLength {
+                expected: 81,
+                found: puzzle.len(),
+            }));
+        }
+        Some(parse_line(puzzle.as_bytes()))
+    })
+}
+
+pub(crate) fn parse_line(line: &[u8]) -> Result<Sudoku, Error> {
+    let mut sudoku = Sudoku::new();
+    for (cell, &c) in sudoku.0.iter_mut().zip(line) {
+        match c {
+            b'.' | b'0' => {}
+            b'1'..=b'9' => cell.set(Value(c - b'1')),
+            _ => return Err(Error::InvalidSymbol(c as char)),
+        }
+    }
+    Ok(sudoku)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_corpora() {
+        for corpus in [Corpus::top1465_sample(), Corpus::seventeen_sample()] {
+            assert!(!corpus.puzzles.is_empty());
+            assert_eq!(corpus.violations(), Vec::<usize>::new(), "{}", corpus.name);
+        }
+    }
+
+    #[test]
+    fn test_read_lines() {
+        let input = format!("# comment\n\n{} first\n", TOP1465.lines().next().unwrap());
+        assert_eq!(read_lines(input.as_bytes()).unwrap().len(), 1);
+        assert!(read_lines("123\n".as_bytes()).is_err());
+        assert!(read_lines("x".repeat(81).as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_collection() {
+        let input = format!("{}\n\n# comment\n123\n{}\n", TOP1465, SEVENTEEN);
+        let mut puzzles = parse_collection(input.as_bytes());
+        assert_eq!(
+            puzzles.next().unwrap().unwrap().to_line(),
+            TOP1465.lines().next().unwrap()
+        );
+        let results: Vec<_> = puzzles.collect();
+        let errors: Vec<_> = results.iter().filter(|r| r.is_err()).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            Err(Error::WrongLength { found: 3, .. })
+        ));
+        let count = TOP1465.lines().count() + SEVENTEEN.lines().count();
+        assert_eq!(results.len(), count);
+    }
+}
@@ -0,0 +1,633 @@
+//! An indexed puzzle database.
+//!
+//! The file starts with a header: the magic `SUDOKUDB`, a little endian
+//! `u32` format version and a `u32` record count. Version 2 headers add a
+//! `u32` of flags and the CRC-32 of all records in order. They are followed
+//! by fixed size records of
+//!
+//! * `er`, `ep` and `ed` ratings as little endian `f32`,
+//! * a flags byte, bit 0 set if the rating is based on a complete solve,
+//! * the number of clues,
+//! * the 81 digits packed two per byte, high nibble first, `0` for blanks,
+//! * if bit 0 of the header flags is set, the CRC-32 of the above.
+//!
+//! Opening a database only reads the rating and clue count prefix of each
+//! record to build the index, puzzles are read on demand. Queries such as
+//! a random unseen hard puzzle with 26 to 28 clues are answered from the
+//! index, reading only the puzzle picked.
+use crate::checksum::crc32;
+use crate::rating::{RatedPuzzle, SeRating, Tier};
+use crate::{Error, Sudoku};
+use rand::Rng;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"SUDOKUDB";
+const VERSION: u32 = 2;
+const PREFIX_LEN: usize = 14;
+const PAYLOAD_LEN: usize = PREFIX_LEN + 41;
+const FLAG_CHECKSUMS: u32 = 1;
+
+/// How the records of a database are laid out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Layout {
+    version: u32,
+    checksums: bool,
+}
+
+impl Layout {
+    fn new(checksums: bool) -> Self {
+        Self {
+            version: VERSION,
+            checksums,
+        }
+    }
+
+    fn header_len(&self) -> usize {
+        if self.version == 1 {
+            16
+        } else {
+            24
+        }
+    }
+
+    pub(crate) fn record_len(&self) -> usize {
+        PAYLOAD_LEN + if self.checksums { 4 } else { 0 }
+    }
+
+    pub(crate) fn offset(&self, index: usize) -> u64 {
+        (self.header_len() + index * self.record_len()) as u64
+    }
+
+    /// The header of `header_len` bytes, version 1 ones without flags and
+    /// file checksum.
+    fn header(&self, count: u32, file_crc: u32) -> Vec<u8> {
+        let mut header = Vec::with_capacity(self.header_len());
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&self.version.to_le_bytes());
+        header.extend_from_slice(&count.to_le_bytes());
+        if self.version == 1 {
+            return header;
+        }
+        let flags = if self.checksums { FLAG_CHECKSUMS } else { 0 };
+        header.extend_from_slice(&flags.to_le_bytes());
+        header.extend_from_slice(&file_crc.to_le_bytes());
+        header
+    }
+}
+
+/// Parses the first 16 or 24 bytes of a database, returning its layout,
+/// record count and file checksum.
+pub(crate) fn parse_header(bytes: &[u8]) -> Result<(Layout, u32, u32), Error> {
+    if bytes.len() < 16 || &bytes[..8] != MAGIC {
+        return Err(Error::InvalidFormat);
+    }
+    let count = u32_at(bytes, 12);
+    match u32_at(bytes, 8) {
+        1 => Ok((
+            Layout {
+                version: 1,
+                checksums: false,
+            },
+            count,
+            0,
+        )),
+        2 if bytes.len() >= 24 => {
+            let layout = Layout::new(u32_at(bytes, 16) & FLAG_CHECKSUMS != 0);
+            Ok((layout, count, u32_at(bytes, 20)))
+        }
+        _ => Err(Error::InvalidFormat),
+    }
+}
+
+/// Checks the checksum of a record and decodes it.
+pub(crate) fn decode_checked(
+    layout: Layout,
+    index: usize,
+    record: &[u8],
+) -> Result<RatedPuzzle, Error> {
+    if layout.checksums && u32_at(record, PAYLOAD_LEN) != crc32(&record[..PAYLOAD_LEN]) {
+        return Err(Error::CorruptRecord { index });
+    }
+    Ok(decode(record))
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    er: f32,
+    clues: u8,
+}
+
+/// Which puzzles [`PuzzleDb::query`] selects, all of them unless narrowed.
+#[derive(Clone, Debug)]
+pub struct Query<'a> {
+    tier: Option<Tier>,
+    er: (Bound<f32>, Bound<f32>),
+    clues: (Bound<usize>, Bound<usize>),
+    seen: Option<&'a HashSet<usize>>,
+}
+
+impl<'a> Query<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puzzles whose `er` falls in the tier, see [`Tier::from_score`].
+    pub fn tier(mut self, tier: Tier) -> Self {
+        self.tier = Some(tier);
+        self
+    }
+
+    /// Puzzles whose `er` lies in `range`.
+    pub fn difficulty<R: RangeBounds<f32>>(mut self, range: R) -> Self {
+        self.er = (range.start_bound().cloned(), range.end_bound().cloned());
+        self
+    }
+
+    pub fn clues<R: RangeBounds<usize>>(mut self, range: R) -> Self {
+        self.clues = (range.start_bound().cloned(), range.end_bound().cloned());
+        self
+    }
+
+    /// Skips the puzzles at these indices, such as those a player has
+    /// already been served.
+    pub fn unseen(mut self, seen: &'a HashSet<usize>) -> Self {
+        self.seen = Some(seen);
+        self
+    }
+}
+
+impl Default for Query<'_> {
+    fn default() -> Self {
+        Self {
+            tier: None,
+            er: (Bound::Unbounded, Bound::Unbounded),
+            clues: (Bound::Unbounded, Bound::Unbounded),
+            seen: None,
+        }
+    }
+}
+
+pub struct PuzzleDb<S = File> {
+    storage: S,
+    layout: Layout,
+    /// Running checksum of all records.
+    file_crc: u32,
+    entries: Vec<Entry>,
+    /// Record indices sorted by `er`, without those whose `er` is NaN.
+    by_difficulty: Vec<u32>,
+}
+
+impl PuzzleDb<File> {
+    /// Opens the database at `path`, creating it if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        Self::new(file)
+    }
+}
+
+impl<S: Read + Write + Seek> PuzzleDb<S> {
+    /// Reads the index of a database, initializing empty storage with
+    /// per-record checksums.
+    pub fn new(storage: S) -> Result<Self, Error> {
+        Self::with_checksums(storage, true)
+    }
+
+    /// Reads the index of a database. Empty storage is initialized with or
+    /// without per-record checksums, existing databases keep their layout.
+    pub fn with_checksums(mut storage: S, checksums: bool) -> Result<Self, Error> {
+        let len = storage.seek(SeekFrom::End(0))?;
+        if len == 0 {
+            storage.write_all(&Layout::new(checksums).header(0, 0))?;
+        }
+        storage.seek(SeekFrom::Start(0))?;
+        let mut header = [0; 24];
+        let read = read_up_to(&mut storage, &mut header)?;
+        let (layout, count, file_crc) = parse_header(&header[..read])?;
+        // a count the storage can't hold is corrupt, not an allocation
+        if layout.offset(count as usize) > len.max(read as u64) {
+            return Err(Error::InvalidFormat);
+        }
+        let mut entries = Vec::with_capacity(count as usize);
+        let mut prefix = [0; PREFIX_LEN];
+        for i in 0..count {
+            storage.seek(SeekFrom::Start(layout.offset(i as usize)))?;
+            storage.read_exact(&mut prefix)?;
+            entries.push(Entry {
+                er: f32_at(&prefix, 0),
+                clues: prefix[13],
+            });
+        }
+        // NaN ratings, only in files written elsewhere, can't be ordered
+        let by_difficulty = (0..count)
+            .filter(|&i| !entries[i as usize].er.is_nan())
+            .collect();
+        let mut db = Self {
+            storage,
+            layout,
+            file_crc,
+            entries,
+            by_difficulty,
+        };
+        db.sort_index();
+        Ok(db)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Appends a puzzle, returning its index. Fails with
+    /// `Error::ValueOutOfRange` for an `er` of NaN.
+    pub fn append(&mut self, puzzle: &RatedPuzzle) -> Result<usize, Error> {
+        let index = self.entries.len();
+        let rating = &puzzle.rating;
+        if rating.er.is_nan() {
+            return Err(Error::ValueOutOfRange);
+        }
+        let clues = puzzle.puzzle.iter_filled().count() as u8;
+        let mut record = vec![0; self.layout.record_len()];
+        record[0..4].copy_from_slice(&rating.er.to_le_bytes());
+        record[4..8].copy_from_slice(&rating.ep.to_le_bytes());
+        record[8..12].copy_from_slice(&rating.ed.to_le_bytes());
+        record[12] = rating.solved as u8;
+        record[13] = clues;
+        for (i, pair) in puzzle.puzzle.digits().chunks(2).enumerate() {
+            let low = pair.get(1).copied().unwrap_or(0);
+            record[PREFIX_LEN + i] = (pair[0] << 4) | low;
+        }
+        if self.layout.checksums {
+            let crc = crc32(&record[..PAYLOAD_LEN]);
+            record[PAYLOAD_LEN..].copy_from_slice(&crc.to_le_bytes());
+        }
+        let file_crc = crate::checksum::crc32_update(self.file_crc, &record);
+        self.storage
+            .seek(SeekFrom::Start(self.layout.offset(index)))?;
+        self.storage.write_all(&record)?;
+        self.storage.seek(SeekFrom::Start(0))?;
+        let header = self.layout.header(index as u32 + 1, file_crc);
+        self.storage.write_all(&header)?;
+        self.storage.flush()?;
+        self.file_crc = file_crc;
+
+        self.entries.push(Entry {
+            er: rating.er,
+            clues,
+        });
+        let pos = self
+            .by_difficulty
+            .partition_point(|&i| self.entries[i as usize].er <= rating.er);
+        self.by_difficulty.insert(pos, index as u32);
+        Ok(index)
+    }
+
+    pub fn get(&mut self, index: usize) -> Result<Option<RatedPuzzle>, Error> {
+        if index >= self.entries.len() {
+            return Ok(None);
+        }
+        let mut record = vec![0; self.layout.record_len()];
+        self.storage
+            .seek(SeekFrom::Start(self.layout.offset(index)))?;
+        self.storage.read_exact(&mut record)?;
+        decode_checked(self.layout, index, &record).map(Some)
+    }
+
+    /// Reads every record, checking the record checksums and the checksum
+    /// of the whole file. Version 1 databases have neither.
+    pub fn verify(&mut self) -> Result<(), Error> {
+        let mut file_crc = 0;
+        let mut record = vec![0; self.layout.record_len()];
+        for index in 0..self.entries.len() {
+            self.storage
+                .seek(SeekFrom::Start(self.layout.offset(index)))?;
+            self.storage.read_exact(&mut record)?;
+            decode_checked(self.layout, index, &record)?;
+            file_crc = crate::checksum::crc32_update(file_crc, &record);
+        }
+        if self.layout.version > 1 && file_crc != self.file_crc {
+            return Err(Error::CorruptFile);
+        }
+        Ok(())
+    }
+
+    /// Number of clues of the puzzle at `index`, answered from the index.
+    pub fn clues(&self, index: usize) -> Option<usize> {
+        self.entries.get(index).map(|entry| entry.clues as usize)
+    }
+
+    /// Iterates over the puzzles whose `er` lies in `range`, easiest first.
+    pub fn iter_by_difficulty<R: RangeBounds<f32>>(
+        &mut self,
+        range: R,
+    ) -> impl Iterator<Item = Result<RatedPuzzle, Error>> + '_ {
+        let (start, end) = self.span(&range);
+        let indices = self.by_difficulty[start..end].to_vec();
+        indices.into_iter().map(move |i| {
+            self.get(i as usize)
+                .map(|puzzle| puzzle.expect("indexed record exists; qed"))
+        })
+    }
+
+    /// Indices of the puzzles matching the query, easiest first.
+    pub fn query(&self, query: &Query) -> Vec<usize> {
+        let (mut start, mut end) = self.span(&query.er);
+        if let Some(tier) = query.tier {
+            // tiers rise with `er`, so they are runs of the index
+            let entries = &self.entries;
+            let tier_of = |i: &u32| Tier::from_score(entries[*i as usize].er);
+            start = start.max(self.by_difficulty.partition_point(|i| tier_of(i) < tier));
+            end = end.min(self.by_difficulty.partition_point(|i| tier_of(i) <= tier));
+        }
+        self.by_difficulty[start..end.max(start)]
+            .iter()
+            .map(|&i| i as usize)
+            .filter(|&i| query.clues.contains(&(self.entries[i].clues as usize)))
+            .filter(|i| !query.seen.is_some_and(|seen| seen.contains(i)))
+            .collect()
+    }
+
+    /// A puzzle picked uniformly from those matching the query, with its
+    /// index, `None` if none do.
+    pub fn random(
+        &mut self,
+        query: &Query,
+        rng: &mut impl Rng,
+    ) -> Result<Option<(usize, RatedPuzzle)>, Error> {
+        let matches = self.query(query);
+        if matches.is_empty() {
+            return Ok(None);
+        }
+        let index = matches[(rng.next_u64() % matches.len() as u64) as usize];
+        let puzzle = self.get(index)?.expect("indexed record exists; qed");
+        Ok(Some((index, puzzle)))
+    }
+
+    /// The run of `by_difficulty` whose `er` lies in `range`.
+    fn span<R: RangeBounds<f32>>(&self, range: &R) -> (usize, usize) {
+        let entries = &self.entries;
+        let er = |i: &u32| entries[*i as usize].er;
+        let start = match range.start_bound() {
+            Bound::Included(&lo) => self.by_difficulty.partition_point(|i| er(i) < lo),
+            Bound::Excluded(&lo) => self.by_difficulty.partition_point(|i| er(i) <= lo),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&hi) => self.by_difficulty.partition_point(|i| er(i) <= hi),
+            Bound::Excluded(&hi) => self.by_difficulty.partition_point(|i| er(i) < hi),
+            Bound::Unbounded => self.by_difficulty.len(),
+        };
+        (start, end.max(start))
+    }
+
+    /// Indices of the puzzles whose clue count lies in `range`.
+    pub fn indices_by_clues<R: RangeBounds<usize>>(&self, range: R) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|&i| range.contains(&(self.entries[i].clues as usize)))
+            .collect()
+    }
+
+    fn sort_index(&mut self) {
+        let entries = &self.entries;
+        self.by_difficulty.sort_by(|&a, &b| {
+            let (a, b) = (entries[a as usize].er, entries[b as usize].er);
+            a.total_cmp(&b)
+        });
+    }
+}
+
+/// Decodes the payload of a record.
+fn decode(record: &[u8]) -> RatedPuzzle {
+    let mut digits = [0; 81];
+    for (i, digit) in digits.iter_mut().enumerate() {
+        let byte = record[PREFIX_LEN + i / 2];
+        *digit = if i % 2 == 0 { byte >> 4 } else { byte & 0xf };
+    }
+    RatedPuzzle {
+        puzzle: Sudoku::from_digits(&digits),
+        rating: SeRating {
+            er: f32_at(record, 0),
+            ep: f32_at(record, 4),
+            ed: f32_at(record, 8),
+            solved: record[12] & 1 != 0,
+        },
+    }
+}
+
+/// Like `read_exact`, but stops early at the end of the storage.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    Ok(read)
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[at..at + 4]);
+    u32::from_le_bytes(buf)
+}
+
+fn f32_at(bytes: &[u8], at: usize) -> f32 {
+    f32::from_bits(u32_at(bytes, at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_puzzle_db() {
+        let easy: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let mut db = PuzzleDb::new(Cursor::new(Vec::new())).unwrap();
+        assert!(db.is_empty());
+        db.append(&RatedPuzzle::new(Sudoku::new())).unwrap();
+        db.append(&RatedPuzzle::new(easy)).unwrap();
+        assert_eq!(db.clues(1), Some(30));
+
+        let mut db = PuzzleDb::new(db.storage).unwrap();
+        assert_eq!(db.len(), 2);
+        let puzzle = db.get(1).unwrap().unwrap();
+        assert_eq!(puzzle.puzzle.to_string(), easy.to_string());
+        assert!(puzzle.rating.solved);
+        assert!(db.get(2).unwrap().is_none());
+
+        let order: Vec<_> = db
+            .iter_by_difficulty(..)
+            .map(|puzzle| puzzle.unwrap().rating.solved)
+            .collect();
+        assert_eq!(order, vec![true, false]);
+        assert_eq!(db.iter_by_difficulty(..3.0).count(), 1);
+        assert_eq!(db.indices_by_clues(..10), vec![0]);
+
+        db.verify().unwrap();
+
+        let garbage = Cursor::new(b"not a database!!".to_vec());
+        assert!(matches!(PuzzleDb::new(garbage), Err(Error::InvalidFormat)));
+        let huge = Cursor::new(Layout::new(false).header(u32::MAX, 0));
+        assert!(matches!(PuzzleDb::new(huge), Err(Error::InvalidFormat)));
+        let mut short = db.storage.into_inner();
+        short.truncate(short.len() - 1);
+        assert!(matches!(
+            PuzzleDb::new(Cursor::new(short)),
+            Err(Error::InvalidFormat)
+        ));
+    }
+
+    #[test]
+    fn test_nan_rating() {
+        let mut db = PuzzleDb::with_checksums(Cursor::new(Vec::new()), false).unwrap();
+        for _ in 0..3 {
+            db.append(&RatedPuzzle::new(Sudoku::new())).unwrap();
+        }
+        let mut nan = RatedPuzzle::new(Sudoku::new());
+        nan.rating.er = f32::NAN;
+        assert!(matches!(db.append(&nan), Err(Error::ValueOutOfRange)));
+        assert_eq!(db.len(), 3);
+
+        let mut bytes = db.storage.into_inner();
+        let at = db.layout.offset(1) as usize;
+        bytes[at..at + 4].copy_from_slice(&f32::NAN.to_le_bytes());
+        let mut db = PuzzleDb::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(db.query(&Query::new()), vec![0, 2]);
+        assert_eq!(db.query(&Query::new().tier(Tier::Diabolical)), vec![0, 2]);
+        assert_eq!(db.iter_by_difficulty(..).count(), 2);
+        assert!(db.get(1).unwrap().unwrap().rating.er.is_nan());
+        assert_eq!(db.indices_by_clues(..), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_version_1() {
+        let mut db = PuzzleDb::with_checksums(Cursor::new(Vec::new()), false).unwrap();
+        let first = RatedPuzzle {
+            puzzle: *crate::solve(&Sudoku::new()).unwrap(),
+            rating: SeRating {
+                er: 2.5,
+                ep: 2.5,
+                ed: 1.5,
+                solved: true,
+            },
+        };
+        db.append(&first).unwrap();
+        // the same record after the 16 byte header of version 1
+        let bytes = db.storage.into_inner();
+        let mut v1 = Layout {
+            version: 1,
+            checksums: false,
+        }
+        .header(1, 0);
+        assert_eq!(v1.len(), 16);
+        v1.extend_from_slice(&bytes[24..]);
+
+        let mut db = PuzzleDb::new(Cursor::new(v1)).unwrap();
+        db.append(&RatedPuzzle::new(Sudoku::new())).unwrap();
+        let mut db = PuzzleDb::new(db.storage).unwrap();
+        assert_eq!(db.len(), 2);
+        let record = db.get(0).unwrap().unwrap();
+        assert_eq!(record.rating, first.rating);
+        assert_eq!(record.puzzle, first.puzzle);
+        assert_eq!(db.get(1).unwrap().unwrap().puzzle, Sudoku::new());
+        db.verify().unwrap();
+    }
+
+    #[test]
+    fn test_query() {
+        let solution = crate::solve(&Sudoku::new()).unwrap();
+        let mut db = PuzzleDb::new(Cursor::new(Vec::new())).unwrap();
+        // clue counts and difficulties, easy, hard, hard, medium, hard and
+        // diabolical
+        let records = [
+            (26, 1.5),
+            (26, 4.0),
+            (27, 4.5),
+            (28, 2.7),
+            (30, 5.0),
+            (28, 7.0),
+        ];
+        for &(clues, er) in &records {
+            let mut puzzle = *solution;
+            for i in clues..81 {
+                puzzle.0[i] = crate::Cell::new();
+            }
+            let rating = SeRating {
+                er,
+                ep: er,
+                ed: 1.0,
+                solved: true,
+            };
+            db.append(&RatedPuzzle { puzzle, rating }).unwrap();
+        }
+
+        assert_eq!(db.query(&Query::new()), vec![0, 3, 1, 2, 4, 5]);
+        let hard = Query::new().tier(Tier::Hard).clues(26..=28);
+        assert_eq!(db.query(&hard), vec![1, 2]);
+        assert_eq!(db.query(&hard.clone().difficulty(4.2..)), vec![2]);
+        assert_eq!(db.query(&Query::new().tier(Tier::Diabolical)), vec![5]);
+        assert!(db.query(&Query::new().clues(..20)).is_empty());
+
+        let mut rng = rand::rngs::Xoshiro256PlusPlus::seed_from_u64(5);
+        let mut seen = HashSet::new();
+        for _ in 0..2 {
+            let query = hard.clone().unseen(&seen);
+            let (index, puzzle) = db.random(&query, &mut rng).unwrap().unwrap();
+            assert!(index == 1 || index == 2);
+            assert_eq!(puzzle.puzzle.iter_filled().count(), records[index].0);
+            seen.insert(index);
+        }
+        assert!(db.random(&hard.unseen(&seen), &mut rng).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_checksums() {
+        let mut db = PuzzleDb::new(Cursor::new(Vec::new())).unwrap();
+        db.append(&RatedPuzzle::new(Sudoku::new())).unwrap();
+        db.append(&RatedPuzzle::new(Sudoku::new())).unwrap();
+        let mut bytes = db.storage.into_inner();
+        let last = bytes.len() - 10;
+        bytes[last] ^= 0x10;
+        let mut db = PuzzleDb::new(Cursor::new(bytes)).unwrap();
+        assert!(db.get(0).is_ok());
+        assert!(matches!(db.get(1), Err(Error::CorruptRecord { index: 1 })));
+        assert!(matches!(
+            db.verify(),
+            Err(Error::CorruptRecord { index: 1 })
+        ));
+
+        let mut plain = PuzzleDb::with_checksums(Cursor::new(Vec::new()), false).unwrap();
+        plain.append(&RatedPuzzle::new(Sudoku::new())).unwrap();
+        let mut bytes = plain.storage.into_inner();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let mut plain = PuzzleDb::new(Cursor::new(bytes)).unwrap();
+        assert!(plain.get(0).is_ok());
+        assert!(matches!(plain.verify(), Err(Error::CorruptFile)));
+    }
+}
@@ -0,0 +1,220 @@
+//! Solving as an exact cover problem with Knuth's dancing links: every
+//! cell, and every digit in every row, column and block, is covered by
+//! exactly one candidate.
+use crate::solver::consistent;
+use crate::verified::SolvedSudoku;
+use crate::{SolveOutcome, Sudoku, Value};
+
+/// A column for each cell, then for each digit of each row, column and
+/// block.
+const COLUMNS: usize = 4 * 81;
+
+/// The circular lists of the exact cover matrix. Node `0` is the root,
+/// nodes `1..=COLUMNS` the column headers and the rest are the four nodes
+/// of each candidate, next to each other.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    /// The number of candidates left in each column.
+    size: Vec<usize>,
+    /// The candidate of each node, `cell * 9 + digit`.
+    candidate: Vec<usize>,
+    /// The candidates picked so far.
+    picked: Vec<usize>,
+}
+
+impl Links {
+    fn new(sudoku: &Sudoku) -> Self {
+        let n = COLUMNS + 1;
+        let mut links = Links {
+            left: (0..n).map(|i| (i + n - 1) % n).collect(),
+            right: (0..n).map(|i| (i + 1) % n).collect(),
+            up: (0..n).collect(),
+            down: (0..n).collect(),
+            column: (0..n).collect(),
+            size: vec![0; n],
+            candidate: vec![0; n],
+            picked: Vec::with_capacity(81),
+        };
+        for cell in 0..81 {
+            let (row, col) = (cell / 9, cell % 9);
+            let block = row / 3 * 3 + col / 3;
+            for value in sudoku.0[cell].values() {
+                let digit = value.0 as usize;
+                let columns = [
+                    cell,
+                    81 + row * 9 + digit,
+                    162 + col * 9 + digit,
+                    243 + block * 9 + digit,
+                ];
+                let first = links.left.len();
+                for (k, &column) in columns.iter().enumerate() {
+                    let node = first + k;
+                    let header = column + 1;
+                    let last = links.up[header];
+                    links.left.push(if k == 0 { first + 3 } else { node - 1 });
+                    links.right.push(if k == 3 { first } else { node + 1 });
+                    links.up.push(last);
+                    links.down.push(header);
+                    links.down[last] = node;
+                    links.up[header] = node;
+                    links.column.push(header);
+                    links.candidate.push(cell * 9 + digit);
+                    links.size[header] += 1;
+                }
+            }
+        }
+        links
+    }
+
+    /// Removes the column and every candidate in it from the other columns.
+    fn cover(&mut self, column: usize) {
+        let (left, right) = (self.left[column], self.right[column]);
+        self.right[left] = right;
+        self.left[right] = left;
+        let mut i = self.down[column];
+        while i != column {
+            let mut j = self.right[i];
+            while j != i {
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    /// Undoes `cover`, in the reverse order.
+    fn uncover(&mut self, column: usize) {
+        let mut i = self.up[column];
+        while i != column {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = j;
+                self.up[down] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (left, right) = (self.left[column], self.right[column]);
+        self.right[left] = column;
+        self.left[right] = column;
+    }
+
+    /// Covers the column with the fewest candidates and tries each of
+    /// them, `Err` once `abort` returns `true`, checked at every node.
+    fn search(&mut self, abort: &mut dyn FnMut() -> bool) -> Result<bool, ()> {
+        if abort() {
+            return Err(());
+        }
+        let mut column = self.right[0];
+        if column == 0 {
+            return Ok(true);
+        }
+        let mut i = self.right[column];
+        while i != 0 {
+            if self.size[i] < self.size[column] {
+                column = i;
+            }
+            i = self.right[i];
+        }
+        self.cover(column);
+        let mut row = self.down[column];
+        while row != column {
+            self.picked.push(self.candidate[row]);
+            let mut j = self.right[row];
+            while j != row {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+            if self.search(abort)? {
+                return Ok(true);
+            }
+            let mut j = self.left[row];
+            while j != row {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            self.picked.pop();
+            row = self.down[row];
+        }
+        self.uncover(column);
+        Ok(false)
+    }
+}
+
+/// Solves with dancing links.
+pub fn solve_dlx(sudoku: &Sudoku) -> Option<SolvedSudoku> {
+    match solve_dlx_limited(sudoku, &mut || false) {
+        SolveOutcome::Solved(solution) => Some(solution),
+        _ => None,
+    }
+}
+
+/// Like `solve_dlx`, giving up once `abort` returns `true`, checked at
+/// every node.
+pub(crate) fn solve_dlx_limited(sudoku: &Sudoku, abort: &mut dyn FnMut() -> bool) -> SolveOutcome {
+    if !consistent(sudoku) {
+        return SolveOutcome::NoSolution;
+    }
+    let mut links = Links::new(sudoku);
+    match links.search(abort) {
+        Err(()) => SolveOutcome::Aborted,
+        Ok(false) => SolveOutcome::NoSolution,
+        Ok(true) => {
+            let mut solution = Sudoku::new();
+            for &candidate in &links.picked {
+                solution.0[candidate / 9].set(Value((candidate % 9) as u8));
+            }
+            match SolvedSudoku::new(solution) {
+                Some(solution) => SolveOutcome::Solved(solution),
+                None => SolveOutcome::NoSolution,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_dlx() {
+        for corpus in [
+            crate::Corpus::top1465_sample(),
+            crate::Corpus::seventeen_sample(),
+        ] {
+            for puzzle in corpus.puzzles.iter() {
+                let solution = solve_dlx(puzzle).unwrap();
+                assert_eq!(
+                    solution.to_string(),
+                    crate::solve(puzzle).unwrap().to_string()
+                );
+            }
+        }
+        assert!(solve_dlx(&Sudoku::new()).is_some());
+
+        let mut sudoku = Sudoku::new();
+        sudoku.get_mut(0, 0).unwrap().set(Value(0));
+        sudoku.get_mut(0, 1).unwrap().set(Value(0));
+        assert!(solve_dlx(&sudoku).is_none());
+
+        let hard = *crate::Corpus::top1465_sample().puzzles.get(0).unwrap();
+        let mut nodes = 0;
+        let mut abort = || {
+            nodes += 1;
+            nodes > 3
+        };
+        assert!(matches!(
+            solve_dlx_limited(&hard, &mut abort),
+            SolveOutcome::Aborted
+        ));
+    }
+}
@@ -0,0 +1,370 @@
+use crate::logic::{SolveStep, Technique};
+use crate::{Coord, Unit, Value};
+
+/// Renders a solve step as prose. Steps only carry structured data, so each
+/// language provides its own formatter.
+pub trait StepFormatter {
+    fn format(&self, step: &SolveStep) -> String;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct English;
+
+impl English {
+    fn unit(unit: Unit) -> String {
+        match unit {
+            Unit::Row(row) => format!("row {}", row + 1),
+            Unit::Col(col) => format!("column {}", col + 1),
+            Unit::Block(block) => format!("box {}", block + 1),
+        }
+    }
+
+    fn cells(cells: &[Coord]) -> String {
+        cells
+            .iter()
+            .map(|cell| cell.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn digits(digits: &[Value]) -> String {
+        let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+        match digits.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            _ => digits.join(""),
+        }
+    }
+
+    /// Distinct line numbers counting from 1.
+    fn lines(lines: impl Iterator<Item = usize>) -> String {
+        let mut lines: Vec<usize> = lines.collect();
+        lines.sort_unstable();
+        lines.dedup();
+        let lines: Vec<String> = lines.iter().map(|l| (l + 1).to_string()).collect();
+        lines.join(", ")
+    }
+
+    /// The line or box shared by all pattern cells outside of the step's unit.
+    fn target(step: &SolveStep) -> Option<Unit> {
+        let first = step.cells.first()?;
+        match step.unit? {
+            Unit::Block(_) if step.cells.iter().all(|c| c.row() == first.row()) => {
+                Some(Unit::Row(first.row()))
+            }
+            Unit::Block(_) => Some(Unit::Col(first.col())),
+            _ => Some(Unit::Block(first.block())),
+        }
+    }
+}
+
+impl StepFormatter for English {
+    fn format(&self, step: &SolveStep) -> String {
+        let name = step.technique.name();
+        let mut name = name[..1].to_uppercase() + &name[1..];
+        let digit = step.digits.first();
+        let cell = step.cells.first();
+        let eliminated: Vec<_> = step.eliminations.iter().map(|(c, _)| *c).collect();
+        let sentence = match (step.technique, digit, cell, step.unit) {
+            (Technique::HiddenSingle, Some(d), Some(c), Some(unit)) => {
+                format!("{} can only go in {} in {}.", d, c, Self::unit(unit))
+            }
+            (Technique::NakedSingle, Some(d), Some(c), _) => {
+                format!("{} can only be {}.", c, d)
+            }
+            (Technique::Pointing, Some(d), Some(_), Some(unit))
+            | (Technique::Claiming, Some(d), Some(_), Some(unit)) => format!(
+                "in {}, {} is confined to {}, so it can be removed from {}.",
+                Self::unit(unit),
+                d,
+                Self::unit(Self::target(step).unwrap_or(unit)),
+                Self::cells(&eliminated)
+            ),
+            (
+                Technique::NakedPair | Technique::NakedTriple | Technique::NakedQuad,
+                Some(_),
+                Some(_),
+                Some(unit),
+            ) => {
+                let mut cells = eliminated.clone();
+                cells.dedup();
+                format!(
+                    "{} in {} only contain {}, so they can be removed from {}.",
+                    Self::cells(&step.cells),
+                    Self::unit(unit),
+                    Self::digits(&step.digits),
+                    Self::cells(&cells)
+                )
+            }
+            (
+                Technique::HiddenPair | Technique::HiddenTriple | Technique::HiddenQuad,
+                Some(_),
+                Some(_),
+                Some(unit),
+            ) => format!(
+                "in {}, {} can only go in {}, so their other candidates can be removed.",
+                Self::unit(unit),
+                Self::digits(&step.digits),
+                Self::cells(&step.cells)
+            ),
+            (
+                Technique::XWing | Technique::Swordfish | Technique::Jellyfish,
+                Some(d),
+                Some(_),
+                _,
+            ) => {
+                let rows = Self::lines(step.cells.iter().map(|c| c.row()));
+                let cols = Self::lines(step.cells.iter().map(|c| c.col()));
+                // the eliminations lie outside of the base lines
+                let row_based = step
+                    .eliminations
+                    .iter()
+                    .all(|(c, _)| step.cells.iter().all(|p| p.row() != c.row()));
+                let (base, cover) = if row_based {
+                    (format!("rows {}", rows), format!("columns {}", cols))
+                } else {
+                    (format!("columns {}", cols), format!("rows {}", rows))
+                };
+                let mut cells = eliminated.clone();
+                cells.dedup();
+                format!(
+                    "in {}, {} is confined to {}, so it can be removed from {}.",
+                    base,
+                    d,
+                    cover,
+                    Self::cells(&cells)
+                )
+            }
+            (Technique::XYWing | Technique::XYZWing, Some(d), Some(pivot), _) => {
+                let mut cells = eliminated.clone();
+                cells.dedup();
+                // the XYZ-wing pivot holds the digit too
+                let holders = match step.technique {
+                    Technique::XYWing => &step.cells[1..],
+                    _ => &step.cells[..],
+                };
+                format!(
+                    "whatever {} is, one of {} is {}, so it can be removed from {}.",
+                    pivot,
+                    Self::cells(holders),
+                    d,
+                    Self::cells(&cells)
+                )
+            }
+            (Technique::WWing, Some(d), Some(_), Some(unit)) if step.cells.len() == 4 => {
+                format!(
+                    "{} and {} hold {} and {} is in {} or {} in {}, so one of them is {} and it can be removed from {}.",
+                    step.cells[0],
+                    step.cells[1],
+                    Self::digits(&[step.digits[0], step.digits[1]]),
+                    step.digits[1],
+                    step.cells[2],
+                    step.cells[3],
+                    Self::unit(unit),
+                    d,
+                    Self::cells(&eliminated)
+                )
+            }
+            (Technique::SimpleColoring, Some(d), Some(_), _) => format!(
+                "the conjugate pairs of {} through {} alternate, so it can be removed from {}.",
+                d,
+                Self::cells(&step.cells),
+                Self::cells(&eliminated)
+            ),
+            (Technique::ForcingChain, Some(&a), Some(&c), _) if step.digits.len() == 2 => {
+                let b = step.digits[1];
+                match step.placements.first() {
+                    Some(&(cell, value)) if cell == c => {
+                        let other = if value == a { b } else { a };
+                        format!(
+                            "{} can't be {} without a contradiction, so it is {}.",
+                            c, other, value
+                        )
+                    }
+                    Some(&(cell, value)) => {
+                        format!("whether {} is {} or {}, {} is {}.", c, a, b, cell, value)
+                    }
+                    None => {
+                        let removed: Vec<_> = step
+                            .eliminations
+                            .iter()
+                            .map(|(c, v)| format!("{}<>{}", c, v))
+                            .collect();
+                        format!("whether {} is {} or {}, {}.", c, a, b, removed.join(", "))
+                    }
+                }
+            }
+            (
+                Technique::UniqueRectangle1
+                | Technique::UniqueRectangle2
+                | Technique::UniqueRectangle3
+                | Technique::UniqueRectangle4,
+                Some(_),
+                Some(_),
+                _,
+            ) => {
+                let removed: Vec<_> = step
+                    .eliminations
+                    .iter()
+                    .map(|(c, v)| format!("{}<>{}", c, v))
+                    .collect();
+                format!(
+                    "{} can't all be limited to {} in a unique puzzle, so remove {}.",
+                    Self::cells(&step.cells),
+                    Self::digits(&step.digits),
+                    removed.join(", ")
+                )
+            }
+            (Technique::Nishio, Some(d), Some(c), _) => format!(
+                "if {} were {}, singles would lead to a contradiction, so it can be removed.",
+                c, d
+            ),
+            (Technique::BugPlusOne, Some(d), Some(c), _) => format!(
+                "every other cell has two candidates, so {} must be {} for the puzzle to be unique.",
+                c, d
+            ),
+            _ => {
+                let mut parts = Vec::new();
+                if !step.placements.is_empty() {
+                    let placed: Vec<_> = step
+                        .placements
+                        .iter()
+                        .map(|(c, v)| format!("{}={}", c, v))
+                        .collect();
+                    parts.push(format!("place {}", placed.join(", ")));
+                }
+                if !step.eliminations.is_empty() {
+                    let removed: Vec<_> = step
+                        .eliminations
+                        .iter()
+                        .map(|(c, v)| format!("{}<>{}", c, v))
+                        .collect();
+                    parts.push(format!("remove {}", removed.join(", ")));
+                }
+                format!("{}.", parts.join(" and "))
+            }
+        };
+        name.push_str(": ");
+        name.push_str(&sentence);
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::LogicalSolver;
+    use crate::Sudoku;
+
+    #[test]
+    fn test_english() {
+        let sudoku: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let solution = LogicalSolver::default().solve(&sudoku);
+        let step = &solution.steps[0];
+        assert_eq!(step.technique.id(), "hidden-single");
+        assert_eq!(
+            English.format(step),
+            "Hidden single: 8 can only go in r1c6 in box 2."
+        );
+
+        let mut step = SolveStep {
+            technique: Technique::Claiming,
+            unit: Some(Unit::Row(2)),
+            cells: vec![Coord::new(2, 0), Coord::new(2, 1)],
+            digits: vec![crate::Value::new(4).unwrap()],
+            placements: Vec::new(),
+            eliminations: vec![(Coord::new(0, 2), crate::Value::new(4).unwrap())],
+        };
+        assert_eq!(
+            English.format(&step),
+            "Claiming: in row 3, 4 is confined to box 1, so it can be removed from r1c3."
+        );
+        step.cells.clear();
+        assert_eq!(English.format(&step), "Claiming: remove r1c3<>4.");
+
+        let step = SolveStep {
+            technique: Technique::NakedPair,
+            unit: Some(Unit::Row(0)),
+            cells: vec![Coord::new(0, 0), Coord::new(0, 1)],
+            digits: vec![Value::new(4).unwrap(), Value::new(7).unwrap()],
+            placements: Vec::new(),
+            eliminations: vec![
+                (Coord::new(0, 4), Value::new(4).unwrap()),
+                (Coord::new(0, 4), Value::new(7).unwrap()),
+            ],
+        };
+        assert_eq!(
+            English.format(&step),
+            "Naked pair: r1c1, r1c2 in row 1 only contain 4 and 7, so they can be removed from r1c5."
+        );
+
+        let one = Value::new(1).unwrap();
+        let step = SolveStep {
+            technique: Technique::XWing,
+            unit: None,
+            cells: vec![
+                Coord::new(0, 1),
+                Coord::new(0, 5),
+                Coord::new(4, 1),
+                Coord::new(4, 5),
+            ],
+            digits: vec![one],
+            placements: Vec::new(),
+            eliminations: vec![(Coord::new(8, 5), one)],
+        };
+        assert_eq!(
+            English.format(&step),
+            "X-wing: in rows 1, 5, 1 is confined to columns 2, 6, so it can be removed from r9c6."
+        );
+
+        let step = SolveStep {
+            technique: Technique::XYWing,
+            unit: None,
+            cells: vec![Coord::new(0, 0), Coord::new(0, 4), Coord::new(4, 0)],
+            digits: vec![Value::new(3).unwrap()],
+            placements: Vec::new(),
+            eliminations: vec![(Coord::new(4, 4), Value::new(3).unwrap())],
+        };
+        assert_eq!(
+            English.format(&step),
+            "XY-wing: whatever r1c1 is, one of r1c5, r5c1 is 3, so it can be removed from r5c5."
+        );
+
+        let (four, seven) = (Value::new(4).unwrap(), Value::new(7).unwrap());
+        let step = SolveStep {
+            technique: Technique::ForcingChain,
+            unit: None,
+            cells: vec![Coord::new(0, 0)],
+            digits: vec![four, seven],
+            placements: vec![(Coord::new(1, 2), Value::new(5).unwrap())],
+            eliminations: Vec::new(),
+        };
+        assert_eq!(
+            English.format(&step),
+            "Forcing chain: whether r1c1 is 4 or 7, r2c3 is 5."
+        );
+
+        let step = SolveStep {
+            technique: Technique::Nishio,
+            unit: None,
+            cells: vec![Coord::new(2, 3)],
+            digits: vec![four],
+            placements: Vec::new(),
+            eliminations: vec![(Coord::new(2, 3), four)],
+        };
+        assert_eq!(
+            English.format(&step),
+            "Nishio: if r3c4 were 4, singles would lead to a contradiction, so it can be removed."
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! A C interface for embedding the solver in other languages, declared in
+//! `include/sudoku.h`.
+//!
+//! Grids are buffers of 81 bytes in row-major order, `0` for blanks and
+//! `1` to `9` for digits.
+use crate::logic::Technique;
+use crate::rating::Bucket;
+use crate::{generate_with, solve, Cell, GenerateOptions, Solution, Sudoku, Value};
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::sync::OnceLock;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SudokuStatus {
+    Ok = 0,
+    NullPointer = 1,
+    /// A byte above 9 or a digit repeated in a unit.
+    InvalidGrid = 2,
+    NoSolution = 3,
+    InvalidDifficulty = 4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SudokuRating {
+    /// From `0` for easy to `3` for diabolical.
+    pub tier: u32,
+    pub score: f32,
+    /// Index of the hardest technique for `sudoku_technique_name`, `-1` if
+    /// there were no steps.
+    pub hardest: i32,
+    pub steps: u32,
+    pub solved: bool,
+    pub clues: u32,
+}
+
+unsafe fn read_grid(grid: *const u8) -> Result<Sudoku, SudokuStatus> {
+    if grid.is_null() {
+        return Err(SudokuStatus::NullPointer);
+    }
+    let bytes = std::slice::from_raw_parts(grid, 81);
+    let mut sudoku = Sudoku::new();
+    for (cell, &byte) in sudoku.0.iter_mut().zip(bytes) {
+        match byte {
+            0 => *cell = Cell::new(),
+            _ => cell.set(Value::new(byte).map_err(|_| SudokuStatus::InvalidGrid)?),
+        }
+    }
+    if let Solution::Invalid(_) = sudoku.validate() {
+        return Err(SudokuStatus::InvalidGrid);
+    }
+    Ok(sudoku)
+}
+
+unsafe fn write_grid(sudoku: &Sudoku, out: *mut u8) {
+    let out = std::slice::from_raw_parts_mut(out, 81);
+    for (byte, cell) in out.iter_mut().zip(sudoku.0.iter()) {
+        *byte = cell.value().map_or(0, |value| value.0 + 1);
+    }
+}
+
+/// Writes the solution of `puzzle` to `solution`, the first if there are
+/// several.
+///
+/// # Safety
+///
+/// Both pointers must point to 81 bytes, `solution` writable.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve_grid(puzzle: *const u8, solution: *mut u8) -> SudokuStatus {
+    if solution.is_null() {
+        return SudokuStatus::NullPointer;
+    }
+    let sudoku = match read_grid(puzzle) {
+        Ok(sudoku) => sudoku,
+        Err(status) => return status,
+    };
+    match solve(&sudoku) {
+        Some(solved) => {
+            write_grid(&solved, solution);
+            SudokuStatus::Ok
+        }
+        None => SudokuStatus::NoSolution,
+    }
+}
+
+/// Writes a new proper puzzle of a difficulty from `0` for beginner to `5`
+/// for diabolical to `puzzle`, the same seed always giving the same one.
+///
+/// # Safety
+///
+/// `puzzle` must point to 81 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_generate_grid(
+    difficulty: u32,
+    seed: u64,
+    puzzle: *mut u8,
+) -> SudokuStatus {
+    if puzzle.is_null() {
+        return SudokuStatus::NullPointer;
+    }
+    let bucket = match Bucket::all().get(difficulty as usize) {
+        Some(&bucket) => bucket,
+        None => return SudokuStatus::InvalidDifficulty,
+    };
+    let options = GenerateOptions {
+        seed: Some(seed),
+        ..GenerateOptions::default()
+    };
+    write_grid(&generate_with(bucket, &options), puzzle);
+    SudokuStatus::Ok
+}
+
+/// Rates `puzzle` as `Sudoku::rate` into `rating`.
+///
+/// # Safety
+///
+/// `puzzle` must point to 81 bytes and `rating` to a writable rating.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_rate_grid(
+    puzzle: *const u8,
+    rating: *mut SudokuRating,
+) -> SudokuStatus {
+    if rating.is_null() {
+        return SudokuStatus::NullPointer;
+    }
+    let sudoku = match read_grid(puzzle) {
+        Ok(sudoku) => sudoku,
+        Err(status) => return status,
+    };
+    let rated = sudoku.rate();
+    let hardest = rated
+        .hardest
+        .and_then(|technique| Technique::all().iter().position(|&t| t == technique));
+    *rating = SudokuRating {
+        tier: rated.tier as u32,
+        score: rated.score,
+        hardest: hardest.map_or(-1, |i| i as i32),
+        steps: rated.steps as u32,
+        solved: rated.solved,
+        clues: sudoku.into_iter().count() as u32,
+    };
+    SudokuStatus::Ok
+}
+
+/// The id of a technique like `"x-wing"`, null if the index is out of
+/// range. The string is static.
+#[no_mangle]
+pub extern "C" fn sudoku_technique_name(index: i32) -> *const c_char {
+    static NAMES: OnceLock<Vec<CString>> = OnceLock::new();
+    let names = NAMES.get_or_init(|| {
+        Technique::all()
+            .iter()
+            .map(|technique| CString::new(technique.id()).expect("ids are ascii; qed"))
+            .collect()
+    });
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| names.get(i))
+        .map_or(std::ptr::null(), |name| name.as_ptr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    const LINE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    fn grid(line: &str) -> [u8; 81] {
+        let mut grid = [0; 81];
+        for (byte, c) in grid.iter_mut().zip(line.bytes()) {
+            *byte = if c == b'.' { 0 } else { c - b'0' };
+        }
+        grid
+    }
+
+    #[test]
+    fn test_ffi() {
+        let puzzle = grid(LINE);
+        let mut solution = [0; 81];
+        unsafe {
+            assert_eq!(
+                sudoku_solve_grid(puzzle.as_ptr(), solution.as_mut_ptr()),
+                SudokuStatus::Ok
+            );
+            assert_eq!(solution[..9], [4, 8, 3, 9, 2, 1, 6, 5, 7]);
+
+            let mut broken = puzzle;
+            broken[0] = 3;
+            assert_eq!(
+                sudoku_solve_grid(broken.as_ptr(), solution.as_mut_ptr()),
+                SudokuStatus::InvalidGrid
+            );
+            assert_eq!(
+                sudoku_solve_grid(std::ptr::null(), solution.as_mut_ptr()),
+                SudokuStatus::NullPointer
+            );
+
+            let mut rating = std::mem::zeroed();
+            assert_eq!(
+                sudoku_rate_grid(puzzle.as_ptr(), &mut rating),
+                SudokuStatus::Ok
+            );
+            assert!(rating.solved);
+            assert_eq!(rating.clues, 32);
+            let name = CStr::from_ptr(sudoku_technique_name(rating.hardest));
+            assert!(!name.to_bytes().is_empty());
+            assert!(sudoku_technique_name(-1).is_null());
+
+            let mut generated = [0; 81];
+            assert_eq!(
+                sudoku_generate_grid(9, 1, generated.as_mut_ptr()),
+                SudokuStatus::InvalidDifficulty
+            );
+            assert_eq!(
+                sudoku_generate_grid(0, 1, generated.as_mut_ptr()),
+                SudokuStatus::Ok
+            );
+            assert!(generated.contains(&0) && generated.iter().all(|&b| b <= 9));
+        }
+    }
+}
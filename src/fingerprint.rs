@@ -0,0 +1,53 @@
+//! Fingerprints are stable across crate versions: they are the 64 bit FNV-1a
+//! hash of the 81 row-major digits of the grid, using the byte `0` for cells
+//! without a value and `1` to `9` for values.
+
+use crate::{canonical_form, Sudoku};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub fn fingerprint(sudoku: &Sudoku) -> u64 {
+    sudoku.digits().iter().fold(FNV_OFFSET, |hash, &digit| {
+        (hash ^ u64::from(digit)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Fingerprint of the canonical form, equal for all equivalent puzzles.
+pub fn canonical_fingerprint(sudoku: &Sudoku) -> u64 {
+    fingerprint(&canonical_form(sudoku))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(&Sudoku::new()), 0x0edb_e9ed_be9a_769f);
+        let sudoku: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        let relabeled: Sudoku = sudoku
+            .to_string()
+            .replace('1', "x")
+            .replace('9', "1")
+            .replace('x', "9")
+            .trim_end()
+            .parse()
+            .unwrap();
+        assert_ne!(fingerprint(&sudoku), fingerprint(&relabeled));
+        assert_eq!(
+            canonical_fingerprint(&sudoku),
+            canonical_fingerprint(&relabeled)
+        );
+    }
+}
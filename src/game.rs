@@ -0,0 +1,582 @@
+//! A puzzle being played, with the moves made so far.
+//!
+//! Games save as lines of text, a header with the format version, the
+//! givens and the time played, then one line per move:
+//!
+//! ```text
+//! sudoku-game 1
+//! givens ..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..
+//! elapsed 95000
+//! place r1c1 4
+//! note r1c2 5
+//! clear r1c1
+//! fill
+//! reset
+//! undone place r9c9 7
+//! ```
+//!
+//! The grid and the notes are derived by replaying the moves, undone moves
+//! are listed in the order they were undone.
+use crate::{solve_unique, CandidateSet, Coord, Error, NotedSudoku, Sudoku, Value};
+use std::fmt::Write;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+
+/// The format version `save` writes, `load` reads all up to it.
+const VERSION: u32 = 1;
+
+/// A change to the grid of a game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Place(Coord, Value),
+    Clear(Coord),
+    ToggleNote(Coord, Value),
+    /// Marks every empty cell with its candidates.
+    FillNotes,
+    /// Takes back all entries and notes, leaving the givens.
+    Reset,
+}
+
+/// An action with the grid before it, to undo it.
+#[derive(Clone, Copy, Debug)]
+pub struct Move {
+    pub action: Action,
+    before: NotedSudoku,
+}
+
+/// A change of a game, sent to the receivers of `Game::subscribe`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameEvent {
+    /// The value of a cell changed, `None` for an empty cell.
+    Cell {
+        coord: Coord,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    Notes {
+        coord: Coord,
+        old: CandidateSet,
+        new: CandidateSet,
+    },
+    /// A move was taken back, the changes of the cells follow.
+    Undo(Action),
+    /// An undone move was made again, the changes of the cells follow.
+    Redo(Action),
+}
+
+/// The senders of the subscribers, which a copy of the game doesn't keep.
+#[derive(Debug, Default)]
+struct Subscribers(Vec<Sender<GameEvent>>);
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Game {
+    givens: Sudoku,
+    grid: NotedSudoku,
+    history: Vec<Move>,
+    /// Undone moves, the last undone last.
+    undone: Vec<Move>,
+    elapsed: Duration,
+    /// `None` unless the puzzle is proper.
+    solution: Option<Sudoku>,
+    subscribers: Subscribers,
+}
+
+impl Game {
+    pub fn new(givens: Sudoku) -> Self {
+        Self {
+            givens,
+            grid: NotedSudoku::new(givens),
+            history: Vec::new(),
+            undone: Vec::new(),
+            elapsed: Duration::default(),
+            solution: solve_unique(&givens).ok().map(|solved| *solved),
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    /// Receives the changes of the cells and notes of every move, undo and
+    /// redo from now on, see `GameEvent`. Dropping the receiver ends the
+    /// subscription.
+    pub fn subscribe(&mut self) -> Receiver<GameEvent> {
+        let (tx, rx) = channel();
+        self.subscribers.0.push(tx);
+        rx
+    }
+
+    pub fn givens(&self) -> &Sudoku {
+        &self.givens
+    }
+
+    /// The givens, the entries and the notes.
+    pub fn grid(&self) -> &NotedSudoku {
+        &self.grid
+    }
+
+    pub fn is_given(&self, coord: Coord) -> bool {
+        self.givens[coord].is_final()
+    }
+
+    /// The moves made, the first first, without the undone ones.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// The time played, as kept by the caller.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// The entries that differ from the solution, empty if the puzzle has
+    /// no unique solution.
+    pub fn check(&self) -> Vec<Coord> {
+        let solution = match &self.solution {
+            Some(solution) => solution,
+            None => return Vec::new(),
+        };
+        self.grid
+            .sudoku()
+            .iter_filled()
+            .filter(|&(coord, value)| solution[coord].value() != Some(value))
+            .map(|(coord, _)| coord)
+            .collect()
+    }
+
+    /// Places the value of the solution in a cell, as a move that can be
+    /// undone: the first wrong entry if there is one, else the empty cell
+    /// leaving the most empty cells with a single candidate. `None` if the
+    /// grid is complete or the puzzle has no unique solution.
+    pub fn reveal(&mut self) -> Option<(Coord, Value)> {
+        let solution = self.solution?;
+        let grid = *self.grid.sudoku();
+        let coord = match self.check().first() {
+            Some(&wrong) => wrong,
+            None => (0..81)
+                .map(Coord::from_index)
+                .filter(|&coord| !grid[coord].is_final())
+                .max_by_key(|&coord| {
+                    let mut next = grid;
+                    next[coord] = solution[coord];
+                    let singles = (0..81)
+                        .map(Coord::from_index)
+                        .filter(|c| next.candidates(c.row(), c.col()).len() == 1)
+                        .count();
+                    // the first of the cells tied for the most singles
+                    (singles, std::cmp::Reverse(coord.index()))
+                })?,
+        };
+        let value = solution[coord].value()?;
+        self.place(coord, value);
+        Some((coord, value))
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.grid.sudoku().valid()
+    }
+
+    /// Applies an action, returns `false` and leaves the game as it is if
+    /// it would change a given. Moves undone before can't be redone after.
+    pub fn apply(&mut self, action: Action) -> bool {
+        let coord = match action {
+            Action::Place(coord, _) | Action::Clear(coord) | Action::ToggleNote(coord, _) => {
+                Some(coord)
+            }
+            Action::FillNotes | Action::Reset => None,
+        };
+        if coord.is_some_and(|coord| self.is_given(coord)) {
+            return false;
+        }
+        let before = self.grid;
+        self.act(action);
+        self.history.push(Move { action, before });
+        self.undone.clear();
+        self.notify(None, &before);
+        true
+    }
+
+    pub fn place(&mut self, coord: Coord, value: Value) -> bool {
+        self.apply(Action::Place(coord, value))
+    }
+
+    pub fn clear(&mut self, coord: Coord) -> bool {
+        self.apply(Action::Clear(coord))
+    }
+
+    pub fn toggle_note(&mut self, coord: Coord, value: Value) -> bool {
+        self.apply(Action::ToggleNote(coord, value))
+    }
+
+    pub fn fill_notes(&mut self) {
+        self.apply(Action::FillNotes);
+    }
+
+    /// Goes back to the givens, as a move that can be undone.
+    pub fn reset(&mut self) {
+        self.apply(Action::Reset);
+    }
+
+    /// The cells filled in by the player.
+    pub fn entries(&self) -> impl Iterator<Item = (Coord, Value)> + '_ {
+        self.grid
+            .sudoku()
+            .iter_filled()
+            .filter(move |&(coord, _)| !self.is_given(coord))
+    }
+
+    /// Takes back the last move, `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(last) => {
+                let before = self.grid;
+                self.grid = last.before;
+                self.undone.push(last);
+                self.notify(Some(GameEvent::Undo(last.action)), &before);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes the last undone move again, `false` if there is none.
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(next) => {
+                self.act(next.action);
+                self.history.push(next);
+                self.notify(Some(GameEvent::Redo(next.action)), &next.before);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sends `event` and the changes from `before` to the current grid.
+    fn notify(&mut self, event: Option<GameEvent>, before: &NotedSudoku) {
+        if self.subscribers.0.is_empty() {
+            return;
+        }
+        let mut events: Vec<GameEvent> = event.into_iter().collect();
+        for coord in (0..81).map(Coord::from_index) {
+            let (old, new) = (before.sudoku()[coord], self.grid.sudoku()[coord]);
+            if old.value() != new.value() {
+                events.push(GameEvent::Cell {
+                    coord,
+                    old: old.value(),
+                    new: new.value(),
+                });
+            }
+            let (old, new) = (before.notes(coord), self.grid.notes(coord));
+            if old != new {
+                events.push(GameEvent::Notes { coord, old, new });
+            }
+        }
+        self.subscribers
+            .0
+            .retain(|tx| events.iter().all(|&event| tx.send(event).is_ok()));
+    }
+
+    fn act(&mut self, action: Action) {
+        match action {
+            Action::Place(coord, value) => self.grid.place(coord, value),
+            Action::Clear(coord) => self.grid.clear(coord),
+            Action::ToggleNote(coord, value) => {
+                self.grid.toggle_note(coord, value);
+            }
+            Action::FillNotes => self.grid.fill_notes(),
+            Action::Reset => self.grid = NotedSudoku::new(self.givens),
+        }
+    }
+
+    /// The game in the format of the module documentation.
+    pub fn save(&self) -> String {
+        let mut out = format!(
+            "sudoku-game {}\ngivens {}\nelapsed {}\n",
+            VERSION,
+            self.givens.to_line(),
+            self.elapsed.as_millis()
+        );
+        for m in &self.history {
+            writeln!(out, "{}", action_line(m.action)).expect("strings grow; qed");
+        }
+        for m in &self.undone {
+            writeln!(out, "undone {}", action_line(m.action)).expect("strings grow; qed");
+        }
+        out
+    }
+
+    pub fn load(save: &str) -> Result<Self, Error> {
+        let mut lines = save.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut field = |name: &str| match lines.next() {
+            Some((line, text)) => match text.split_once(' ') {
+                Some((key, value)) if key == name => Ok((line, value)),
+                _ => Err(Error::CorruptSave { line }),
+            },
+            None => Err(Error::CorruptSave { line: 0 }),
+        };
+        let (line, version) = field("sudoku-game")?;
+        let version: u32 = version.parse().map_err(|_| Error::CorruptSave { line })?;
+        if version == 0 || version > VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let (line, givens) = field("givens")?;
+        let givens: Sudoku = givens.parse().map_err(|_| Error::CorruptSave { line })?;
+        let (line, elapsed) = field("elapsed")?;
+        let elapsed = elapsed.parse().map_err(|_| Error::CorruptSave { line })?;
+
+        let mut game = Game::new(givens);
+        game.elapsed = Duration::from_millis(elapsed);
+        let mut undone = Vec::new();
+        for (line, text) in lines {
+            let corrupt = || Error::CorruptSave { line };
+            match text.strip_prefix("undone ") {
+                Some(text) => undone.push((line, parse_action(text).ok_or_else(corrupt)?)),
+                None if undone.is_empty() => {
+                    if !game.apply(parse_action(text).ok_or_else(corrupt)?) {
+                        return Err(corrupt());
+                    }
+                }
+                None => return Err(corrupt()),
+            }
+        }
+        // replay the undone moves from the first to be redone, then undo them
+        for &(line, action) in undone.iter().rev() {
+            if !game.apply(action) {
+                return Err(Error::CorruptSave { line });
+            }
+        }
+        for _ in &undone {
+            game.undo();
+        }
+        Ok(game)
+    }
+}
+
+fn action_line(action: Action) -> String {
+    match action {
+        Action::Place(coord, value) => format!("place {} {}", coord, value),
+        Action::Clear(coord) => format!("clear {}", coord),
+        Action::ToggleNote(coord, value) => format!("note {} {}", coord, value),
+        Action::FillNotes => "fill".into(),
+        Action::Reset => "reset".into(),
+    }
+}
+
+fn parse_action(line: &str) -> Option<Action> {
+    let mut words = line.split(' ');
+    let action = match words.next()? {
+        "place" => Action::Place(words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+        "clear" => Action::Clear(words.next()?.parse().ok()?),
+        "note" => Action::ToggleNote(words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+        "fill" => Action::FillNotes,
+        "reset" => Action::Reset,
+        _ => return None,
+    };
+    match words.next() {
+        Some(_) => None,
+        None => Some(action),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    #[test]
+    fn test_undo_redo() {
+        let mut game = Game::new(LINE.parse().unwrap());
+        let (corner, next) = (Coord::new(0, 0), Coord::new(0, 1));
+        let (four, five) = (Value::new(4).unwrap(), Value::new(5).unwrap());
+        assert!(!game.place(Coord::new(0, 2), five));
+        game.fill_notes();
+        assert!(game.grid().notes(next).contains(four));
+        assert!(game.place(corner, four));
+        assert!(!game.grid().notes(next).contains(four));
+        assert!(game.toggle_note(next, five));
+        assert_eq!(game.history().len(), 3);
+
+        assert!(game.undo());
+        assert!(game.grid().notes(next).contains(five));
+        assert!(game.undo());
+        assert_eq!(game.grid().sudoku().get(0, 0).unwrap().value(), None);
+        assert!(game.grid().notes(next).contains(four));
+        assert!(game.redo());
+        assert_eq!(game.grid().sudoku().get(0, 0).unwrap().value(), Some(four));
+        assert!(!game.grid().notes(next).contains(four));
+        assert_eq!(game.history().len(), 2);
+
+        assert!(game.clear(corner));
+        assert!(!game.redo());
+        assert!(game.undo() && game.undo() && game.undo());
+        assert!(!game.undo());
+        assert_eq!(game.grid().sudoku().to_line(), LINE);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut game = Game::new(LINE.parse().unwrap());
+        let corner = Coord::new(0, 0);
+        game.fill_notes();
+        game.place(corner, Value::new(4).unwrap());
+        assert_eq!(
+            game.entries().collect::<Vec<_>>(),
+            [(corner, Value::new(4).unwrap())]
+        );
+        game.reset();
+        assert_eq!(game.entries().count(), 0);
+        assert!(game.grid().notes(Coord::new(0, 1)).is_empty());
+        assert_eq!(game.grid().sudoku(), game.givens());
+        assert!(game.save().ends_with("\nreset\n"));
+        assert!(game.undo());
+        assert_eq!(game.entries().count(), 1);
+    }
+
+    #[test]
+    fn test_check() {
+        let mut game = Game::new(LINE.parse().unwrap());
+        // the solution starts 483921657
+        game.place(Coord::new(0, 0), Value::new(4).unwrap());
+        game.place(Coord::new(0, 1), Value::new(9).unwrap());
+        game.place(Coord::new(0, 3), Value::new(5).unwrap());
+        assert_eq!(game.check(), [Coord::new(0, 1), Coord::new(0, 3)]);
+
+        let mut open = Game::new(Sudoku::new());
+        open.place(Coord::new(0, 0), Value::new(1).unwrap());
+        assert!(open.check().is_empty());
+    }
+
+    #[test]
+    fn test_reveal() {
+        let mut game = Game::new(LINE.parse().unwrap());
+        // the solution starts 483921657
+        game.place(Coord::new(0, 1), Value::new(9).unwrap());
+        let four = Value::new(4).unwrap();
+        assert_eq!(
+            game.reveal(),
+            Some((Coord::new(0, 1), Value::new(8).unwrap()))
+        );
+        let (coord, value) = game.reveal().unwrap();
+        assert_eq!(game.grid().sudoku()[coord].value(), Some(value));
+        assert!(game.check().is_empty());
+        assert!(game.undo());
+        assert!(!game.grid().sudoku()[coord].is_final());
+
+        while game.reveal().is_some() {}
+        assert!(game.is_solved());
+        assert_eq!(game.grid().sudoku()[Coord::new(0, 0)].value(), Some(four));
+        assert_eq!(Game::new(Sudoku::new()).reveal(), None);
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let mut game = Game::new(LINE.parse().unwrap());
+        let rx = game.subscribe();
+        let (corner, next) = (Coord::new(0, 0), Coord::new(0, 1));
+        let four = Value::new(4).unwrap();
+        game.toggle_note(next, four);
+        game.place(corner, four);
+        let mut noted = CandidateSet::empty();
+        noted.insert(four);
+        let note = GameEvent::Notes {
+            coord: next,
+            old: CandidateSet::empty(),
+            new: noted,
+        };
+        let place = GameEvent::Cell {
+            coord: corner,
+            old: None,
+            new: Some(four),
+        };
+        // placing the 4 also takes the mark off its peer
+        let unnote = GameEvent::Notes {
+            coord: next,
+            old: noted,
+            new: CandidateSet::empty(),
+        };
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [note, place, unnote]);
+
+        game.undo();
+        let events: Vec<_> = rx.try_iter().collect();
+        assert_eq!(events[0], GameEvent::Undo(Action::Place(corner, four)));
+        assert!(events.contains(&GameEvent::Cell {
+            coord: corner,
+            old: Some(four),
+            new: None,
+        }));
+        assert_eq!(events.len(), 3);
+        // a copy of the game doesn't notify, and dropping the receiver ends
+        // the subscription
+        assert!(game.clone().subscribers.0.is_empty());
+        drop(rx);
+        game.redo();
+        assert!(game.subscribers.0.is_empty());
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut game = Game::new(LINE.parse().unwrap());
+        let four = Value::new(4).unwrap();
+        game.fill_notes();
+        game.place(Coord::new(0, 0), four);
+        game.toggle_note(Coord::new(0, 1), Value::new(5).unwrap());
+        game.place(Coord::new(8, 8), Value::new(7).unwrap());
+        game.clear(Coord::new(8, 8));
+        game.undo();
+        game.undo();
+        game.set_elapsed(Duration::from_secs(95));
+
+        let save = game.save();
+        assert!(save.starts_with("sudoku-game 1\n"));
+        assert!(save.contains("\nplace r1c1 4\n"));
+        assert!(save.ends_with("undone clear r9c9\nundone place r9c9 7\n"));
+
+        let mut loaded = Game::load(&save).unwrap();
+        assert_eq!(loaded.save(), save);
+        assert_eq!(loaded.elapsed(), Duration::from_secs(95));
+        assert_eq!(
+            loaded.grid().notes(Coord::new(0, 1)),
+            game.grid().notes(Coord::new(0, 1))
+        );
+        assert_eq!(loaded.history().len(), 3);
+        assert!(loaded.redo());
+        assert_eq!(
+            loaded
+                .grid()
+                .sudoku()
+                .get(8, 8)
+                .unwrap()
+                .value()
+                .unwrap()
+                .to_string(),
+            "7"
+        );
+
+        let newer = save.replacen("sudoku-game 1", "sudoku-game 2", 1);
+        assert!(matches!(
+            Game::load(&newer),
+            Err(Error::UnsupportedVersion(2))
+        ));
+        let given = format!("{}place r1c3 4\n", save.split("undone").next().unwrap());
+        assert!(matches!(
+            Game::load(&given),
+            Err(Error::CorruptSave { line: 7 })
+        ));
+        // an undone move on a given must not undo the real last move
+        let undone_given = save.replace("undone place r9c9 7", "undone place r1c3 4");
+        assert!(matches!(
+            Game::load(&undone_given),
+            Err(Error::CorruptSave { line: 8 })
+        ));
+    }
+}
@@ -0,0 +1,770 @@
+use crate::analysis::Symmetry;
+use crate::fingerprint::{canonical_fingerprint, fingerprint};
+use crate::grid::Grid;
+use crate::logic::{LogicalSolver, Technique};
+use crate::rating::{classify, Bucket, RatedPuzzle};
+use crate::rng::XorShift;
+use crate::solver::legal;
+use crate::verified::SolvedSudoku;
+use crate::{Error, Sudoku, Value};
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReclueOptions {
+    /// Stop removing clues once this many are left.
+    pub min_clues: usize,
+    /// Keep the clues symmetric by removing whole orbits of cells.
+    pub symmetry: Option<Symmetry>,
+}
+
+impl Sudoku {
+    /// A random solution grid, the same for the same state of `rng`, as
+    /// `fill_complete`.
+    pub fn random_filled(rng: &mut impl Rng) -> SolvedSudoku {
+        Self::fill_complete(rng)
+    }
+
+    /// A random solution grid, built for speed
+    /// without the solver: a random top band, the other bands filled in
+    /// row-major order with bit masks and restarted from the band when the
+    /// search runs long, and the result scrambled to spread out the bias of
+    /// that order.
+    pub fn fill_complete(rng: &mut impl Rng) -> SolvedSudoku {
+        let mut rng = XorShift::new(rng.next_u64());
+        let mut digits = [0u8; 81];
+        let mut first: Vec<u8> = (0..9).collect();
+        rng.shuffle(&mut first);
+        digits[..9].copy_from_slice(&first);
+        // every band completes, so the first search doesn't need a budget
+        let band =
+            fill_from(&digits, 9..27, usize::MAX, &mut rng).expect("any row starts a band; qed");
+        loop {
+            if let Some(grid) = fill_from(&band, 27..81, 200, &mut rng) {
+                let filled = Sudoku::from_digits(&grid.map(|d| d + 1));
+                return SolvedSudoku::from_solver(filled.scrambled(rng.next()));
+            }
+        }
+    }
+
+    /// Removes clues in row-major order while the solution stays unique,
+    /// leaving a minimal puzzle: removing any remaining clue makes it
+    /// ambiguous. Returns the puzzle unchanged unless it is proper.
+    pub fn minimize(&self) -> Sudoku {
+        let mut puzzle = *self;
+        if !puzzle.has_unique_solution() {
+            return puzzle;
+        }
+        // removing clues only adds solutions, so a clue that is needed
+        // stays needed and a single pass suffices
+        for i in 0..81 {
+            let clue = puzzle.0[i];
+            if !clue.is_final() {
+                continue;
+            }
+            puzzle.0[i] = Default::default();
+            if !puzzle.has_unique_solution() {
+                puzzle.0[i] = clue;
+            }
+        }
+        puzzle
+    }
+}
+
+/// Produces a proper puzzle whose solution is `solution`, different seeds
+/// removing the clues in a different order.
+pub fn reclue(solution: &SolvedSudoku, seed: u64, options: &ReclueOptions) -> Sudoku {
+    reclue_while(solution, seed, options, &|grid| {
+        grid.count_solutions(2) == 1
+    })
+}
+
+/// Like `reclue`, keeping a clue removed only if `accept` holds after
+/// removing it, which must imply that the solution is still unique.
+fn reclue_while(
+    solution: &SolvedSudoku,
+    seed: u64,
+    options: &ReclueOptions,
+    accept: &dyn Fn(&Grid) -> bool,
+) -> Sudoku {
+    let mut rng = XorShift::new(seed);
+    let mut order: Vec<usize> = (0..81).collect();
+    rng.shuffle(&mut order);
+
+    let mut grid = Grid::from_sudoku(solution);
+    let mut clues = 81;
+    let mut visited = [false; 81];
+    for i in order {
+        if visited[i] {
+            continue;
+        }
+        let orbit = orbit(i, options.symmetry);
+        for &j in &orbit {
+            visited[j] = true;
+        }
+        if clues - orbit.len() < options.min_clues {
+            continue;
+        }
+        for &j in &orbit {
+            grid.set(j / 9, j % 9, None)
+                .expect("blank is in range; qed");
+        }
+        if accept(&grid) {
+            clues -= orbit.len();
+        } else {
+            for &j in &orbit {
+                let digit = solution.0[j].value().map(|v| v.0 + 1);
+                grid.set(j / 9, j % 9, digit)
+                    .expect("digit of a solution is in range; qed");
+            }
+        }
+    }
+    grid.to_sudoku()
+}
+
+/// Number of solution grids `generate` tries before settling for the
+/// closest difficulty.
+const ATTEMPTS: usize = 100;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GenerateOptions {
+    /// Keep the clues symmetric, both when removing and adding them back.
+    pub symmetry: Option<Symmetry>,
+    /// Only return minimal puzzles. No clues are added back to make the
+    /// puzzle easier, and the symmetry may be lost.
+    pub minimal: bool,
+    /// Makes generation reproducible, the same seed and options always
+    /// giving the same puzzle.
+    pub seed: Option<u64>,
+}
+
+/// A proper puzzle of the given difficulty. Fills a random grid, removes
+/// clues while the solution stays unique and adds some back while the
+/// puzzle is too hard. If no puzzle of that difficulty turns up within a
+/// bounded number of grids, the closest one found is returned; buckets
+/// beyond the techniques of the logical solver can't be hit exactly.
+pub fn generate(difficulty: Bucket) -> Sudoku {
+    generate_with(difficulty, &GenerateOptions::default())
+}
+
+pub fn generate_with(difficulty: Bucket, options: &GenerateOptions) -> Sudoku {
+    match options.seed {
+        // a fixed algorithm rather than `StdRng`, which may change
+        Some(seed) => generate_from(
+            difficulty,
+            options,
+            &mut Xoshiro256PlusPlus::seed_from_u64(seed),
+        ),
+        None => generate_from(difficulty, options, &mut rand::rng()),
+    }
+}
+
+/// Like `generate`, the same seed always giving the same puzzle, for
+/// example a daily puzzle seeded by the date.
+pub fn generate_with_seed(seed: u64, difficulty: Bucket) -> Sudoku {
+    let options = GenerateOptions {
+        seed: Some(seed),
+        ..GenerateOptions::default()
+    };
+    generate_with(difficulty, &options)
+}
+
+/// A proper puzzle solvable with `techniques` alone, checked with a
+/// `LogicalSolver` trying them in the given order. Clues are removed while
+/// the puzzle stays solvable that way, so it is as hard as the techniques
+/// allow for its grid, though it needn't use every one of them.
+pub fn generate_with_techniques(techniques: &[Technique], options: &GenerateOptions) -> Sudoku {
+    let mut solver = LogicalSolver::new(techniques.to_vec());
+    // only proper grids get as far as the solver
+    solver.set_assume_unique(true);
+    let reclue_options = ReclueOptions {
+        symmetry: options.symmetry,
+        ..ReclueOptions::default()
+    };
+    let accept =
+        |grid: &Grid| grid.count_solutions(2) == 1 && solver.solve(&grid.to_sudoku()).solved;
+    let (solution, seed) = match options.seed {
+        Some(seed) => {
+            let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+            (Sudoku::random_filled(&mut rng), rng.next_u64())
+        }
+        None => {
+            let mut rng = rand::rng();
+            (Sudoku::random_filled(&mut rng), rng.next_u64())
+        }
+    };
+    reclue_while(&solution, seed, &reclue_options, &accept)
+}
+
+/// The number of clues `generate_with_clues` aims for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClueTarget {
+    Exactly(usize),
+    /// The fewest found among the grids tried.
+    Fewest,
+}
+
+/// A proper puzzle with a number of clues rather than a difficulty, trying
+/// up to `attempts` solution grids. Fails with `Error::ClueCount` if none
+/// gives exactly the clues asked for, right away for fewer than 17, which
+/// no proper puzzle has. `Fewest` tries every grid and always succeeds.
+pub fn generate_with_clues(
+    target: ClueTarget,
+    options: &GenerateOptions,
+    attempts: usize,
+) -> Result<Sudoku, Error> {
+    match options.seed {
+        Some(seed) => clues_from(
+            target,
+            options,
+            attempts,
+            &mut Xoshiro256PlusPlus::seed_from_u64(seed),
+        ),
+        None => clues_from(target, options, attempts, &mut rand::rng()),
+    }
+}
+
+fn clues_from(
+    target: ClueTarget,
+    options: &GenerateOptions,
+    attempts: usize,
+    rng: &mut impl Rng,
+) -> Result<Sudoku, Error> {
+    let (min_clues, exact) = match target {
+        ClueTarget::Exactly(clues) if !(17..=81).contains(&clues) => {
+            return Err(Error::ClueCount { clues, attempts: 0 })
+        }
+        ClueTarget::Exactly(clues) => (clues, Some(clues)),
+        ClueTarget::Fewest => (0, None),
+    };
+    let reclue_options = ReclueOptions {
+        min_clues,
+        symmetry: options.symmetry,
+    };
+    let mut fewest: Option<(Sudoku, usize)> = None;
+    for _ in 0..attempts.max(1) {
+        let solution = Sudoku::random_filled(rng);
+        let puzzle = reclue(&solution, rng.next_u64(), &reclue_options);
+        let clues = puzzle.iter_filled().count();
+        if exact == Some(clues) {
+            return Ok(puzzle);
+        }
+        if fewest.is_none_or(|(_, best)| clues < best) {
+            fewest = Some((puzzle, clues));
+        }
+    }
+    match exact {
+        Some(clues) => Err(Error::ClueCount { clues, attempts }),
+        None => Ok(fewest.expect("at least one attempt; qed").0),
+    }
+}
+
+/// `count` puzzles like `generate`, spread over the available cores.
+/// Puzzles equivalent under the validity preserving transformations are
+/// only returned once.
+pub fn generate_batch(count: usize, difficulty: Bucket) -> Vec<Sudoku> {
+    generate_batch_with(count, difficulty, &GenerateOptions::default())
+}
+
+/// Like `generate_batch`. With a seed the batch is reproducible, puzzle `k`
+/// of the run being generated from the seed plus `k`.
+pub fn generate_batch_with(
+    count: usize,
+    difficulty: Bucket,
+    options: &GenerateOptions,
+) -> Vec<Sudoku> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut puzzles = Vec::with_capacity(count);
+    let mut seen = HashSet::new();
+    let mut next = 0u64;
+    while puzzles.len() < count {
+        // generate the missing puzzles in parallel, then deduplicate them in
+        // order so that the result doesn't depend on the scheduling
+        let indices: Vec<u64> = (next..next + (count - puzzles.len()) as u64).collect();
+        next += indices.len() as u64;
+        let chunk = indices.len().div_ceil(threads);
+        let round: Vec<Sudoku> = std::thread::scope(|scope| {
+            let workers: Vec<_> = indices
+                .chunks(chunk)
+                .map(|indices| {
+                    scope.spawn(move || {
+                        indices
+                            .iter()
+                            .map(|&k| {
+                                let options = GenerateOptions {
+                                    seed: options.seed.map(|seed| seed.wrapping_add(k)),
+                                    ..*options
+                                };
+                                generate_with(difficulty, &options)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("generation doesn't panic; qed"))
+                .collect()
+        });
+        for puzzle in round {
+            if seen.insert(canonical_fingerprint(&puzzle)) {
+                puzzles.push(puzzle);
+            }
+        }
+    }
+    puzzles
+}
+
+/// An endless stream of proper puzzles with their ratings, see
+/// `puzzle_stream`.
+pub struct PuzzleStream {
+    rng: Xoshiro256PlusPlus,
+    options: GenerateOptions,
+    /// Canonical fingerprints of the puzzles yielded so far.
+    seen: HashSet<u64>,
+}
+
+/// Lazily generates puzzles of any difficulty, reproducibly if the options
+/// have a seed, the next one only when asked for. Puzzles equivalent to
+/// one yielded before are skipped.
+pub fn puzzle_stream(options: &GenerateOptions) -> PuzzleStream {
+    let seed = options.seed.unwrap_or_else(|| rand::rng().next_u64());
+    PuzzleStream {
+        rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+        options: *options,
+        seen: HashSet::new(),
+    }
+}
+
+impl Iterator for PuzzleStream {
+    type Item = RatedPuzzle;
+
+    fn next(&mut self) -> Option<RatedPuzzle> {
+        let reclue_options = ReclueOptions {
+            symmetry: self.options.symmetry,
+            ..ReclueOptions::default()
+        };
+        loop {
+            let solution = Sudoku::random_filled(&mut self.rng);
+            let mut puzzle = reclue(&solution, self.rng.next_u64(), &reclue_options);
+            if self.options.minimal {
+                puzzle = puzzle.minimize();
+            }
+            if self.seen.insert(canonical_fingerprint(&puzzle)) {
+                return Some(RatedPuzzle::new(puzzle));
+            }
+        }
+    }
+}
+
+fn generate_from(difficulty: Bucket, options: &GenerateOptions, rng: &mut impl Rng) -> Sudoku {
+    let reclue_options = ReclueOptions {
+        symmetry: options.symmetry,
+        ..ReclueOptions::default()
+    };
+    let distance = |bucket: Bucket| (bucket as i32 - difficulty as i32).abs();
+    let mut closest: Option<(Sudoku, i32)> = None;
+    for _ in 0..ATTEMPTS {
+        let solution = Sudoku::random_filled(rng);
+        let mut puzzle = reclue(&solution, rng.next_u64(), &reclue_options);
+        if options.minimal {
+            puzzle = puzzle.minimize();
+        }
+        let mut empty: Vec<usize> = (0..81).filter(|&i| !puzzle.0[i].is_final()).collect();
+        empty.shuffle(rng);
+        loop {
+            let bucket = classify(&puzzle);
+            if closest.is_none_or(|(_, best)| distance(bucket) < best) {
+                closest = Some((puzzle, distance(bucket)));
+            }
+            if bucket == difficulty {
+                return puzzle;
+            }
+            // too easy already, adding clues won't help
+            if bucket < difficulty || options.minimal {
+                break;
+            }
+            // cells earlier in the list may have come back with an orbit
+            match empty.iter().rposition(|&i| !puzzle.0[i].is_final()) {
+                Some(position) => {
+                    for j in orbit(empty[position], options.symmetry) {
+                        puzzle.0[j] = solution.0[j];
+                    }
+                    empty.truncate(position);
+                }
+                None => break,
+            }
+        }
+    }
+    closest.expect("at least one attempt; qed").0
+}
+
+/// Number of mutated offspring evaluated per generation.
+const OFFSPRING: usize = 8;
+
+/// Searches for a proper puzzle maximizing `fitness` by repeatedly adding,
+/// removing and moving clues of `seed`, keeping the fittest proper
+/// offspring of each generation. The search is deterministic for a given
+/// `seed`, which is returned unchanged if it isn't proper.
+pub fn evolve<F: Fn(&Sudoku) -> f32>(seed: &Sudoku, generations: usize, fitness: F) -> Sudoku {
+    if !seed.has_unique_solution() {
+        return *seed;
+    }
+    let mut rng = XorShift::new(fingerprint(seed));
+    let mut best = (*seed, fitness(seed));
+    for _ in 0..generations {
+        for _ in 0..OFFSPRING {
+            let child = mutate(&best.0, &mut rng);
+            if !child.has_unique_solution() {
+                continue;
+            }
+            let score = fitness(&child);
+            // accepting ties lets the search drift across plateaus
+            if score >= best.1 {
+                best = (child, score);
+            }
+        }
+    }
+    best.0
+}
+
+fn mutate(puzzle: &Sudoku, rng: &mut XorShift) -> Sudoku {
+    let mut child = *puzzle;
+    let (remove, add) = match rng.below(3) {
+        0 => (true, false),
+        1 => (false, true),
+        _ => (true, true),
+    };
+    if remove {
+        let clues: Vec<usize> = (0..81).filter(|&i| child.0[i].is_final()).collect();
+        if !clues.is_empty() {
+            child.0[clues[rng.below(clues.len())]] = Default::default();
+        }
+    }
+    if add {
+        let empty: Vec<usize> = (0..81).filter(|&i| !child.0[i].is_final()).collect();
+        if !empty.is_empty() {
+            let i = empty[rng.below(empty.len())];
+            let digits: Vec<Value> = (0..9)
+                .map(Value)
+                .filter(|&v| legal(&child, i / 9, i % 9, v))
+                .collect();
+            if !digits.is_empty() {
+                child.0[i].set(digits[rng.below(digits.len())]);
+            }
+        }
+    }
+    child
+}
+
+/// Cells a clue at index `i` maps to under `symmetry`, including `i`.
+fn orbit(i: usize, symmetry: Option<Symmetry>) -> Vec<usize> {
+    let mut orbit = vec![i];
+    if let Some(symmetry) = symmetry {
+        loop {
+            let last = *orbit.last().expect("orbit is not empty; qed");
+            let (x, y) = symmetry.map(last / 9, last % 9);
+            let next = x * 9 + y;
+            if next == i {
+                break;
+            }
+            orbit.push(next);
+        }
+    }
+    orbit
+}
+
+/// Fills the `cells` of a grid filled up to them with a random depth-first
+/// search of at most `budget` dead ends, digits zero-based.
+fn fill_from(
+    digits: &[u8; 81],
+    cells: Range<usize>,
+    budget: usize,
+    rng: &mut XorShift,
+) -> Option<[u8; 81]> {
+    let (start, end) = (cells.start, cells.end);
+    let mut grid = *digits;
+    let (mut rows, mut cols, mut boxes) = ([0u16; 9], [0u16; 9], [0u16; 9]);
+    let unit = |i: usize| (i / 9, i % 9, i / 27 * 3 + i % 9 / 3);
+    for (i, &d) in grid[..start].iter().enumerate() {
+        let (r, c, b) = unit(i);
+        rows[r] |= 1 << d;
+        cols[c] |= 1 << d;
+        boxes[b] |= 1 << d;
+    }
+    // the untried digits of each cell on the path, `None` before the
+    // cell is reached
+    let mut untried: [Option<u16>; 81] = [None; 81];
+    let mut dead_ends = 0;
+    let mut i = start;
+    while i < end {
+        let (r, c, b) = unit(i);
+        let free = untried[i].unwrap_or(!(rows[r] | cols[c] | boxes[b]) & 0x1ff);
+        if free == 0 {
+            untried[i] = None;
+            dead_ends += 1;
+            if i == start || dead_ends > budget {
+                return None;
+            }
+            i -= 1;
+            let (r, c, b) = unit(i);
+            let d = grid[i];
+            rows[r] &= !(1 << d);
+            cols[c] &= !(1 << d);
+            boxes[b] &= !(1 << d);
+            continue;
+        }
+        // the k-th free digit
+        let mut rest = free;
+        for _ in 0..rng.below(free.count_ones() as usize) {
+            rest &= rest - 1;
+        }
+        let d = rest.trailing_zeros() as u8;
+        untried[i] = Some(free & !(1 << d));
+        grid[i] = d;
+        rows[r] |= 1 << d;
+        cols[c] |= 1 << d;
+        boxes[b] |= 1 << d;
+        i += 1;
+    }
+    Some(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::detect_symmetry;
+    use crate::solver::solve_unique;
+
+    #[test]
+    fn test_random_filled() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let a = Sudoku::random_filled(&mut StdRng::seed_from_u64(7));
+        let b = Sudoku::random_filled(&mut StdRng::seed_from_u64(7));
+        let c = Sudoku::random_filled(&mut StdRng::seed_from_u64(8));
+        assert!(a.valid());
+        assert_eq!(a.to_string(), b.to_string());
+        assert_ne!(a.to_string(), c.to_string());
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let grids: HashSet<String> = (0..50)
+            .map(|_| Sudoku::fill_complete(&mut rng))
+            .inspect(|grid| assert!(grid.valid()))
+            .map(|grid| grid.to_string())
+            .collect();
+        assert_eq!(grids.len(), 50);
+        let (d, e) = (
+            Sudoku::fill_complete(&mut StdRng::seed_from_u64(9)),
+            Sudoku::fill_complete(&mut StdRng::seed_from_u64(9)),
+        );
+        assert_eq!(d.to_string(), e.to_string());
+    }
+
+    #[test]
+    fn test_generate() {
+        for difficulty in [Bucket::Beginner, Bucket::Medium] {
+            let puzzle = generate(difficulty);
+            assert!(puzzle.has_unique_solution());
+            assert_eq!(classify(&puzzle), difficulty);
+        }
+    }
+
+    #[test]
+    fn test_generate_with_seed() {
+        let a = generate_with_seed(20261014, Bucket::Easy);
+        let b = generate_with_seed(20261014, Bucket::Easy);
+        let c = generate_with_seed(20261015, Bucket::Easy);
+        assert_eq!(a.to_string(), b.to_string());
+        assert_ne!(a.to_string(), c.to_string());
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let options = GenerateOptions {
+            seed: Some(1),
+            ..GenerateOptions::default()
+        };
+        let batch = generate_batch_with(6, Bucket::Beginner, &options);
+        assert_eq!(batch.len(), 6);
+        let distinct: HashSet<u64> = batch.iter().map(canonical_fingerprint).collect();
+        assert_eq!(distinct.len(), 6);
+        let again = generate_batch_with(6, Bucket::Beginner, &options);
+        assert_eq!(
+            batch.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+            again.iter().map(|p| p.to_string()).collect::<Vec<_>>()
+        );
+        assert!(generate_batch(0, Bucket::Easy).is_empty());
+    }
+
+    #[test]
+    fn test_puzzle_stream() {
+        let options = GenerateOptions {
+            seed: Some(5),
+            ..GenerateOptions::default()
+        };
+        let puzzles: Vec<RatedPuzzle> = puzzle_stream(&options).take(4).collect();
+        let distinct: HashSet<u64> = puzzles
+            .iter()
+            .map(|p| canonical_fingerprint(&p.puzzle))
+            .collect();
+        assert_eq!(distinct.len(), 4);
+        for rated in &puzzles {
+            assert!(rated.puzzle.has_unique_solution());
+            assert_eq!(rated.rating, crate::se_rating(&rated.puzzle));
+        }
+        let again = puzzle_stream(&options).nth(3).unwrap();
+        assert_eq!(again.puzzle, puzzles[3].puzzle);
+    }
+
+    #[test]
+    fn test_generate_with_clues() {
+        let options = GenerateOptions {
+            seed: Some(3),
+            ..GenerateOptions::default()
+        };
+        for clues in [26, 40] {
+            let puzzle = generate_with_clues(ClueTarget::Exactly(clues), &options, 20).unwrap();
+            assert_eq!(puzzle.iter_filled().count(), clues);
+            assert!(puzzle.has_unique_solution());
+        }
+        let fewest = generate_with_clues(ClueTarget::Fewest, &options, 5).unwrap();
+        assert!(fewest.has_unique_solution() && fewest.iter_filled().count() < 26);
+        assert!(matches!(
+            generate_with_clues(ClueTarget::Exactly(16), &options, 5),
+            Err(Error::ClueCount { clues: 16, .. })
+        ));
+        assert!(matches!(
+            generate_with_clues(ClueTarget::Exactly(18), &options, 2),
+            Err(Error::ClueCount {
+                clues: 18,
+                attempts: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_generate_with_techniques() {
+        let options = GenerateOptions {
+            seed: Some(11),
+            ..GenerateOptions::default()
+        };
+        let singles = [Technique::HiddenSingle, Technique::NakedSingle];
+        let puzzle = generate_with_techniques(&singles, &options);
+        assert!(puzzle.has_unique_solution());
+        assert!(LogicalSolver::singles().solve(&puzzle).solved);
+        let options = GenerateOptions {
+            symmetry: Some(Symmetry::Rotational180),
+            ..options
+        };
+        let puzzle = generate_with_techniques(&singles, &options);
+        assert!(detect_symmetry(&puzzle).has(Symmetry::Rotational180));
+        assert!(LogicalSolver::singles().solve(&puzzle).solved);
+    }
+
+    #[test]
+    fn test_generate_symmetric() {
+        for symmetry in [Symmetry::Rotational180, Symmetry::Diagonal] {
+            let options = GenerateOptions {
+                symmetry: Some(symmetry),
+                ..GenerateOptions::default()
+            };
+            let puzzle = generate_with(Bucket::Easy, &options);
+            assert!(detect_symmetry(&puzzle).has(symmetry));
+            assert!(puzzle.has_unique_solution());
+        }
+    }
+
+    #[test]
+    fn test_minimize() {
+        let puzzle: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let minimal = puzzle.minimize();
+        assert!(minimal.has_unique_solution());
+        assert!(minimal.iter_filled().count() < puzzle.iter_filled().count());
+        for (coord, _) in minimal.iter_filled() {
+            let mut reduced = minimal;
+            *reduced.get_mut(coord.row(), coord.col()).unwrap() = Default::default();
+            assert!(!reduced.has_unique_solution());
+        }
+        assert_eq!(Sudoku::new().minimize().iter_filled().count(), 0);
+
+        let options = GenerateOptions {
+            minimal: true,
+            ..GenerateOptions::default()
+        };
+        let generated = generate_with(Bucket::Easy, &options);
+        assert_eq!(generated.minimize().to_string(), generated.to_string());
+    }
+
+    #[test]
+    fn test_reclue() {
+        let solution: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        let solution = SolvedSudoku::new(solution).unwrap();
+        let first = reclue(&solution, 1, &ReclueOptions::default());
+        let second = reclue(&solution, 2, &ReclueOptions::default());
+        assert_ne!(first.to_string(), second.to_string());
+        for puzzle in &[first, second] {
+            let solutions = Grid::from_sudoku(puzzle).solutions(2);
+            assert_eq!(solutions.len(), 1);
+            assert_eq!(solutions[0].to_sudoku().to_string(), solution.to_string());
+        }
+
+        let options = ReclueOptions {
+            min_clues: 40,
+            symmetry: Some(Symmetry::Rotational180),
+        };
+        let puzzle = reclue(&solution, 3, &options);
+        assert!(puzzle.iter_filled().count() >= 40);
+        assert!(detect_symmetry(&puzzle).has(Symmetry::Rotational180));
+        assert!(solve_unique(&puzzle).is_ok());
+    }
+
+    #[test]
+    fn test_evolve() {
+        let puzzle: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let blanks = |p: &Sudoku| p.iter_empty().count() as f32;
+        let evolved = evolve(&puzzle, 20, blanks);
+        assert!(blanks(&evolved) > blanks(&puzzle));
+        assert!(evolved.has_unique_solution());
+        assert_eq!(evolve(&puzzle, 20, blanks).to_string(), evolved.to_string());
+
+        let empty = Sudoku::new();
+        assert_eq!(evolve(&empty, 5, blanks).to_string(), empty.to_string());
+    }
+}
@@ -0,0 +1,1685 @@
+//! Grids of other sizes than 9x9, with square boxes like 4x4, 16x16 and
+//! 25x25 grids, or rectangular ones like 6x6 with 2x3 boxes or 12x12 with
+//! 3x4 boxes. [`Sudoku`] stays the fixed size type the 9x9 techniques work
+//! on.
+use crate::constraint::{Arrow, Constraint, Distinct, Inequalities, Palindrome, Whisper};
+use crate::rng::XorShift;
+use crate::symbols::SymbolSet;
+use crate::{Error, Sudoku};
+use std::sync::Arc;
+
+/// Size of the boxes of a grid. The grid has `rows * cols` rows, columns
+/// and digits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct BoxShape {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl BoxShape {
+    /// The largest supported grid has 36 digits, written `1`-`9` and `A`-`Z`.
+    pub fn new(rows: usize, cols: usize) -> Result<Self, Error> {
+        if rows == 0 || cols == 0 || rows * cols > 36 {
+            return Err(Error::InvalidShape);
+        }
+        Ok(Self { rows, cols })
+    }
+
+    /// Square boxes of `order` by `order` cells, `2` for 4x4 grids up to `5`
+    /// for 25x25 grids, or `6` for 36x36.
+    pub fn square(order: usize) -> Result<Self, Error> {
+        Self::new(order, order)
+    }
+
+    pub fn size(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// Box of a cell, numbered row-major.
+    pub fn box_of(&self, row: usize, col: usize) -> usize {
+        (row / self.rows) * self.rows + col / self.cols
+    }
+
+    /// Cells of box `b` in row-major order.
+    pub fn box_cells(&self, b: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = (self.rows, self.cols);
+        let top = (b / rows) * rows;
+        let left = (b % rows) * cols;
+        (0..rows).flat_map(move |r| (0..cols).map(move |c| (top + r, left + c)))
+    }
+}
+
+/// A Kropki dot on the edge between two orthogonally adjacent cells.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Dot {
+    /// The digits are consecutive.
+    White,
+    /// One digit is double the other.
+    Black,
+}
+
+/// Whether a shaded cell holds an even or an odd digit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+impl Parity {
+    pub fn allows(self, digit: u8) -> bool {
+        digit.is_multiple_of(2) == (self == Parity::Even)
+    }
+
+    /// The digits of the parity up to `size`, as a mask with bit `d` for
+    /// digit `d`.
+    fn mask(self, size: usize) -> u64 {
+        (1..=size as u8)
+            .filter(|&d| self.allows(d))
+            .fold(0, |mask, d| mask | 1 << d)
+    }
+}
+
+/// The rule between the digits of two orthogonal neighbours.
+#[derive(Clone, Copy)]
+enum Edge {
+    Dot(Dot),
+    NonConsecutive,
+}
+
+impl Edge {
+    fn allows(self, a: u8, b: u8) -> bool {
+        match self {
+            Edge::Dot(Dot::White) => a.abs_diff(b) == 1,
+            Edge::Dot(Dot::Black) => a == 2 * b || b == 2 * a,
+            Edge::NonConsecutive => a.abs_diff(b) != 1,
+        }
+    }
+}
+
+/// Constraints added to a grid. Grids compare equal if they share them,
+/// there is no telling whether two constraints are the same rule.
+#[derive(Clone, Debug, Default)]
+struct Constraints(Vec<Arc<dyn Constraint>>);
+
+impl PartialEq for Constraints {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl Eq for Constraints {}
+
+impl std::hash::Hash for Constraints {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.len().hash(state);
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Grid {
+    shape: BoxShape,
+    /// Row-major digits, `0` for blanks.
+    cells: Vec<u8>,
+    /// Irregular boxes of a jigsaw grid replacing those of the shape, as
+    /// row-major indices.
+    boxes: Option<Vec<Vec<usize>>>,
+    /// Units beyond the rows, columns and boxes, as row-major indices.
+    regions: Vec<Vec<usize>>,
+    /// Whether equal digits may not be a knight's move apart.
+    anti_knight: bool,
+    /// Whether equal digits may not be a king's move apart.
+    anti_king: bool,
+    /// Whether orthogonal neighbours without a dot may not be consecutive.
+    non_consecutive: bool,
+    /// Kropki dots between pairs of row-major indices, the smaller first.
+    dots: Vec<(usize, usize, Dot)>,
+    /// Shaded cells as row-major indices, in the order they were shaded.
+    parities: Vec<(usize, Parity)>,
+    /// Signs between orthogonal neighbours, as pairs of row-major indices
+    /// with the smaller digit first.
+    inequalities: Vec<(usize, usize)>,
+    /// Rules beyond the built-in ones.
+    constraints: Constraints,
+}
+
+impl Grid {
+    pub fn new(shape: BoxShape) -> Self {
+        Self {
+            shape,
+            cells: vec![0; shape.size() * shape.size()],
+            boxes: None,
+            regions: Vec::new(),
+            anti_knight: false,
+            anti_king: false,
+            non_consecutive: false,
+            dots: Vec::new(),
+            parities: Vec::new(),
+            inequalities: Vec::new(),
+            constraints: Constraints::default(),
+        }
+    }
+
+    /// Parses a grid ignoring whitespace, with `.` or `0` for blanks.
+    pub fn parse(shape: BoxShape, s: &str) -> Result<Self, Error> {
+        Self::parse_with(shape, s, &SymbolSet::alphanumeric())
+    }
+
+    /// Parses a grid ignoring whitespace, reading digits and blanks with
+    /// `symbols`.
+    pub fn parse_with(shape: BoxShape, s: &str, symbols: &SymbolSet) -> Result<Self, Error> {
+        let size = shape.size();
+        let mut cells = Vec::with_capacity(size * size);
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            let digit = if symbols.is_blank(c) {
+                0
+            } else {
+                match symbols.digit(c) {
+                    Some(d) if d as usize <= size => d,
+                    _ => return Err(Error::InvalidSymbol(c)),
+                }
+            };
+            cells.push(digit);
+        }
+        if cells.len() != size * size {
+            return Err(Error::WrongLength {
+                expected: size * size,
+                found: cells.len(),
+            });
+        }
+        Ok(Self {
+            shape,
+            cells,
+            boxes: None,
+            regions: Vec::new(),
+            anti_knight: false,
+            anti_king: false,
+            non_consecutive: false,
+            dots: Vec::new(),
+            parities: Vec::new(),
+            inequalities: Vec::new(),
+            constraints: Constraints::default(),
+        })
+    }
+
+    /// Formats one row per line, writing digits and blanks with `symbols`.
+    pub fn to_string_with(&self, symbols: &SymbolSet) -> String {
+        let mut string = String::new();
+        for row in self.cells.chunks(self.shape.size()) {
+            for &digit in row {
+                string.push(symbols.symbol(digit).unwrap_or_else(|| symbols.blank()));
+            }
+            string.push('\n');
+        }
+        string
+    }
+
+    pub fn shape(&self) -> BoxShape {
+        self.shape
+    }
+
+    /// The digit at `row`, `col`, `None` for blanks and cells outside the
+    /// grid.
+    pub fn get(&self, row: usize, col: usize) -> Option<u8> {
+        let size = self.shape.size();
+        if row >= size || col >= size {
+            return None;
+        }
+        match self.cells[row * size + col] {
+            0 => None,
+            digit => Some(digit),
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, digit: Option<u8>) -> Result<(), Error> {
+        let size = self.shape.size();
+        if row >= size || col >= size {
+            return Err(Error::OutOfBounds { row, col });
+        }
+        let digit = digit.unwrap_or(0);
+        if digit as usize > size {
+            return Err(Error::ValueOutOfRange);
+        }
+        self.cells[row * size + col] = digit;
+        Ok(())
+    }
+
+    pub fn clues(&self) -> usize {
+        self.cells.iter().filter(|&&d| d != 0).count()
+    }
+
+    /// Digits of box `b` in row-major order, `None` for blanks.
+    pub fn box_iter(&self, b: usize) -> impl Iterator<Item = Option<u8>> + '_ {
+        self.box_indices(b)
+            .into_iter()
+            .map(move |i| match self.cells[i] {
+                0 => None,
+                digit => Some(digit),
+            })
+    }
+
+    fn box_indices(&self, b: usize) -> Vec<usize> {
+        let size = self.shape.size();
+        match &self.boxes {
+            Some(boxes) => boxes[b].clone(),
+            None => self
+                .shape
+                .box_cells(b)
+                .map(|(row, col)| row * size + col)
+                .collect(),
+        }
+    }
+
+    /// An empty jigsaw grid, with the box of each cell given in row-major
+    /// order, see [`Grid::set_boxes`]. The size follows from the number of
+    /// cells.
+    pub fn jigsaw(boxes: &[usize]) -> Result<Self, Error> {
+        let size = (1..=36).find(|n| n * n >= boxes.len()).unwrap_or(0);
+        // the most square shape of that size, which only matters to code
+        // drawing the grid
+        let rows = (1..=size).rev().find(|r| r * r <= size && size % r == 0);
+        let shape = BoxShape::new(rows.unwrap_or(1), size / rows.unwrap_or(1))?;
+        let mut grid = Self::new(shape);
+        grid.set_boxes(boxes)?;
+        Ok(grid)
+    }
+
+    /// Replaces the boxes by irregular ones, as for jigsaw sudoku. `boxes`
+    /// holds the box of each cell in row-major order, numbered from `0`.
+    /// Each box needs to be a connected set of as many cells as the grid has
+    /// digits.
+    pub fn set_boxes(&mut self, boxes: &[usize]) -> Result<(), Error> {
+        let size = self.shape.size();
+        if boxes.len() != size * size {
+            return Err(Error::WrongLength {
+                expected: size * size,
+                found: boxes.len(),
+            });
+        }
+        let mut cells = vec![Vec::with_capacity(size); size];
+        for (i, &b) in boxes.iter().enumerate() {
+            cells.get_mut(b).ok_or(Error::InvalidShape)?.push(i);
+        }
+        for region in &cells {
+            if region.len() != size || !connected(region, size) {
+                return Err(Error::InvalidShape);
+            }
+        }
+        self.boxes = Some(cells);
+        Ok(())
+    }
+
+    /// Adds a unit in which no digit may repeat, like the diagonals of
+    /// Sudoku X. It needs as many distinct cells as the grid has digits.
+    pub fn add_region(&mut self, cells: &[(usize, usize)]) -> Result<(), Error> {
+        let size = self.shape.size();
+        if cells.len() != size {
+            return Err(Error::WrongLength {
+                expected: size,
+                found: cells.len(),
+            });
+        }
+        let mut region: Vec<usize> = Vec::with_capacity(size);
+        for &(row, col) in cells {
+            let i = row * size + col;
+            if row >= size || col >= size || region.contains(&i) {
+                return Err(Error::InvalidShape);
+            }
+            region.push(i);
+        }
+        self.regions.push(region);
+        Ok(())
+    }
+
+    /// Adds the two main diagonals as units, turning the grid into a
+    /// Sudoku X.
+    pub fn add_diagonals(&mut self) {
+        let size = self.shape.size();
+        let main: Vec<_> = (0..size).map(|i| (i, i)).collect();
+        let anti: Vec<_> = (0..size).map(|i| (i, size - 1 - i)).collect();
+        self.add_region(&main)
+            .expect("a diagonal has a cell per digit; qed");
+        self.add_region(&anti)
+            .expect("a diagonal has a cell per digit; qed");
+    }
+
+    /// Adds the windows of hyper sudoku (windoku) as units: boxes set one
+    /// cell apart from each other and from the edges, four of them on a 9x9
+    /// grid. Only square boxes have windows.
+    pub fn add_windows(&mut self) -> Result<(), Error> {
+        let BoxShape { rows: order, cols } = self.shape;
+        if order != cols {
+            return Err(Error::InvalidShape);
+        }
+        let size = self.shape.size();
+        let offsets: Vec<usize> = (0..)
+            .map(|k| 1 + k * (order + 1))
+            .take_while(|offset| offset + order < size)
+            .collect();
+        for &top in &offsets {
+            for &left in &offsets {
+                let cells: Vec<_> = (top..top + order)
+                    .flat_map(|row| (left..left + order).map(move |col| (row, col)))
+                    .collect();
+                self.add_region(&cells)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// An empty 9x9 hyper sudoku.
+    pub fn windoku() -> Self {
+        let mut grid = Self::new(BoxShape { rows: 3, cols: 3 });
+        grid.add_windows().expect("9x9 boxes are square; qed");
+        grid
+    }
+
+    /// An empty 9x9 Sudoku X.
+    pub fn sudoku_x() -> Self {
+        let mut grid = Self::new(BoxShape { rows: 3, cols: 3 });
+        grid.add_diagonals();
+        grid
+    }
+
+    /// Forbids equal digits a knight's move apart.
+    pub fn set_anti_knight(&mut self, on: bool) {
+        self.anti_knight = on;
+    }
+
+    pub fn is_anti_knight(&self) -> bool {
+        self.anti_knight
+    }
+
+    /// Forbids equal digits a king's move apart. Orthogonal neighbours share
+    /// a row or column anyway, so this adds the diagonal ones.
+    pub fn set_anti_king(&mut self, on: bool) {
+        self.anti_king = on;
+    }
+
+    pub fn is_anti_king(&self) -> bool {
+        self.anti_king
+    }
+
+    /// Cells a chess move away from each cell under the anti-knight and
+    /// anti-king constraints, as row-major indices. Empty if neither is on.
+    fn peers(&self) -> Vec<Vec<usize>> {
+        const KNIGHT: [(isize, isize); 8] = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+        const KING: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+        if !self.anti_knight && !self.anti_king {
+            return Vec::new();
+        }
+        let size = self.shape.size();
+        let mut moves = Vec::new();
+        if self.anti_knight {
+            moves.extend_from_slice(&KNIGHT);
+        }
+        if self.anti_king {
+            moves.extend_from_slice(&KING);
+        }
+        (0..size * size)
+            .map(|i| {
+                let (row, col) = (i / size, i % size);
+                moves
+                    .iter()
+                    .filter_map(|&(dr, dc)| {
+                        let r = row.checked_add_signed(dr).filter(|&r| r < size)?;
+                        let c = col.checked_add_signed(dc).filter(|&c| c < size)?;
+                        Some(r * size + c)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Forbids consecutive digits in orthogonally adjacent cells, except
+    /// across a dot, which has its own rule.
+    pub fn set_non_consecutive(&mut self, on: bool) {
+        self.non_consecutive = on;
+    }
+
+    pub fn is_non_consecutive(&self) -> bool {
+        self.non_consecutive
+    }
+
+    /// Puts a Kropki dot between two orthogonally adjacent cells, replacing
+    /// any dot already there.
+    pub fn add_dot(&mut self, a: (usize, usize), b: (usize, usize), dot: Dot) -> Result<(), Error> {
+        let size = self.shape.size();
+        let adjacent = a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1;
+        if a.0 >= size || a.1 >= size || b.0 >= size || b.1 >= size || !adjacent {
+            return Err(Error::InvalidShape);
+        }
+        let (a, b) = (a.0 * size + a.1, b.0 * size + b.1);
+        let (a, b) = (a.min(b), a.max(b));
+        self.dots.retain(|&(x, y, _)| (x, y) != (a, b));
+        self.dots.push((a, b, dot));
+        Ok(())
+    }
+
+    /// The Kropki dots with the cells on either side.
+    pub fn dots(&self) -> impl Iterator<Item = ((usize, usize), (usize, usize), Dot)> + '_ {
+        let size = self.shape.size();
+        self.dots
+            .iter()
+            .map(move |&(a, b, dot)| ((a / size, a % size), (b / size, b % size), dot))
+    }
+
+    /// Restricts a cell to even or odd digits, `None` to lift it.
+    pub fn set_parity(
+        &mut self,
+        row: usize,
+        col: usize,
+        parity: Option<Parity>,
+    ) -> Result<(), Error> {
+        let size = self.shape.size();
+        if row >= size || col >= size {
+            return Err(Error::OutOfBounds { row, col });
+        }
+        let i = row * size + col;
+        self.parities.retain(|&(j, _)| j != i);
+        if let Some(parity) = parity {
+            self.parities.push((i, parity));
+        }
+        Ok(())
+    }
+
+    pub fn parity(&self, row: usize, col: usize) -> Option<Parity> {
+        let i = row * self.shape.size() + col;
+        self.parities
+            .iter()
+            .find_map(|&(j, parity)| Some(parity).filter(|_| j == i))
+    }
+
+    /// Shades the cells of a layout with one character per cell, ignoring
+    /// whitespace: `E` or `e` for even, `O` or `o` for odd and `.` for
+    /// neither. Replaces the shading there was.
+    ///
+    /// ```text
+    /// E...O....
+    /// ```
+    pub fn parse_parities(&mut self, layout: &str) -> Result<(), Error> {
+        let size = self.shape.size();
+        let mut parities = Vec::new();
+        let mut cells = 0;
+        for c in layout.chars().filter(|c| !c.is_whitespace()) {
+            match c {
+                'E' | 'e' => parities.push((cells, Parity::Even)),
+                'O' | 'o' => parities.push((cells, Parity::Odd)),
+                '.' => {}
+                _ => return Err(Error::InvalidSymbol(c)),
+            }
+            cells += 1;
+        }
+        if cells != size * size {
+            return Err(Error::WrongLength {
+                expected: size * size,
+                found: cells,
+            });
+        }
+        self.parities = parities;
+        Ok(())
+    }
+
+    /// The shading in the layout of `parse_parities`, one row per line.
+    pub fn parities_to_string(&self) -> String {
+        let size = self.shape.size();
+        let mut string = String::new();
+        for row in 0..size {
+            for col in 0..size {
+                string.push(match self.parity(row, col) {
+                    Some(Parity::Even) => 'E',
+                    Some(Parity::Odd) => 'O',
+                    None => '.',
+                });
+            }
+            string.push('\n');
+        }
+        string
+    }
+
+    /// Puts a sign between two orthogonally adjacent cells, the digit of
+    /// `less` smaller than that of `greater`, replacing any sign there.
+    pub fn add_inequality(
+        &mut self,
+        less: (usize, usize),
+        greater: (usize, usize),
+    ) -> Result<(), Error> {
+        let size = self.shape.size();
+        let (a, b) = (less, greater);
+        let adjacent = a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1;
+        if a.0 >= size || a.1 >= size || b.0 >= size || b.1 >= size || !adjacent {
+            return Err(Error::InvalidShape);
+        }
+        let (a, b) = (a.0 * size + a.1, b.0 * size + b.1);
+        self.inequalities
+            .retain(|&(x, y)| (x, y) != (a, b) && (x, y) != (b, a));
+        self.inequalities.push((a, b));
+        Ok(())
+    }
+
+    /// The signs, the cell with the smaller digit first.
+    pub fn inequalities(&self) -> impl Iterator<Item = ((usize, usize), (usize, usize))> + '_ {
+        let size = self.shape.size();
+        self.inequalities
+            .iter()
+            .map(move |&(a, b)| ((a / size, a % size), (b / size, b % size)))
+    }
+
+    /// Reads the signs of a layout replacing those there were, ignoring
+    /// whitespace within lines and blank lines. The lines alternate between
+    /// the signs within a row, `<`, `>` or `.` for none between each pair
+    /// of cells, and those between two rows, `^` where the upper digit is
+    /// smaller, `v` where the lower one is or `.`. A 4x4 layout:
+    ///
+    /// ```text
+    /// <.>
+    /// ^..v
+    /// ...
+    /// ....
+    /// >><
+    /// .^..
+    /// ...
+    /// ```
+    pub fn parse_inequalities(&mut self, layout: &str) -> Result<(), Error> {
+        let size = self.shape.size();
+        let lines: Vec<Vec<char>> = layout
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+            .filter(|line: &Vec<char>| !line.is_empty())
+            .collect();
+        if lines.len() != 2 * size - 1 {
+            return Err(Error::WrongLength {
+                expected: 2 * size - 1,
+                found: lines.len(),
+            });
+        }
+        let mut inequalities = Vec::new();
+        for (k, line) in lines.iter().enumerate() {
+            let (row, between) = (k / 2, k % 2 == 1);
+            let expected = if between { size } else { size - 1 };
+            if line.len() != expected {
+                return Err(Error::WrongLength {
+                    expected,
+                    found: line.len(),
+                });
+            }
+            for (col, &c) in line.iter().enumerate() {
+                let a = row * size + col;
+                let b = if between { a + size } else { a + 1 };
+                match (between, c) {
+                    (_, '.') => {}
+                    (false, '<') | (true, '^') => inequalities.push((a, b)),
+                    (false, '>') | (true, 'v') => inequalities.push((b, a)),
+                    _ => return Err(Error::InvalidSymbol(c)),
+                }
+            }
+        }
+        self.inequalities = inequalities;
+        Ok(())
+    }
+
+    /// The signs in the layout of `parse_inequalities`.
+    pub fn inequalities_to_string(&self) -> String {
+        let size = self.shape.size();
+        let sign = |a: usize, b: usize, signs: [char; 2]| {
+            if self.inequalities.contains(&(a, b)) {
+                signs[0]
+            } else if self.inequalities.contains(&(b, a)) {
+                signs[1]
+            } else {
+                '.'
+            }
+        };
+        let mut string = String::new();
+        for row in 0..size {
+            for col in 0..size - 1 {
+                let a = row * size + col;
+                string.push(sign(a, a + 1, ['<', '>']));
+            }
+            string.push('\n');
+            if row + 1 < size {
+                for col in 0..size {
+                    let a = row * size + col;
+                    string.push(sign(a, a + size, ['^', 'v']));
+                }
+                string.push('\n');
+            }
+        }
+        string
+    }
+
+    /// The digits each cell may hold by its parity, empty without shaded
+    /// cells.
+    fn parity_masks(&self) -> Vec<u64> {
+        let size = self.shape.size();
+        if self.parities.is_empty() {
+            return Vec::new();
+        }
+        let mut masks = vec![((1u64 << size) - 1) << 1; size * size];
+        for &(i, parity) in &self.parities {
+            masks[i] = parity.mask(size);
+        }
+        masks
+    }
+
+    /// Pairs of orthogonal neighbours whose digits are related by a dot or
+    /// the non-consecutive rule, as row-major indices.
+    fn edges(&self) -> Vec<(usize, usize, Edge)> {
+        let mut edges: Vec<_> = self
+            .dots
+            .iter()
+            .map(|&(a, b, dot)| (a, b, Edge::Dot(dot)))
+            .collect();
+        if self.non_consecutive {
+            let size = self.shape.size();
+            for a in 0..size * size {
+                let right = (a % size + 1 < size).then_some(a + 1);
+                let below = (a + size < size * size).then_some(a + size);
+                for b in right.into_iter().chain(below) {
+                    if !self.dots.iter().any(|&(x, y, _)| (x, y) == (a, b)) {
+                        edges.push((a, b, Edge::NonConsecutive));
+                    }
+                }
+            }
+        }
+        edges
+    }
+
+    /// The neighbours of each cell across an edge, with the candidates each
+    /// of their digits leaves the cell, at `0` those of a blank neighbour.
+    /// Empty if there are no edges.
+    fn edge_masks(&self) -> Vec<Vec<(usize, Vec<u64>)>> {
+        let size = self.shape.size();
+        let edges = self.edges();
+        if edges.is_empty() {
+            return Vec::new();
+        }
+        let mut masks = vec![Vec::new(); size * size];
+        for (a, b, edge) in edges {
+            // every rule is symmetric, so both cells share the table
+            let allowed = |other: u8| {
+                (1..=size as u8)
+                    .filter(|&d| edge.allows(d, other))
+                    .fold(0, |mask, d| mask | 1u64 << d)
+            };
+            let blank = (1..=size as u8).fold(0, |mask, other| mask | allowed(other));
+            let table: Vec<u64> = std::iter::once(blank)
+                .chain((1..=size as u8).map(allowed))
+                .collect();
+            masks[a].push((b, table.clone()));
+            masks[b].push((a, table));
+        }
+        masks
+    }
+
+    /// Units beyond the rows, columns and boxes.
+    pub fn regions(&self) -> impl Iterator<Item = Vec<(usize, usize)>> + '_ {
+        let size = self.shape.size();
+        self.regions
+            .iter()
+            .map(move |region| region.iter().map(|&i| (i / size, i % size)).collect())
+    }
+
+    /// Adds a rule of its own to the grid, honored by the solver and the
+    /// generator like the built-in ones.
+    pub fn add_constraint(&mut self, constraint: impl Constraint + 'static) {
+        self.constraints.0.push(Arc::new(constraint));
+    }
+
+    /// Draws an arrow from the circle along the path, see [`Arrow`].
+    pub fn add_arrow(
+        &mut self,
+        circle: (usize, usize),
+        path: &[(usize, usize)],
+    ) -> Result<(), Error> {
+        let arrow = Arrow::new(circle, path, self.shape.size())?;
+        self.add_constraint(arrow);
+        Ok(())
+    }
+
+    /// Draws a line reading the same from both ends, see [`Palindrome`].
+    pub fn add_palindrome(&mut self, line: &[(usize, usize)]) -> Result<(), Error> {
+        let palindrome = Palindrome::new(line, self.shape.size())?;
+        self.add_constraint(palindrome);
+        Ok(())
+    }
+
+    /// Draws a German whisper, see [`Whisper::german`].
+    pub fn add_whisper(&mut self, line: &[(usize, usize)]) -> Result<(), Error> {
+        let whisper = Whisper::german(line, self.shape.size())?;
+        self.add_constraint(whisper);
+        Ok(())
+    }
+
+    /// The rows, columns, boxes and extra regions as [`Distinct`]
+    /// constraints, followed by those added with
+    /// [`Grid::add_constraint`]. The search keeps track of the built-in
+    /// ones with digit masks rather than through the trait.
+    pub fn constraints(&self) -> Vec<Arc<dyn Constraint>> {
+        let units = self.units().into_iter().map(|unit| {
+            let distinct: Arc<dyn Constraint> = Arc::new(Distinct::from_indices(unit));
+            distinct
+        });
+        units.chain(self.constraints.0.iter().cloned()).collect()
+    }
+
+    /// Cells of each row, column, box and extra region, as row-major
+    /// indices.
+    fn units(&self) -> Vec<Vec<usize>> {
+        let size = self.shape.size();
+        let rows = (0..size).map(|row| (0..size).map(|col| row * size + col).collect());
+        let cols = (0..size).map(|col| (0..size).map(|row| row * size + col).collect());
+        let boxes = (0..size).map(|b| self.box_indices(b));
+        rows.chain(cols)
+            .chain(boxes)
+            .chain(self.regions.iter().cloned())
+            .collect()
+    }
+
+    /// Cells whose digit repeats in a row, column, box or extra region, is
+    /// a forbidden chess move away or breaks the rule of an edge, in
+    /// row-major order. Added constraints only tell whether they hold, see
+    /// [`Grid::is_consistent`].
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let size = self.shape.size();
+        let mut conflicting = vec![false; size * size];
+        for unit in self.units() {
+            for (j, &a) in unit.iter().enumerate() {
+                for &b in &unit[j + 1..] {
+                    if self.cells[a] != 0 && self.cells[a] == self.cells[b] {
+                        conflicting[a] = true;
+                        conflicting[b] = true;
+                    }
+                }
+            }
+        }
+        for (a, peers) in self.peers().iter().enumerate() {
+            if self.cells[a] != 0 && peers.iter().any(|&b| self.cells[b] == self.cells[a]) {
+                conflicting[a] = true;
+            }
+        }
+        for (a, b, edge) in self.edges() {
+            let (x, y) = (self.cells[a], self.cells[b]);
+            if x != 0 && y != 0 && !edge.allows(x, y) {
+                conflicting[a] = true;
+                conflicting[b] = true;
+            }
+        }
+        for &(i, parity) in &self.parities {
+            if self.cells[i] != 0 && !parity.allows(self.cells[i]) {
+                conflicting[i] = true;
+            }
+        }
+        for &(a, b) in &self.inequalities {
+            let (x, y) = (self.cells[a], self.cells[b]);
+            if x != 0 && y != 0 && x >= y {
+                conflicting[a] = true;
+                conflicting[b] = true;
+            }
+        }
+        (0..size * size)
+            .filter(|&i| conflicting[i])
+            .map(|i| (i / size, i % size))
+            .collect()
+    }
+
+    fn search(&self) -> Option<Search> {
+        // on classic grids up to 9x9 the scan costs more than the cell
+        // branching loses
+        let peers = self.peers();
+        let edges = self.edge_masks();
+        let classic = self.boxes.is_none()
+            && self.regions.is_empty()
+            && peers.is_empty()
+            && edges.is_empty()
+            && self.parities.is_empty()
+            && self.inequalities.is_empty()
+            && self.constraints.0.is_empty();
+        let size = self.shape.size();
+        let mut constraints = self.constraints.0.clone();
+        if !self.inequalities.is_empty() {
+            constraints.push(Arc::new(Inequalities {
+                pairs: self.inequalities.clone(),
+                size,
+            }));
+        }
+        let rules = Rules {
+            peers,
+            edges,
+            allowed: self.parity_masks(),
+            constraints,
+        };
+        Search::new(
+            self.cells.clone(),
+            size,
+            self.units(),
+            rules,
+            !classic || size > 9,
+        )
+    }
+
+    fn with_cells(&self, cells: Vec<u8>) -> Grid {
+        Grid {
+            cells,
+            ..self.clone()
+        }
+    }
+
+    /// Up to `limit` solutions, trying the branches in random order if `rng`
+    /// is given. A search sometimes gets stuck behind an early bad guess, as
+    /// on an empty anti-knight grid, so it restarts with a doubled budget,
+    /// every other time in random order, until one finishes or finds `limit`
+    /// solutions.
+    fn run(&self, limit: usize, rng: Option<&mut XorShift>) -> Vec<Grid> {
+        let mut restarts = XorShift::new(0x5eed);
+        let shuffled = rng.is_some();
+        let rng = rng.unwrap_or(&mut restarts);
+        let mut budget = 1000;
+        for attempt in 0.. {
+            let mut search = match self.search() {
+                Some(search) => search,
+                None => break,
+            };
+            search.budget = Some(budget);
+            let mut solutions = Vec::new();
+            let order = (shuffled || attempt % 2 == 1).then_some(&mut *rng);
+            search.run(&mut solutions, limit, order);
+            if solutions.len() >= limit || search.budget != Some(0) {
+                return solutions
+                    .into_iter()
+                    .map(|cells| self.with_cells(cells))
+                    .collect();
+            }
+            budget *= 2;
+        }
+        Vec::new()
+    }
+
+    /// Whether no digit repeats in a unit or breaks another rule of the
+    /// grid.
+    pub fn is_consistent(&self) -> bool {
+        self.search().is_some()
+    }
+
+    /// Whether the grid is full and no digit repeats in a unit.
+    pub fn is_valid(&self) -> bool {
+        self.clues() == self.cells.len() && self.is_consistent()
+    }
+
+    pub fn solve(&self) -> Option<Grid> {
+        self.run(1, None).pop()
+    }
+
+    /// Counts solutions, stopping at `limit`.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions(limit).len()
+    }
+
+    /// Enumerates up to `limit` solutions.
+    pub fn solutions(&self, limit: usize) -> Vec<Grid> {
+        self.run(limit, None)
+    }
+
+    /// Generates a puzzle with a unique solution from `seed` by filling an
+    /// empty grid at random and removing clues while the solution stays
+    /// unique.
+    pub fn generate(shape: BoxShape, seed: u64) -> Grid {
+        Self::new(shape)
+            .generate_variant(seed)
+            .expect("empty grid has a solution; qed")
+    }
+
+    /// Like [`Grid::generate`] for a grid with the shape, the extra regions
+    /// and the other constraints of `self`, whose digits are ignored.
+    /// Returns `None` if they leave no solution.
+    pub fn generate_variant(&self, seed: u64) -> Option<Grid> {
+        let mut rng = XorShift::new(seed);
+        let mut empty = self.clone();
+        empty.cells.iter_mut().for_each(|digit| *digit = 0);
+        let mut puzzle = empty.fill(&mut rng)?;
+        let mut order: Vec<usize> = (0..puzzle.cells.len()).collect();
+        rng.shuffle(&mut order);
+        for i in order {
+            let digit = puzzle.cells[i];
+            puzzle.cells[i] = 0;
+            if puzzle.count_solutions(2) != 1 {
+                puzzle.cells[i] = digit;
+            }
+        }
+        Some(puzzle)
+    }
+
+    /// A random solution of the grid.
+    fn fill(&self, rng: &mut XorShift) -> Option<Grid> {
+        self.run(1, Some(rng)).pop()
+    }
+
+    pub(crate) fn from_sudoku(sudoku: &Sudoku) -> Self {
+        Self {
+            shape: BoxShape { rows: 3, cols: 3 },
+            cells: sudoku.digits().to_vec(),
+            boxes: None,
+            regions: Vec::new(),
+            anti_knight: false,
+            anti_king: false,
+            non_consecutive: false,
+            dots: Vec::new(),
+            parities: Vec::new(),
+            inequalities: Vec::new(),
+            constraints: Constraints::default(),
+        }
+    }
+
+    pub(crate) fn to_sudoku(&self) -> Sudoku {
+        let mut digits = [0; 81];
+        digits.copy_from_slice(&self.cells);
+        Sudoku::from_digits(&digits)
+    }
+}
+
+/// Whether the cells, as row-major indices of a grid `size` cells wide, are
+/// joined by shared edges.
+fn connected(cells: &[usize], size: usize) -> bool {
+    let mut reached = vec![false; cells.len()];
+    let mut stack = vec![0];
+    reached[0] = true;
+    while let Some(j) = stack.pop() {
+        let (row, col) = (cells[j] / size, cells[j] % size);
+        for (k, &other) in cells.iter().enumerate() {
+            let (r, c) = (other / size, other % size);
+            if !reached[k] && row.abs_diff(r) + col.abs_diff(c) == 1 {
+                reached[k] = true;
+                stack.push(k);
+            }
+        }
+    }
+    reached.iter().all(|&r| r)
+}
+
+impl From<&Sudoku> for Grid {
+    fn from(sudoku: &Sudoku) -> Self {
+        Self::from_sudoku(sudoku)
+    }
+}
+
+impl std::fmt::Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.to_string_with(&SymbolSet::alphanumeric()))
+    }
+}
+
+/// Rules of a search beyond its units.
+#[derive(Default)]
+pub(crate) struct Rules {
+    /// Cells outside of the units that may not hold the same digit of each
+    /// cell, like those a knight's move away. Empty if there are none.
+    pub peers: Vec<Vec<usize>>,
+    /// Neighbours of each cell whose digit limits its candidates, with the
+    /// candidates left by each of their digits, `0` for a blank. Empty if
+    /// there are none.
+    pub edges: Vec<Vec<(usize, Vec<u64>)>>,
+    /// The digits each cell may hold at all, empty if any.
+    pub allowed: Vec<u64>,
+    pub constraints: Vec<Arc<dyn Constraint>>,
+}
+
+/// Backtracking over digit masks of the units. Branches on the cell with
+/// the fewest candidates or, if it has fewer, on the places left for a digit
+/// in a unit, which keeps large and irregular grids tractable.
+///
+/// Cells are indices into a list of digits, `0` for blanks, and cells in no
+/// unit are left alone, so that layouts with holes like samurai sudoku can
+/// use the same search.
+pub(crate) struct Search {
+    cells: Vec<u8>,
+    digits: usize,
+    /// Digits used by each unit.
+    masks: Vec<u64>,
+    /// Cell indices of each unit.
+    units: Vec<Vec<usize>>,
+    /// Units of each cell.
+    cell_units: Vec<Vec<usize>>,
+    /// See [`Rules`].
+    peers: Vec<Vec<usize>>,
+    edges: Vec<Vec<(usize, Vec<u64>)>>,
+    allowed: Vec<u64>,
+    /// Checked at every node, pruning the candidates of all cells.
+    constraints: Vec<Arc<dyn Constraint>>,
+    /// Whether to look for digits with few places in a unit.
+    scan_units: bool,
+    /// Branches left to try before giving up, if limited.
+    budget: Option<usize>,
+}
+
+/// What to branch on: a cell and its candidates, or a digit and the cells
+/// of a unit it can go in.
+enum Branch {
+    Cell(usize, u64),
+    Digit(u8, Vec<usize>),
+}
+
+impl Search {
+    /// `None` if a digit repeats in a unit or among peers, or breaks
+    /// another rule.
+    pub(crate) fn new(
+        cells: Vec<u8>,
+        digits: usize,
+        units: Vec<Vec<usize>>,
+        rules: Rules,
+        scan_units: bool,
+    ) -> Option<Self> {
+        let mut cell_units = vec![Vec::new(); cells.len()];
+        for (u, unit) in units.iter().enumerate() {
+            for &i in unit {
+                cell_units[i].push(u);
+            }
+        }
+        let mut search = Self {
+            cells: vec![0; cells.len()],
+            digits,
+            masks: vec![0; units.len()],
+            units,
+            cell_units,
+            peers: rules.peers,
+            edges: rules.edges,
+            allowed: rules.allowed,
+            constraints: rules.constraints,
+            scan_units,
+            budget: None,
+        };
+        for (i, &digit) in cells.iter().enumerate() {
+            if digit == 0 {
+                continue;
+            }
+            if search.candidates(i) & (1 << digit) == 0 {
+                return None;
+            }
+            search.cells[i] = digit;
+            search.toggle(i, digit);
+        }
+        let cells = &search.cells;
+        if !search.constraints.iter().all(|c| c.is_satisfied(cells)) {
+            return None;
+        }
+        Some(search)
+    }
+
+    fn candidates(&self, i: usize) -> u64 {
+        let all = self
+            .allowed
+            .get(i)
+            .copied()
+            .unwrap_or(((1u64 << self.digits) - 1) << 1);
+        let used = self.cell_units[i]
+            .iter()
+            .fold(0, |mask, &u| mask | self.masks[u]);
+        let taken = self.peers.get(i).map_or(0, |peers| {
+            peers
+                .iter()
+                .fold(0, |mask, &j| mask | 1u64 << self.cells[j])
+        });
+        let allowed = self.edges.get(i).map_or(all, |edges| {
+            edges.iter().fold(all, |mask, (j, table)| {
+                mask & table[self.cells[*j] as usize]
+            })
+        });
+        allowed & !used & !taken
+    }
+
+    fn toggle(&mut self, i: usize, digit: u8) {
+        for &u in &self.cell_units[i] {
+            self.masks[u] ^= 1 << digit;
+        }
+    }
+
+    /// The most constrained branch, `None` if the grid is full and
+    /// `Some(Err(()))` on a dead end.
+    fn branch(&self) -> Option<Result<Branch, ()>> {
+        let mut best: Option<(u32, Branch)> = None;
+        let mut candidates = vec![0; self.cells.len()];
+        // added constraints prune with the candidates of all cells at hand
+        let pruned = !self.constraints.is_empty();
+        if pruned {
+            if !self.constraints.iter().all(|c| c.is_satisfied(&self.cells)) {
+                return Some(Err(()));
+            }
+            for (i, mask) in candidates.iter_mut().enumerate() {
+                if self.cells[i] == 0 && !self.cell_units[i].is_empty() {
+                    *mask = self.candidates(i);
+                }
+            }
+            for constraint in &self.constraints {
+                constraint.prune_candidates(&self.cells, &mut candidates);
+            }
+        }
+        for (i, mask) in candidates.iter_mut().enumerate() {
+            if self.cells[i] != 0 || self.cell_units[i].is_empty() {
+                continue;
+            }
+            if !pruned {
+                *mask = self.candidates(i);
+            }
+            let count = mask.count_ones();
+            if count == 0 {
+                return Some(Err(()));
+            }
+            if best.as_ref().is_none_or(|(b, _)| count < *b) {
+                best = Some((count, Branch::Cell(i, *mask)));
+                if count == 1 {
+                    return best.map(|(_, branch)| Ok(branch));
+                }
+            }
+        }
+        if best.is_none() || !self.scan_units {
+            return best.map(|(_, branch)| Ok(branch));
+        }
+        // filled cells have no candidates left
+        for (unit, &used) in self.units.iter().zip(&self.masks) {
+            for digit in (1..=self.digits as u8).filter(|&d| used & (1 << d) == 0) {
+                let places = |&&i: &&usize| candidates[i] & (1 << digit) != 0;
+                let count = unit.iter().filter(places).count() as u32;
+                if count == 0 {
+                    return Some(Err(()));
+                }
+                if best.as_ref().is_none_or(|(b, _)| count < *b) {
+                    let cells = unit.iter().filter(places).copied().collect();
+                    best = Some((count, Branch::Digit(digit, cells)));
+                    if count == 1 {
+                        break;
+                    }
+                }
+            }
+        }
+        best.map(|(_, branch)| Ok(branch))
+    }
+
+    /// Collects solutions until there are `limit` of them, trying the
+    /// branches in random order if `rng` is given.
+    pub(crate) fn run(
+        &mut self,
+        solutions: &mut Vec<Vec<u8>>,
+        limit: usize,
+        mut rng: Option<&mut XorShift>,
+    ) {
+        match &mut self.budget {
+            Some(0) => return,
+            Some(budget) => *budget -= 1,
+            None => {}
+        }
+        let mut moves: Vec<(usize, u8)> = match self.branch() {
+            None => {
+                solutions.push(self.cells.clone());
+                return;
+            }
+            Some(Err(())) => return,
+            Some(Ok(Branch::Cell(i, candidates))) => (1..=self.digits as u8)
+                .filter(|&d| candidates & (1 << d) != 0)
+                .map(|d| (i, d))
+                .collect(),
+            Some(Ok(Branch::Digit(digit, cells))) => {
+                cells.into_iter().map(|i| (i, digit)).collect()
+            }
+        };
+        if let Some(rng) = rng.as_deref_mut() {
+            rng.shuffle(&mut moves);
+        }
+        for (i, digit) in moves {
+            self.cells[i] = digit;
+            self.toggle(i, digit);
+            self.run(solutions, limit, rng.as_deref_mut());
+            self.toggle(i, digit);
+            self.cells[i] = 0;
+            if solutions.len() >= limit {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangular_boxes() {
+        let shape = BoxShape::new(2, 3).unwrap();
+        let grid = Grid::parse(
+            shape,
+            "1..4..\n\
+             ..6..3\n\
+             2..5..\n\
+             ..4..1\n\
+             3..6..\n\
+             ..5..2",
+        )
+        .unwrap();
+        assert_eq!(grid.clues(), 12);
+        let solution = grid.solve().unwrap();
+        assert_eq!(solution.clues(), 36);
+        assert_eq!(solution.get(0, 0), Some(1));
+        assert_eq!(solution.get(0, 6), None);
+        let mut copy = solution.clone();
+        assert!(copy.set(6, 0, Some(1)).is_err());
+        assert!(copy.set(0, 0, Some(7)).is_err());
+
+        let cells: Vec<_> = shape.box_cells(3).collect();
+        assert_eq!(cells, vec![(2, 3), (2, 4), (2, 5), (3, 3), (3, 4), (3, 5)]);
+        assert!(cells.iter().all(|&(row, col)| shape.box_of(row, col) == 3));
+        let digits: Vec<_> = grid.box_iter(2).collect();
+        assert_eq!(digits, vec![Some(2), None, None, None, None, Some(4)]);
+
+        let shape = BoxShape::new(3, 4).unwrap();
+        assert_eq!(shape.box_of(3, 4), 4);
+        assert!(shape
+            .box_cells(4)
+            .all(|(row, col)| shape.box_of(row, col) == 4));
+        let full = Grid::new(shape).solve().unwrap();
+        assert_eq!(full.clues(), 144);
+        let reparsed = Grid::parse(shape, &full.to_string()).unwrap();
+        assert_eq!(reparsed, full);
+
+        assert!(matches!(
+            Grid::parse(shape, "12"),
+            Err(Error::WrongLength { .. })
+        ));
+        assert!(BoxShape::new(6, 7).is_err());
+
+        let shape = BoxShape::new(4, 4).unwrap();
+        let hex = SymbolSet::hex();
+        let full = Grid::new(shape).solve().unwrap();
+        let written = full.to_string_with(&hex);
+        assert!(written.contains('0') && written.contains('F'));
+        assert_eq!(Grid::parse_with(shape, &written, &hex).unwrap(), full);
+    }
+
+    #[test]
+    fn test_square_sizes() {
+        let shape = BoxShape::square(2).unwrap();
+        let mut grid = Grid::parse(shape, "1... ..3. .4.. ...2").unwrap();
+        assert!(grid.is_consistent() && !grid.is_valid());
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        assert!(solution.conflicts().is_empty());
+        grid.set(0, 3, Some(1)).unwrap();
+        assert_eq!(grid.conflicts(), vec![(0, 0), (0, 3)]);
+        assert!(!grid.is_consistent());
+        assert!(grid.solve().is_none());
+
+        let hex = SymbolSet::hex();
+        let shape = BoxShape::square(4).unwrap();
+        let full = Grid::new(shape).solve().unwrap();
+        assert!(full.is_valid());
+        let mut puzzle = full.clone();
+        for (row, col) in (0..16).flat_map(|row| (0..16).map(move |col| (row, col))) {
+            if (row * 7 + col * 3) % 5 < 3 {
+                puzzle.set(row, col, None).unwrap();
+            }
+        }
+        let written = puzzle.to_string_with(&hex);
+        let solution = Grid::parse_with(shape, &written, &hex)
+            .unwrap()
+            .solve()
+            .unwrap();
+        assert!(solution.is_valid());
+
+        let shape = BoxShape::square(5).unwrap();
+        let full = Grid::new(shape).solve().unwrap();
+        assert!(full.is_valid());
+        assert!(full.to_string().contains('P'));
+        assert!(BoxShape::square(7).is_err());
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let shape = BoxShape::square(3).unwrap();
+        let mut grid = Grid::new(shape);
+        grid.add_diagonals();
+        assert_eq!(grid.regions().count(), 2);
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        let diagonal: std::collections::HashSet<_> = (0..9).map(|i| solution.get(i, i)).collect();
+        assert_eq!(diagonal.len(), 9);
+        let anti: std::collections::HashSet<_> = (0..9).map(|i| solution.get(i, 8 - i)).collect();
+        assert_eq!(anti.len(), 9);
+
+        // the first row of a classic solution shifted by three each band
+        let mut classic = Grid::from(&Sudoku::new());
+        for (row, col) in (0..9).flat_map(|row| (0..9).map(move |col| (row, col))) {
+            let digit = (row * 3 + row / 3 + col) % 9 + 1;
+            classic.set(row, col, Some(digit as u8)).unwrap();
+        }
+        assert!(classic.is_valid());
+        classic.add_diagonals();
+        assert!(!classic.is_valid());
+        assert!(!classic.conflicts().is_empty());
+
+        assert!(matches!(
+            grid.add_region(&[(0, 0)]),
+            Err(Error::WrongLength { .. })
+        ));
+        let repeated = vec![(0, 0); 9];
+        assert!(matches!(
+            grid.add_region(&repeated),
+            Err(Error::InvalidShape)
+        ));
+    }
+
+    #[test]
+    fn test_windows() {
+        let grid = Grid::windoku();
+        let windows: Vec<_> = grid.regions().collect();
+        assert_eq!(windows.len(), 4);
+        assert_eq!(windows[0][0], (1, 1));
+        assert_eq!(windows[3][8], (7, 7));
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+
+        let puzzle = grid.generate_variant(3).unwrap();
+        assert!(puzzle.clues() < 81);
+        assert_eq!(puzzle.regions().count(), 4);
+        assert_eq!(puzzle.count_solutions(2), 1);
+        let mut classic = Grid::parse(puzzle.shape(), &puzzle.to_string()).unwrap();
+        assert!(classic.count_solutions(2) >= 1);
+        classic.add_windows().unwrap();
+        assert_eq!(classic, puzzle);
+
+        let mut small = Grid::new(BoxShape::square(2).unwrap());
+        small.add_windows().unwrap();
+        assert_eq!(small.regions().count(), 1);
+        let mut kids = Grid::new(BoxShape::new(2, 3).unwrap());
+        assert!(matches!(kids.add_windows(), Err(Error::InvalidShape)));
+
+        let x = Grid::sudoku_x().generate_variant(5).unwrap();
+        assert_eq!(x.count_solutions(2), 1);
+    }
+
+    #[test]
+    fn test_chess_constraints() {
+        let apart = |grid: &Grid, moves: &[(usize, usize)]| {
+            (0..81).all(|i| {
+                let (row, col) = (i / 9, i % 9);
+                moves.iter().all(|&(dr, dc)| {
+                    let (r, c) = (row + dr, col + dc);
+                    let right = c >= 9 || r >= 9 || grid.get(row, col) != grid.get(r, c);
+                    let left = dc > col || r >= 9 || grid.get(row, col) != grid.get(r, col - dc);
+                    right && left
+                })
+            })
+        };
+        let mut knight = Grid::new(BoxShape::square(3).unwrap());
+        knight.set_anti_knight(true);
+        let solution = knight.solve().unwrap();
+        assert!(solution.is_valid() && solution.is_anti_knight());
+        assert!(apart(&solution, &[(1, 2), (2, 1)]));
+
+        let mut king = Grid::new(BoxShape::square(3).unwrap());
+        king.set_anti_king(true);
+        let solution = king.solve().unwrap();
+        assert!(apart(&solution, &[(1, 1)]));
+        let puzzle = king.generate_variant(7).unwrap();
+        assert!(puzzle.is_anti_king() && puzzle.clues() < 81);
+        assert_eq!(puzzle.count_solutions(2), 1);
+
+        let mut grid = knight.clone();
+        grid.set(0, 2, Some(5)).unwrap();
+        grid.set(2, 3, Some(5)).unwrap();
+        assert_eq!(grid.conflicts(), vec![(0, 2), (2, 3)]);
+        assert!(!grid.is_consistent());
+        grid.set_anti_knight(false);
+        assert!(grid.conflicts().is_empty() && grid.is_consistent());
+    }
+
+    #[test]
+    fn test_edge_constraints() {
+        let shape = BoxShape::square(3).unwrap();
+        let mut grid = Grid::new(shape);
+        grid.set_non_consecutive(true);
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid() && solution.is_non_consecutive());
+        for (row, col) in (0..9).flat_map(|row| (0..8).map(move |col| (row, col))) {
+            let (a, b) = (solution.get(row, col), solution.get(row, col + 1));
+            assert_ne!(a.unwrap().abs_diff(b.unwrap()), 1);
+            let (a, b) = (solution.get(col, row), solution.get(col + 1, row));
+            assert_ne!(a.unwrap().abs_diff(b.unwrap()), 1);
+        }
+
+        let mut kropki = Grid::new(shape);
+        kropki.add_dot((0, 0), (0, 1), Dot::White).unwrap();
+        kropki.add_dot((1, 0), (0, 0), Dot::Black).unwrap();
+        assert_eq!(kropki.dots().count(), 2);
+        let solution = kropki.solve().unwrap();
+        let (a, b, c) = (solution.get(0, 0), solution.get(0, 1), solution.get(1, 0));
+        assert_eq!(a.unwrap().abs_diff(b.unwrap()), 1);
+        assert!(a.unwrap() == 2 * c.unwrap() || c.unwrap() == 2 * a.unwrap());
+
+        // no digit is double or half of 5, 7 or 9
+        kropki.set(1, 0, Some(7)).unwrap();
+        assert!(kropki.conflicts().is_empty() && !kropki.is_consistent());
+        kropki.set(0, 0, Some(3)).unwrap();
+        assert_eq!(kropki.conflicts(), vec![(0, 0), (1, 0)]);
+        kropki.add_dot((0, 0), (1, 0), Dot::White).unwrap();
+        assert_eq!(kropki.dots().count(), 2);
+        kropki.set(0, 0, Some(8)).unwrap();
+        assert!(kropki.conflicts().is_empty() && kropki.is_consistent());
+
+        assert!(matches!(
+            grid.add_dot((0, 0), (1, 1), Dot::White),
+            Err(Error::InvalidShape)
+        ));
+        assert!(grid.add_dot((8, 8), (8, 9), Dot::Black).is_err());
+    }
+
+    #[test]
+    fn test_parity() {
+        let mut grid = Grid::new(BoxShape::square(3).unwrap());
+        let layout = format!("EEEE.OOOO\n{}", ".........\n".repeat(8));
+        grid.parse_parities(&layout).unwrap();
+        assert_eq!(grid.parities_to_string(), layout);
+        assert_eq!(grid.parity(0, 0), Some(Parity::Even));
+        assert_eq!(grid.parity(0, 4), None);
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        // the four even digits leave an odd one in the middle
+        assert!((0..4).all(|col| Parity::Even.allows(solution.get(0, col).unwrap())));
+        assert!((4..9).all(|col| Parity::Odd.allows(solution.get(0, col).unwrap())));
+
+        grid.set(0, 0, Some(3)).unwrap();
+        assert_eq!(grid.conflicts(), [(0, 0)]);
+        assert!(!grid.is_consistent());
+        grid.set_parity(0, 0, None).unwrap();
+        assert!(grid.conflicts().is_empty());
+        grid.set_parity(0, 0, Some(Parity::Odd)).unwrap();
+        assert!(grid.parities_to_string().starts_with("OEEE.OOOO"));
+        assert!(grid.set_parity(9, 0, Some(Parity::Odd)).is_err());
+        assert!(grid.parse_parities("E").is_err());
+        assert!(matches!(
+            grid.parse_parities(&layout.replace('.', "x")),
+            Err(Error::InvalidSymbol('x'))
+        ));
+    }
+
+    #[test]
+    fn test_inequalities() {
+        let layout = "<.<\n^..v\n...\n....\n>.>\n.^..\n...\n";
+        let mut grid = Grid::new(BoxShape::square(2).unwrap());
+        grid.parse_inequalities(layout).unwrap();
+        assert_eq!(grid.inequalities_to_string(), layout);
+        assert_eq!(grid.inequalities().count(), 7);
+        assert!(grid.inequalities().any(|pair| pair == ((1, 3), (0, 3))));
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        for (a, b) in grid.inequalities() {
+            assert!(solution.get(a.0, a.1) < solution.get(b.0, b.1));
+        }
+
+        // no givens at all, the signs alone make the puzzle
+        let mut chain = Grid::new(BoxShape::square(2).unwrap());
+        chain.add_inequality((0, 0), (0, 1)).unwrap();
+        chain.add_inequality((0, 1), (0, 2)).unwrap();
+        chain.add_inequality((0, 2), (0, 3)).unwrap();
+        let solution = chain.solve().unwrap();
+        assert_eq!(
+            (0..4).map(|col| solution.get(0, col)).collect::<Vec<_>>(),
+            [Some(1), Some(2), Some(3), Some(4)]
+        );
+        chain.add_inequality((0, 3), (0, 2)).unwrap();
+        assert_eq!(chain.inequalities().count(), 3);
+        chain.set(0, 2, Some(1)).unwrap();
+        chain.set(0, 3, Some(4)).unwrap();
+        assert_eq!(chain.conflicts(), [(0, 2), (0, 3)]);
+        assert!(!chain.is_consistent());
+
+        assert!(grid.add_inequality((0, 0), (1, 1)).is_err());
+        assert!(grid.parse_inequalities("<.>").is_err());
+        assert!(matches!(
+            grid.parse_inequalities(&layout.replacen('^', "<", 1)),
+            Err(Error::InvalidSymbol('<'))
+        ));
+    }
+
+    #[test]
+    fn test_arrows() {
+        let mut grid = Grid::new(BoxShape::square(3).unwrap());
+        grid.add_arrow((0, 0), &[(1, 1), (2, 2), (3, 3)]).unwrap();
+        grid.add_arrow((8, 8), &[(8, 7), (7, 6)]).unwrap();
+        assert!(grid.add_arrow((4, 4), &[(6, 6)]).is_err());
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        let digit = |row, col| solution.get(row, col).unwrap();
+        assert_eq!(digit(0, 0), digit(1, 1) + digit(2, 2) + digit(3, 3));
+        assert_eq!(digit(8, 8), digit(8, 7) + digit(7, 6));
+
+        grid.set(0, 0, Some(2)).unwrap();
+        assert!(grid.solve().is_none());
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut grid = Grid::new(BoxShape::square(3).unwrap());
+        let palindrome = [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)];
+        let whisper = [(8, 0), (7, 1), (6, 2), (6, 3), (6, 4), (7, 5)];
+        grid.add_palindrome(&palindrome).unwrap();
+        grid.add_whisper(&whisper).unwrap();
+        assert!(grid.add_whisper(&[(0, 0)]).is_err());
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        let digit = |(row, col)| solution.get(row, col).unwrap();
+        assert_eq!(digit(palindrome[0]), digit(palindrome[4]));
+        assert_eq!(digit(palindrome[1]), digit(palindrome[3]));
+        for pair in whisper.windows(2) {
+            assert!(digit(pair[0]).abs_diff(digit(pair[1])) >= 5);
+        }
+
+        grid.set(7, 1, Some(5)).unwrap();
+        assert!(!grid.is_consistent());
+        assert!(grid.solve().is_none());
+    }
+
+    #[test]
+    fn test_constraints() {
+        /// The center box of a 9x9 grid as a magic square.
+        #[derive(Debug)]
+        struct MagicSquare;
+
+        const LINES: [[usize; 3]; 8] = [
+            [30, 31, 32],
+            [39, 40, 41],
+            [48, 49, 50],
+            [30, 39, 48],
+            [31, 40, 49],
+            [32, 41, 50],
+            [30, 40, 50],
+            [32, 40, 48],
+        ];
+
+        impl Constraint for MagicSquare {
+            fn is_satisfied(&self, cells: &[u8]) -> bool {
+                LINES.iter().all(|line| {
+                    let digits = line.map(|i| cells[i]);
+                    digits.contains(&0) || digits.iter().sum::<u8>() == 15
+                })
+            }
+
+            fn prune_candidates(&self, cells: &[u8], candidates: &mut [u64]) {
+                if cells[40] == 0 {
+                    candidates[40] &= 1 << 5;
+                }
+            }
+        }
+
+        let mut grid = Grid::new(BoxShape::square(3).unwrap());
+        grid.add_constraint(MagicSquare);
+        assert_eq!(grid.constraints().len(), 28);
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        assert!(MagicSquare.is_satisfied(&solution.cells));
+        assert_eq!(solution.get(4, 4), Some(5));
+        assert!(solution
+            .constraints()
+            .iter()
+            .all(|c| c.is_satisfied(&solution.cells)));
+
+        grid.set(3, 3, Some(1)).unwrap();
+        grid.set(3, 4, Some(2)).unwrap();
+        grid.set(3, 5, Some(3)).unwrap();
+        assert!(grid.conflicts().is_empty() && !grid.is_consistent());
+        assert!(grid.solve().is_none());
+        assert_ne!(grid, Grid::parse(grid.shape(), &grid.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_jigsaw() {
+        // the classic boxes with r1c3 of the first and r3c4 of the second
+        // box swapped
+        let mut boxes: Vec<usize> = (0..81).map(|i| (i / 27) * 3 + (i % 9) / 3).collect();
+        boxes.swap(3, 20);
+        let grid = Grid::jigsaw(&boxes).unwrap();
+        assert_eq!(grid.shape(), BoxShape::square(3).unwrap());
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_valid());
+        assert_eq!(solution.box_iter(0).count(), 9);
+        let mut first: Vec<_> = solution.box_iter(0).map(|d| d.unwrap()).collect();
+        first.sort_unstable();
+        assert_eq!(first, (1..=9).collect::<Vec<_>>());
+
+        let puzzle = grid.generate_variant(2).unwrap();
+        assert_eq!(puzzle.count_solutions(2), 1);
+
+        let mut classic = Grid::new(BoxShape::square(3).unwrap());
+        classic.set(1, 0, Some(1)).unwrap();
+        classic.set(0, 3, Some(1)).unwrap();
+        assert!(classic.is_consistent());
+        classic.set_boxes(&boxes).unwrap();
+        assert_eq!(classic.conflicts(), vec![(0, 3), (1, 0)]);
+
+        // r3c3 cut off from the rest of its box
+        let mut disconnected = boxes.clone();
+        disconnected.swap(20, 21);
+        disconnected.swap(3, 21);
+        assert!(matches!(
+            Grid::jigsaw(&disconnected),
+            Err(Error::InvalidShape)
+        ));
+        let mut uneven = boxes;
+        uneven[0] = 1;
+        assert!(matches!(Grid::jigsaw(&uneven), Err(Error::InvalidShape)));
+        assert!(Grid::jigsaw(&[0; 5]).is_err());
+    }
+
+    #[test]
+    fn test_generate() {
+        let shape = BoxShape::new(2, 4).unwrap();
+        let puzzle = Grid::generate(shape, 7);
+        assert!(puzzle.clues() < 64);
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(Grid::generate(shape, 7), puzzle);
+    }
+}
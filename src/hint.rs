@@ -0,0 +1,112 @@
+//! The next step of a human solve, for trainers and help buttons.
+use crate::explain::{English, StepFormatter};
+use crate::logic::{LogicState, LogicalSolver, SolveStep, Technique};
+use crate::{Coord, Sudoku, Value};
+
+/// A step a human can take from the current grid, with its explanation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hint {
+    pub technique: Technique,
+    /// Cells forming the pattern.
+    pub cells: Vec<Coord>,
+    pub placements: Vec<(Coord, Value)>,
+    pub eliminations: Vec<(Coord, Value)>,
+    /// The step rendered as a sentence, like "Hidden single: 7 can only go
+    /// in r3c5 in box 2."
+    pub explanation: String,
+}
+
+impl Hint {
+    pub fn new(step: &SolveStep, formatter: &dyn StepFormatter) -> Self {
+        Self {
+            technique: step.technique,
+            cells: step.cells.clone(),
+            placements: step.placements.clone(),
+            eliminations: step.eliminations.clone(),
+            explanation: formatter.format(step),
+        }
+    }
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.explanation)
+    }
+}
+
+impl Sudoku {
+    /// The easiest step the default techniques find, explained in English,
+    /// those assuming a unique solution only if the grid has one.
+    /// Candidates already removed from the grid are taken into account.
+    /// Returns `None` if the grid is solved, broken or beyond the
+    /// techniques.
+    pub fn next_hint(&self) -> Option<Hint> {
+        self.next_hint_with(&LogicalSolver::for_puzzle(self), &English)
+    }
+
+    pub fn next_hint_with(
+        &self,
+        solver: &LogicalSolver,
+        formatter: &dyn StepFormatter,
+    ) -> Option<Hint> {
+        let step = solver.next_step(&LogicState::new(self))?;
+        Some(Hint::new(&step, formatter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_hint() {
+        let sudoku: Sudoku = "\
+            4.....8.5\
+            .3.......\
+            ...7.....\
+            .2.....6.\
+            ....8.4..\
+            ....1....\
+            ...6.3.7.\
+            5..2.....\
+            1.4......"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let hint = sudoku.next_hint().unwrap();
+        assert_eq!(hint.technique, Technique::HiddenSingle);
+        assert_eq!(hint.placements.len(), 1);
+        assert_eq!(hint.cells, vec![hint.placements[0].0]);
+        assert_eq!(
+            hint.explanation,
+            "Hidden single: 4 can only go in r6c2 in box 4."
+        );
+        assert_eq!(hint.to_string(), hint.explanation);
+
+        let solved = crate::solve(&sudoku).unwrap();
+        assert!(solved.next_hint().is_none());
+    }
+
+    #[test]
+    fn test_unique_rectangle_hint() {
+        let mut grid: Sudoku =
+            "3.......7.7..4...2.6...7.5...9..3......5.8.2.13....6.4........341.9.......3.5...1"
+                .parse()
+                .unwrap();
+        loop {
+            let hint = grid.next_hint().unwrap();
+            if hint.technique == Technique::UniqueRectangle1 {
+                break;
+            }
+            for &(coord, value) in &hint.placements {
+                grid.0[coord.index()].set(value);
+            }
+            for &(coord, value) in &hint.eliminations {
+                grid.0[coord.index()].0 &= !value.mask();
+            }
+        }
+        // skipped unless the solver may assume a unique solution
+        let step = LogicalSolver::default().next_step(&LogicState::new(&grid));
+        assert_ne!(step.unwrap().technique, Technique::UniqueRectangle1);
+    }
+}
@@ -0,0 +1,294 @@
+//! Raster images of a grid, encoded as PNG for printing puzzle sheets.
+//!
+//! Digits are drawn from bitmap fonts scaled to the cells, the image is
+//! written with uncompressed deflate blocks so that no codec is needed.
+use crate::checksum::{crc32, crc32_update};
+use crate::{Error, Sudoku};
+
+/// Digits as bitmaps of rows, the leftmost pixel in the highest of `width`
+/// bits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Font {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// The glyph of digit `n` is at index `n - 1`.
+    glyphs: Vec<Vec<u32>>,
+}
+
+impl Font {
+    /// A font from the rows of the glyphs of the digits `1` to `9`, all of
+    /// the same height and at most 32 pixels wide.
+    pub fn new(width: u32, glyphs: [Vec<u32>; 9]) -> Result<Self, Error> {
+        let height = glyphs[0].len() as u32;
+        if width == 0 || width > 32 || height == 0 {
+            return Err(Error::InvalidShape);
+        }
+        for glyph in &glyphs {
+            if glyph.len() as u32 != height {
+                return Err(Error::WrongLength {
+                    expected: height as usize,
+                    found: glyph.len(),
+                });
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            glyphs: glyphs.to_vec(),
+        })
+    }
+
+    pub(crate) fn pixel(&self, digit: u8, x: u32, y: u32) -> bool {
+        self.glyphs[digit as usize - 1][y as usize] & (1 << (self.width - 1 - x)) != 0
+    }
+}
+
+impl Default for Font {
+    /// Digits 5 pixels wide and 7 high.
+    fn default() -> Self {
+        let glyphs = [
+            vec![0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+            vec![0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+            vec![0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+            vec![0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+            vec![0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+            vec![0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+            vec![0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+            vec![0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+            vec![0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+        ];
+        Self::new(5, glyphs).expect("glyphs are 7 rows of 5 pixels; qed")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageStyle {
+    /// Width and height of a cell in pixels, without the lines.
+    pub cell_size: u32,
+    /// Width of the lines between cells of a box.
+    pub thin_line: u32,
+    /// Width of the lines around the boxes and the grid.
+    pub thick_line: u32,
+    pub font: Font,
+    /// Height of the digits as a share of the cell size.
+    pub digit_height: f32,
+    /// Colors as red, green and blue.
+    pub background: [u8; 3],
+    pub lines: [u8; 3],
+    pub digits: [u8; 3],
+}
+
+impl Default for ImageStyle {
+    fn default() -> Self {
+        Self {
+            cell_size: 40,
+            thin_line: 1,
+            thick_line: 3,
+            font: Font::default(),
+            digit_height: 0.6,
+            background: [255; 3],
+            lines: [0; 3],
+            digits: [0; 3],
+        }
+    }
+}
+
+/// An RGB image, row-major.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.pixels[(row * self.width + col) as usize] = color;
+            }
+        }
+    }
+}
+
+/// Draws the grid and encodes it as a PNG file.
+pub fn render_png(sudoku: &Sudoku, style: &ImageStyle) -> Vec<u8> {
+    let (thin, thick) = (style.thin_line, style.thick_line);
+    // offset of the line before cell `i`, the tenth one closing the grid
+    let line_at = |i: u32| {
+        let thick_before = i.div_ceil(3);
+        i * style.cell_size + thick_before * thick + (i - thick_before) * thin
+    };
+    let line_width = |i: u32| if i.is_multiple_of(3) { thick } else { thin };
+    let size = line_at(9) + thick;
+    let mut canvas = Canvas {
+        width: size,
+        height: size,
+        pixels: vec![style.background; (size * size) as usize],
+    };
+    for i in 0..=9 {
+        let offset = line_at(i);
+        canvas.fill(offset, 0, line_width(i), size, style.lines);
+        canvas.fill(0, offset, size, line_width(i), style.lines);
+    }
+    let font = &style.font;
+    let scale =
+        ((style.cell_size as f32 * style.digit_height) / font.height as f32).max(1.0) as u32;
+    let (glyph_width, glyph_height) = (font.width * scale, font.height * scale);
+    for (coord, value) in sudoku {
+        let (row, col) = (coord.row() as u32, coord.col() as u32);
+        let start = |i: u32| line_at(i) + line_width(i);
+        let left = start(col) + style.cell_size.saturating_sub(glyph_width) / 2;
+        let top = start(row) + style.cell_size.saturating_sub(glyph_height) / 2;
+        let digit = value.0 + 1;
+        for y in 0..font.height {
+            for x in (0..font.width).filter(|&x| font.pixel(digit, x, y)) {
+                canvas.fill(
+                    left + x * scale,
+                    top + y * scale,
+                    scale,
+                    scale,
+                    style.digits,
+                );
+            }
+        }
+    }
+    encode(&canvas)
+}
+
+fn encode(canvas: &Canvas) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(canvas.pixels.len() * 3 + canvas.height as usize);
+    for row in canvas.pixels.chunks(canvas.width as usize) {
+        // no filter
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = Vec::new();
+    header.extend_from_slice(&canvas.width.to_be_bytes());
+    header.extend_from_slice(&canvas.height.to_be_bytes());
+    // 8 bit RGB, deflate, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+pub(crate) fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32_update(crc32(kind), data).to_be_bytes());
+}
+
+/// A zlib stream of uncompressed deflate blocks.
+pub(crate) fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = data.chunks(0xffff).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        out.push((i + 1 == blocks.len()) as u8);
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    if blocks.is_empty() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    /// The pixels of a PNG written by `encode`.
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut data = Vec::new();
+        let mut size = (0, 0);
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, body) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32_update(crc32(kind), body));
+            match kind {
+                b"IHDR" => {
+                    size = (
+                        u32::from_be_bytes(body[..4].try_into().unwrap()),
+                        u32::from_be_bytes(body[4..8].try_into().unwrap()),
+                    )
+                }
+                b"IDAT" => data.extend_from_slice(body),
+                _ => {}
+            }
+            rest = &rest[12 + len..];
+        }
+        let mut raw = Vec::new();
+        let mut block = &data[2..];
+        loop {
+            let len = u16::from_le_bytes([block[1], block[2]]) as usize;
+            raw.extend_from_slice(&block[5..5 + len]);
+            if block[0] & 1 == 1 {
+                break;
+            }
+            block = &block[5 + len..];
+        }
+        assert_eq!(
+            &block[5 + u16::from_le_bytes([block[1], block[2]]) as usize..],
+            &adler32(&raw).to_be_bytes()
+        );
+        (size.0, size.1, raw)
+    }
+
+    #[test]
+    fn test_render_png() {
+        let mut sudoku = Sudoku::new();
+        sudoku
+            .get_mut(0, 0)
+            .unwrap()
+            .set(crate::Value::new(1).unwrap());
+        let style = ImageStyle {
+            digits: [255, 0, 0],
+            ..ImageStyle::default()
+        };
+        let (width, height, raw) = decode(&render_png(&sudoku, &style));
+        // 9 cells, 6 thin and 4 thick lines
+        assert_eq!((width, height), (9 * 40 + 6 + 12, 9 * 40 + 6 + 12));
+        assert_eq!(raw.len() as u32, height * (1 + 3 * width));
+        let pixel = |x: u32, y: u32| {
+            let i = (y * (1 + 3 * width) + 1 + 3 * x) as usize;
+            [raw[i], raw[i + 1], raw[i + 2]]
+        };
+        assert_eq!(pixel(0, 0), [0; 3]);
+        assert_eq!(pixel(10, 10), [255; 3]);
+        // the stem of the 1 in the middle of the first cell
+        let red = (3..43).filter(|&y| pixel(23, y) == [255, 0, 0]).count();
+        assert_eq!(red, 21);
+        assert_eq!(pixel(63, 23), [255; 3]);
+
+        assert!(Font::new(33, Default::default()).is_err());
+        let mut glyphs: [Vec<u32>; 9] = Default::default();
+        glyphs.iter_mut().for_each(|g| *g = vec![1]);
+        glyphs[8] = vec![1, 1];
+        assert!(matches!(
+            Font::new(1, glyphs),
+            Err(Error::WrongLength {
+                expected: 1,
+                found: 2
+            })
+        ));
+    }
+}
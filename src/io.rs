@@ -0,0 +1,208 @@
+//! Puzzle files of the common desktop programs, read into and written from
+//! a [`PuzzleSet`].
+use crate::corpus::{parse_line, read_lines};
+use crate::set::PuzzleSet;
+use crate::{Error, Sudoku};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// Formats named after their file extensions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// SadMan Sudoku, nine rows with `.` for blanks after `#` header lines.
+    /// Only the `[Puzzle]` section of saved games is read.
+    Sdk,
+    /// A collection of one puzzle of 81 cells per line, `.` or `0` for
+    /// blanks.
+    Sdm,
+    /// Simple Sudoku, nine rows with `|` between the boxes and a line of
+    /// dashes between the bands.
+    Ss,
+}
+
+impl Format {
+    /// The format of a file extension, ignoring case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "sdk" => Some(Format::Sdk),
+            "sdm" => Some(Format::Sdm),
+            "ss" => Some(Format::Ss),
+            _ => None,
+        }
+    }
+
+    fn of_path(path: &Path) -> Result<Self, Error> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_extension)
+            .ok_or(Error::InvalidFormat)
+    }
+}
+
+/// Reads the puzzles of a file. The grid formats hold a single puzzle but
+/// several are read if separated by empty lines.
+pub fn read<R: BufRead>(reader: R, format: Format) -> Result<PuzzleSet, Error> {
+    if format == Format::Sdm {
+        return read_lines(reader);
+    }
+    let mut set = PuzzleSet::new();
+    let mut rows = String::new();
+    let mut skipping = false;
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(section) = line.strip_prefix('[') {
+            skipping = !section.eq_ignore_ascii_case("puzzle]");
+            continue;
+        }
+        if skipping || line.starts_with('#') || line.starts_with('-') {
+            continue;
+        }
+        if line.is_empty() {
+            if !rows.is_empty() {
+                set.push(parse_rows(&rows)?);
+                rows.clear();
+            }
+            continue;
+        }
+        let row: String = line.chars().filter(|c| !matches!(c, '|' | ' ')).collect();
+        if row.len() != 9 {
+            return Err(Error::WrongLength {
+                expected: 9,
+                found: row.len(),
+            });
+        }
+        rows.push_str(&row);
+        if rows.len() == 81 {
+            set.push(parse_rows(&rows)?);
+            rows.clear();
+        }
+    }
+    if !rows.is_empty() {
+        set.push(parse_rows(&rows)?);
+    }
+    Ok(set)
+}
+
+fn parse_rows(rows: &str) -> Result<Sudoku, Error> {
+    if rows.len() != 81 {
+        return Err(Error::WrongLength {
+            expected: 81,
+            found: rows.len(),
+        });
+    }
+    parse_line(rows.as_bytes())
+}
+
+/// Writes the puzzles, those of the grid formats separated by empty lines.
+pub fn write<'a, W: Write>(
+    mut writer: W,
+    puzzles: impl IntoIterator<Item = &'a Sudoku>,
+    format: Format,
+) -> Result<(), Error> {
+    for (i, puzzle) in puzzles.into_iter().enumerate() {
+        let line = puzzle.to_line();
+        if format == Format::Sdm {
+            writeln!(writer, "{}", line)?;
+            continue;
+        }
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        for (row, cells) in line.as_bytes().chunks(9).enumerate() {
+            let cells = std::str::from_utf8(cells).expect("lines are ascii; qed");
+            match format {
+                Format::Ss => {
+                    if row == 3 || row == 6 {
+                        writeln!(writer, "-----------")?;
+                    }
+                    let (a, rest) = cells.split_at(3);
+                    let (b, c) = rest.split_at(3);
+                    writeln!(writer, "{}|{}|{}", a, b, c)?;
+                }
+                _ => writeln!(writer, "{}", cells)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a file in the format of its extension.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<PuzzleSet, Error> {
+    let format = Format::of_path(path.as_ref())?;
+    read(BufReader::new(std::fs::File::open(path)?), format)
+}
+
+/// Writes a file in the format of its extension.
+pub fn write_file<'a, P: AsRef<Path>>(
+    path: P,
+    puzzles: impl IntoIterator<Item = &'a Sudoku>,
+) -> Result<(), Error> {
+    let format = Format::of_path(path.as_ref())?;
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write(&mut writer, puzzles, format)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    #[test]
+    fn test_sdk() {
+        let file = "#A Someone\n#D An easy one\n[Puzzle]\n..3.2.6..\n9..3.5..1\n..18.64..\n\
+                    ..81.29..\n7.......8\n..67.82..\n..26.95..\n8..2.3..9\n..5.1.3..\n\
+                    [State]\n4.3.2.6..\n9..3.5..1\n..18.64..\n..81.29..\n7.......8\n\
+                    ..67.82..\n..26.95..\n8..2.3..9\n..5.1.3..\n";
+        let set = read(file.as_bytes(), Format::Sdk).unwrap();
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.get(0).unwrap().to_line(), LINE);
+
+        let mut written = Vec::new();
+        write(&mut written, set.iter(), Format::Sdk).unwrap();
+        assert!(String::from_utf8_lossy(&written).starts_with("..3.2.6..\n9..3.5..1\n"));
+        let reread = read(&written[..], Format::Sdk).unwrap();
+        assert_eq!(reread.get(0).unwrap().to_line(), LINE);
+
+        assert!(matches!(
+            read("..3.2.6..\n9..3.5..1\n".as_bytes(), Format::Sdk),
+            Err(Error::WrongLength {
+                expected: 81,
+                found: 18
+            })
+        ));
+        assert!(read("..3.2.6\n".as_bytes(), Format::Sdk).is_err());
+    }
+
+    #[test]
+    fn test_ss() {
+        let puzzle: Sudoku = LINE.parse().unwrap();
+        let mut written = Vec::new();
+        write(&mut written, [&puzzle, &puzzle], Format::Ss).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        assert!(written.starts_with("..3|.2.|6..\n9..|3.5|..1\n..1|8.6|4..\n-----------\n"));
+        let set = read(written.as_bytes(), Format::Ss).unwrap();
+        assert_eq!(set.len(), 2);
+        assert!(set.iter().all(|p| p.to_line() == LINE));
+    }
+
+    #[test]
+    fn test_sdm() {
+        let puzzle: Sudoku = LINE.parse().unwrap();
+        let mut written = Vec::new();
+        write(&mut written, [&puzzle; 3], Format::Sdm).unwrap();
+        assert_eq!(written.len(), 3 * 82);
+        assert_eq!(read(&written[..], Format::Sdm).unwrap().len(), 3);
+
+        assert_eq!(Format::from_extension("SDM"), Some(Format::Sdm));
+        assert_eq!(Format::from_extension("txt"), None);
+        assert!(matches!(
+            read_file("puzzles.txt"),
+            Err(Error::InvalidFormat)
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+use crate::rating::{se_rating, RatedPuzzle};
+use crate::solver::solve;
+use crate::verified::SolvedSudoku;
+use crate::Sudoku;
+use std::collections::HashSet;
+use std::ops::RangeBounds;
+
+/// Pipeline stages for streams of puzzles.
+pub trait PuzzleIteratorExt: Iterator<Item = Sudoku> + Sized {
+    /// Pairs each puzzle with a solution, dropping unsolvable puzzles.
+    fn solved(self) -> Solved<Self> {
+        Solved { iter: self }
+    }
+
+    fn rated(self) -> Rated<Self> {
+        Rated { iter: self }
+    }
+
+    /// Keeps puzzles whose Sudoku Explainer rating lies in `range`.
+    fn filter_difficulty<R: RangeBounds<f32>>(self, range: R) -> FilterDifficulty<Self, R> {
+        FilterDifficulty { iter: self, range }
+    }
+
+    /// Keeps puzzles with exactly one solution.
+    fn unique_only(self) -> UniqueOnly<Self> {
+        UniqueOnly { iter: self }
+    }
+
+    /// Drops puzzles equivalent to an earlier puzzle of the stream.
+    fn canonical_dedup(self) -> CanonicalDedup<Self> {
+        CanonicalDedup {
+            iter: self,
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Sudoku>> PuzzleIteratorExt for I {}
+
+pub struct Solved<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = Sudoku>> Iterator for Solved<I> {
+    type Item = (Sudoku, SolvedSudoku);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .by_ref()
+            .find_map(|puzzle| solve(&puzzle).map(|solution| (puzzle, solution)))
+    }
+}
+
+pub struct Rated<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = Sudoku>> Iterator for Rated<I> {
+    type Item = RatedPuzzle;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(RatedPuzzle::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+pub struct FilterDifficulty<I, R> {
+    iter: I,
+    range: R,
+}
+
+impl<I: Iterator<Item = Sudoku>, R: RangeBounds<f32>> Iterator for FilterDifficulty<I, R> {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = &self.range;
+        self.iter
+            .by_ref()
+            .find(|puzzle| range.contains(&se_rating(puzzle).er))
+    }
+}
+
+pub struct UniqueOnly<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = Sudoku>> Iterator for UniqueOnly<I> {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .by_ref()
+            .find(|puzzle| puzzle.has_unique_solution())
+    }
+}
+
+pub struct CanonicalDedup<I> {
+    iter: I,
+    seen: HashSet<Vec<u8>>,
+}
+
+impl<I: Iterator<Item = Sudoku>> Iterator for CanonicalDedup<I> {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let seen = &mut self.seen;
+        self.iter.by_ref().find(|puzzle| {
+            let canonical = crate::canonical_form(puzzle).digits().to_vec();
+            seen.insert(canonical)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_puzzle_iterator_ext() {
+        let puzzle: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let puzzles = vec![puzzle, Sudoku::new(), puzzle.transposed()];
+        assert_eq!(puzzles.clone().into_iter().unique_only().count(), 2);
+        assert_eq!(puzzles.clone().into_iter().solved().count(), 3);
+        let deduped: Vec<_> = puzzles
+            .clone()
+            .into_iter()
+            .unique_only()
+            .canonical_dedup()
+            .collect();
+        assert_eq!(deduped.len(), 1);
+        let easy: Vec<_> = puzzles
+            .into_iter()
+            .filter_difficulty(..2.0)
+            .rated()
+            .collect();
+        assert_eq!(easy.len(), 2);
+        assert!(easy.iter().all(|rated| rated.rating.solved));
+    }
+}
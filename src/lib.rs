@@ -1,6 +1,192 @@
-use std::num::ParseIntError;
+//! Sudoku grids, solvers, generators and ratings.
+//!
+//! Without the default `std` feature only the grid types, validation and
+//! the backtracking solver are built, as `no_std` and without allocating.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::num::ParseIntError;
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod annotated;
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "std")]
+pub mod calibration;
+pub mod candidate_set;
+#[cfg(feature = "std")]
+pub mod candidates;
+#[cfg(feature = "std")]
+pub mod canonical;
+#[cfg(feature = "std")]
+mod checksum;
+#[cfg(feature = "std")]
+pub mod constraint;
+#[cfg(feature = "std")]
+pub mod corpus;
+#[cfg(feature = "std")]
+pub mod db;
+#[cfg(feature = "std")]
+pub mod dlx;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod fingerprint;
+#[cfg(feature = "std")]
+pub mod game;
+#[cfg(feature = "std")]
+pub mod generator;
+#[cfg(feature = "std")]
+pub mod grid;
+#[cfg(feature = "std")]
+pub mod hint;
+#[cfg(feature = "image")]
+pub mod image;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod iter;
+#[cfg(feature = "std")]
+pub mod logic;
+mod masks;
+#[cfg(all(feature = "mmap", unix))]
+pub mod mmap;
+#[cfg(feature = "std")]
+pub mod notation;
+pub mod notes;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+#[cfg(feature = "std")]
+pub mod parse;
+#[cfg(feature = "std")]
+pub mod pattern;
+#[cfg(feature = "puzzles")]
+pub mod puzzles;
+#[cfg(feature = "std")]
+pub mod rating;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(feature = "std")]
+pub mod samurai;
+#[cfg(feature = "std")]
+pub mod sandwich;
+#[cfg(feature = "sat")]
+pub mod sat;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod service;
+#[cfg(feature = "std")]
+pub mod set;
+#[cfg(feature = "std")]
+mod share;
+#[cfg(feature = "async")]
+pub mod solve_async;
+pub mod solver;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "std")]
+pub mod symbols;
+mod tables;
+#[cfg(feature = "std")]
+pub mod transform;
+#[cfg(all(feature = "tui", unix))]
+pub mod tui;
+pub mod verified;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+#[cfg(feature = "std")]
+pub use annotated::{AnnotatedGrid, Annotation};
+#[cfg(feature = "std")]
+pub use backend::{benchmark, Backend, BackendStats, SolveReport, Solver, SolverBuilder};
+pub use candidate_set::CandidateSet;
+#[cfg(feature = "std")]
+pub use candidates::CandidateGrid;
+#[cfg(feature = "std")]
+pub use canonical::canonical_form;
+#[cfg(feature = "std")]
+pub use constraint::{Arrow, Constraint, Distinct, Palindrome, Sandwich, Whisper};
+#[cfg(feature = "std")]
+pub use corpus::{parse_collection, Corpus};
+#[cfg(feature = "std")]
+pub use db::{PuzzleDb, Query};
+#[cfg(feature = "std")]
+pub use explain::{English, StepFormatter};
+#[cfg(feature = "std")]
+pub use fingerprint::{canonical_fingerprint, fingerprint};
+#[cfg(feature = "std")]
+pub use game::{Action, Game, GameEvent, Move};
+#[cfg(feature = "std")]
+pub use generator::{
+    evolve, generate, generate_batch, generate_batch_with, generate_with, generate_with_clues,
+    generate_with_seed, generate_with_techniques, puzzle_stream, reclue, ClueTarget,
+    GenerateOptions, PuzzleStream, ReclueOptions,
+};
+#[cfg(feature = "std")]
+pub use grid::{BoxShape, Dot, Grid, Parity};
+#[cfg(feature = "std")]
+pub use hint::Hint;
+#[cfg(feature = "std")]
+pub use iter::PuzzleIteratorExt;
+#[cfg(feature = "std")]
+pub use logic::{LogicalSolver, SolveStep, Technique};
+pub use notes::NotedSudoku;
+#[cfg(feature = "std")]
+pub use parse::{parse_flexible, parse_lossy, IssueKind, ParseIssue};
+#[cfg(feature = "std")]
+pub use pattern::{generate_with_pattern, Pattern, PatternOptions};
+#[cfg(feature = "std")]
+pub use rating::{
+    classify, rate_batch, rating_profile, se_rating, Bucket, RatedPuzzle, Rating, RatingConfig,
+    RatingProfile, RatingReport, SeRating, Tier,
+};
+#[cfg(feature = "std")]
+pub use render::{render_ansi, render_html, render_latex, render_plain, render_pretty, GridStyle};
+#[cfg(feature = "color")]
+pub use render::{render_color, Highlight};
+#[cfg(feature = "std")]
+pub use samurai::Samurai;
+#[cfg(feature = "std")]
+pub use sandwich::SandwichSudoku;
+#[cfg(feature = "std")]
+pub use search::{SearchState, SearchStatus};
+#[cfg(feature = "std")]
+pub use service::{GenerationEvent, GenerationRequest, GenerationService};
+#[cfg(feature = "std")]
+pub use set::PuzzleSet;
+#[cfg(feature = "async")]
+pub use solve_async::{solve_async, SolveHandle};
+#[cfg(feature = "rayon")]
+pub use solver::solve_parallel;
+pub use solver::{solve, Heuristic, SolveStats, SolverConfig};
+#[cfg(feature = "std")]
+pub use solver::{
+    solve_batch, solve_unique, solve_with_events, solve_with_options, solve_with_stats, SolveEvent,
+    SolveOptions, SolveOutcome, SolveResult, SolveUniqueError, WhatIf,
+};
+#[cfg(feature = "std")]
+pub use symbols::SymbolSet;
+#[cfg(feature = "std")]
+pub use transform::{Axis, DigitPermutation, Quarter};
+#[cfg(feature = "std")]
+pub use verified::ProperPuzzle;
+pub use verified::SolvedSudoku;
+
+/// Grids are equal when every cell has the same candidates.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Sudoku([Cell; 81]);
 
 impl Sudoku {
@@ -8,73 +194,523 @@ impl Sudoku {
         Self([Default::default(); 81])
     }
 
+    /// `None` if `x` or `y` is out of range.
     pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
-        let i = x * 9 + y;
-        self.0.get(i)
+        let coord = Coord::try_new(x, y).ok()?;
+        Some(&self.0[coord.index()])
     }
 
+    /// `None` if `x` or `y` is out of range.
     pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Cell> {
-        let i = x * 9 + y;
-        self.0.get_mut(i)
+        let coord = Coord::try_new(x, y).ok()?;
+        Some(&mut self.0[coord.index()])
     }
 
-    fn validate_group(&self, iter: impl Iterator<Item = (usize, usize)>) -> bool {
-        let mut check = 0;
-        for (x, y) in iter {
-            let cell = self.get(x, y).unwrap();
-            if !cell.is_final() {
-                return false;
+    /// Row-major digits with `0` for cells without a value.
+    pub(crate) fn digits(&self) -> [u8; 81] {
+        let mut digits = [0; 81];
+        for (digit, cell) in digits.iter_mut().zip(self.0.iter()) {
+            if let Some(value) = cell.value() {
+                *digit = value.0 + 1;
             }
-            check |= cell.0
         }
-        check == 0b1_1111_1111
+        digits
     }
 
-    pub fn valid(&self) -> bool {
-        for i in 0..9 {
-            let row = self.validate_group(row_iter(i));
-            let col = self.validate_group(col_iter(i));
-            let block = self.validate_group(block_iter(i));
-            if !(row && col && block) {
-                return false;
+    /// Inverse of `digits`, digits outside `1..=9` become empty cells.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_digits(digits: &[u8; 81]) -> Self {
+        let mut sudoku = Self::new();
+        for (cell, &digit) in sudoku.0.iter_mut().zip(digits.iter()) {
+            if let Ok(value) = Value::new(digit) {
+                cell.set(value);
+            }
+        }
+        sudoku
+    }
+
+    /// Iterates over the cells with a value in row-major order.
+    pub fn iter_filled(&self) -> Iter<'_> {
+        Iter {
+            sudoku: self,
+            index: 0,
+        }
+    }
+
+    pub fn iter_empty(&self) -> impl Iterator<Item = Coord> + '_ {
+        (0..81)
+            .filter(move |&i| !self.0[i].is_final())
+            .map(Coord::from_index)
+    }
+
+    /// Number of cells with a value.
+    pub fn filled_count(&self) -> usize {
+        self.0.iter().filter(|cell| cell.is_final()).count()
+    }
+
+    /// The share of cells with a value, from 0.0 for an empty grid to 1.0
+    /// for a full one.
+    pub fn completion_ratio(&self) -> f32 {
+        self.filled_count() as f32 / 81.0
+    }
+
+    /// How many of each digit are still to be placed, indexed by digit
+    /// minus one. Zero once a digit is placed nine times, or more often in
+    /// an inconsistent grid.
+    pub fn remaining_per_digit(&self) -> [u8; 9] {
+        let mut remaining = [9u8; 9];
+        for (_, value) in self {
+            remaining[value.0 as usize] = remaining[value.0 as usize].saturating_sub(1);
+        }
+        remaining
+    }
+
+    /// All 81 cells in row-major order with their values.
+    pub fn cells(&self) -> impl Iterator<Item = (Coord, Option<Value>)> + '_ {
+        (0..81).map(move |i| (Coord::from_index(i), self.0[i].value()))
+    }
+
+    /// The cells whose values differ, in row-major order, with the value
+    /// here and the one in `other`, `None` for an empty cell. A solution
+    /// only fills in the puzzle when each old value is `None`.
+    #[cfg(feature = "std")]
+    pub fn diff(&self, other: &Sudoku) -> Vec<(Coord, Option<Value>, Option<Value>)> {
+        self.cells()
+            .zip(other.cells())
+            .filter(|((_, a), (_, b))| a != b)
+            .map(|((coord, a), (_, b))| (coord, a, b))
+            .collect()
+    }
+
+    /// The cells of a unit in the order of `Unit::cells`.
+    pub fn unit(&self, unit: Unit) -> impl Iterator<Item = (Coord, Option<Value>)> + '_ {
+        unit.cells().map(move |(x, y)| {
+            let coord = Coord::new(x, y);
+            (coord, self[coord].value())
+        })
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = (Coord, Option<Value>)> + '_> {
+        (0..9).map(move |row| self.unit(Unit::Row(row)))
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = impl Iterator<Item = (Coord, Option<Value>)> + '_> {
+        (0..9).map(move |col| self.unit(Unit::Col(col)))
+    }
+
+    pub fn blocks(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = (Coord, Option<Value>)> + '_> {
+        (0..9).map(move |block| self.unit(Unit::Block(block)))
+    }
+
+    /// Rows, then columns, then blocks, as `Unit::all`.
+    pub fn units(&self) -> impl Iterator<Item = impl Iterator<Item = (Coord, Option<Value>)> + '_> {
+        Unit::all().map(move |unit| self.unit(unit))
+    }
+
+    /// Builds a grid from a matrix of optional digits, indexed by row then
+    /// column.
+    pub fn from_partial(rows: [[Option<u8>; 9]; 9]) -> Result<Self, Error> {
+        let mut sudoku = Sudoku::new();
+        for (x, row) in rows.iter().enumerate() {
+            for (y, digit) in row.iter().enumerate() {
+                if let Some(digit) = *digit {
+                    sudoku.get_mut(x, y).unwrap().set(Value::new(digit)?);
+                }
+            }
+        }
+        Ok(sudoku)
+    }
+
+    /// The 81 cells in row-major order on a single line, `.` for blanks.
+    #[cfg(feature = "std")]
+    pub fn to_line(&self) -> String {
+        self.0
+            .iter()
+            .map(|cell| match cell.value() {
+                Some(value) => (b'1' + value.0) as char,
+                None => '.',
+            })
+            .collect()
+    }
+
+    /// Inverse of `from_partial`.
+    pub fn to_partial(&self) -> [[Option<u8>; 9]; 9] {
+        let mut rows = [[None; 9]; 9];
+        for (coord, value) in self {
+            rows[coord.row()][coord.col()] = Some(value.0 + 1);
+        }
+        rows
+    }
+
+    /// Returns the cells of a block, `None` for cells without a value.
+    pub fn get_block(&self, block: usize) -> [[Option<Value>; 3]; 3] {
+        let mut cells = [[None; 3]; 3];
+        for (i, (x, y)) in Unit::Block(block).cells().enumerate() {
+            cells[i / 3][i % 3] = self.get(x, y).unwrap().value();
+        }
+        cells
+    }
+
+    /// Overwrites the cells of a block, `None` clears a cell.
+    pub fn set_block(&mut self, block: usize, cells: [[Option<Value>; 3]; 3]) {
+        for (i, (x, y)) in Unit::Block(block).cells().enumerate() {
+            let cell = self.get_mut(x, y).unwrap();
+            match cells[i / 3][i % 3] {
+                Some(value) => cell.set(value),
+                None => *cell = Cell::new(),
+            }
+        }
+    }
+
+    /// The verdict of `report`, conflicts taking precedence over empty
+    /// cells.
+    #[cfg(feature = "std")]
+    pub fn validate(&self) -> Solution {
+        // grids without conflicts or empty cells need no unit scan
+        if self.valid() {
+            return Solution::Valid;
+        }
+        let report = self.report();
+        if !report.conflicts.is_empty() {
+            Solution::Invalid(report.conflicts)
+        } else if !report.empty.is_empty() {
+            Solution::Incomplete(report.empty)
+        } else {
+            Solution::Valid
+        }
+    }
+
+    /// Both the conflicts and the empty cells of every unit.
+    #[cfg(feature = "std")]
+    pub fn report(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for unit in Unit::all() {
+            self.report_unit(unit, &mut report);
+        }
+        report
+    }
+
+    /// Like `report` for a single unit, valid once the unit is complete
+    /// without a repeated digit. Panics if the unit's index is out of
+    /// range.
+    #[cfg(feature = "std")]
+    pub fn check_unit(&self, unit: Unit) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        self.report_unit(unit, &mut report);
+        report
+    }
+
+    #[cfg(feature = "std")]
+    pub fn check_row(&self, row: usize) -> ValidationReport {
+        self.check_unit(Unit::Row(row))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn check_col(&self, col: usize) -> ValidationReport {
+        self.check_unit(Unit::Col(col))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn check_block(&self, block: usize) -> ValidationReport {
+        self.check_unit(Unit::Block(block))
+    }
+
+    #[cfg(feature = "std")]
+    fn report_unit(&self, unit: Unit, report: &mut ValidationReport) {
+        let mut seen: [Vec<(usize, usize)>; 9] = Default::default();
+        let mut empty = Vec::new();
+        for (x, y) in unit.cells() {
+            match self[(x, y)].value() {
+                Some(value) => seen[value.0 as usize].push((x, y)),
+                None => empty.push((x, y)),
+            }
+        }
+        for (i, cells) in seen.iter().enumerate() {
+            if cells.len() > 1 {
+                report.conflicts.push(Conflict {
+                    unit,
+                    value: Value(i as u8),
+                    cells: cells.clone(),
+                });
+            }
+        }
+        if !empty.is_empty() {
+            report.empty.push(EmptyCells { unit, cells: empty });
+        }
+    }
+
+    /// The pairs of cells that share a unit and a value, each once with
+    /// the earlier cell in row-major order first.
+    #[cfg(feature = "std")]
+    pub fn conflicts(&self) -> Vec<(Coord, Coord)> {
+        let mut pairs = Vec::new();
+        for (coord, value) in self {
+            for peer in coord.peers() {
+                if peer > coord && self[peer].value() == Some(value) {
+                    pairs.push((coord, peer));
+                }
             }
         }
-        true
+        pairs.sort();
+        pairs
+    }
+
+    /// Whether this is a solved grid agreeing with every given of
+    /// `puzzle`.
+    pub fn is_completion_of(&self, puzzle: &Sudoku) -> bool {
+        self.valid()
+            && puzzle
+                .into_iter()
+                .all(|(coord, value)| self[coord].value() == Some(value))
     }
 
+    pub fn valid(&self) -> bool {
+        self.0.iter().all(Cell::is_final) && self.is_consistent()
+    }
+
+    /// Whether no unit repeats a value, empty cells allowed.
+    pub fn is_consistent(&self) -> bool {
+        masks::UnitMasks::from_sudoku(self).is_some()
+    }
+
+    #[cfg(feature = "std")]
     pub fn rows_mut<'a>(
         &'a mut self,
     ) -> impl Iterator<Item = impl Iterator<Item = &'a mut Cell> + 'a> + 'a {
         let mut cells: Vec<Option<&mut Cell>> = SudokuIterMut {
             sudoku: self,
-            indices: (0..9).map(row_iter).flatten(),
+            indices: (0..9).flat_map(|row| Unit::Row(row).cells()),
         }
-        .map(|c| Some(c))
+        .map(Some)
         .collect();
         let cells: Vec<Vec<Option<&mut Cell>>> = cells
             .chunks_mut(9)
-            .map(|chunk| chunk.into_iter().map(|e| e.take()).collect::<Vec<_>>())
+            .map(|chunk| chunk.iter_mut().map(|e| e.take()).collect::<Vec<_>>())
             .collect();
         cells.into_iter().map(|v| v.into_iter().map(|e| e.unwrap()))
     }
 }
 
-fn row_iter(row: usize) -> impl Iterator<Item = (usize, usize)> {
-    (0..9).map(move |field| (row, field))
+impl<'a> IntoIterator for &'a Sudoku {
+    type Item = (Coord, Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_filled()
+    }
 }
 
-fn col_iter(col: usize) -> impl Iterator<Item = (usize, usize)> {
-    (0..9).map(move |field| (field, col))
+pub struct Iter<'a> {
+    sudoku: &'a Sudoku,
+    index: usize,
 }
 
-fn block_iter(block: usize) -> impl Iterator<Item = (usize, usize)> {
-    let offset = ((block % 3) * 3, (block / 3) * 3);
-    std::iter::once(0)
-        .cycle()
-        .zip(0..3)
-        .chain(std::iter::once(1).cycle().zip(0..3))
-        .chain(std::iter::once(2).cycle().zip(0..3))
-        .map(move |(x, y)| (x + offset.0, y + offset.1))
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Coord, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < 81 {
+            let i = self.index;
+            self.index += 1;
+            if let Some(value) = self.sudoku.0[i].value() {
+                return Some((Coord::from_index(i), value));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Coord {
+    row: u8,
+    col: u8,
+}
+
+impl Coord {
+    /// Panics if `row` or `col` is out of range.
+    pub fn new(row: usize, col: usize) -> Self {
+        assert!(row < 9 && col < 9, "coordinate out of range");
+        Self {
+            row: row as u8,
+            col: col as u8,
+        }
+    }
+
+    pub fn try_new(row: usize, col: usize) -> Result<Self, Error> {
+        if row < 9 && col < 9 {
+            Ok(Self::new(row, col))
+        } else {
+            Err(Error::OutOfBounds { row, col })
+        }
+    }
+
+    pub(crate) fn from_index(i: usize) -> Self {
+        Self::new(i / 9, i % 9)
+    }
+
+    pub fn row(&self) -> usize {
+        self.row as usize
+    }
+
+    pub fn col(&self) -> usize {
+        self.col as usize
+    }
+
+    /// Row-major index of the block containing the cell.
+    pub fn block(&self) -> usize {
+        block_of(*self)
+    }
+
+    /// Row-major index of the cell.
+    pub fn index(&self) -> usize {
+        self.row() * 9 + self.col()
+    }
+
+    /// The 20 other cells sharing a row, column or block with this one,
+    /// each once.
+    pub fn peers(self) -> impl Iterator<Item = Coord> {
+        tables::PEERS[self.index()]
+            .iter()
+            .map(|&i| Coord::from_index(i as usize))
+    }
+}
+
+pub fn block_of(coord: Coord) -> usize {
+    (coord.row() / 3) * 3 + coord.col() / 3
+}
+
+impl From<Coord> for (usize, usize) {
+    fn from(coord: Coord) -> Self {
+        (coord.row(), coord.col())
+    }
+}
+
+impl core::fmt::Display for Coord {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "r{}c{}", self.row + 1, self.col + 1)
+    }
+}
+
+/// Parses the `r1c7` form `Display` writes.
+impl core::str::FromStr for Coord {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let b = s.as_bytes();
+        match b {
+            [b'r' | b'R', row @ b'1'..=b'9', b'c' | b'C', col @ b'1'..=b'9'] => {
+                Ok(Coord::new((row - b'1') as usize, (col - b'1') as usize))
+            }
+            _ => Err(Error::InvalidCoord),
+        }
+    }
+}
+
+/// Cells sharing a row, column or block with `(x, y)`, as `Coord::peers`.
+pub(crate) fn peers(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    tables::PEERS[Coord::new(x, y).index()]
+        .iter()
+        .map(|&i| (i as usize / 9, i as usize % 9))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    Block(usize),
+}
+
+impl Unit {
+    pub fn all() -> impl Iterator<Item = Unit> {
+        (0..9)
+            .map(Unit::Row)
+            .chain((0..9).map(Unit::Col))
+            .chain((0..9).map(Unit::Block))
+    }
+
+    pub fn cell(&self, i: usize) -> (usize, usize) {
+        match *self {
+            Unit::Row(row) => (row, i),
+            Unit::Col(col) => (i, col),
+            Unit::Block(block) => ((block / 3) * 3 + i / 3, (block % 3) * 3 + i % 3),
+        }
+    }
+
+    pub fn cells(self) -> impl Iterator<Item = (usize, usize)> {
+        let (first, i) = match self {
+            Unit::Row(row) => (0, row),
+            Unit::Col(col) => (9, col),
+            Unit::Block(block) => (18, block),
+        };
+        assert!(i < 9, "{:?} is outside the grid", self);
+        tables::UNITS[first + i]
+            .iter()
+            .map(|&i| (i as usize / 9, i as usize % 9))
+    }
+
+    pub fn coords(self) -> impl Iterator<Item = Coord> {
+        self.cells().map(|(x, y)| Coord::new(x, y))
+    }
+}
+
+impl core::fmt::Display for Unit {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Unit::Row(row) => write!(f, "row {}", row + 1),
+            Unit::Col(col) => write!(f, "column {}", col + 1),
+            Unit::Block(block) => write!(f, "block {}", block + 1),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Solution {
+    Valid,
+    Invalid(Vec<Conflict>),
+    Incomplete(Vec<EmptyCells>),
+}
+
+/// A value that occurs in more than one cell of a unit.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Conflict {
+    pub unit: Unit,
+    pub value: Value,
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// The cells of a unit that don't have a value yet.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmptyCells {
+    pub unit: Unit,
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Everything wrong with a grid, in the order of `Unit::all`.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    pub conflicts: Vec<Conflict>,
+    pub empty: Vec<EmptyCells>,
+}
+
+#[cfg(feature = "std")]
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.conflicts.is_empty() && self.empty.is_empty()
+    }
+
+    /// The units repeating a value, each once.
+    pub fn violated_units(&self) -> Vec<Unit> {
+        let mut units: Vec<Unit> = self.conflicts.iter().map(|c| c.unit).collect();
+        units.dedup();
+        units
+    }
 }
 
 pub struct SudokuIterMut<'a, I> {
@@ -98,40 +734,208 @@ impl<'a, I: Iterator<Item = (usize, usize)>> Iterator for SudokuIterMut<'a, I> {
     }
 }
 
+impl Sudoku {
+    /// Builds a grid from placements, failing if a cell is set twice or a
+    /// value repeats in a row, column or block.
+    pub fn try_from_iter<I: IntoIterator<Item = (Coord, Value)>>(iter: I) -> Result<Self, Error> {
+        let mut sudoku = Sudoku::new();
+        for (coord, value) in iter {
+            let (x, y) = coord.into();
+            if sudoku.0[coord.index()].is_final() {
+                return Err(Error::DuplicateCell(coord));
+            }
+            if peers(x, y).any(|(px, py)| sudoku.get(px, py).unwrap().value() == Some(value)) {
+                return Err(Error::ConflictingValue(coord));
+            }
+            sudoku.0[coord.index()].set(value);
+        }
+        Ok(sudoku)
+    }
+}
+
+/// Later placements overwrite earlier ones, no validation is performed. Use
+/// `Sudoku::try_from_iter` to reject inconsistent input.
+impl core::iter::FromIterator<(Coord, Value)> for Sudoku {
+    fn from_iter<I: IntoIterator<Item = (Coord, Value)>>(iter: I) -> Self {
+        let mut sudoku = Sudoku::new();
+        sudoku.extend(iter);
+        sudoku
+    }
+}
+
+/// Fills the cells in row-major order, blanks for `None`. Cells past the
+/// end of the iterator stay empty and values past the 81st are dropped.
+impl core::iter::FromIterator<Option<Value>> for Sudoku {
+    fn from_iter<I: IntoIterator<Item = Option<Value>>>(iter: I) -> Self {
+        let mut sudoku = Sudoku::new();
+        for (cell, value) in sudoku.0.iter_mut().zip(iter) {
+            if let Some(value) = value {
+                cell.set(value);
+            }
+        }
+        sudoku
+    }
+}
+
+/// Fills the cells in row-major order as the `Option<Value>` version.
+impl core::iter::FromIterator<Value> for Sudoku {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        iter.into_iter().map(Some).collect()
+    }
+}
+
+/// Row-major digits, `0` for blanks.
+impl core::convert::TryFrom<[u8; 81]> for Sudoku {
+    type Error = Error;
+
+    fn try_from(digits: [u8; 81]) -> Result<Self, Error> {
+        Sudoku::try_from(&digits[..])
+    }
+}
+
+/// Row-major digits, `0` for blanks, exactly 81 of them.
+impl core::convert::TryFrom<&[u8]> for Sudoku {
+    type Error = Error;
+
+    fn try_from(digits: &[u8]) -> Result<Self, Error> {
+        if digits.len() != 81 {
+            return Err(Error::WrongLength {
+                expected: 81,
+                found: digits.len(),
+            });
+        }
+        let mut sudoku = Sudoku::new();
+        for (cell, &digit) in sudoku.0.iter_mut().zip(digits) {
+            if digit != 0 {
+                cell.set(Value::new(digit)?);
+            }
+        }
+        Ok(sudoku)
+    }
+}
+
+/// Row-major digits, `0` for cells without a value.
+impl From<Sudoku> for [u8; 81] {
+    fn from(sudoku: Sudoku) -> Self {
+        sudoku.digits()
+    }
+}
+
+/// Overwrites the cells with the given values, no validation is performed.
+impl Extend<(Coord, Value)> for Sudoku {
+    fn extend<I: IntoIterator<Item = (Coord, Value)>>(&mut self, iter: I) {
+        for (coord, value) in iter {
+            self.0[coord.index()].set(value);
+        }
+    }
+}
+
 impl Default for Sudoku {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl std::str::FromStr for Sudoku {
+impl core::ops::Index<Coord> for Sudoku {
+    type Output = Cell;
+
+    fn index(&self, coord: Coord) -> &Cell {
+        &self.0[coord.index()]
+    }
+}
+
+impl core::ops::IndexMut<Coord> for Sudoku {
+    fn index_mut(&mut self, coord: Coord) -> &mut Cell {
+        &mut self.0[coord.index()]
+    }
+}
+
+/// Panics if the row or column is out of range.
+impl core::ops::Index<(usize, usize)> for Sudoku {
+    type Output = Cell;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Cell {
+        &self[Coord::new(row, col)]
+    }
+}
+
+/// Panics if the row or column is out of range.
+impl core::ops::IndexMut<(usize, usize)> for Sudoku {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Cell {
+        &mut self[Coord::new(row, col)]
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::str::FromStr for Sudoku {
     type Err = Error;
 
+    /// Reads either one row per line with spaces for blanks, or all 81
+    /// cells on a single line with `.`, `0` or a space for blanks as used by
+    /// puzzle collections. Fails on the first character that isn't a cell,
+    /// including those past the ninth row or column.
     fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let line = string.trim_matches(|c| c == '\n' || c == '\r');
         let mut sudoku = Sudoku::new();
+        if line.chars().count() == 81 && !line.contains('\n') {
+            for (column, (cell, c)) in sudoku.0.iter_mut().zip(line.chars()).enumerate() {
+                match c {
+                    '.' | '0' | ' ' => {}
+                    '1'..='9' => cell.set(Value(c as u8 - b'1')),
+                    found => return Err(parse_error(0, column, found)),
+                }
+            }
+            return Ok(sudoku);
+        }
         for (x, row) in string.split('\n').enumerate() {
+            let row = row.strip_suffix('\r').unwrap_or(row);
             for (y, c) in row.chars().enumerate() {
-                let value = c.to_string().parse()?;
-                sudoku.get_mut(x, y).unwrap().set(value);
+                match c {
+                    _ if x >= 9 || y >= 9 => return Err(parse_error(x, y, c)),
+                    ' ' => {}
+                    '1'..='9' => sudoku.0[x * 9 + y].set(Value(c as u8 - b'1')),
+                    found => return Err(parse_error(x, y, found)),
+                }
             }
         }
         Ok(sudoku)
     }
 }
 
-impl std::fmt::Display for Sudoku {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+#[cfg(feature = "std")]
+fn parse_error(row: usize, col: usize, found: char) -> Error {
+    Error::ParseError {
+        line: row + 1,
+        column: col + 1,
+        found,
+    }
+}
+
+/// One row per line with spaces for blanks, as read by `from_str`. The
+/// alternate flag, `{:#}`, draws the boxes with box drawing characters
+/// instead, see [`render_pretty`].
+impl core::fmt::Display for Sudoku {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        #[cfg(feature = "std")]
+        if f.alternate() {
+            let style = GridStyle {
+                unicode: true,
+                padding: 1,
+                ..GridStyle::default()
+            };
+            return f.write_str(&render_pretty(self, &style));
+        }
         for x in 0..9 {
             for y in 0..9 {
                 write!(f, "{}", self.get(x, y).unwrap())?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Cell(u16);
 
 impl Cell {
@@ -140,7 +944,22 @@ impl Cell {
     }
 
     pub fn is_final(&self) -> bool {
-        self.0 & (self.0 - 1) == 0
+        self.0.is_power_of_two()
+    }
+
+    /// Number of remaining candidates.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterates over the remaining candidates in ascending order.
+    pub fn values(&self) -> impl Iterator<Item = Value> {
+        let mask = self.0;
+        (0..9).filter(move |i| mask & (1 << i) != 0).map(Value)
     }
 
     pub fn set(&mut self, value: Value) {
@@ -183,7 +1002,13 @@ impl Default for Cell {
     }
 }
 
-impl std::str::FromStr for Cell {
+impl From<Value> for Cell {
+    fn from(value: Value) -> Self {
+        Self(value.mask())
+    }
+}
+
+impl core::str::FromStr for Cell {
     type Err = Error;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
@@ -197,8 +1022,8 @@ impl std::str::FromStr for Cell {
     }
 }
 
-impl std::fmt::Display for Cell {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for Cell {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         if let Some(value) = self.value() {
             write!(f, "{}", value)
         } else {
@@ -207,12 +1032,12 @@ impl std::fmt::Display for Cell {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Value(u8);
 
 impl Value {
     pub fn new(value: u8) -> Result<Self, Error> {
-        if value >= 1 && value <= 9 {
+        if (1..=9).contains(&value) {
             Ok(Self(value - 1))
         } else {
             Err(Error::ValueOutOfRange)
@@ -224,7 +1049,7 @@ impl Value {
     }
 }
 
-impl std::str::FromStr for Value {
+impl core::str::FromStr for Value {
     type Err = Error;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
@@ -232,21 +1057,100 @@ impl std::str::FromStr for Value {
     }
 }
 
-impl std::fmt::Display for Value {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "{}", self.0 + 1)
     }
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Error))]
 pub enum Error {
-    #[error("value out of range")]
+    #[cfg_attr(feature = "std", error("value out of range"))]
     ValueOutOfRange,
+    #[cfg_attr(feature = "std", error("cell ({row}, {col}) is outside the grid"))]
+    OutOfBounds { row: usize, col: usize },
+    #[cfg_attr(
+        feature = "std",
+        error("lines {a} and {b} are in different bands or stacks")
+    )]
+    DifferentBands { a: usize, b: usize },
+    #[cfg_attr(feature = "std", error("expected a cell like r1c1"))]
+    InvalidCoord,
+    #[cfg_attr(feature = "std", error("expected a move like r4c7=5 or r4c7<>5"))]
+    InvalidMove,
+    #[cfg_attr(feature = "std", error("not a permutation of the digits 1 to 9"))]
+    InvalidPermutation,
+    #[cfg_attr(feature = "std", error("cell {0} is set more than once"))]
+    DuplicateCell(Coord),
+    #[cfg_attr(feature = "std", error("value at {0} repeats in a unit"))]
+    ConflictingValue(Coord),
+    #[cfg_attr(feature = "std", error("search checkpoint is inconsistent"))]
+    InvalidCheckpoint,
+    #[cfg_attr(feature = "std", error(transparent))]
+    ParseInt(#[cfg_attr(feature = "std", from)] ParseIntError),
+    #[cfg_attr(feature = "std", error("unsupported box shape"))]
+    InvalidShape,
+    #[cfg_attr(feature = "std", error("unexpected symbol {0:?}"))]
+    InvalidSymbol(char),
+    #[cfg_attr(
+        feature = "std",
+        error("unexpected {found:?} at line {line}, column {column}")
+    )]
+    ParseError {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    #[cfg_attr(feature = "std", error("expected {expected} cells, found {found}"))]
+    WrongLength { expected: usize, found: usize },
+    #[cfg_attr(feature = "std", error("not a puzzle database"))]
+    InvalidFormat,
+    #[cfg_attr(feature = "std", error("record {index} is corrupt"))]
+    CorruptRecord { index: usize },
+    #[cfg_attr(feature = "std", error("file checksum mismatch"))]
+    CorruptFile,
+    #[cfg_attr(feature = "std", error("saved game is corrupt at line {line}"))]
+    CorruptSave { line: usize },
+    #[cfg_attr(feature = "std", error("format version {0} is not supported"))]
+    UnsupportedVersion(u32),
+    #[cfg_attr(feature = "std", error("not a share code"))]
+    InvalidShareCode,
+    #[cfg_attr(
+        feature = "std",
+        error("no puzzle with {clues} clues within {attempts} attempts")
+    )]
+    ClueCount { clues: usize, attempts: usize },
+    #[cfg_attr(feature = "std", error("no attempt produced a unique solution"))]
+    NoProperPuzzle,
+    #[cfg_attr(
+        feature = "std",
+        error("no unique puzzle in the requested rating range was found")
+    )]
+    RatingOutOfRange,
+    #[cfg_attr(feature = "std", error("not a PNG image"))]
+    InvalidImage,
+    #[cfg_attr(feature = "std", error("no grid found in the image"))]
+    GridNotFound,
+    #[cfg(feature = "std")]
+    #[error("unknown difficulty {0:?}")]
+    UnknownDifficulty(String),
+    #[cfg(feature = "std")]
+    #[error("unknown symmetry {0:?}")]
+    UnknownSymmetry(String),
+    #[cfg(feature = "std")]
     #[error(transparent)]
-    ParseInt(#[from] ParseIntError),
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(not(feature = "std"))]
+impl From<ParseIntError> for Error {
+    fn from(err: ParseIntError) -> Self {
+        Error::ParseInt(err)
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -270,9 +1174,177 @@ mod tests {
         assert_eq!(cell.value(), Some(Value::new(3).unwrap()));
     }
 
+    #[test]
+    fn test_iter() {
+        let mut sudoku = Sudoku::new();
+        sudoku.get_mut(0, 3).unwrap().set(Value::new(4).unwrap());
+        sudoku.get_mut(5, 1).unwrap().set(Value::new(7).unwrap());
+        let filled: Vec<_> = (&sudoku).into_iter().collect();
+        assert_eq!(
+            filled,
+            [
+                (Coord::new(0, 3), Value::new(4).unwrap()),
+                (Coord::new(5, 1), Value::new(7).unwrap())
+            ]
+        );
+        assert_eq!(sudoku.iter_empty().count(), 79);
+        assert_eq!(sudoku.iter_empty().next(), Some(Coord::new(0, 0)));
+        assert_eq!(Coord::new(5, 1).to_string(), "r6c2");
+
+        assert_eq!(sudoku.filled_count(), 2);
+        assert_eq!(sudoku.completion_ratio(), 2.0 / 81.0);
+        assert_eq!(sudoku.remaining_per_digit(), [9, 9, 9, 8, 9, 9, 8, 9, 9]);
+        let full = Sudoku::from_partial([[Some(1); 9]; 9]).unwrap();
+        assert_eq!(full.completion_ratio(), 1.0);
+        assert_eq!(full.remaining_per_digit(), [0, 9, 9, 9, 9, 9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let (four, seven) = (Value::new(4).unwrap(), Value::new(7).unwrap());
+        let mut old = Sudoku::new();
+        old[Coord::new(0, 3)] = four.into();
+        old[Coord::new(2, 2)] = seven.into();
+        let mut new = old;
+        new[Coord::new(0, 3)] = seven.into();
+        new[Coord::new(1, 0)] = four.into();
+        new[Coord::new(2, 2)] = Cell::new();
+        assert_eq!(
+            old.diff(&new),
+            [
+                (Coord::new(0, 3), Some(four), Some(seven)),
+                (Coord::new(1, 0), None, Some(four)),
+                (Coord::new(2, 2), Some(seven), None),
+            ]
+        );
+        assert!(old.diff(&old).is_empty());
+
+        let puzzle: Sudoku =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+                .parse()
+                .unwrap();
+        let solution = solve(&puzzle).unwrap();
+        let fills = puzzle.diff(&solution);
+        assert_eq!(fills.len(), 81 - 32);
+        assert!(fills.iter().all(|&(_, old, _)| old.is_none()));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let one = Value::new(1).unwrap();
+        let two = Value::new(2).unwrap();
+        let sudoku: Sudoku = vec![(Coord::new(0, 0), one), (Coord::new(0, 0), two)]
+            .into_iter()
+            .collect();
+        assert_eq!(sudoku.get(0, 0).unwrap().value(), Some(two));
+
+        let mut other = Sudoku::try_from_iter(sudoku.iter_filled()).unwrap();
+        other.extend(vec![(Coord::new(8, 8), one)]);
+        assert_eq!(other.iter_filled().count(), 2);
+
+        let res = Sudoku::try_from_iter(vec![(Coord::new(0, 0), one), (Coord::new(0, 0), two)]);
+        assert!(matches!(res, Err(Error::DuplicateCell(_))));
+        let res = Sudoku::try_from_iter(vec![(Coord::new(0, 0), one), (Coord::new(1, 1), one)]);
+        assert!(matches!(res, Err(Error::ConflictingValue(c)) if c == Coord::new(1, 1)));
+    }
+
+    #[test]
+    fn test_index() {
+        let mut sudoku = Sudoku::new();
+        let five = Value::new(5).unwrap();
+        sudoku[(4, 7)] = five.into();
+        assert_eq!(sudoku[Coord::new(4, 7)].value(), Some(five));
+        sudoku[Coord::new(0, 0)].set(five);
+        assert_eq!(sudoku.get(0, 0).unwrap().value(), Some(five));
+        assert!(sudoku.get(0, 9).is_none());
+        assert!(sudoku.get_mut(9, 0).is_none());
+        assert!(std::panic::catch_unwind(|| sudoku[(0, 9)]).is_err());
+    }
+
+    #[test]
+    fn test_peers() {
+        let coord = Coord::new(4, 7);
+        let peers: Vec<Coord> = coord.peers().collect();
+        assert_eq!(peers.len(), 20);
+        assert!(!peers.contains(&coord));
+        assert!(peers.contains(&Coord::new(3, 6)));
+        assert!(peers.contains(&Coord::new(0, 7)));
+        assert!(!peers.contains(&Coord::new(2, 6)));
+        let mut sorted = peers.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 20);
+    }
+
+    #[test]
+    fn test_coord() {
+        assert_eq!(Coord::try_new(4, 7).unwrap(), Coord::new(4, 7));
+        let err = Coord::try_new(2, 9).unwrap_err();
+        assert!(matches!(err, Error::OutOfBounds { row: 2, col: 9 }));
+        assert_eq!(err.to_string(), "cell (2, 9) is outside the grid");
+        assert_eq!("r5c8".parse::<Coord>().unwrap(), Coord::new(4, 7));
+        assert_eq!(
+            Coord::new(4, 7).to_string().parse::<Coord>().unwrap(),
+            Coord::new(4, 7)
+        );
+        assert!(matches!("r0c1".parse::<Coord>(), Err(Error::InvalidCoord)));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let mut sudoku = Sudoku::new();
+        let five = Value::new(5).ok();
+        sudoku.get_mut(4, 7).unwrap().set(five.unwrap());
+        assert_eq!(block_of(Coord::new(4, 7)), 5);
+        let block = sudoku.get_block(5);
+        assert_eq!(block[1][1], five);
+        assert_eq!(block.iter().flatten().filter(|v| v.is_some()).count(), 1);
+        sudoku.set_block(0, block);
+        sudoku.set_block(5, [[None; 3]; 3]);
+        assert_eq!(sudoku.get(1, 1).unwrap().value(), five);
+        assert_eq!(sudoku.get(4, 7).unwrap().value(), None);
+    }
+
+    #[test]
+    fn test_partial() {
+        let mut rows = [[None; 9]; 9];
+        rows[3][4] = Some(9);
+        let sudoku = Sudoku::from_partial(rows).unwrap();
+        assert_eq!(sudoku.get(3, 4).unwrap().value(), Value::new(9).ok());
+        assert_eq!(sudoku.to_partial(), rows);
+        rows[0][0] = Some(10);
+        assert!(matches!(
+            Sudoku::from_partial(rows),
+            Err(Error::ValueOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_units() {
+        let mut sudoku = Sudoku::new();
+        let five = Value::new(5).unwrap();
+        sudoku[(4, 7)].set(five);
+        assert_eq!(sudoku.cells().count(), 81);
+        assert_eq!(
+            sudoku.cells().nth(4 * 9 + 7),
+            Some((Coord::new(4, 7), Some(five)))
+        );
+        let row: Vec<_> = sudoku.rows().nth(4).unwrap().collect();
+        assert_eq!(row[7], (Coord::new(4, 7), Some(five)));
+        assert_eq!(sudoku.cols().nth(7).unwrap().nth(4), row.get(7).copied());
+        let block: Vec<_> = sudoku.blocks().nth(5).unwrap().collect();
+        assert_eq!(block[4], (Coord::new(4, 7), Some(five)));
+        assert_eq!(sudoku.units().count(), 27);
+        let filled = sudoku
+            .units()
+            .flatten()
+            .filter(|(_, value)| value.is_some());
+        assert_eq!(filled.count(), 3);
+    }
+
     #[test]
     fn test_block_iter() {
-        let indices: Vec<_> = block_iter(0).collect();
+        let indices: Vec<_> = Unit::Block(0).cells().collect();
         assert_eq!(
             indices,
             &[
@@ -305,4 +1377,246 @@ mod tests {
         println!("{}", sudoku);
         assert!(sudoku.valid());
     }
+
+    #[test]
+    fn test_line_format() {
+        let line =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        let sudoku: Sudoku = line.parse().unwrap();
+        assert_eq!(sudoku.get(0, 2).unwrap().value(), Value::new(3).ok());
+        assert_eq!(sudoku.iter_filled().count(), 32);
+        assert_eq!(sudoku.to_line(), line);
+        let zeros: Sudoku = format!("{}\n", line.replace('.', "0")).parse().unwrap();
+        assert_eq!(zeros.to_line(), line);
+        assert_eq!(
+            sudoku.to_string().parse::<Sudoku>().unwrap().to_line(),
+            line
+        );
+        assert!(matches!(
+            line.replace('.', "x").parse::<Sudoku>(),
+            Err(Error::ParseError {
+                line: 1,
+                column: 1,
+                found: 'x'
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let rows = "5\n\n  3\n";
+        assert_eq!(
+            rows.parse::<Sudoku>().unwrap().get(2, 2).unwrap().value(),
+            Value::new(3).ok()
+        );
+        let err = "5\n  3 x\n".parse::<Sudoku>().unwrap_err();
+        assert_eq!(err.to_string(), "unexpected 'x' at line 2, column 5");
+        assert!(matches!(
+            "1234567891\n".parse::<Sudoku>(),
+            Err(Error::ParseError {
+                line: 1,
+                column: 10,
+                found: '1'
+            })
+        ));
+        assert!(matches!(
+            format!("{}1", "\n".repeat(9)).parse::<Sudoku>(),
+            Err(Error::ParseError {
+                line: 10,
+                column: 1,
+                found: '1'
+            })
+        ));
+        assert!("1\r\n 2\r\n".parse::<Sudoku>().is_ok());
+    }
+
+    #[test]
+    fn test_eq() {
+        let line =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        let puzzle: Sudoku = line.parse().unwrap();
+        let solution = *crate::solve(&puzzle).unwrap();
+        assert_eq!(puzzle, line.parse().unwrap());
+        assert_ne!(puzzle, solution);
+        let set: std::collections::HashSet<Sudoku> = [puzzle, solution, puzzle].into();
+        assert_eq!(set.len(), 2);
+
+        assert!(solution.is_completion_of(&puzzle));
+        assert!(solution.is_completion_of(&Sudoku::new()));
+        assert!(!puzzle.is_completion_of(&puzzle));
+        let other =
+            solution.relabel(&crate::DigitPermutation::new([2, 1, 3, 4, 5, 6, 7, 8, 9]).unwrap());
+        assert!(!other.is_completion_of(&puzzle));
+    }
+
+    #[test]
+    fn test_conversions() {
+        use std::convert::TryFrom;
+
+        let mut digits = [0; 81];
+        digits[0] = 5;
+        digits[80] = 9;
+        let sudoku = Sudoku::try_from(digits).unwrap();
+        assert_eq!(sudoku.get(8, 8).unwrap().value(), Value::new(9).ok());
+        assert_eq!(<[u8; 81]>::from(sudoku), digits);
+        digits[1] = 10;
+        assert!(matches!(
+            Sudoku::try_from(digits),
+            Err(Error::ValueOutOfRange)
+        ));
+        assert!(matches!(
+            Sudoku::try_from(&digits[..80]),
+            Err(Error::WrongLength {
+                expected: 81,
+                found: 80
+            })
+        ));
+
+        let values = (1..=9).map(|d| Value::new(d).unwrap());
+        let first_row: Sudoku = values.collect();
+        assert_eq!(&first_row.to_line()[..10], "123456789.");
+        let options = [None, Value::new(3).ok()];
+        let sparse: Sudoku = options.iter().copied().collect();
+        assert_eq!(&sparse.to_line()[..3], ".3.");
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut sudoku = Sudoku::new();
+        let five = Value::new(5).unwrap();
+        for &(x, y) in &[(0, 0), (0, 8), (1, 1), (4, 4)] {
+            sudoku[(x, y)].set(five);
+        }
+        sudoku[(8, 0)].set(Value::new(6).unwrap());
+        assert_eq!(
+            sudoku.conflicts(),
+            [
+                (Coord::new(0, 0), Coord::new(0, 8)),
+                (Coord::new(0, 0), Coord::new(1, 1))
+            ]
+        );
+        assert!(Sudoku::new().conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut sudoku = Sudoku::new();
+        sudoku.get_mut(0, 0).unwrap().set(Value::new(5).unwrap());
+        assert_eq!(format!("{}", sudoku).lines().next(), Some("5        "));
+        let pretty = format!("{:#}", sudoku);
+        let lines: Vec<&str> = pretty.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "┌─────────┬─────────┬─────────┐");
+        assert_eq!(lines[1], "│ 5  .  . │ .  .  . │ .  .  . │");
+        assert_eq!(lines[4], "├─────────┼─────────┼─────────┤");
+    }
+
+    #[test]
+    fn test_validate_report() {
+        let mut sudoku: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        *sudoku.get_mut(8, 8).unwrap() = Cell::new();
+        match sudoku.validate() {
+            Solution::Incomplete(empty) => {
+                assert_eq!(empty.len(), 3);
+                assert!(empty.iter().all(|e| e.cells == [(8, 8)]));
+                assert_eq!(empty[2].unit, Unit::Block(8));
+            }
+            s => panic!("unexpected {:?}", s),
+        }
+
+        assert!(!sudoku.report().is_valid());
+        sudoku.get_mut(8, 8).unwrap().set(Value::new(1).unwrap());
+        sudoku.get_mut(0, 0).unwrap().set(Value::new(2).unwrap());
+        let report = sudoku.report();
+        assert_eq!(report.conflicts.len(), 6);
+        assert_eq!(report.violated_units()[..2], [Unit::Row(0), Unit::Row(8)]);
+        assert!(report.empty.is_empty());
+        *sudoku.get_mut(0, 0).unwrap() = "5".parse().unwrap();
+        match sudoku.validate() {
+            Solution::Invalid(conflicts) => {
+                assert_eq!(conflicts.len(), 3);
+                assert_eq!(conflicts[0].unit, Unit::Row(8));
+                assert_eq!(conflicts[0].value, Value::new(1).unwrap());
+                assert_eq!(conflicts[0].cells, [(8, 6), (8, 8)]);
+            }
+            s => panic!("unexpected {:?}", s),
+        }
+    }
+
+    #[test]
+    fn test_check_units() {
+        let mut sudoku: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286171"
+            .parse()
+            .unwrap();
+        assert!(sudoku.check_row(0).is_valid());
+        assert!(sudoku.check_block(4).is_valid());
+        let row = sudoku.check_row(8);
+        assert_eq!(row.conflicts.len(), 1);
+        assert_eq!(row.conflicts[0].cells, [(8, 6), (8, 8)]);
+        assert!(row.empty.is_empty());
+        assert_eq!(sudoku.check_col(8).violated_units(), [Unit::Col(8)]);
+        assert_eq!(sudoku.check_block(8), sudoku.check_unit(Unit::Block(8)));
+        *sudoku.get_mut(4, 4).unwrap() = Cell::new();
+        let block = sudoku.check_block(4);
+        assert!(block.conflicts.is_empty());
+        assert_eq!(block.empty[0].unit, Unit::Block(4));
+        assert_eq!(block.empty[0].cells, [(4, 4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the grid")]
+    fn test_check_row_out_of_range() {
+        Sudoku::new().check_row(9);
+    }
+
+    #[test]
+    fn test_report() {
+        let mut sudoku: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        assert!(sudoku.report().is_valid());
+
+        // a repeated digit and an empty cell are both reported
+        sudoku.get_mut(0, 0).unwrap().set(Value::new(2).unwrap());
+        *sudoku.get_mut(8, 8).unwrap() = Cell::new();
+        let report = sudoku.report();
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.violated_units(),
+            [Unit::Row(0), Unit::Col(0), Unit::Block(0)]
+        );
+        assert_eq!(report.conflicts[0].cells, [(0, 0), (0, 8)]);
+        assert_eq!(report.empty.len(), 3);
+        assert!(report.empty.iter().all(|e| e.cells == [(8, 8)]));
+        match sudoku.validate() {
+            Solution::Invalid(conflicts) => assert_eq!(conflicts, report.conflicts),
+            s => panic!("unexpected {:?}", s),
+        }
+    }
 }
@@ -0,0 +1,1385 @@
+use crate::{peers, Coord, Sudoku, Unit, Value};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Technique {
+    HiddenSingle,
+    NakedSingle,
+    /// Locked candidates in a block pointing along a row or column.
+    Pointing,
+    /// Locked candidates in a row or column claiming a block.
+    Claiming,
+    /// Cells of a unit whose candidates are limited to the same digits,
+    /// one digit per cell.
+    NakedPair,
+    NakedTriple,
+    NakedQuad,
+    /// Digits of a unit limited to the same cells, one cell per digit.
+    HiddenPair,
+    HiddenTriple,
+    HiddenQuad,
+    /// A digit confined to the same columns in as many rows, or the other
+    /// way around.
+    XWing,
+    Swordfish,
+    Jellyfish,
+    /// A cell with two candidates and two cells it sees, each sharing one
+    /// of them and a third digit.
+    XYWing,
+    /// Like the XY-wing with the third digit also in the pivot.
+    XYZWing,
+    /// Two cells with the same two candidates, joined by a strong link on
+    /// one of them.
+    WWing,
+    /// Two colors alternating along the conjugate pairs of a digit, one of
+    /// which must be true.
+    SimpleColoring,
+    /// The two candidates of a cell leading to the same conclusion, or one
+    /// of them to a contradiction, through a bounded number of singles.
+    ForcingChain,
+    /// Four cells in two rows, two columns and two boxes sharing two
+    /// candidates, which a unique puzzle can't leave to both. Removes the
+    /// pair from the only corner with other candidates.
+    UniqueRectangle1,
+    /// A unique rectangle whose two corners with other candidates share one
+    /// extra digit, which must be in one of them.
+    UniqueRectangle2,
+    /// A unique rectangle whose extra digits form a naked subset with other
+    /// cells of a unit.
+    UniqueRectangle3,
+    /// A unique rectangle with a strong link on one of the pair between the
+    /// corners with other candidates, which can't hold the other one.
+    UniqueRectangle4,
+    /// All unplaced cells but one have two candidates, and the remaining one
+    /// must take the digit that would otherwise leave two solutions.
+    BugPlusOne,
+    /// A candidate leading to a contradiction through a bounded number of
+    /// singles, which removes it.
+    Nishio,
+}
+
+impl Technique {
+    /// All techniques, roughly ordered from easiest to hardest.
+    pub fn all() -> &'static [Technique] {
+        &[
+            Technique::HiddenSingle,
+            Technique::NakedSingle,
+            Technique::Pointing,
+            Technique::Claiming,
+            Technique::NakedPair,
+            Technique::XWing,
+            Technique::HiddenPair,
+            Technique::NakedTriple,
+            Technique::Swordfish,
+            Technique::HiddenTriple,
+            Technique::XYWing,
+            Technique::XYZWing,
+            Technique::WWing,
+            Technique::UniqueRectangle1,
+            Technique::SimpleColoring,
+            Technique::UniqueRectangle2,
+            Technique::UniqueRectangle3,
+            Technique::UniqueRectangle4,
+            Technique::NakedQuad,
+            Technique::Jellyfish,
+            Technique::HiddenQuad,
+            Technique::BugPlusOne,
+            Technique::ForcingChain,
+            Technique::Nishio,
+        ]
+    }
+
+    /// Stable identifier of the technique.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "hidden-single",
+            Technique::NakedSingle => "naked-single",
+            Technique::Pointing => "pointing",
+            Technique::Claiming => "claiming",
+            Technique::NakedPair => "naked-pair",
+            Technique::NakedTriple => "naked-triple",
+            Technique::NakedQuad => "naked-quad",
+            Technique::HiddenPair => "hidden-pair",
+            Technique::HiddenTriple => "hidden-triple",
+            Technique::HiddenQuad => "hidden-quad",
+            Technique::XWing => "x-wing",
+            Technique::Swordfish => "swordfish",
+            Technique::Jellyfish => "jellyfish",
+            Technique::XYWing => "xy-wing",
+            Technique::XYZWing => "xyz-wing",
+            Technique::WWing => "w-wing",
+            Technique::SimpleColoring => "simple-coloring",
+            Technique::ForcingChain => "forcing-chain",
+            Technique::UniqueRectangle1 => "unique-rectangle-1",
+            Technique::UniqueRectangle2 => "unique-rectangle-2",
+            Technique::UniqueRectangle3 => "unique-rectangle-3",
+            Technique::UniqueRectangle4 => "unique-rectangle-4",
+            Technique::BugPlusOne => "bug-plus-one",
+            Technique::Nishio => "nishio",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "hidden single",
+            Technique::NakedSingle => "naked single",
+            Technique::Pointing => "pointing",
+            Technique::Claiming => "claiming",
+            Technique::NakedPair => "naked pair",
+            Technique::NakedTriple => "naked triple",
+            Technique::NakedQuad => "naked quad",
+            Technique::HiddenPair => "hidden pair",
+            Technique::HiddenTriple => "hidden triple",
+            Technique::HiddenQuad => "hidden quad",
+            Technique::XWing => "x-wing",
+            Technique::Swordfish => "swordfish",
+            Technique::Jellyfish => "jellyfish",
+            Technique::XYWing => "XY-wing",
+            Technique::XYZWing => "XYZ-wing",
+            Technique::WWing => "W-wing",
+            Technique::SimpleColoring => "simple coloring",
+            Technique::ForcingChain => "forcing chain",
+            Technique::UniqueRectangle1 => "unique rectangle type 1",
+            Technique::UniqueRectangle2 => "unique rectangle type 2",
+            Technique::UniqueRectangle3 => "unique rectangle type 3",
+            Technique::UniqueRectangle4 => "unique rectangle type 4",
+            Technique::BugPlusOne => "BUG+1",
+            Technique::Nishio => "Nishio",
+        }
+    }
+
+    /// Whether the technique is only sound for puzzles known to have a
+    /// unique solution.
+    pub fn requires_uniqueness(&self) -> bool {
+        matches!(
+            self,
+            Technique::UniqueRectangle1
+                | Technique::UniqueRectangle2
+                | Technique::UniqueRectangle3
+                | Technique::UniqueRectangle4
+                | Technique::BugPlusOne
+        )
+    }
+
+    fn find(&self, solver: &LogicalSolver, state: &LogicState) -> Option<SolveStep> {
+        match self {
+            Technique::HiddenSingle => hidden_single(state),
+            Technique::NakedSingle => naked_single(state),
+            Technique::Pointing => pointing(state),
+            Technique::Claiming => claiming(state),
+            Technique::NakedPair => naked_subset(state, 2),
+            Technique::NakedTriple => naked_subset(state, 3),
+            Technique::NakedQuad => naked_subset(state, 4),
+            Technique::HiddenPair => hidden_subset(state, 2),
+            Technique::HiddenTriple => hidden_subset(state, 3),
+            Technique::HiddenQuad => hidden_subset(state, 4),
+            Technique::XWing => fish(state, 2),
+            Technique::Swordfish => fish(state, 3),
+            Technique::Jellyfish => fish(state, 4),
+            Technique::XYWing => xy_wing(state, false),
+            Technique::XYZWing => xy_wing(state, true),
+            Technique::WWing => w_wing(state),
+            Technique::SimpleColoring => simple_coloring(state),
+            Technique::ForcingChain => forcing_chain(state, solver.max_chain_length),
+            Technique::UniqueRectangle1 => unique_rectangle(state, 1),
+            Technique::UniqueRectangle2 => unique_rectangle(state, 2),
+            Technique::UniqueRectangle3 => unique_rectangle(state, 3),
+            Technique::UniqueRectangle4 => unique_rectangle(state, 4),
+            Technique::BugPlusOne => bug_plus_one(state),
+            Technique::Nishio => nishio(state, solver.max_trial_length),
+        }
+    }
+}
+
+impl std::fmt::Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolveStep {
+    pub technique: Technique,
+    /// The unit the pattern was found in, if it is confined to one.
+    pub unit: Option<Unit>,
+    /// Cells forming the pattern.
+    pub cells: Vec<Coord>,
+    /// Digits forming the pattern.
+    pub digits: Vec<Value>,
+    pub placements: Vec<(Coord, Value)>,
+    pub eliminations: Vec<(Coord, Value)>,
+}
+
+impl SolveStep {
+    fn new(technique: Technique, unit: Option<Unit>) -> Self {
+        Self {
+            technique,
+            unit,
+            cells: Vec::new(),
+            digits: Vec::new(),
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        }
+    }
+}
+
+/// Candidates of a partially solved grid, keeping track of which cells have
+/// already been placed.
+#[derive(Clone, Copy, Debug)]
+pub struct LogicState {
+    grid: Sudoku,
+    placed: [bool; 81],
+}
+
+impl LogicState {
+    pub fn new(sudoku: &Sudoku) -> Self {
+        let mut state = Self {
+            grid: *sudoku,
+            placed: [false; 81],
+        };
+        for i in 0..81 {
+            state.placed[i] = state.grid.0[i].is_final();
+        }
+        for i in 0..81 {
+            if state.placed[i] {
+                state.eliminate_peers(i);
+            }
+        }
+        state
+    }
+
+    pub fn grid(&self) -> &Sudoku {
+        &self.grid
+    }
+
+    pub fn is_placed(&self, x: usize, y: usize) -> bool {
+        self.placed[x * 9 + y]
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.placed.iter().all(|&placed| placed)
+    }
+
+    /// Returns `true` if a cell ran out of candidates.
+    pub fn is_broken(&self) -> bool {
+        self.grid.0.iter().any(|cell| cell.is_empty())
+    }
+
+    pub fn apply(&mut self, step: &SolveStep) {
+        for &(coord, value) in &step.eliminations {
+            self.grid.0[coord.index()].0 &= !value.mask();
+        }
+        for &(coord, value) in &step.placements {
+            self.place(coord, value);
+        }
+    }
+
+    /// Places `value` at `coord` without a technique justifying it.
+    pub fn place(&mut self, coord: Coord, value: Value) {
+        let i = coord.index();
+        self.grid.0[i].set(value);
+        self.placed[i] = true;
+        self.eliminate_peers(i);
+    }
+
+    fn eliminate_peers(&mut self, i: usize) {
+        let mask = self.grid.0[i].0;
+        for (x, y) in peers(i / 9, i % 9) {
+            let j = x * 9 + y;
+            if !self.placed[j] {
+                self.grid.0[j].0 &= !mask;
+            }
+        }
+    }
+
+    /// Candidate mask of an unplaced cell, zero for placed cells.
+    fn mask(&self, coord: Coord) -> u16 {
+        let i = coord.index();
+        if self.placed[i] {
+            0
+        } else {
+            self.grid.0[i].0
+        }
+    }
+
+    fn cells_with(&self, unit: Unit, value: Value) -> Vec<Coord> {
+        unit.coords()
+            .filter(|&cell| self.mask(cell) & value.mask() != 0)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogicSolution {
+    pub grid: Sudoku,
+    pub steps: Vec<SolveStep>,
+    /// Whether the techniques were sufficient to solve the puzzle.
+    pub solved: bool,
+}
+
+/// The number of singles a forcing chain may follow from each candidate by
+/// default.
+pub const DEFAULT_MAX_CHAIN_LENGTH: usize = 8;
+
+/// The number of singles a Nishio trial may follow by default.
+pub const DEFAULT_MAX_TRIAL_LENGTH: usize = 16;
+
+#[derive(Clone, Debug)]
+pub struct LogicalSolver {
+    techniques: Vec<Technique>,
+    max_chain_length: usize,
+    max_trial_length: usize,
+    assume_unique: bool,
+}
+
+impl LogicalSolver {
+    /// Creates a solver that tries `techniques` in the given order.
+    pub fn new(techniques: Vec<Technique>) -> Self {
+        Self {
+            techniques,
+            max_chain_length: DEFAULT_MAX_CHAIN_LENGTH,
+            max_trial_length: DEFAULT_MAX_TRIAL_LENGTH,
+            assume_unique: false,
+        }
+    }
+
+    /// A solver limited to naked and hidden singles, the techniques every
+    /// newspaper puzzle is expected to need at most.
+    pub fn singles() -> Self {
+        Self::new(vec![Technique::HiddenSingle, Technique::NakedSingle])
+    }
+
+    pub fn techniques(&self) -> &[Technique] {
+        &self.techniques
+    }
+
+    pub fn max_chain_length(&self) -> usize {
+        self.max_chain_length
+    }
+
+    /// Limits how many singles a forcing chain follows, which bounds the
+    /// time spent on each step.
+    pub fn set_max_chain_length(&mut self, len: usize) {
+        self.max_chain_length = len;
+    }
+
+    pub fn max_trial_length(&self) -> usize {
+        self.max_trial_length
+    }
+
+    /// Limits how many singles a Nishio trial follows before giving up on
+    /// the candidate.
+    pub fn set_max_trial_length(&mut self, len: usize) {
+        self.max_trial_length = len;
+    }
+
+    pub fn assumes_unique(&self) -> bool {
+        self.assume_unique
+    }
+
+    /// Allows the techniques that rely on the puzzle having a unique
+    /// solution. They are skipped otherwise, since they can remove the
+    /// solutions of a puzzle with several.
+    pub fn set_assume_unique(&mut self, assume_unique: bool) {
+        self.assume_unique = assume_unique;
+    }
+
+    /// The default solver, with the uniqueness techniques allowed if
+    /// `sudoku` has a unique solution.
+    pub(crate) fn for_puzzle(sudoku: &Sudoku) -> Self {
+        let mut solver = Self::default();
+        solver.set_assume_unique(sudoku.has_unique_solution());
+        solver
+    }
+
+    pub fn next_step(&self, state: &LogicState) -> Option<SolveStep> {
+        if state.is_broken() {
+            return None;
+        }
+        self.techniques
+            .iter()
+            .filter(|t| self.assume_unique || !t.requires_uniqueness())
+            .find_map(|t| t.find(self, state))
+    }
+
+    pub fn solve(&self, sudoku: &Sudoku) -> LogicSolution {
+        let mut state = LogicState::new(sudoku);
+        let mut steps = Vec::new();
+        while !state.is_solved() {
+            match self.next_step(&state) {
+                Some(step) => {
+                    state.apply(&step);
+                    steps.push(step);
+                }
+                None => break,
+            }
+        }
+        LogicSolution {
+            solved: state.is_solved() && state.grid.valid(),
+            grid: state.grid,
+            steps,
+        }
+    }
+
+    /// Whether the techniques solve the puzzle without guessing.
+    pub fn can_solve(&self, sudoku: &Sudoku) -> bool {
+        self.solve(sudoku).solved
+    }
+}
+
+impl Default for LogicalSolver {
+    fn default() -> Self {
+        Self::new(Technique::all().to_vec())
+    }
+}
+
+fn values() -> impl Iterator<Item = Value> {
+    (0..9).map(Value)
+}
+
+fn blocks_then_lines() -> impl Iterator<Item = Unit> {
+    (0..9)
+        .map(Unit::Block)
+        .chain((0..9).map(Unit::Row))
+        .chain((0..9).map(Unit::Col))
+}
+
+fn naked_single(state: &LogicState) -> Option<SolveStep> {
+    let cell = (0..81)
+        .map(Coord::from_index)
+        .find(|&cell| state.mask(cell).count_ones() == 1)?;
+    let value = state.grid.0[cell.index()].value()?;
+    let mut step = SolveStep::new(Technique::NakedSingle, None);
+    step.cells.push(cell);
+    step.digits.push(value);
+    step.placements.push((cell, value));
+    Some(step)
+}
+
+fn hidden_single(state: &LogicState) -> Option<SolveStep> {
+    for unit in blocks_then_lines() {
+        for value in values() {
+            if let [cell] = state.cells_with(unit, value)[..] {
+                let mut step = SolveStep::new(Technique::HiddenSingle, Some(unit));
+                step.cells.push(cell);
+                step.digits.push(value);
+                step.placements.push((cell, value));
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+fn pointing(state: &LogicState) -> Option<SolveStep> {
+    for block in 0..9 {
+        for value in values() {
+            let cells = state.cells_with(Unit::Block(block), value);
+            let first = match cells.first() {
+                Some(&cell) => cell,
+                None => continue,
+            };
+            let lines = [
+                (
+                    cells.iter().all(|c| c.row() == first.row()),
+                    Unit::Row(first.row()),
+                ),
+                (
+                    cells.iter().all(|c| c.col() == first.col()),
+                    Unit::Col(first.col()),
+                ),
+            ];
+            for &(locked, line) in &lines {
+                if !locked {
+                    continue;
+                }
+                let mut step = SolveStep::new(Technique::Pointing, Some(Unit::Block(block)));
+                step.eliminations = state
+                    .cells_with(line, value)
+                    .into_iter()
+                    .filter(|cell| cell.block() != block)
+                    .map(|cell| (cell, value))
+                    .collect();
+                if !step.eliminations.is_empty() {
+                    step.cells = cells;
+                    step.digits.push(value);
+                    return Some(step);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn claiming(state: &LogicState) -> Option<SolveStep> {
+    for line in (0..9).map(Unit::Row).chain((0..9).map(Unit::Col)) {
+        for value in values() {
+            let cells = state.cells_with(line, value);
+            let block = match cells.first() {
+                Some(cell) => cell.block(),
+                None => continue,
+            };
+            if cells.iter().any(|cell| cell.block() != block) {
+                continue;
+            }
+            let mut step = SolveStep::new(Technique::Claiming, Some(line));
+            step.eliminations = state
+                .cells_with(Unit::Block(block), value)
+                .into_iter()
+                .filter(|cell| !cells.contains(cell))
+                .map(|cell| (cell, value))
+                .collect();
+            if !step.eliminations.is_empty() {
+                step.cells = cells;
+                step.digits.push(value);
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// All ways of picking `k` of the indices `0..n`, in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    fn extend(
+        start: usize,
+        n: usize,
+        k: usize,
+        current: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if current.len() == k {
+            out.push(current.clone());
+            return;
+        }
+        for i in start..n {
+            current.push(i);
+            extend(i + 1, n, k, current, out);
+            current.pop();
+        }
+    }
+    let mut out = Vec::new();
+    extend(0, n, k, &mut Vec::with_capacity(k), &mut out);
+    out
+}
+
+fn subset_technique(naked: bool, size: usize) -> Technique {
+    match (naked, size) {
+        (true, 2) => Technique::NakedPair,
+        (true, 3) => Technique::NakedTriple,
+        (true, _) => Technique::NakedQuad,
+        (false, 2) => Technique::HiddenPair,
+        (false, 3) => Technique::HiddenTriple,
+        (false, _) => Technique::HiddenQuad,
+    }
+}
+
+fn mask_values(mask: u16) -> Vec<Value> {
+    values().filter(|v| mask & v.mask() != 0).collect()
+}
+
+/// `size` unplaced cells of a unit holding only `size` digits between them,
+/// which can be removed from the other cells of the unit.
+fn naked_subset(state: &LogicState, size: usize) -> Option<SolveStep> {
+    for unit in blocks_then_lines() {
+        let open: Vec<Coord> = unit.coords().filter(|&c| state.mask(c) != 0).collect();
+        if open.len() <= size {
+            continue;
+        }
+        for combo in combinations(open.len(), size) {
+            let cells: Vec<Coord> = combo.iter().map(|&i| open[i]).collect();
+            let union = cells.iter().fold(0, |mask, &c| mask | state.mask(c));
+            if union.count_ones() as usize != size {
+                continue;
+            }
+            let mut step = SolveStep::new(subset_technique(true, size), Some(unit));
+            for &cell in open.iter().filter(|c| !cells.contains(c)) {
+                for value in mask_values(state.mask(cell) & union) {
+                    step.eliminations.push((cell, value));
+                }
+            }
+            if !step.eliminations.is_empty() {
+                step.cells = cells;
+                step.digits = mask_values(union);
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// `size` digits of a unit confined to `size` cells between them, whose
+/// other candidates can be removed.
+fn hidden_subset(state: &LogicState, size: usize) -> Option<SolveStep> {
+    for unit in blocks_then_lines() {
+        let open: Vec<Value> = values()
+            .filter(|&v| !state.cells_with(unit, v).is_empty())
+            .collect();
+        if open.len() <= size {
+            continue;
+        }
+        for combo in combinations(open.len(), size) {
+            let digits: Vec<Value> = combo.iter().map(|&i| open[i]).collect();
+            let mask = digits.iter().fold(0, |mask, v| mask | v.mask());
+            let cells: Vec<Coord> = unit
+                .coords()
+                .filter(|&c| state.mask(c) & mask != 0)
+                .collect();
+            if cells.len() != size {
+                continue;
+            }
+            let mut step = SolveStep::new(subset_technique(false, size), Some(unit));
+            for &cell in &cells {
+                for value in mask_values(state.mask(cell) & !mask) {
+                    step.eliminations.push((cell, value));
+                }
+            }
+            if !step.eliminations.is_empty() {
+                step.cells = cells;
+                step.digits = digits;
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// A digit whose candidates in `size` rows lie in `size` columns, so that
+/// it can be removed from the rest of those columns, or the same with rows
+/// and columns swapped.
+fn fish(state: &LogicState, size: usize) -> Option<SolveStep> {
+    let technique = match size {
+        2 => Technique::XWing,
+        3 => Technique::Swordfish,
+        _ => Technique::Jellyfish,
+    };
+    for value in values() {
+        for &rows in &[true, false] {
+            let line = |i: usize| if rows { Unit::Row(i) } else { Unit::Col(i) };
+            let cross = |c: &Coord| if rows { c.col() } else { c.row() };
+            let base: Vec<(usize, Vec<Coord>)> = (0..9)
+                .map(|i| (i, state.cells_with(line(i), value)))
+                .filter(|(_, cells)| (2..=size).contains(&cells.len()))
+                .collect();
+            if base.len() < size {
+                continue;
+            }
+            for combo in combinations(base.len(), size) {
+                let cells: Vec<Coord> = combo.iter().flat_map(|&i| base[i].1.clone()).collect();
+                let cover = cells.iter().fold(0u16, |mask, c| mask | 1 << cross(c));
+                if cover.count_ones() as usize != size {
+                    continue;
+                }
+                let lines: Vec<usize> = combo.iter().map(|&i| base[i].0).collect();
+                let mut step = SolveStep::new(technique, None);
+                for c in (0..9).filter(|c| cover & 1 << c != 0) {
+                    let covered = if rows { Unit::Col(c) } else { Unit::Row(c) };
+                    for cell in state.cells_with(covered, value) {
+                        let along = if rows { cell.row() } else { cell.col() };
+                        if !lines.contains(&along) {
+                            step.eliminations.push((cell, value));
+                        }
+                    }
+                }
+                if !step.eliminations.is_empty() {
+                    step.cells = cells;
+                    step.digits.push(value);
+                    return Some(step);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether two different cells share a unit.
+fn sees(a: Coord, b: Coord) -> bool {
+    a != b && (a.row() == b.row() || a.col() == b.col() || a.block() == b.block())
+}
+
+/// Removes `value` from the unplaced cells seeing all of `cells`.
+fn eliminate_seen(state: &LogicState, cells: &[Coord], value: Value) -> Vec<(Coord, Value)> {
+    (0..81)
+        .map(Coord::from_index)
+        .filter(|&c| state.mask(c) & value.mask() != 0)
+        .filter(|&c| cells.iter().all(|&cell| sees(c, cell)))
+        .map(|c| (c, value))
+        .collect()
+}
+
+fn bivalue_cells(state: &LogicState) -> Vec<Coord> {
+    (0..81)
+        .map(Coord::from_index)
+        .filter(|&c| state.mask(c).count_ones() == 2)
+        .collect()
+}
+
+/// A pivot seeing two pincers, the pivot holding `x` and `y` (and `z` for
+/// the XYZ-wing) and the pincers `x` and `z`, and `y` and `z`. Whichever
+/// value the pivot takes, `z` ends up in a pincer or, for the XYZ-wing, in
+/// the pivot.
+fn xy_wing(state: &LogicState, xyz: bool) -> Option<SolveStep> {
+    let technique = if xyz {
+        Technique::XYZWing
+    } else {
+        Technique::XYWing
+    };
+    let pincers = bivalue_cells(state);
+    let pivot_size = if xyz { 3 } else { 2 };
+    for pivot in (0..81).map(Coord::from_index) {
+        let pivot_mask = state.mask(pivot);
+        if pivot_mask.count_ones() != pivot_size {
+            continue;
+        }
+        let wings: Vec<Coord> = pincers
+            .iter()
+            .copied()
+            .filter(|&c| sees(pivot, c))
+            .collect();
+        for (i, &a) in wings.iter().enumerate() {
+            for &b in &wings[i + 1..] {
+                let (ma, mb) = (state.mask(a), state.mask(b));
+                let z = ma & mb;
+                if ma == mb || z.count_ones() != 1 {
+                    continue;
+                }
+                let fits = if xyz {
+                    ma | mb == pivot_mask
+                } else {
+                    (ma | mb) & !z == pivot_mask && pivot_mask & z == 0
+                };
+                if !fits {
+                    continue;
+                }
+                let z = mask_values(z)[0];
+                let cells: &[Coord] = if xyz { &[pivot, a, b] } else { &[a, b] };
+                let mut step = SolveStep::new(technique, None);
+                step.eliminations = eliminate_seen(state, cells, z)
+                    .into_iter()
+                    .filter(|(c, _)| *c != pivot)
+                    .collect();
+                if !step.eliminations.is_empty() {
+                    step.cells = vec![pivot, a, b];
+                    step.digits = vec![z];
+                    return Some(step);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Two cells with candidates `x` and `y` that don't see each other. If a
+/// unit has only two places for `x`, one seeing each cell, one of the cells
+/// is `y`.
+fn w_wing(state: &LogicState) -> Option<SolveStep> {
+    let cells = bivalue_cells(state);
+    for (i, &a) in cells.iter().enumerate() {
+        for &b in &cells[i + 1..] {
+            let mask = state.mask(a);
+            if state.mask(b) != mask || sees(a, b) {
+                continue;
+            }
+            let digits = mask_values(mask);
+            for (x, y) in [(digits[0], digits[1]), (digits[1], digits[0])] {
+                for unit in Unit::all() {
+                    let link = match state.cells_with(unit, x)[..] {
+                        [c, d] => [c, d],
+                        _ => continue,
+                    };
+                    let (c, d) = if sees(link[0], a) && sees(link[1], b) {
+                        (link[0], link[1])
+                    } else if sees(link[1], a) && sees(link[0], b) {
+                        (link[1], link[0])
+                    } else {
+                        continue;
+                    };
+                    if [a, b].contains(&c) || [a, b].contains(&d) {
+                        continue;
+                    }
+                    let mut step = SolveStep::new(Technique::WWing, Some(unit));
+                    step.eliminations = eliminate_seen(state, &[a, b], y);
+                    if !step.eliminations.is_empty() {
+                        step.cells = vec![a, b, c, d];
+                        step.digits = vec![y, x];
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether a cell has no candidates left or a unit no place for a digit.
+fn contradicts(state: &LogicState) -> bool {
+    state.is_broken()
+        || Unit::all().any(|unit| {
+            let used = unit
+                .coords()
+                .fold(0, |mask, c| mask | state.grid.0[c.index()].0);
+            used != 0b1_1111_1111
+        })
+}
+
+/// Colors the cells of `value` linked by conjugate pairs, returning the
+/// colored cells of each chain with their color.
+fn color_chains(state: &LogicState, value: Value) -> Vec<Vec<(Coord, bool)>> {
+    let mut links: Vec<Vec<Coord>> = vec![Vec::new(); 81];
+    for unit in Unit::all() {
+        if let [a, b] = state.cells_with(unit, value)[..] {
+            links[a.index()].push(b);
+            links[b.index()].push(a);
+        }
+    }
+    let mut color: [Option<bool>; 81] = [None; 81];
+    let mut chains = Vec::new();
+    for start in 0..81 {
+        if color[start].is_some() || links[start].is_empty() {
+            continue;
+        }
+        color[start] = Some(true);
+        let mut chain = vec![(Coord::from_index(start), true)];
+        let mut i = 0;
+        while i < chain.len() {
+            let (cell, c) = chain[i];
+            for &next in &links[cell.index()] {
+                if color[next.index()].is_none() {
+                    color[next.index()] = Some(!c);
+                    chain.push((next, !c));
+                }
+            }
+            i += 1;
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+/// Either color of a chain holds the digit. Two cells of one color seeing
+/// each other rule that color out, and a cell seeing both colors can't hold
+/// the digit.
+fn simple_coloring(state: &LogicState) -> Option<SolveStep> {
+    for value in values() {
+        for chain in color_chains(state, value) {
+            let mut step = SolveStep::new(Technique::SimpleColoring, None);
+            for color in [true, false] {
+                let cells: Vec<Coord> = chain
+                    .iter()
+                    .filter(|(_, c)| *c == color)
+                    .map(|(cell, _)| *cell)
+                    .collect();
+                let wrapped = cells
+                    .iter()
+                    .enumerate()
+                    .any(|(i, &a)| cells[i + 1..].iter().any(|&b| sees(a, b)));
+                if wrapped {
+                    step.eliminations = cells.into_iter().map(|cell| (cell, value)).collect();
+                    break;
+                }
+            }
+            if step.eliminations.is_empty() {
+                step.eliminations = (0..81)
+                    .map(Coord::from_index)
+                    .filter(|&c| state.mask(c) & value.mask() != 0)
+                    .filter(|&c| chain.iter().all(|(cell, _)| *cell != c))
+                    .filter(|&c| {
+                        [true, false].iter().all(|&color| {
+                            chain
+                                .iter()
+                                .any(|&(cell, col)| col == color && sees(c, cell))
+                        })
+                    })
+                    .map(|c| (c, value))
+                    .collect();
+            }
+            if !step.eliminations.is_empty() {
+                step.cells = chain.iter().map(|(cell, _)| *cell).collect();
+                step.digits.push(value);
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// Places `value` at `cell` and follows at most `len` singles, `None` on a
+/// contradiction.
+fn follow_singles(state: &LogicState, cell: Coord, value: Value, len: usize) -> Option<LogicState> {
+    let mut state = *state;
+    state.place(cell, value);
+    for _ in 0..len {
+        if contradicts(&state) {
+            return None;
+        }
+        match naked_single(&state).or_else(|| hidden_single(&state)) {
+            Some(step) => state.apply(&step),
+            None => break,
+        }
+    }
+    if contradicts(&state) {
+        None
+    } else {
+        Some(state)
+    }
+}
+
+/// Assumes each candidate of a cell with two of them in turn. If one leads
+/// to a contradiction the cell holds the other, and what follows from both
+/// holds regardless.
+fn forcing_chain(state: &LogicState, len: usize) -> Option<SolveStep> {
+    for cell in bivalue_cells(state) {
+        let digits = mask_values(state.mask(cell));
+        let (a, b) = (digits[0], digits[1]);
+        let mut step = SolveStep::new(Technique::ForcingChain, None);
+        step.cells.push(cell);
+        step.digits = digits;
+        let (left, right) = match (
+            follow_singles(state, cell, a, len),
+            follow_singles(state, cell, b, len),
+        ) {
+            (None, None) => continue,
+            (None, Some(_)) => {
+                step.placements.push((cell, b));
+                return Some(step);
+            }
+            (Some(_), None) => {
+                step.placements.push((cell, a));
+                return Some(step);
+            }
+            (Some(left), Some(right)) => (left, right),
+        };
+        for other in (0..81).map(Coord::from_index).filter(|&c| c != cell) {
+            let mask = state.mask(other);
+            let (l, r) = (left.grid.0[other.index()].0, right.grid.0[other.index()].0);
+            if mask == 0 {
+                continue;
+            }
+            if l == r && l.count_ones() == 1 && mask != l {
+                step.placements.push((other, mask_values(l)[0]));
+            }
+            for value in mask_values(mask & !(l | r)) {
+                step.eliminations.push((other, value));
+            }
+        }
+        if !step.placements.is_empty() {
+            step.placements.truncate(1);
+            step.eliminations.clear();
+            return Some(step);
+        }
+        if !step.eliminations.is_empty() {
+            return Some(step);
+        }
+    }
+    None
+}
+
+/// Assumes each candidate of each cell in turn, removing the first one
+/// that leads to a contradiction. Cells with two candidates are left to
+/// forcing chains.
+fn nishio(state: &LogicState, len: usize) -> Option<SolveStep> {
+    for cell in (0..81).map(Coord::from_index) {
+        let mask = state.mask(cell);
+        if state.is_placed(cell.row(), cell.col()) || mask.count_ones() < 3 {
+            continue;
+        }
+        for value in mask_values(mask) {
+            if follow_singles(state, cell, value, len).is_none() {
+                let mut step = SolveStep::new(Technique::Nishio, None);
+                step.cells.push(cell);
+                step.digits.push(value);
+                step.eliminations.push((cell, value));
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// Units containing both cells.
+fn common_units(a: Coord, b: Coord) -> Vec<Unit> {
+    let mut units = Vec::new();
+    if a.row() == b.row() {
+        units.push(Unit::Row(a.row()));
+    }
+    if a.col() == b.col() {
+        units.push(Unit::Col(a.col()));
+    }
+    if a.block() == b.block() {
+        units.push(Unit::Block(a.block()));
+    }
+    units
+}
+
+/// Rectangles of unplaced cells in two boxes, with a pair of digits all of
+/// their corners share. The corners are ordered so that the first two and
+/// the last two share a line, as do the first and third.
+fn rectangles(state: &LogicState) -> Vec<([Coord; 4], u16)> {
+    let mut out = Vec::new();
+    for (r1, r2) in (0..9).flat_map(|r1| (r1 + 1..9).map(move |r2| (r1, r2))) {
+        for (c1, c2) in (0..9).flat_map(|c1| (c1 + 1..9).map(move |c2| (c1, c2))) {
+            if (r1 / 3 == r2 / 3) == (c1 / 3 == c2 / 3) {
+                continue;
+            }
+            let corners = [
+                Coord::new(r1, c1),
+                Coord::new(r1, c2),
+                Coord::new(r2, c1),
+                Coord::new(r2, c2),
+            ];
+            let common = corners.iter().fold(0x1ff, |mask, &c| mask & state.mask(c));
+            let digits = mask_values(common);
+            for (i, a) in digits.iter().enumerate() {
+                for b in &digits[i + 1..] {
+                    out.push((corners, a.mask() | b.mask()));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Unique rectangles of type 1 to 4, see the variants of [`Technique`].
+fn unique_rectangle(state: &LogicState, kind: u8) -> Option<SolveStep> {
+    let technique = match kind {
+        1 => Technique::UniqueRectangle1,
+        2 => Technique::UniqueRectangle2,
+        3 => Technique::UniqueRectangle3,
+        _ => Technique::UniqueRectangle4,
+    };
+    for (corners, pair) in rectangles(state) {
+        let mut step = SolveStep::new(technique, None);
+        step.digits = mask_values(pair);
+        let extra: Vec<Coord> = corners
+            .iter()
+            .copied()
+            .filter(|&c| state.mask(c) != pair)
+            .collect();
+        if kind == 1 {
+            if let [corner] = extra[..] {
+                step.eliminations = step.digits.iter().map(|&v| (corner, v)).collect();
+                step.cells = corners.to_vec();
+                return Some(step);
+            }
+            continue;
+        }
+        let roof = match extra[..] {
+            [a, b] if a.row() == b.row() || a.col() == b.col() => [a, b],
+            _ => continue,
+        };
+        let extras = (state.mask(roof[0]) | state.mask(roof[1])) & !pair;
+        match kind {
+            2 => {
+                if state.mask(roof[0]) != state.mask(roof[1]) || extras.count_ones() != 1 {
+                    continue;
+                }
+                step.eliminations = eliminate_seen(state, &roof, mask_values(extras)[0]);
+            }
+            3 => {
+                for unit in common_units(roof[0], roof[1]) {
+                    let others: Vec<Coord> = unit
+                        .coords()
+                        .filter(|&c| state.mask(c) != 0 && !roof.contains(&c))
+                        .collect();
+                    for size in 1..=3.min(others.len()) {
+                        for combo in combinations(others.len(), size) {
+                            let union = combo
+                                .iter()
+                                .fold(extras, |mask, &i| mask | state.mask(others[i]));
+                            if union.count_ones() as usize != size + 1 {
+                                continue;
+                            }
+                            for (i, &cell) in others.iter().enumerate() {
+                                if !combo.contains(&i) {
+                                    for value in mask_values(state.mask(cell) & union) {
+                                        step.eliminations.push((cell, value));
+                                    }
+                                }
+                            }
+                            if !step.eliminations.is_empty() {
+                                step.unit = Some(unit);
+                                break;
+                            }
+                        }
+                        if !step.eliminations.is_empty() {
+                            break;
+                        }
+                    }
+                    if !step.eliminations.is_empty() {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                for unit in common_units(roof[0], roof[1]) {
+                    for (i, &linked) in step.digits.iter().enumerate() {
+                        if state.cells_with(unit, linked)[..] == roof[..] {
+                            let other = step.digits[1 - i];
+                            step.eliminations = roof.iter().map(|&c| (c, other)).collect();
+                            step.unit = Some(unit);
+                            break;
+                        }
+                    }
+                    if !step.eliminations.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+        if !step.eliminations.is_empty() {
+            step.cells = corners.to_vec();
+            return Some(step);
+        }
+    }
+    None
+}
+
+/// All unplaced cells have two candidates but one with three, and in each
+/// unit every digit has two places but the one the extra candidate belongs
+/// to, which has three. Without it the grid would have no or two solutions.
+fn bug_plus_one(state: &LogicState) -> Option<SolveStep> {
+    let open: Vec<Coord> = (0..81)
+        .map(Coord::from_index)
+        .filter(|&c| state.mask(c) != 0)
+        .collect();
+    let cell = match open
+        .iter()
+        .filter(|&&c| state.mask(c).count_ones() != 2)
+        .collect::<Vec<_>>()[..]
+    {
+        [&cell] if state.mask(cell).count_ones() == 3 => cell,
+        _ => return None,
+    };
+    for unit in Unit::all() {
+        for value in values() {
+            let count = state.cells_with(unit, value).len();
+            let extra = unit.coords().any(|c| c == cell) && state.mask(cell) & value.mask() != 0;
+            if count != 0 && count != 2 && !(extra && count == 3) {
+                return None;
+            }
+        }
+    }
+    let value = mask_values(state.mask(cell)).into_iter().find(|&v| {
+        let row = Unit::Row(cell.row());
+        state.cells_with(row, v).len() == 3
+    })?;
+    let mut step = SolveStep::new(Technique::BugPlusOne, None);
+    step.cells.push(cell);
+    step.digits.push(value);
+    step.placements.push((cell, value));
+    Some(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logical_solver() {
+        let sudoku: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let solution = LogicalSolver::default().solve(&sudoku);
+        assert!(solution.solved);
+        assert!(solution.grid.valid());
+        assert_eq!(solution.steps.len(), 51);
+
+        assert!(LogicalSolver::singles().can_solve(&sudoku));
+        let singles = LogicalSolver::new(vec![Technique::NakedSingle]);
+        assert!(!singles.solve(&Sudoku::new()).solved);
+        assert!(!LogicalSolver::singles().can_solve(&Sudoku::new()));
+    }
+
+    #[test]
+    fn test_subsets() {
+        let mut sudoku = Sudoku::new();
+        sudoku.0[0].0 = 0b11;
+        sudoku.0[1].0 = 0b11;
+        let step = naked_subset(&LogicState::new(&sudoku), 2).unwrap();
+        assert_eq!(step.technique, Technique::NakedPair);
+        assert_eq!(step.unit, Some(Unit::Block(0)));
+        assert_eq!(step.cells, vec![Coord::new(0, 0), Coord::new(0, 1)]);
+        assert_eq!(step.digits, vec![Value(0), Value(1)]);
+        assert!(step.eliminations.contains(&(Coord::new(0, 2), Value(1))));
+        assert_eq!(step.eliminations.len(), 14);
+
+        // 1 and 2 are confined to the first two cells of row 1
+        let mut sudoku = Sudoku::new();
+        for cell in &mut sudoku.0[2..9] {
+            cell.0 &= !0b11;
+        }
+        let state = LogicState::new(&sudoku);
+        assert!(naked_subset(&state, 2).is_none());
+        let step = hidden_subset(&state, 2).unwrap();
+        assert_eq!(step.technique, Technique::HiddenPair);
+        assert_eq!(step.unit, Some(Unit::Row(0)));
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(!step.eliminations.contains(&(Coord::new(0, 0), Value(0))));
+    }
+
+    #[test]
+    fn test_fish() {
+        // 1 only fits in columns 2 and 6 of rows 1 and 5
+        let mut sudoku = Sudoku::new();
+        for &row in &[0, 4] {
+            for col in (0..9).filter(|&col| col != 1 && col != 5) {
+                sudoku.0[row * 9 + col].0 &= !1;
+            }
+        }
+        let step = fish(&LogicState::new(&sudoku), 2).unwrap();
+        assert_eq!(step.technique, Technique::XWing);
+        assert_eq!(step.cells.len(), 4);
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step.eliminations.contains(&(Coord::new(8, 5), Value(0))));
+        assert!(fish(&LogicState::new(&Sudoku::new()), 3).is_none());
+    }
+
+    #[test]
+    fn test_wings() {
+        let mask = |digits: &[u8]| digits.iter().fold(0, |m, d| m | 1 << (d - 1));
+        // pivot r1c1 {1,2}, pincers r1c5 {1,3} and r5c1 {2,3}
+        let mut sudoku = Sudoku::new();
+        sudoku.0[0].0 = mask(&[1, 2]);
+        sudoku.0[4].0 = mask(&[1, 3]);
+        sudoku.0[36].0 = mask(&[2, 3]);
+        let step = xy_wing(&LogicState::new(&sudoku), false).unwrap();
+        assert_eq!(step.technique, Technique::XYWing);
+        assert_eq!(step.digits, vec![Value(2)]);
+        assert_eq!(step.eliminations, vec![(Coord::new(4, 4), Value(2))]);
+
+        sudoku.0[0].0 = mask(&[1, 2, 3]);
+        let state = LogicState::new(&sudoku);
+        assert!(xy_wing(&state, false).is_none());
+        assert!(xy_wing(&state, true).is_none());
+
+        // r1c1 and r5c5 are {1,2}, 2 in column 9 only at r1c9 and r5c9
+        let mut sudoku = Sudoku::new();
+        sudoku.0[0].0 = mask(&[1, 2]);
+        sudoku.0[40].0 = mask(&[1, 2]);
+        for row in (0..9).filter(|&row| row != 0 && row != 4) {
+            sudoku.0[row * 9 + 8].0 &= !mask(&[2]);
+        }
+        let step = w_wing(&LogicState::new(&sudoku)).unwrap();
+        assert_eq!(step.technique, Technique::WWing);
+        assert_eq!(step.digits, vec![Value(0), Value(1)]);
+        assert_eq!(
+            step.eliminations,
+            vec![(Coord::new(0, 4), Value(0)), (Coord::new(4, 0), Value(0))]
+        );
+    }
+
+    #[test]
+    fn test_chains() {
+        let techniques = vec![
+            Technique::HiddenSingle,
+            Technique::NakedSingle,
+            Technique::SimpleColoring,
+            Technique::ForcingChain,
+        ];
+        let corpus = crate::Corpus::seventeen_sample();
+        for puzzle in corpus.puzzles.iter() {
+            let truth = crate::solve(puzzle).unwrap();
+            let solution = LogicalSolver::new(techniques.clone()).solve(puzzle);
+            assert!(solution.solved);
+            for step in &solution.steps {
+                for &(cell, value) in &step.eliminations {
+                    assert_ne!(
+                        truth.get(cell.row(), cell.col()).unwrap().value(),
+                        Some(value)
+                    );
+                }
+                for &(cell, value) in &step.placements {
+                    assert_eq!(
+                        truth.get(cell.row(), cell.col()).unwrap().value(),
+                        Some(value)
+                    );
+                }
+            }
+        }
+
+        let puzzle = corpus.puzzles.iter().nth(4).unwrap();
+        let mut solver = LogicalSolver::new(techniques);
+        assert!(solver.can_solve(puzzle));
+        solver.set_max_chain_length(0);
+        assert!(!solver.can_solve(puzzle));
+    }
+
+    #[test]
+    fn test_nishio() {
+        let corpus = crate::Corpus::top1465_sample();
+        let puzzle = corpus.puzzles.iter().next().unwrap();
+        let truth = crate::solve(puzzle).unwrap();
+        let mut solver = LogicalSolver::new(vec![
+            Technique::HiddenSingle,
+            Technique::NakedSingle,
+            Technique::Nishio,
+        ]);
+        let solution = solver.solve(puzzle);
+        assert!(solution
+            .steps
+            .iter()
+            .any(|s| s.technique == Technique::Nishio));
+        for step in solution
+            .steps
+            .iter()
+            .filter(|s| s.technique == Technique::Nishio)
+        {
+            let (cell, value) = step.eliminations[0];
+            assert_ne!(truth[cell].value(), Some(value));
+        }
+        assert!(solution.solved);
+        solver.set_max_trial_length(0);
+        // without following singles only immediate contradictions are found
+        assert!(!solver.solve(puzzle).solved);
+    }
+
+    #[test]
+    fn test_unique_rectangles() {
+        let pair = 0b11;
+        // r1c1, r1c5 and r2c1 are {1,2}, r2c5 also holds 3
+        let mut sudoku = Sudoku::new();
+        for &i in &[0, 4, 9] {
+            sudoku.0[i].0 = pair;
+        }
+        sudoku.0[13].0 = pair | 0b100;
+        let state = LogicState::new(&sudoku);
+        let step = unique_rectangle(&state, 1).unwrap();
+        assert_eq!(
+            step.eliminations,
+            vec![(Coord::new(1, 4), Value(0)), (Coord::new(1, 4), Value(1))]
+        );
+        let mut solver = LogicalSolver::new(vec![Technique::UniqueRectangle1]);
+        assert!(solver.next_step(&state).is_none());
+        solver.set_assume_unique(true);
+        assert_eq!(solver.next_step(&state), Some(step));
+
+        // the roof r2c1, r2c5 is {1,2,3}
+        sudoku.0[9].0 = pair | 0b100;
+        let step = unique_rectangle(&LogicState::new(&sudoku), 2).unwrap();
+        assert_eq!(step.eliminations.len(), 7);
+        assert!(step
+            .eliminations
+            .iter()
+            .all(|&(c, v)| c.row() == 1 && v == Value(2)));
+
+        // 1 only fits in the roof of row 2
+        for i in (9..18).filter(|&i| i != 9 && i != 13) {
+            sudoku.0[i].0 &= !1;
+        }
+        let step = unique_rectangle(&LogicState::new(&sudoku), 4).unwrap();
+        assert_eq!(step.unit, Some(Unit::Row(1)));
+        assert_eq!(
+            step.eliminations,
+            vec![(Coord::new(1, 0), Value(1)), (Coord::new(1, 4), Value(1))]
+        );
+    }
+}
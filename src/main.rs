@@ -0,0 +1,539 @@
+//! The `sudoku` command line tool.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::ExitCode;
+use std::time::Instant;
+use sudoku::analysis::Symmetry;
+use sudoku::io::Format;
+use sudoku::{
+    generate_batch_with, parse_collection, solve, Bucket, Error, GenerateOptions, Sudoku,
+};
+
+const USAGE: &str = "\
+usage: sudoku solve [options] [FILE]
+       sudoku rate [options] [FILE]
+       sudoku generate [options]
+       sudoku play [options] [FILE]
+       sudoku serve [--addr ADDR]
+
+solve reads puzzles from FILE, or from stdin if there is none, and prints
+their solutions. rate prints each puzzle with its difficulty tier, score,
+hardest technique and number of clues, separated by tabs.
+
+  --from FORMAT    input format: sdm (one puzzle per line), sdk or ss,
+                   taken from the extension of FILE or the input otherwise
+  --time           print the time taken for each puzzle to stderr (solve)
+
+generate prints new proper puzzles.
+
+  --difficulty D   beginner, easy, medium (default), hard, expert or
+                   diabolical
+  --count N        number of puzzles, 1 by default and at most 10000
+  --seed N         makes the puzzles reproducible
+  --symmetry S     rotational, rotational90, horizontal, vertical, diagonal
+                   or anti-diagonal
+  --minimal        only minimal puzzles, possibly losing the symmetry
+
+play starts a game in the terminal with the first puzzle of FILE, or a new
+one of the --difficulty and --seed given. It needs the tui feature.
+
+serve answers HTTP requests on ADDR, 127.0.0.1:8080 by default. It needs
+the server feature.
+
+solve and generate:
+  --to FORMAT      output format: line (default), grid, pretty, sdk or ss
+";
+
+/// The most puzzles `generate` makes in one run.
+const MAX_COUNT: u64 = 10_000;
+
+/// How puzzles are written.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Output {
+    /// 81 cells on a line.
+    Line,
+    /// One row per line as by `Display`.
+    Grid,
+    /// With box drawing characters.
+    Pretty,
+    Sdk,
+    Ss,
+}
+
+impl std::str::FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "line" | "sdm" => Ok(Output::Line),
+            "grid" => Ok(Output::Grid),
+            "pretty" => Ok(Output::Pretty),
+            "sdk" => Ok(Output::Sdk),
+            "ss" => Ok(Output::Ss),
+            _ => Err(format!("unknown output format {:?}", s)),
+        }
+    }
+}
+
+impl Output {
+    /// Writes a puzzle, `first` tells if others came before it.
+    fn write(self, out: &mut impl Write, sudoku: &Sudoku, first: bool) -> Result<(), Error> {
+        let grid = matches!(
+            self,
+            Output::Grid | Output::Pretty | Output::Sdk | Output::Ss
+        );
+        if grid && !first {
+            writeln!(out)?;
+        }
+        match self {
+            Output::Line => writeln!(out, "{}", sudoku.to_line())?,
+            Output::Grid => write!(out, "{}", sudoku)?,
+            Output::Pretty => write!(out, "{:#}", sudoku)?,
+            Output::Sdk => sudoku::io::write(out, [sudoku], Format::Sdk)?,
+            Output::Ss => sudoku::io::write(out, [sudoku], Format::Ss)?,
+        }
+        Ok(())
+    }
+}
+
+/// The value following an option.
+fn value<'a>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> Result<&'a str, String> {
+    args.next()
+        .map(String::as_str)
+        .ok_or(format!("{} needs a value", option))
+}
+
+fn parse_number(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number {:?}", value))
+}
+
+/// Where puzzles are read from, shared by the commands taking puzzles.
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Input {
+    file: Option<String>,
+    from: Option<Format>,
+}
+
+impl Input {
+    /// Takes `--from` and the file, `false` if `arg` is neither.
+    fn parse_arg<'a>(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        match arg {
+            "--from" => {
+                let format = value(args, arg)?;
+                let format = Format::from_extension(format)
+                    .ok_or(format!("unknown input format {:?}", format))?;
+                self.from = Some(format);
+            }
+            _ if arg.starts_with("--") => return Ok(false),
+            _ if self.file.is_none() => self.file = Some(arg.to_string()),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+        Ok(true)
+    }
+
+    /// The puzzles of the input, streamed if given one per line.
+    fn puzzles(&self) -> Result<Box<dyn Iterator<Item = Result<Sudoku, Error>>>, Error> {
+        let mut reader: Box<dyn BufRead> = match &self.file {
+            Some(path) => Box::new(BufReader::new(File::open(path)?)),
+            None => Box::new(BufReader::new(io::stdin())),
+        };
+        let extension = self
+            .file
+            .as_deref()
+            .and_then(|path| path.rsplit_once('.'))
+            .and_then(|(_, extension)| Format::from_extension(extension));
+        let format = match self.from.or(extension) {
+            Some(format) => format,
+            None => detect(&mut reader)?,
+        };
+        if format == Format::Sdm {
+            return Ok(Box::new(parse_collection(reader)));
+        }
+        let set = sudoku::io::read(reader, format)?;
+        let puzzles: Vec<_> = set.iter().map(|&puzzle| Ok(puzzle)).collect();
+        Ok(Box::new(puzzles.into_iter()))
+    }
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Solve {
+    input: Input,
+    to: Option<Output>,
+    time: bool,
+}
+
+impl Solve {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--to" => options.to = Some(value(&mut args, arg)?.parse()?),
+                "--time" => options.time = true,
+                _ if options.input.parse_arg(arg, &mut args)? => {}
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Solves every puzzle, `false` if one is malformed or has no solution.
+    fn run(&self) -> Result<bool, Error> {
+        let stdout = io::stdout();
+        let mut out = io::BufWriter::new(stdout.lock());
+        let to = self.to.unwrap_or(Output::Line);
+        let mut ok = true;
+        for (i, puzzle) in self.input.puzzles()?.enumerate() {
+            let puzzle = match puzzle {
+                Ok(puzzle) => puzzle,
+                Err(err) => {
+                    eprintln!("puzzle {}: {}", i + 1, err);
+                    ok = false;
+                    continue;
+                }
+            };
+            let start = Instant::now();
+            let solution = solve(&puzzle);
+            if self.time {
+                let ms = start.elapsed().as_secs_f64() * 1000.0;
+                eprintln!("puzzle {}: {:.3} ms", i + 1, ms);
+            }
+            match solution {
+                Some(solution) => to.write(&mut out, &solution, i == 0)?,
+                None => {
+                    eprintln!("puzzle {}: no solution", i + 1);
+                    ok = false;
+                }
+            }
+        }
+        out.flush()?;
+        Ok(ok)
+    }
+}
+
+/// Tells the one line format from grids by the first line of the input
+/// that isn't a comment.
+fn detect(reader: &mut impl BufRead) -> Result<Format, Error> {
+    let text = String::from_utf8_lossy(reader.fill_buf()?).into_owned();
+    let first = text
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .find(|token| !token.starts_with('#'));
+    Ok(match first {
+        Some(token) if token.len() == 81 => Format::Sdm,
+        _ => Format::Sdk,
+    })
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Generate {
+    difficulty: Option<Bucket>,
+    count: Option<usize>,
+    options: GenerateOptions,
+    to: Option<Output>,
+}
+
+impl Generate {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut generate = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--difficulty" => {
+                    generate.difficulty = Some(parse_difficulty(value(&mut args, arg)?)?)
+                }
+                "--count" => match parse_number(value(&mut args, arg)?)? {
+                    count if count > MAX_COUNT => {
+                        return Err(format!("--count is at most {}", MAX_COUNT))
+                    }
+                    count => generate.count = Some(count as usize),
+                },
+                "--seed" => generate.options.seed = Some(parse_number(value(&mut args, arg)?)?),
+                "--symmetry" => {
+                    generate.options.symmetry = Some(parse_symmetry(value(&mut args, arg)?)?)
+                }
+                "--minimal" => generate.options.minimal = true,
+                "--to" => generate.to = Some(value(&mut args, arg)?.parse()?),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(generate)
+    }
+
+    fn run(&self) -> Result<bool, Error> {
+        let stdout = io::stdout();
+        let mut out = io::BufWriter::new(stdout.lock());
+        let to = self.to.unwrap_or(Output::Line);
+        let difficulty = self.difficulty.unwrap_or(Bucket::Medium);
+        let puzzles = generate_batch_with(self.count.unwrap_or(1), difficulty, &self.options);
+        for (i, puzzle) in puzzles.iter().enumerate() {
+            to.write(&mut out, puzzle, i == 0)?;
+        }
+        out.flush()?;
+        Ok(true)
+    }
+}
+
+fn parse_difficulty(name: &str) -> Result<Bucket, String> {
+    name.parse().map_err(|err: Error| err.to_string())
+}
+
+fn parse_symmetry(name: &str) -> Result<Symmetry, String> {
+    name.parse().map_err(|err: Error| err.to_string())
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Rate {
+    input: Input,
+}
+
+impl Rate {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut rate = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !rate.input.parse_arg(arg, &mut args)? {
+                return Err(format!("unknown option {}", arg));
+            }
+        }
+        Ok(rate)
+    }
+
+    fn run(&self) -> Result<bool, Error> {
+        let stdout = io::stdout();
+        let mut out = io::BufWriter::new(stdout.lock());
+        let ok = self.write(&mut out)?;
+        out.flush()?;
+        Ok(ok)
+    }
+
+    /// Writes a line per puzzle with its tier, score, hardest technique and
+    /// clue count, separated by tabs. `false` if one is malformed.
+    fn write(&self, out: &mut impl Write) -> Result<bool, Error> {
+        let mut ok = true;
+        for (i, puzzle) in self.input.puzzles()?.enumerate() {
+            let puzzle = match puzzle {
+                Ok(puzzle) => puzzle,
+                Err(err) => {
+                    eprintln!("puzzle {}: {}", i + 1, err);
+                    ok = false;
+                    continue;
+                }
+            };
+            let rating = puzzle.rate();
+            writeln!(
+                out,
+                "{}\t{}\t{:.1}\t{}\t{}",
+                puzzle.to_line(),
+                rating.tier.name(),
+                rating.score,
+                rating.hardest.map_or("-", |technique| technique.id()),
+                puzzle.into_iter().count(),
+            )?;
+        }
+        Ok(ok)
+    }
+}
+
+#[cfg(all(feature = "tui", unix))]
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Play {
+    input: Input,
+    difficulty: Option<Bucket>,
+    seed: Option<u64>,
+}
+
+#[cfg(all(feature = "tui", unix))]
+impl Play {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut play = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--difficulty" => play.difficulty = Some(parse_difficulty(value(&mut args, arg)?)?),
+                "--seed" => play.seed = Some(parse_number(value(&mut args, arg)?)?),
+                _ if play.input.parse_arg(arg, &mut args)? => {}
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(play)
+    }
+
+    fn run(&self) -> Result<bool, Error> {
+        let puzzle = if self.input.file.is_some() {
+            match self.input.puzzles()?.next() {
+                Some(puzzle) => puzzle?,
+                None => return Err(Error::InvalidFormat),
+            }
+        } else {
+            let options = GenerateOptions {
+                seed: self.seed,
+                ..GenerateOptions::default()
+            };
+            sudoku::generate_with(self.difficulty.unwrap_or(Bucket::Medium), &options)
+        };
+        let grid = sudoku::tui::play(&puzzle)?;
+        println!("{}", grid.to_line());
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "server")]
+struct Serve {
+    addr: String,
+}
+
+#[cfg(feature = "server")]
+impl Serve {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut serve = Self {
+            addr: "127.0.0.1:8080".into(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--addr" => serve.addr = value(&mut args, arg)?.to_string(),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(serve)
+    }
+
+    fn run(&self) -> Result<bool, Error> {
+        let server = sudoku::server::Server::bind(&self.addr)?;
+        eprintln!("listening on {}", server.local_addr()?);
+        server.run()?;
+        Ok(true)
+    }
+}
+
+/// Parses the arguments of a command and runs it.
+fn run<T>(
+    args: &[String],
+    parse: fn(&[String]) -> Result<T, String>,
+    run: fn(&T) -> Result<bool, Error>,
+) -> ExitCode {
+    let command = match parse(args) {
+        Ok(command) => command,
+        Err(err) => {
+            eprint!("{}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => {
+            eprint!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match command {
+        "solve" => run(rest, Solve::parse, Solve::run),
+        "generate" => run(rest, Generate::parse, Generate::run),
+        "rate" => run(rest, Rate::parse, Rate::run),
+        #[cfg(all(feature = "tui", unix))]
+        "play" => run(rest, Play::parse, Play::run),
+        #[cfg(feature = "server")]
+        "serve" => run(rest, Serve::parse, Serve::run),
+        "-h" | "--help" | "help" => {
+            print!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprint!("unknown command {}\n\n{}", command, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_options() {
+        let options = Solve::parse(&args("--from ss --to pretty --time a.txt")).unwrap();
+        assert_eq!(options.to, Some(Output::Pretty));
+        assert!(options.time);
+        assert_eq!(options.input.from, Some(Format::Ss));
+        assert_eq!(options.input.file.as_deref(), Some("a.txt"));
+        assert!(Solve::parse(&args("--to")).is_err());
+        assert!(Solve::parse(&args("--to png")).is_err());
+        assert!(Solve::parse(&args("a b")).is_err());
+        let rate = Rate::parse(&args("--from sdm puzzles.txt")).unwrap();
+        assert_eq!(rate.input.from, Some(Format::Sdm));
+        assert!(Rate::parse(&args("--time")).is_err());
+    }
+
+    #[test]
+    fn test_generate_options() {
+        let generate = Generate::parse(&args(
+            "--difficulty hard --count 100 --seed 42 --symmetry rotational",
+        ))
+        .unwrap();
+        assert_eq!(generate.difficulty, Some(Bucket::Hard));
+        assert_eq!(generate.count, Some(100));
+        assert_eq!(generate.options.seed, Some(42));
+        assert_eq!(generate.options.symmetry, Some(Symmetry::Rotational180));
+        assert!(Generate::parse(&args("--difficulty impossible")).is_err());
+        assert!(Generate::parse(&args("--count many")).is_err());
+        assert!(Generate::parse(&args("--count 10001")).is_err());
+        assert!(Generate::parse(&args("puzzles.sdm")).is_err());
+    }
+
+    #[test]
+    fn test_rate() {
+        let path = std::env::temp_dir().join(format!("sudoku-rate-{}.sdm", std::process::id()));
+        let line =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        std::fs::write(&path, format!("{}\nnot a puzzle\n", line)).unwrap();
+        let rate = Rate::parse(&[path.to_string_lossy().into_owned()]).unwrap();
+        let mut out = Vec::new();
+        let ok = rate.write(&mut out).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!ok);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}\teasy\t1.2\thidden-single\t32\n", line)
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let line =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        let input = format!("# puzzles\n\n{}\n", line);
+        assert_eq!(detect(&mut input.as_bytes()).unwrap(), Format::Sdm);
+        let grid = "..3|.2.|6..\n9..|3.5|..1\n";
+        assert_eq!(detect(&mut grid.as_bytes()).unwrap(), Format::Sdk);
+
+        let puzzle: Sudoku = line.parse().unwrap();
+        let mut out = Vec::new();
+        Output::Ss.write(&mut out, &puzzle, true).unwrap();
+        Output::Ss.write(&mut out, &puzzle, false).unwrap();
+        let set = sudoku::io::read(&out[..], Format::Ss).unwrap();
+        assert_eq!(set.len(), 2);
+    }
+}
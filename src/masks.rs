@@ -0,0 +1,66 @@
+//! The digits used by each row, column and block as bitmasks.
+use crate::{Sudoku, Value};
+
+const ALL: u16 = 0b1_1111_1111;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct UnitMasks {
+    rows: [u16; 9],
+    cols: [u16; 9],
+    blocks: [u16; 9],
+}
+
+fn block(x: usize, y: usize) -> usize {
+    (x / 3) * 3 + y / 3
+}
+
+impl UnitMasks {
+    /// The digits of the cells with a value, `None` if a unit repeats one.
+    pub fn from_sudoku(sudoku: &Sudoku) -> Option<Self> {
+        let mut masks = Self::default();
+        for (i, cell) in sudoku.0.iter().enumerate() {
+            if let Some(value) = cell.value() {
+                if !masks.allows(i / 9, i % 9, value) {
+                    return None;
+                }
+                masks.place(i / 9, i % 9, value);
+            }
+        }
+        Some(masks)
+    }
+
+    /// The digits none of the units of the cell use yet.
+    pub fn allowed(&self, x: usize, y: usize) -> u16 {
+        !(self.rows[x] | self.cols[y] | self.blocks[block(x, y)]) & ALL
+    }
+
+    pub fn allows(&self, x: usize, y: usize, value: Value) -> bool {
+        self.allowed(x, y) & value.mask() != 0
+    }
+
+    pub fn place(&mut self, x: usize, y: usize, value: Value) {
+        self.rows[x] |= value.mask();
+        self.cols[y] |= value.mask();
+        self.blocks[block(x, y)] |= value.mask();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_masks() {
+        let mut sudoku = Sudoku::new();
+        sudoku.get_mut(0, 0).unwrap().set(Value(4));
+        let masks = UnitMasks::from_sudoku(&sudoku).unwrap();
+        assert!(!masks.allows(0, 8, Value(4)));
+        assert!(!masks.allows(8, 0, Value(4)));
+        assert!(!masks.allows(2, 2, Value(4)));
+        assert!(masks.allows(3, 3, Value(4)));
+        assert_eq!(masks.allowed(1, 1), ALL & !Value(4).mask());
+
+        sudoku.get_mut(0, 5).unwrap().set(Value(4));
+        assert!(UnitMasks::from_sudoku(&sudoku).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Random access into puzzle files through a read-only memory map.
+use crate::corpus::parse_line;
+use crate::db::{decode_checked, parse_header, Layout};
+use crate::rating::RatedPuzzle;
+use crate::{Error, Sudoku};
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// The mapping is private and read-only.
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    fn open(path: &Path) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Ok(Self {
+                ptr: std::ptr::null_mut(),
+                len,
+            });
+        }
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Self { ptr, len })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+/// A file with one puzzle of 81 characters per line, `.` or `0` for blanks.
+/// All lines must have the same length, either `\n` or `\r\n` terminated.
+pub struct MappedLines {
+    map: Mmap,
+    stride: usize,
+}
+
+impl MappedLines {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let map = Mmap::open(path.as_ref())?;
+        let bytes = map.bytes();
+        let stride = match bytes.iter().position(|&b| b == b'\n') {
+            Some(81) => 82,
+            Some(82) if bytes[81] == b'\r' => 83,
+            Some(found) => {
+                return Err(Error::WrongLength {
+                    expected: 81,
+                    found,
+                })
+            }
+            None if bytes.is_empty() || bytes.len() == 81 => 82,
+            None => {
+                return Err(Error::WrongLength {
+                    expected: 81,
+                    found: bytes.len(),
+                })
+            }
+        };
+        Ok(Self { map, stride })
+    }
+
+    pub fn len(&self) -> usize {
+        let len = self.map.len;
+        // the last line may lack its line terminator
+        len / self.stride + (len % self.stride >= 81) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The raw 81 characters of a line.
+    pub fn line(&self, index: usize) -> Option<&[u8]> {
+        if index >= self.len() {
+            return None;
+        }
+        let start = index * self.stride;
+        Some(&self.map.bytes()[start..start + 81])
+    }
+
+    pub fn get(&self, index: usize) -> Option<Result<Sudoku, Error>> {
+        self.line(index).map(parse_line)
+    }
+}
+
+/// A puzzle database mapped into memory, see [`PuzzleDb`](crate::PuzzleDb)
+/// for the format.
+pub struct MappedDb {
+    map: Mmap,
+    layout: Layout,
+    len: usize,
+}
+
+impl MappedDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let map = Mmap::open(path.as_ref())?;
+        let bytes = map.bytes();
+        let (layout, len, _) = parse_header(bytes)?;
+        let len = len as usize;
+        if layout.offset(len) as usize > bytes.len() {
+            return Err(Error::InvalidFormat);
+        }
+        Ok(Self { map, layout, len })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The raw bytes of a record.
+    pub fn record(&self, index: usize) -> Option<&[u8]> {
+        if index >= self.len {
+            return None;
+        }
+        let start = self.layout.offset(index) as usize;
+        Some(&self.map.bytes()[start..start + self.layout.record_len()])
+    }
+
+    /// Decodes a record, checking its checksum if the database has them.
+    pub fn get(&self, index: usize) -> Option<Result<RatedPuzzle, Error>> {
+        self.record(index)
+            .map(|record| decode_checked(self.layout, index, record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PuzzleDb;
+
+    const EASY: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn test_mapped_lines() {
+        let path = std::env::temp_dir().join(format!("sudoku-lines-{}", std::process::id()));
+        std::fs::write(&path, format!("{}\n{}", EASY, EASY.replace('.', "0"))).unwrap();
+        let lines = MappedLines::open(&path).unwrap();
+        assert_eq!(lines.len(), 2);
+        let first = lines.get(0).unwrap().unwrap();
+        let second = lines.get(1).unwrap().unwrap();
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(first.iter_filled().count(), 30);
+        assert!(lines.get(2).is_none());
+
+        std::fs::write(&path, "123\n").unwrap();
+        assert!(MappedLines::open(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_db() {
+        let path = std::env::temp_dir().join(format!("sudoku-db-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let easy = parse_line(EASY.as_bytes()).unwrap();
+        let mut db = PuzzleDb::open(&path).unwrap();
+        db.append(&RatedPuzzle::new(easy)).unwrap();
+        drop(db);
+
+        let mapped = MappedDb::open(&path).unwrap();
+        assert_eq!(mapped.len(), 1);
+        let puzzle = mapped.get(0).unwrap().unwrap();
+        assert_eq!(puzzle.puzzle.to_string(), easy.to_string());
+        assert!(puzzle.rating.solved);
+        assert!(mapped.get(1).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+//! The move notation of solving logs, `r4c7=5` to place a 5 in row 4,
+//! column 7 and `r4c7<>5` to rule it out.
+use crate::{Coord, Error, Game, Sudoku, Value};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Move {
+    Place(Coord, Value),
+    Eliminate(Coord, Value),
+}
+
+impl Move {
+    pub fn coord(&self) -> Coord {
+        match *self {
+            Move::Place(coord, _) | Move::Eliminate(coord, _) => coord,
+        }
+    }
+
+    pub fn value(&self) -> Value {
+        match *self {
+            Move::Place(_, value) | Move::Eliminate(_, value) => value,
+        }
+    }
+
+    /// Applies the move to the candidates of a grid, returns `false` and
+    /// leaves the grid as it is if it eliminates the value of the cell.
+    pub fn apply(&self, sudoku: &mut Sudoku) -> bool {
+        match *self {
+            Move::Place(coord, value) => sudoku[coord].set(value),
+            Move::Eliminate(coord, value) => {
+                if sudoku[coord].value() == Some(value) {
+                    return false;
+                }
+                sudoku[coord].remove(value);
+            }
+        }
+        true
+    }
+
+    /// Plays the move, an elimination taking the mark off if there is
+    /// one. Returns `false` if the game refuses the move, see
+    /// `Game::apply`.
+    pub fn apply_to_game(&self, game: &mut Game) -> bool {
+        match *self {
+            Move::Place(coord, value) => game.place(coord, value),
+            Move::Eliminate(coord, value) => {
+                if game.is_given(coord) {
+                    return false;
+                }
+                !game.grid().notes(coord).contains(value) || game.toggle_note(coord, value)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Move::Place(coord, value) => write!(f, "{}={}", coord, value),
+            Move::Eliminate(coord, value) => write!(f, "{}<>{}", coord, value),
+        }
+    }
+}
+
+impl std::str::FromStr for Move {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let s = s.trim();
+        let (coord, value, eliminate) = match s.split_once("<>") {
+            Some((coord, value)) => (coord, value, true),
+            None => {
+                let (coord, value) = s.split_once('=').ok_or(Error::InvalidMove)?;
+                (coord, value, false)
+            }
+        };
+        let coord = coord.trim().parse().map_err(|_| Error::InvalidMove)?;
+        let value = value.trim().parse().map_err(|_| Error::InvalidMove)?;
+        Ok(match eliminate {
+            true => Move::Eliminate(coord, value),
+            false => Move::Place(coord, value),
+        })
+    }
+}
+
+/// Parses a log of moves separated by whitespace, commas or semicolons.
+pub fn parse_moves(log: &str) -> Result<Vec<Move>, Error> {
+    log.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|word| !word.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation() {
+        let (coord, five) = (Coord::new(3, 6), Value::new(5).unwrap());
+        assert_eq!("r4c7=5".parse::<Move>().unwrap(), Move::Place(coord, five));
+        assert_eq!(
+            " R4C7 <> 5".parse::<Move>().unwrap(),
+            Move::Eliminate(coord, five)
+        );
+        for m in [Move::Place(coord, five), Move::Eliminate(coord, five)] {
+            assert_eq!(m.to_string().parse::<Move>().unwrap(), m);
+        }
+        assert_eq!(Move::Eliminate(coord, five).to_string(), "r4c7<>5");
+        for bad in ["r4c7", "r4c7=0", "r0c7=5", "r4c7<5", "r4c7=55"] {
+            assert!(matches!(bad.parse::<Move>(), Err(Error::InvalidMove)));
+        }
+
+        let moves = parse_moves("r1c1<>2, r1c1<>3\nr1c2=4;").unwrap();
+        assert_eq!(moves.len(), 3);
+        let mut sudoku = Sudoku::new();
+        for m in &moves {
+            assert!(m.apply(&mut sudoku));
+        }
+        assert_eq!(sudoku[Coord::new(0, 0)].len(), 7);
+        assert_eq!(sudoku[Coord::new(0, 1)].value(), Value::new(4).ok());
+        assert!(!"r1c2<>4".parse::<Move>().unwrap().apply(&mut sudoku));
+        assert!(parse_moves("r1c1=1 r1c1").is_err());
+
+        let mut game = Game::new(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+                .parse()
+                .unwrap(),
+        );
+        game.fill_notes();
+        for m in parse_moves("r1c2<>4 r1c2<>4 r1c1=4").unwrap() {
+            assert!(m.apply_to_game(&mut game));
+        }
+        assert!(!game
+            .grid()
+            .notes(Coord::new(0, 1))
+            .contains(Value::new(4).unwrap()));
+        assert_eq!(game.history().len(), 3);
+        assert!(!"r1c3<>3".parse::<Move>().unwrap().apply_to_game(&mut game));
+        assert!(!"r1c3=4".parse::<Move>().unwrap().apply_to_game(&mut game));
+    }
+}
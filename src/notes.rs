@@ -0,0 +1,116 @@
+//! Pencil marks kept consistent with the digits placed.
+use crate::{CandidateSet, Cell, Coord, Sudoku, Value};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct NotedSudoku {
+    sudoku: Sudoku,
+    notes: [CandidateSet; 81],
+}
+
+impl NotedSudoku {
+    /// The grid without any notes.
+    pub fn new(sudoku: Sudoku) -> Self {
+        Self {
+            sudoku,
+            notes: [CandidateSet::empty(); 81],
+        }
+    }
+
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
+    /// The marks of a cell, always empty for cells with a value.
+    pub fn notes(&self, coord: Coord) -> CandidateSet {
+        self.notes[coord.index()]
+    }
+
+    /// Replaces the marks of an empty cell, returns `false` if the cell has
+    /// a value.
+    pub fn set_notes(&mut self, coord: Coord, notes: CandidateSet) -> bool {
+        if self.sudoku[coord].is_final() {
+            return false;
+        }
+        self.notes[coord.index()] = notes;
+        true
+    }
+
+    /// Adds or removes a mark of an empty cell, returns `false` if the cell
+    /// has a value.
+    pub fn toggle_note(&mut self, coord: Coord, value: Value) -> bool {
+        let mut notes = self.notes(coord);
+        if notes.contains(value) {
+            notes.remove(value);
+        } else {
+            notes.insert(value);
+        }
+        self.set_notes(coord, notes)
+    }
+
+    /// Marks every empty cell with its candidates.
+    pub fn fill_notes(&mut self) {
+        for i in 0..81 {
+            let coord = Coord::from_index(i);
+            self.notes[i] = self.sudoku.candidates(coord.row(), coord.col());
+        }
+    }
+
+    pub fn clear_notes(&mut self) {
+        self.notes = [CandidateSet::empty(); 81];
+    }
+
+    /// Places a digit, dropping the marks of the cell and the mark of the
+    /// digit from its peers.
+    pub fn place(&mut self, coord: Coord, value: Value) {
+        self.sudoku[coord].set(value);
+        self.notes[coord.index()] = CandidateSet::empty();
+        for peer in coord.peers() {
+            self.notes[peer.index()].remove(value);
+        }
+    }
+
+    /// Empties a cell, leaving it without marks.
+    pub fn clear(&mut self, coord: Coord) {
+        self.sudoku[coord] = Cell::new();
+    }
+}
+
+impl From<Sudoku> for NotedSudoku {
+    fn from(sudoku: Sudoku) -> Self {
+        Self::new(sudoku)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes() {
+        let sudoku: Sudoku =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+                .parse()
+                .unwrap();
+        let mut noted = NotedSudoku::new(sudoku);
+        let (corner, given) = (Coord::new(0, 0), Coord::new(0, 2));
+        let (four, five) = (Value::new(4).unwrap(), Value::new(5).unwrap());
+        assert!(noted.toggle_note(corner, five));
+        assert!(noted.notes(corner).contains(five));
+        assert!(noted.toggle_note(corner, five));
+        assert!(noted.notes(corner).is_empty());
+        assert!(!noted.toggle_note(given, five));
+
+        noted.fill_notes();
+        assert_eq!(noted.notes(corner), sudoku.candidates(0, 0));
+        assert!(noted.notes(given).is_empty());
+        assert!(noted.notes(Coord::new(0, 1)).contains(four));
+        assert!(noted.notes(Coord::new(1, 1)).contains(four));
+
+        noted.place(corner, four);
+        assert!(noted.notes(corner).is_empty());
+        assert!(!noted.notes(Coord::new(0, 1)).contains(four));
+        assert!(!noted.notes(Coord::new(1, 1)).contains(four));
+        noted.clear(corner);
+        assert_eq!(noted.sudoku().get(0, 0).unwrap().value(), None);
+    }
+}
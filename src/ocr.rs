@@ -0,0 +1,719 @@
+//! Puzzles read from images of printed grids, such as screenshots, scans
+//! and cropped photos.
+//!
+//! The image is split into dark and light pixels, the grid is taken to be
+//! the largest dark shape and divided evenly into cells. A mark in a cell
+//! is matched against the digits of a [`Font`] scaled to the same size.
+//! Only PNG files are read, and the grid must be upright and seen from the
+//! front, a photo at an angle needs straightening first.
+use crate::checksum::{crc32, crc32_update};
+use crate::image::{adler32, Font};
+use crate::{Error, Sudoku, Value};
+use std::convert::TryInto;
+
+/// Images beyond this many pixels are refused rather than decoded.
+const MAX_PIXELS: usize = 1 << 26;
+/// Marks are compared as this many columns and rows of coverage.
+const SAMPLES: (usize, usize) = (8, 12);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OcrOptions {
+    /// The digits marks are matched against.
+    pub font: Font,
+    /// Cells with marks less tall than this share of the cell are blank.
+    pub min_digit_height: f32,
+}
+
+impl Default for OcrOptions {
+    fn default() -> Self {
+        Self {
+            font: Font::default(),
+            min_digit_height: 0.3,
+        }
+    }
+}
+
+impl Sudoku {
+    /// Reads the givens from a PNG image with the default options, see
+    /// [`recognize`].
+    pub fn from_image(bytes: &[u8]) -> Result<Sudoku, Error> {
+        recognize(bytes, &OcrOptions::default())
+    }
+}
+
+/// Reads the givens from a PNG image, failing with `Error::InvalidImage`
+/// for other files and `Error::GridNotFound` if nothing looks like a grid.
+pub fn recognize(png: &[u8], options: &OcrOptions) -> Result<Sudoku, Error> {
+    let image = decode_png(png)?;
+    let dark = threshold(&image);
+    let (labels, grid, (x0, y0, x1, y1)) =
+        largest_component(&dark, image.width, image.height).ok_or(Error::GridNotFound)?;
+    let (width, height) = (x1 - x0 + 1, y1 - y0 + 1);
+    if width < 27 || height < 27 || width * 3 < height * 2 || height * 3 < width * 2 {
+        return Err(Error::GridNotFound);
+    }
+    let templates: Vec<(Vec<f32>, f32)> = (1..=9).map(|d| glyph(&options.font, d)).collect();
+    let mut sudoku = Sudoku::new();
+    for i in 0..81 {
+        let (row, col) = (i / 9, i % 9);
+        let (left, right) = (x0 + width * col / 9, x0 + width * (col + 1) / 9);
+        let (top, bottom) = (y0 + height * row / 9, y0 + height * (row + 1) / 9);
+        // keep clear of the lines around the cell
+        let (mx, my) = ((right - left) / 10, (bottom - top) / 10);
+        let mark = |x: usize, y: usize| {
+            let p = y * image.width + x;
+            dark[p] && labels[p] != grid
+        };
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for y in top + my..bottom - my {
+            for x in (left + mx..right - mx).filter(|&x| mark(x, y)) {
+                bounds = Some(match bounds {
+                    None => (x, y, x, y),
+                    Some((a, b, c, d)) => (a.min(x), b.min(y), c.max(x), d.max(y)),
+                });
+            }
+        }
+        let (a, b, c, d) = match bounds {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+        let (w, h) = (c - a + 1, d - b + 1);
+        if (h as f32) < options.min_digit_height * (bottom - top) as f32 {
+            continue;
+        }
+        let samples = sample(w, h, |x, y| mark(a + x, b + y));
+        let aspect = w as f32 / h as f32;
+        let score = |(template, ratio): &(Vec<f32>, f32)| {
+            let diff: f32 = template
+                .iter()
+                .zip(&samples)
+                .map(|(t, s)| (t - s) * (t - s))
+                .sum();
+            diff / samples.len() as f32 + (ratio - aspect) * (ratio - aspect)
+        };
+        let best = (0..9)
+            .min_by(|&x, &y| score(&templates[x]).total_cmp(&score(&templates[y])))
+            .expect("nine templates; qed");
+        sudoku.0[i].set(Value(best as u8));
+    }
+    Ok(sudoku)
+}
+
+/// The coverage of a digit of the font cropped to its bounds, and its
+/// width over its height.
+fn glyph(font: &Font, digit: u8) -> (Vec<f32>, f32) {
+    let on: Vec<(u32, u32)> = (0..font.height)
+        .flat_map(|y| (0..font.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| font.pixel(digit, x, y))
+        .collect();
+    if on.is_empty() {
+        return (vec![0.0; SAMPLES.0 * SAMPLES.1], 1.0);
+    }
+    let (a, b) = on
+        .iter()
+        .fold((u32::MAX, u32::MAX), |(a, b), &(x, y)| (a.min(x), b.min(y)));
+    let (c, d) = on
+        .iter()
+        .fold((0, 0), |(c, d), &(x, y)| (c.max(x), d.max(y)));
+    let (w, h) = ((c - a + 1) as usize, (d - b + 1) as usize);
+    let samples = sample(w, h, |x, y| font.pixel(digit, a + x as u32, b + y as u32));
+    (samples, w as f32 / h as f32)
+}
+
+/// The share of dark points in each of `SAMPLES` rectangles over a
+/// bitmap, three by three points each.
+fn sample(width: usize, height: usize, dark: impl Fn(usize, usize) -> bool) -> Vec<f32> {
+    let (columns, rows) = SAMPLES;
+    let mut samples = Vec::with_capacity(columns * rows);
+    for sy in 0..rows {
+        for sx in 0..columns {
+            let mut hits = 0;
+            for py in 0..3 {
+                for px in 0..3 {
+                    let x = ((sx * 3 + px) * 2 + 1) * width / (columns * 6);
+                    let y = ((sy * 3 + py) * 2 + 1) * height / (rows * 6);
+                    hits += dark(x, y) as u32;
+                }
+            }
+            samples.push(hits as f32 / 9.0);
+        }
+    }
+    samples
+}
+
+/// A grayscale image, row-major.
+struct Gray {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// The dark pixels, split from the light ones by Otsu's method.
+fn threshold(image: &Gray) -> Vec<bool> {
+    let mut histogram = [0u64; 256];
+    for &p in &image.pixels {
+        histogram[p as usize] += 1;
+    }
+    let total = image.pixels.len() as f64;
+    let sum: f64 = (0..256).map(|t| t as f64 * histogram[t] as f64).sum();
+    let (mut below, mut below_sum) = (0.0, 0.0);
+    let (mut best, mut cut) = (0.0, None);
+    for (t, &count) in histogram.iter().enumerate() {
+        below += count as f64;
+        below_sum += t as f64 * count as f64;
+        let above = total - below;
+        if below == 0.0 || above == 0.0 {
+            continue;
+        }
+        let spread = (below_sum / below - (sum - below_sum) / above).powi(2);
+        let variance = below * above * spread;
+        if variance > best {
+            best = variance;
+            cut = Some(t as u8);
+        }
+    }
+    match cut {
+        Some(cut) => image.pixels.iter().map(|&p| p <= cut).collect(),
+        None => vec![false; image.pixels.len()],
+    }
+}
+
+/// Labels the shapes of touching dark pixels, diagonals included, from 1,
+/// and returns the labels with the label and bounds of the largest shape.
+#[allow(clippy::type_complexity)]
+fn largest_component(
+    dark: &[bool],
+    width: usize,
+    height: usize,
+) -> Option<(Vec<u32>, u32, (usize, usize, usize, usize))> {
+    let mut labels = vec![0u32; dark.len()];
+    let mut largest = None;
+    let mut next = 0;
+    let mut stack = Vec::new();
+    for start in 0..dark.len() {
+        if !dark[start] || labels[start] != 0 {
+            continue;
+        }
+        next += 1;
+        labels[start] = next;
+        stack.push(start);
+        let (mut size, mut bounds) = (0, (usize::MAX, usize::MAX, 0, 0));
+        while let Some(p) = stack.pop() {
+            let (x, y) = (p % width, p / width);
+            size += 1;
+            bounds = (
+                bounds.0.min(x),
+                bounds.1.min(y),
+                bounds.2.max(x),
+                bounds.3.max(y),
+            );
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let q = ny * width + nx;
+                    if dark[q] && labels[q] == 0 {
+                        labels[q] = next;
+                        stack.push(q);
+                    }
+                }
+            }
+        }
+        if largest.is_none_or(|(_, most, _)| size > most) {
+            largest = Some((next, size, bounds));
+        }
+    }
+    largest.map(|(label, _, bounds)| (labels, label, bounds))
+}
+
+/// The gray of a color, lighter the more transparent it is.
+fn gray(rgb: [u8; 3], alpha: u8) -> u8 {
+    let luma = (rgb[0] as u32 * 299 + rgb[1] as u32 * 587 + rgb[2] as u32 * 114) / 1000;
+    ((luma * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8
+}
+
+/// The pixels of a PNG file as gray, for any color type and bit depth but
+/// without interlacing.
+fn decode_png(png: &[u8]) -> Result<Gray, Error> {
+    let mut rest = png
+        .strip_prefix(b"\x89PNG\r\n\x1a\n")
+        .ok_or(Error::InvalidImage)?;
+    let (mut header, mut palette, mut data) = (None, Vec::new(), Vec::new());
+    while rest.len() >= 12 {
+        let be = |bytes: &[u8]| u32::from_be_bytes(bytes.try_into().expect("four bytes; qed"));
+        let len = be(&rest[..4]) as usize;
+        if rest.len() - 12 < len {
+            return Err(Error::InvalidImage);
+        }
+        let (kind, body) = (&rest[4..8], &rest[8..8 + len]);
+        if be(&rest[8 + len..12 + len]) != crc32_update(crc32(kind), body) {
+            return Err(Error::InvalidImage);
+        }
+        match kind {
+            b"IHDR" if len == 13 => {
+                header = Some((
+                    be(&body[..4]) as usize,
+                    be(&body[4..8]) as usize,
+                    body[8],
+                    body[9],
+                    body[12],
+                ))
+            }
+            b"PLTE" => palette = body.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = &rest[12 + len..];
+    }
+    let (width, height, depth, color, interlace) = header.ok_or(Error::InvalidImage)?;
+    let channels = match (color, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) => 1,
+        (3, 1 | 2 | 4 | 8) => 1,
+        (2 | 6, 8 | 16) => 3 + (color == 6) as usize,
+        (4, 8 | 16) => 2,
+        _ => return Err(Error::InvalidImage),
+    };
+    if width == 0 || height == 0 || width.saturating_mul(height) > MAX_PIXELS || interlace != 0 {
+        return Err(Error::InvalidImage);
+    }
+    let bits = channels * depth as usize;
+    let (stride, step) = ((width * bits).div_ceil(8), bits.div_ceil(8));
+    let raw = inflate_zlib(&data, height * (stride + 1))?;
+    if raw.len() != height * (stride + 1) {
+        return Err(Error::InvalidImage);
+    }
+    let rows = unfilter(&raw, stride, step)?;
+
+    let max = (1u32 << depth.min(8)) - 1;
+    let sample = |row: &[u8], i: usize| -> u8 {
+        match depth {
+            8 => row[i],
+            16 => row[2 * i],
+            _ => {
+                let bit = i * depth as usize;
+                let value = (row[bit / 8] >> (8 - depth as usize - bit % 8)) as u32 & max;
+                if color == 3 {
+                    value as u8
+                } else {
+                    (value * 255 / max) as u8
+                }
+            }
+        }
+    };
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in rows.chunks(stride) {
+        for x in 0..width {
+            let s = |c: usize| sample(row, x * channels + c);
+            pixels.push(match color {
+                0 => s(0),
+                2 => gray([s(0), s(1), s(2)], 255),
+                3 => gray(*palette.get(s(0) as usize).ok_or(Error::InvalidImage)?, 255),
+                4 => gray([s(0); 3], s(1)),
+                _ => gray([s(0), s(1), s(2)], s(3)),
+            });
+        }
+    }
+    Ok(Gray {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Undoes the filters of the scanlines, each one a filter byte and
+/// `stride` bytes, with `step` bytes to the previous pixel.
+fn unfilter(raw: &[u8], stride: usize, step: usize) -> Result<Vec<u8>, Error> {
+    let mut rows = vec![0u8; raw.len() / (stride + 1) * stride];
+    for (y, line) in raw.chunks(stride + 1).enumerate() {
+        let start = y * stride;
+        for x in 0..stride {
+            let a = if x >= step { rows[start + x - step] } else { 0 };
+            let b = if y > 0 { rows[start - stride + x] } else { 0 };
+            let c = if x >= step && y > 0 {
+                rows[start - stride + x - step]
+            } else {
+                0
+            };
+            let predicted = match line[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => {
+                    let p = a as i16 + b as i16 - c as i16;
+                    let (pa, pb, pc) = (
+                        (p - a as i16).abs(),
+                        (p - b as i16).abs(),
+                        (p - c as i16).abs(),
+                    );
+                    if pa <= pb && pa <= pc {
+                        a
+                    } else if pb <= pc {
+                        b
+                    } else {
+                        c
+                    }
+                }
+                _ => return Err(Error::InvalidImage),
+            };
+            rows[start + x] = line[1 + x].wrapping_add(predicted);
+        }
+    }
+    Ok(rows)
+}
+
+/// Base and extra bits of the lengths and distances of deflate.
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+/// The order code lengths of a dynamic block are sent in.
+const CODE_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Bits of a deflate stream, least significant first.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Bits<'_> {
+    fn take(&mut self, n: u8) -> Result<u32, Error> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos / 8).ok_or(Error::InvalidImage)?;
+            value |= (byte as u32 >> (self.pos % 8) & 1) << i;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// A canonical Huffman code, the number of codes of each length and the
+/// symbols in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|&s| lengths[s as usize] != 0)
+            .collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, Error> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.take(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                let symbol = self.symbols.get((index + code - first) as usize);
+                return symbol.copied().ok_or(Error::InvalidImage);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::InvalidImage)
+    }
+}
+
+/// Decompresses a zlib stream, failing once it grows beyond `limit`.
+fn inflate_zlib(data: &[u8], limit: usize) -> Result<Vec<u8>, Error> {
+    match data {
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8
+                && flg & 0x20 == 0
+                && (*cmf as u16 * 256 + *flg as u16).is_multiple_of(31) => {}
+        _ => return Err(Error::InvalidImage),
+    }
+    let mut bits = Bits { data, pos: 16 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => {
+                let start = bits.pos.div_ceil(8);
+                let header = data.get(start..start + 4).ok_or(Error::InvalidImage)?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if len != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(Error::InvalidImage);
+                }
+                let block = data
+                    .get(start + 4..start + 4 + len as usize)
+                    .ok_or(Error::InvalidImage)?;
+                out.extend_from_slice(block);
+                bits.pos = (start + 4 + len as usize) * 8;
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut bits, &literals, &distances, &mut out, limit)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                inflate_block(&mut bits, &literals, &distances, &mut out, limit)?;
+            }
+            _ => return Err(Error::InvalidImage),
+        }
+        if out.len() > limit {
+            return Err(Error::InvalidImage);
+        }
+        if last {
+            break;
+        }
+    }
+    let end = bits.pos.div_ceil(8);
+    match data.get(end..end + 4) {
+        Some(checksum) if checksum == adler32(&out).to_be_bytes() => Ok(out),
+        _ => Err(Error::InvalidImage),
+    }
+}
+
+/// The codes of a dynamic block, from its header.
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), Error> {
+    let literals = bits.take(5)? as usize + 257;
+    let distances = bits.take(5)? as usize + 1;
+    let codes = bits.take(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_ORDER[..codes] {
+        code_lengths[i] = bits.take(3)? as u8;
+    }
+    let code = Huffman::new(&code_lengths);
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (len, repeat) = match code.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths.last().ok_or(Error::InvalidImage)?,
+                3 + bits.take(2)?,
+            ),
+            17 => (0, 3 + bits.take(3)?),
+            18 => (0, 11 + bits.take(7)?),
+            _ => return Err(Error::InvalidImage),
+        };
+        lengths.extend((0..repeat).map(|_| len));
+    }
+    if lengths.len() != literals + distances || lengths[256] == 0 {
+        return Err(Error::InvalidImage);
+    }
+    let (literal_lengths, distance_lengths) = lengths.split_at(literals);
+    Ok((
+        Huffman::new(literal_lengths),
+        Huffman::new(distance_lengths),
+    ))
+}
+
+/// Decodes the symbols of a compressed block up to its end.
+fn inflate_block(
+    bits: &mut Bits,
+    literals: &Huffman,
+    distances: &Huffman,
+    out: &mut Vec<u8>,
+    limit: usize,
+) -> Result<(), Error> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+        let &(base, extra) = LENGTHS.get(symbol - 257).ok_or(Error::InvalidImage)?;
+        let len = base as usize + bits.take(extra)? as usize;
+        let symbol = distances.decode(bits)? as usize;
+        let &(base, extra) = DISTANCES.get(symbol).ok_or(Error::InvalidImage)?;
+        let distance = base as usize + bits.take(extra)? as usize;
+        if distance > out.len() || out.len() + len > limit {
+            return Err(Error::InvalidImage);
+        }
+        for _ in 0..len {
+            out.push(out[out.len() - distance]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::{chunk, render_png, zlib_stored, ImageStyle};
+
+    /// A PNG of gray pixels, each row with a filter cycling through the
+    /// five kinds.
+    fn gray_png(width: usize, pixels: &[u8]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        let rows: Vec<&[u8]> = pixels.chunks(width).collect();
+        for (y, row) in rows.iter().enumerate() {
+            let kind = y % 5;
+            raw.push(kind as u8);
+            for x in 0..width {
+                let a = if x > 0 { row[x - 1] } else { 0 };
+                let b = if y > 0 { rows[y - 1][x] } else { 0 };
+                let c = if x > 0 && y > 0 {
+                    rows[y - 1][x - 1]
+                } else {
+                    0
+                };
+                let predicted = match kind {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => {
+                        let p = a as i16 + b as i16 - c as i16;
+                        let d = |v: u8| (p - v as i16).abs();
+                        if d(a) <= d(b) && d(a) <= d(c) {
+                            a
+                        } else if d(b) <= d(c) {
+                            b
+                        } else {
+                            c
+                        }
+                    }
+                };
+                raw.push(row[x].wrapping_sub(predicted));
+            }
+        }
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = (width as u32).to_be_bytes().to_vec();
+        header.extend_from_slice(&(rows.len() as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 0, 0, 0, 0]);
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn test_decode_png() {
+        let pixels: Vec<u8> = (0..60u32).map(|i| (i * 37 % 256) as u8).collect();
+        let image = decode_png(&gray_png(6, &pixels)).unwrap();
+        assert_eq!((image.width, image.height), (6, 10));
+        assert_eq!(image.pixels, pixels);
+
+        // fixed and dynamic Huffman blocks, as zlib writes them
+        let fixed = [
+            120, 218, 43, 46, 77, 201, 207, 46, 85, 40, 70, 166, 0, 84, 212, 8, 18,
+        ];
+        assert_eq!(inflate_zlib(&fixed, 100).unwrap(), b"sudoku sudoku sudoku");
+        assert!(inflate_zlib(&fixed, 10).is_err());
+        let dynamic = [
+            120, 218, 205, 140, 209, 13, 64, 33, 12, 2, 55, 186, 68, 180, 181, 238, 191, 152, 244,
+            189, 37, 36, 124, 16, 200, 1, 19, 145, 112, 112, 10, 24, 118, 145, 11, 171, 6, 114,
+            191, 249, 85, 144, 155, 82, 103, 37, 39, 190, 74, 230, 154, 14, 163, 179, 167, 215, 15,
+            47, 14, 210, 46, 72,
+        ];
+        let puzzle =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+        assert_eq!(
+            inflate_zlib(&dynamic, 1000).unwrap(),
+            puzzle.repeat(3).as_bytes()
+        );
+
+        assert!(matches!(decode_png(b"GIF89a"), Err(Error::InvalidImage)));
+        let mut corrupt = gray_png(6, &pixels);
+        corrupt[40] ^= 1;
+        assert!(matches!(decode_png(&corrupt), Err(Error::InvalidImage)));
+    }
+
+    #[test]
+    fn test_from_image() {
+        let corpus = crate::Corpus::top1465_sample();
+        let puzzle = *corpus.puzzles.iter().next().unwrap();
+        let png = render_png(&puzzle, &ImageStyle::default());
+        assert_eq!(Sudoku::from_image(&png).unwrap(), puzzle);
+
+        let style = ImageStyle {
+            cell_size: 30,
+            thin_line: 2,
+            thick_line: 4,
+            digit_height: 0.5,
+            background: [230, 230, 210],
+            lines: [40, 40, 90],
+            digits: [0, 0, 200],
+            ..ImageStyle::default()
+        };
+        let solution = crate::solve(&puzzle).unwrap();
+        let png = render_png(&solution, &style);
+        assert_eq!(Sudoku::from_image(&png).unwrap(), *solution);
+
+        let blank = gray_png(40, &[255; 1600]);
+        assert!(matches!(
+            Sudoku::from_image(&blank),
+            Err(Error::GridNotFound)
+        ));
+    }
+}
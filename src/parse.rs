@@ -0,0 +1,265 @@
+//! Parsing that recovers from malformed input.
+use crate::{Error, Sudoku, Value};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IssueKind {
+    /// A character that is neither a digit nor a blank, the cell is left
+    /// empty.
+    BadChar(char),
+    /// A character that was read as a blank but might mean something else,
+    /// like `0`, `_`, `-`, `*`, `x` or a tab.
+    AmbiguousBlank(char),
+    /// A row with fewer than 9 cells, the rest of it is left empty.
+    ShortRow(usize),
+    /// A row with more than 9 cells, the extra ones are ignored.
+    LongRow(usize),
+    /// The input ended before row `row`, the remaining rows are empty.
+    MissingRows,
+    /// Lines after the ninth row that aren't empty are ignored.
+    ExtraRows,
+}
+
+/// A problem found at a zero-based `row` and `col` of the input.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseIssue {
+    pub row: usize,
+    pub col: usize,
+    pub kind: IssueKind,
+}
+
+impl std::fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (row, col) = (self.row + 1, self.col + 1);
+        match self.kind {
+            IssueKind::BadChar(c) => write!(f, "r{}c{}: unexpected {:?}", row, col, c),
+            IssueKind::AmbiguousBlank(c) => write!(f, "r{}c{}: {:?} read as blank", row, col, c),
+            IssueKind::ShortRow(len) => write!(f, "row {} has only {} cells", row, len),
+            IssueKind::LongRow(len) => write!(f, "row {} has {} cells", row, len),
+            IssueKind::MissingRows => write!(f, "input ends before row {}", row),
+            IssueKind::ExtraRows => write!(f, "unexpected input after row 9"),
+        }
+    }
+}
+
+/// Parses one row per line like `from_str`, filling in what it can and
+/// reporting everything else instead of failing.
+pub fn parse_lossy(string: &str) -> (Sudoku, Vec<ParseIssue>) {
+    let mut sudoku = Sudoku::new();
+    let mut issues = Vec::new();
+    let mut lines = string.lines();
+    for (row, line) in lines.by_ref().take(9).enumerate() {
+        let len = line.chars().count();
+        for (col, c) in line.chars().enumerate().take(9) {
+            let issue = |kind| ParseIssue { row, col, kind };
+            match c {
+                ' ' | '.' => {}
+                '1'..='9' => sudoku.0[row * 9 + col].set(Value(c as u8 - b'1')),
+                '0' | '_' | '-' | '*' | 'x' | 'X' | '\t' => {
+                    issues.push(issue(IssueKind::AmbiguousBlank(c)))
+                }
+                _ => issues.push(issue(IssueKind::BadChar(c))),
+            }
+        }
+        if len < 9 {
+            issues.push(ParseIssue {
+                row,
+                col: len,
+                kind: IssueKind::ShortRow(len),
+            });
+        } else if len > 9 {
+            issues.push(ParseIssue {
+                row,
+                col: 9,
+                kind: IssueKind::LongRow(len),
+            });
+        }
+    }
+    let rows = string.lines().take(9).count();
+    if rows < 9 {
+        issues.push(ParseIssue {
+            row: rows,
+            col: 0,
+            kind: IssueKind::MissingRows,
+        });
+    }
+    if lines.any(|line| !line.trim().is_empty()) {
+        issues.push(ParseIssue {
+            row: 9,
+            col: 0,
+            kind: IssueKind::ExtraRows,
+        });
+    }
+    (sudoku, issues)
+}
+
+/// Parses the layouts puzzles are usually pasted in: a single line of 81
+/// cells, or one row per line, with `.`, `0`, `_`, `-`, `*` or `x` for
+/// blanks. Rows may separate their cells with spaces and their boxes with
+/// `|`, and lines of only `-`, `+`, `=` and `|` between bands are skipped.
+/// Rows without separators may also use spaces for blanks, as `from_str`
+/// reads them.
+pub fn parse_flexible(string: &str) -> Result<Sudoku, Error> {
+    let mut cells = Vec::with_capacity(81);
+    for (line_no, line) in string.lines().enumerate() {
+        let line = line.trim_end();
+        if line.trim().is_empty() || is_rule(line) {
+            continue;
+        }
+        let error = |col: usize, found| Error::ParseError {
+            line: line_no + 1,
+            column: col + 1,
+            found,
+        };
+        let marks: Vec<(usize, char)> = line
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| !c.is_whitespace() && c != '|')
+            .collect();
+        let row: Vec<(usize, char)> = if marks.len().is_multiple_of(9) {
+            marks
+        } else if line.chars().count() <= 9 && !line.contains('|') {
+            // spaces for blanks, trailing ones trimmed
+            let mut row: Vec<_> = line.chars().enumerate().collect();
+            row.resize(9, (line.len(), ' '));
+            row
+        } else {
+            // a tenth cell, or the end of a row that is too short
+            let (col, c) = marks
+                .get(9)
+                .copied()
+                .unwrap_or((line.chars().count(), '\n'));
+            return Err(error(col, c));
+        };
+        for (col, c) in row {
+            cells.push(match c {
+                '1'..='9' => Some(Value(c as u8 - b'1')),
+                '.' | '0' | '_' | '-' | '*' | 'x' | 'X' | ' ' => None,
+                _ => return Err(error(col, c)),
+            });
+        }
+    }
+    if cells.len() != 81 {
+        return Err(Error::WrongLength {
+            expected: 81,
+            found: cells.len(),
+        });
+    }
+    Ok(cells.into_iter().collect())
+}
+
+/// A line drawn between bands, like `------+-------+------`.
+fn is_rule(line: &str) -> bool {
+    line.chars()
+        .all(|c| c.is_whitespace() || matches!(c, '-' | '+' | '=' | '|'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flexible() {
+        let line =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        let expected = parse_flexible(line).unwrap();
+        assert_eq!(expected.to_line(), line);
+        let layouts = [
+            line.replace('.', "0"),
+            format!("  {}  \n", line),
+            "53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n\
+             7...2...6\n.6....28.\n...419..5\n....8..79"
+                .to_string(),
+            "53  7\n6  195\n 98    6\n8   6   3\n4  8 3  1\n\
+             7   2   6\n 6    28\n   419  5\n    8  79"
+                .to_string(),
+            "5 3 . | . 7 . | . . .\n6 . . | 1 9 5 | . . .\n. 9 8 | . . . | . 6 .\n\
+             ------+-------+------\n\
+             8 . . | . 6 . | . . 3\n4 . . | 8 . 3 | . . 1\n7 . . | . 2 . | . . 6\n\
+             ------+-------+------\n\
+             . 6 . | . . . | 2 8 .\n. . . | 4 1 9 | . . 5\n. . . | . 8 . | . 7 9\n"
+                .to_string(),
+            "+-------+-------+-------+\n\
+             | 5 3 0 | 0 7 0 | 0 0 0 |\n| 6 0 0 | 1 9 5 | 0 0 0 |\n| 0 9 8 | 0 0 0 | 0 6 0 |\n\
+             +-------+-------+-------+\n\
+             | 8 0 0 | 0 6 0 | 0 0 3 |\n| 4 0 0 | 8 0 3 | 0 0 1 |\n| 7 0 0 | 0 2 0 | 0 0 6 |\n\
+             +-------+-------+-------+\n\
+             | 0 6 0 | 0 0 0 | 2 8 0 |\n| 0 0 0 | 4 1 9 | 0 0 5 |\n| 0 0 0 | 0 8 0 | 0 7 9 |\n\
+             +-------+-------+-------+"
+                .to_string(),
+        ];
+        for layout in layouts.iter() {
+            assert_eq!(parse_flexible(layout).unwrap(), expected, "{}", layout);
+        }
+        assert!(matches!(
+            parse_flexible("53..7...."),
+            Err(Error::WrongLength { found: 9, .. })
+        ));
+        assert!(matches!(
+            parse_flexible("5 3 . | 7 . ."),
+            Err(Error::ParseError {
+                line: 1,
+                column: 14,
+                found: '\n'
+            })
+        ));
+        assert!(matches!(
+            parse_flexible("53..7...a"),
+            Err(Error::ParseError {
+                line: 1,
+                column: 9,
+                found: 'a'
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_lossy() {
+        let (sudoku, issues) = parse_lossy(
+            "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79\n",
+        );
+        assert!(issues.is_empty());
+        assert_eq!(sudoku.iter_filled().count(), 30);
+
+        let (sudoku, issues) = parse_lossy("53a.0\n123456789x\n");
+        assert_eq!(sudoku.iter_filled().count(), 11);
+        assert_eq!(
+            issues,
+            vec![
+                ParseIssue {
+                    row: 0,
+                    col: 2,
+                    kind: IssueKind::BadChar('a')
+                },
+                ParseIssue {
+                    row: 0,
+                    col: 4,
+                    kind: IssueKind::AmbiguousBlank('0')
+                },
+                ParseIssue {
+                    row: 0,
+                    col: 5,
+                    kind: IssueKind::ShortRow(5)
+                },
+                ParseIssue {
+                    row: 1,
+                    col: 9,
+                    kind: IssueKind::LongRow(10)
+                },
+                ParseIssue {
+                    row: 2,
+                    col: 0,
+                    kind: IssueKind::MissingRows
+                },
+            ]
+        );
+        assert_eq!(issues[0].to_string(), "r1c3: unexpected 'a'");
+    }
+}
@@ -0,0 +1,256 @@
+//! Puzzles whose clues form a picture.
+use crate::grid::Grid;
+use crate::rating::{se_rating, RatedPuzzle, SE_UNSOLVED};
+use crate::rng::XorShift;
+use crate::{Error, Sudoku};
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::SeedableRng;
+
+/// The cells that hold clues.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Pattern([bool; 81]);
+
+const GALLERY: &[(&str, &str)] = &[
+    (
+        "heart",
+        ".xx...xx.\
+         xxxx.xxxx\
+         xxxxxxxxx\
+         xxxxxxxxx\
+         xxx.x.xxx\
+         .xxx.xxx.\
+         ..xxxxx..\
+         ...xxx...\
+         ....x....",
+    ),
+    (
+        "diagonal-cross",
+        "xx.....xx\
+         xxx...xxx\
+         .xxx.xxx.\
+         ..xx.xx..\
+         ....x....\
+         ..xx.xx..\
+         .xxx.xxx.\
+         xxx...xxx\
+         xx.....xx",
+    ),
+    (
+        "letter-h",
+        "xx.....xx\
+         xx.....xx\
+         xx..x..xx\
+         xxxxxxxxx\
+         xxx.x.xxx\
+         xxxxxxxxx\
+         xx..x..xx\
+         xx.....xx\
+         xx.....xx",
+    ),
+    (
+        "frame",
+        "xxxxxxxxx\
+         x.......x\
+         x.xxxxx.x\
+         x.x...x.x\
+         x.x.x.x.x\
+         x.x...x.x\
+         x.xxxxx.x\
+         x.......x\
+         xxxxxxxxx",
+    ),
+];
+
+impl Pattern {
+    /// Parses 81 characters, `x` marking clues and `.` blanks. Whitespace
+    /// is ignored.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut cells = [false; 81];
+        let mut n = 0;
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            match c {
+                'x' | 'X' => *cells.get_mut(n)? = true,
+                '.' => {}
+                _ => return None,
+            }
+            n += 1;
+        }
+        if n == 81 {
+            Some(Self(cells))
+        } else {
+            None
+        }
+    }
+
+    /// The clue positions of a puzzle.
+    pub fn of(sudoku: &Sudoku) -> Self {
+        let mut cells = [false; 81];
+        for (coord, _) in sudoku.iter_filled() {
+            cells[coord.index()] = true;
+        }
+        Self(cells)
+    }
+
+    /// Names of the built-in patterns.
+    pub fn gallery() -> impl Iterator<Item = &'static str> {
+        GALLERY.iter().map(|(name, _)| *name)
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        GALLERY
+            .iter()
+            .find(|(n, _)| *n == name)
+            .and_then(|(_, pattern)| Self::parse(pattern))
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.0[x * 9 + y]
+    }
+
+    pub fn clues(&self) -> usize {
+        self.0.iter().filter(|&&clue| clue).count()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PatternOptions {
+    /// Range of acceptable Sudoku Explainer ratings.
+    pub min_rating: f32,
+    pub max_rating: f32,
+    /// Number of solution grids tried before giving up.
+    pub attempts: usize,
+    /// Clues outside the pattern that may be added when the pattern alone
+    /// leaves the solution ambiguous.
+    pub extra_clues: usize,
+}
+
+impl Default for PatternOptions {
+    fn default() -> Self {
+        Self {
+            min_rating: 0.0,
+            max_rating: SE_UNSOLVED,
+            attempts: 1000,
+            extra_clues: 0,
+        }
+    }
+}
+
+/// Searches for a proper puzzle with clues at the cells of `pattern`.
+///
+/// Each attempt masks a fresh random solution grid. If that leaves several
+/// solutions, up to `options.extra_clues` clues are added where a second
+/// solution differs from the grid before moving on to the next attempt.
+/// Fails with `Error::ClueCount` right away for fewer than 17 clues, with
+/// `Error::NoProperPuzzle` if no attempt is proper and with
+/// `Error::RatingOutOfRange` if none rates within the options.
+pub fn generate_with_pattern(
+    pattern: &Pattern,
+    seed: u64,
+    options: &PatternOptions,
+) -> Result<RatedPuzzle, Error> {
+    // no puzzle with 16 or fewer clues has a unique solution
+    if pattern.clues() < 17 {
+        return Err(Error::ClueCount {
+            clues: pattern.clues(),
+            attempts: 0,
+        });
+    }
+    let mut rng = XorShift::new(seed);
+    let mut found_unique = false;
+    for _ in 0..options.attempts {
+        let filled = Sudoku::fill_complete(&mut Xoshiro256PlusPlus::seed_from_u64(rng.next()));
+        let solution = Grid::from(&*filled);
+        let mut grid = solution.clone();
+        for (i, &clue) in pattern.0.iter().enumerate() {
+            if !clue {
+                grid.set(i / 9, i % 9, None)
+                    .expect("blank is in range; qed");
+            }
+        }
+        if !disambiguate(&mut grid, &solution, options.extra_clues, &mut rng) {
+            continue;
+        }
+        found_unique = true;
+        let puzzle = grid.to_sudoku();
+        let rating = se_rating(&puzzle);
+        if rating.er >= options.min_rating && rating.er <= options.max_rating {
+            return Ok(RatedPuzzle { puzzle, rating });
+        }
+    }
+    if found_unique {
+        Err(Error::RatingOutOfRange)
+    } else {
+        Err(Error::NoProperPuzzle)
+    }
+}
+
+/// Adds up to `extra` clues of `solution` until `grid` has a unique
+/// solution.
+fn disambiguate(grid: &mut Grid, solution: &Grid, extra: usize, rng: &mut XorShift) -> bool {
+    for _ in 0..extra {
+        let other = match grid.solutions(2).into_iter().find(|s| s != solution) {
+            Some(other) => other,
+            None => return true,
+        };
+        let size = solution.shape().size();
+        let differ: Vec<(usize, usize)> = (0..size * size)
+            .map(|i| (i / size, i % size))
+            .filter(|&(x, y)| other.get(x, y) != solution.get(x, y))
+            .collect();
+        let (x, y) = differ[rng.below(differ.len())];
+        grid.set(x, y, solution.get(x, y))
+            .expect("digit of a solution is in range; qed");
+    }
+    grid.count_solutions(2) == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solve_unique;
+
+    #[test]
+    fn test_gallery() {
+        for name in Pattern::gallery() {
+            let pattern = Pattern::named(name).unwrap();
+            assert!(pattern.clues() >= 17, "{}", name);
+        }
+        assert!(Pattern::named("unknown").is_none());
+        assert!(Pattern::parse("x.x").is_none());
+    }
+
+    #[test]
+    fn test_generate_with_pattern() {
+        let pattern = Pattern::named("diagonal-cross").unwrap();
+        let puzzle = generate_with_pattern(&pattern, 1, &PatternOptions::default()).unwrap();
+        assert_eq!(Pattern::of(&puzzle.puzzle), pattern);
+        assert!(solve_unique(&puzzle.puzzle).is_ok());
+
+        let heart = Pattern::named("heart").unwrap();
+        let options = PatternOptions {
+            extra_clues: 4,
+            ..Default::default()
+        };
+        let puzzle = generate_with_pattern(&heart, 1, &options).unwrap();
+        let clues = Pattern::of(&puzzle.puzzle);
+        assert!(clues.clues() <= heart.clues() + 4);
+        assert!((0..81).all(|i| !heart.contains(i / 9, i % 9) || clues.contains(i / 9, i % 9)));
+        assert!(solve_unique(&puzzle.puzzle).is_ok());
+
+        let sparse = Pattern::parse(&format!("{}{}", "x".repeat(16), ".".repeat(65))).unwrap();
+        assert!(matches!(
+            generate_with_pattern(&sparse, 1, &PatternOptions::default()),
+            Err(Error::ClueCount { clues: 16, .. })
+        ));
+        let options = PatternOptions {
+            min_rating: 20.0,
+            max_rating: 30.0,
+            attempts: 50,
+            extra_clues: 0,
+        };
+        assert!(matches!(
+            generate_with_pattern(&pattern, 1, &options),
+            Err(Error::RatingOutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+//! Puzzles bundled with the crate, a handful per tier, for examples and
+//! demos that don't want to ship data files or wait for the generator:
+//! `puzzles::easy().choose(&mut rng)`.
+use crate::corpus::read_lines;
+use crate::rating::Tier;
+use crate::set::PuzzleSet;
+
+const EASY: &str = "\
+6.........9.57.4.61.7.......8.6..7....27...894.6.8...22.8.9.36...9..3.45.....6.7.
+6.5..2..4.9.......2....953.....8....31..2.9.......57.1962178.5..5........74..3...
+7......6.14.87.9.....15.........5...263...7.....28..9..2.....4....4..587.....9..1
+..1...3..8.27...1.5......8.......65....1.62.93.5........3.6.4..19............95.1
+..4..71...1.8.654.....1....6.9...81.......72..3.....95..1.7...4.5..6...8.6.52..3.
+";
+
+const MEDIUM: &str = "\
+........4.47.8....1..5...7..2...7.96...8..3.5....6....3.6.4..1....3.....5.8.7..6.
+8.5..2..3.........3.....54....98.7..72..1.8..1.4...9..43.....9.6....3......4.6...
+...9...4.4..528..9......6..1.8..9.......7...5.9623......5.....82......31.4......7
+......5.7...8..6...3..571...1...6..4..7....6..6..2.71.95.17...8..4..5.....8...9..
+....2....5..8..6.334..........6..9..8..4..2..4.5....81.....8...1......5.6.7.358..
+";
+
+const HARD: &str = "\
+.......814..3....2.125.9.6.......7.8.....64.5.2..........6.5.....62.7....3...1.9.
+78...52...3..6..9.1....2.........37...43..5.1...81..........13.2........6.1.8...4
+83.......5.7.....84.28...7.......75.3...........976....61..932......3..5...41..8.
+....3.2.........89.4...1...1...5....9..3...477.5....9.4.7.....3....2.9...2..7.8.5
+..1.7.....6.9...4...7..52.3.....34.5....1.......4..8.......8...19.34..5.23.7..9.8
+";
+
+const DIABOLICAL: &str = "\
+5.......6..8...3...3..7...5......9..8...34..14..92..6..597.6....1..8.2.....1.....
+5......2...81..6...4..9...79..8.54...1.7..28.....2.........2.5...6.73.4.1.....3..
+.4.6...31.1...5......7.8.4.4.......37...5......634..17.73.............2..5...1.8.
+....3..69...1.5.7...8..65..5...4....79......1.6.3....71..9.........5..34......8..
+1.3.7...2..2..9....7..8....5.13...2.9...4..6..3...7..8.....46..3.....4.....6.....
+";
+
+/// The bundled puzzles of a tier, each proper and rated that tier by
+/// `Sudoku::rate`.
+pub fn tier(tier: Tier) -> PuzzleSet {
+    let lines = match tier {
+        Tier::Easy => EASY,
+        Tier::Medium => MEDIUM,
+        Tier::Hard => HARD,
+        Tier::Diabolical => DIABOLICAL,
+    };
+    read_lines(lines.as_bytes()).expect("bundled puzzles are valid; qed")
+}
+
+pub fn easy() -> PuzzleSet {
+    tier(Tier::Easy)
+}
+
+pub fn medium() -> PuzzleSet {
+    tier(Tier::Medium)
+}
+
+pub fn hard() -> PuzzleSet {
+    tier(Tier::Hard)
+}
+
+pub fn diabolical() -> PuzzleSet {
+    tier(Tier::Diabolical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers() {
+        for t in [Tier::Easy, Tier::Medium, Tier::Hard, Tier::Diabolical] {
+            let puzzles = tier(t);
+            assert_eq!(puzzles.len(), 5);
+            for puzzle in puzzles.iter() {
+                assert!(puzzle.has_unique_solution());
+                assert_eq!(puzzle.rate().tier, t, "{}", puzzle.to_line());
+            }
+        }
+        assert!(easy().choose(&mut rand::rng()).is_some());
+    }
+}
@@ -0,0 +1,648 @@
+use crate::logic::{LogicSolution, LogicalSolver, SolveStep, Technique};
+use crate::solver::par_map;
+use crate::{Error, SolverConfig, Sudoku, Unit};
+use std::collections::HashMap;
+
+/// Rating used for puzzles that need more than the implemented techniques.
+pub const SE_UNSOLVED: f32 = 11.0;
+
+/// An approximation of the ratings reported by Sudoku Explainer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeRating {
+    /// Difficulty of the hardest step.
+    pub er: f32,
+    /// Difficulty of the hardest step up to the first placement.
+    pub ep: f32,
+    /// Difficulty of the first step.
+    pub ed: f32,
+    /// Whether the rating is based on a complete logical solve.
+    pub solved: bool,
+}
+
+/// Sudoku Explainer difficulty of a single step.
+pub fn se_difficulty(step: &SolveStep) -> f32 {
+    match (step.technique, step.unit) {
+        (Technique::HiddenSingle, Some(Unit::Block(_))) => 1.2,
+        (technique, _) => se_weight(technique),
+    }
+}
+
+/// Sudoku Explainer rating of a technique, for hidden singles that of the
+/// ones in rows and columns.
+fn se_weight(technique: Technique) -> f32 {
+    match technique {
+        Technique::HiddenSingle => 1.5,
+        Technique::NakedSingle => 2.3,
+        Technique::Pointing => 2.6,
+        Technique::Claiming => 2.8,
+        Technique::NakedPair => 3.0,
+        Technique::HiddenPair => 3.4,
+        Technique::NakedTriple => 3.6,
+        Technique::HiddenTriple => 4.0,
+        Technique::NakedQuad => 5.0,
+        Technique::HiddenQuad => 5.4,
+        Technique::XWing => 3.2,
+        Technique::Swordfish => 3.8,
+        Technique::Jellyfish => 5.2,
+        Technique::XYWing => 4.2,
+        Technique::XYZWing => 4.4,
+        Technique::WWing => 4.4,
+        Technique::SimpleColoring => 4.5,
+        Technique::ForcingChain => 6.6,
+        Technique::UniqueRectangle1 => 4.5,
+        Technique::UniqueRectangle2 => 4.6,
+        Technique::UniqueRectangle3 => 4.6,
+        Technique::UniqueRectangle4 => 4.6,
+        Technique::BugPlusOne => 5.6,
+        Technique::Nishio => 7.5,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RatedPuzzle {
+    pub puzzle: Sudoku,
+    pub rating: SeRating,
+}
+
+impl RatedPuzzle {
+    pub fn new(puzzle: Sudoku) -> Self {
+        Self {
+            rating: se_rating(&puzzle),
+            puzzle,
+        }
+    }
+}
+
+/// Per technique weights of a rating model. A puzzle is scored by the
+/// weight of its hardest step.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TechniqueWeights {
+    weights: Vec<(Technique, f32)>,
+    /// Score of puzzles the techniques can't solve.
+    pub unsolved: f32,
+}
+
+impl TechniqueWeights {
+    pub fn get(&self, technique: Technique) -> f32 {
+        self.weights
+            .iter()
+            .find(|(t, _)| *t == technique)
+            .map(|(_, w)| *w)
+            .unwrap_or(self.unsolved)
+    }
+
+    pub fn set(&mut self, technique: Technique, weight: f32) {
+        match self.weights.iter_mut().find(|(t, _)| *t == technique) {
+            Some(entry) => entry.1 = weight,
+            None => self.weights.push((technique, weight)),
+        }
+    }
+
+    pub fn score(&self, solution: &LogicSolution) -> f32 {
+        if !solution.solved {
+            return self.unsolved;
+        }
+        solution
+            .steps
+            .iter()
+            .map(|step| self.get(step.technique))
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Default for TechniqueWeights {
+    /// Sudoku Explainer ratings of the techniques.
+    fn default() -> Self {
+        Self {
+            weights: Technique::all()
+                .iter()
+                .map(|&technique| (technique, se_weight(technique)))
+                .collect(),
+            unsolved: SE_UNSOLVED,
+        }
+    }
+}
+
+/// A difficulty model: the techniques in the order the solver tries them and
+/// their weights.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RatingConfig {
+    pub techniques: Vec<Technique>,
+    pub weights: TechniqueWeights,
+}
+
+impl RatingConfig {
+    pub fn solver(&self) -> LogicalSolver {
+        LogicalSolver::new(self.techniques.clone())
+    }
+
+    pub fn score(&self, sudoku: &Sudoku) -> f32 {
+        self.weights.score(&self.solver().solve(sudoku))
+    }
+}
+
+impl Default for RatingConfig {
+    fn default() -> Self {
+        Self {
+            techniques: Technique::all().to_vec(),
+            weights: TechniqueWeights::default(),
+        }
+    }
+}
+
+fn trace(sudoku: &Sudoku) -> (LogicSolution, Vec<f32>) {
+    let solution = LogicalSolver::for_puzzle(sudoku).solve(sudoku);
+    let difficulties = solution.steps.iter().map(se_difficulty).collect();
+    (solution, difficulties)
+}
+
+fn max(difficulties: &[f32]) -> f32 {
+    difficulties.iter().copied().fold(0.0, f32::max)
+}
+
+pub fn se_rating(sudoku: &Sudoku) -> SeRating {
+    let (solution, difficulties) = trace(sudoku);
+    let first_placement = solution
+        .steps
+        .iter()
+        .position(|step| !step.placements.is_empty())
+        .map(|i| i + 1)
+        .unwrap_or_else(|| difficulties.len());
+    let mut rating = SeRating {
+        er: max(&difficulties),
+        ep: max(&difficulties[..first_placement]),
+        ed: difficulties.first().copied().unwrap_or(0.0),
+        solved: solution.solved,
+    };
+    if !solution.solved {
+        rating.er = SE_UNSOLVED;
+        if first_placement == difficulties.len() {
+            rating.ep = SE_UNSOLVED;
+        }
+        if difficulties.is_empty() {
+            rating.ed = SE_UNSOLVED;
+        }
+    }
+    rating
+}
+
+/// The difficulty curve of a logical solve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RatingProfile {
+    /// Difficulty of each step in solving order.
+    pub difficulties: Vec<f32>,
+    /// Number of steps taken before the techniques ran out, equal to the
+    /// number of steps if the puzzle was solved.
+    pub guess_free_steps: usize,
+    /// Index of the first step with the highest difficulty.
+    pub hardest_step: Option<usize>,
+    pub solved: bool,
+}
+
+impl RatingProfile {
+    pub fn hardest(&self) -> f32 {
+        max(&self.difficulties)
+    }
+
+    pub fn mean(&self) -> f32 {
+        if self.difficulties.is_empty() {
+            return 0.0;
+        }
+        self.difficulties.iter().sum::<f32>() / self.difficulties.len() as f32
+    }
+
+    /// Ratio of the hardest to the average step. Smooth puzzles are close to
+    /// one, puzzles with a single hard step have a high spikiness.
+    pub fn spikiness(&self) -> f32 {
+        let mean = self.mean();
+        if mean == 0.0 {
+            return 0.0;
+        }
+        self.hardest() / mean
+    }
+}
+
+pub fn rating_profile(sudoku: &Sudoku) -> RatingProfile {
+    let (solution, difficulties) = trace(sudoku);
+    let hardest = max(&difficulties);
+    RatingProfile {
+        guess_free_steps: difficulties.len(),
+        hardest_step: difficulties.iter().position(|&d| d == hardest),
+        solved: solution.solved,
+        difficulties,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Bucket {
+    Beginner,
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Diabolical,
+}
+
+impl Bucket {
+    pub fn all() -> [Bucket; 6] {
+        [
+            Bucket::Beginner,
+            Bucket::Easy,
+            Bucket::Medium,
+            Bucket::Hard,
+            Bucket::Expert,
+            Bucket::Diabolical,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Bucket::Beginner => "beginner",
+            Bucket::Easy => "easy",
+            Bucket::Medium => "medium",
+            Bucket::Hard => "hard",
+            Bucket::Expert => "expert",
+            Bucket::Diabolical => "diabolical",
+        }
+    }
+}
+
+impl std::str::FromStr for Bucket {
+    type Err = Error;
+
+    /// Parses a bucket by its `name`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Bucket::all()
+            .iter()
+            .copied()
+            .find(|bucket| bucket.name() == s)
+            .ok_or_else(|| Error::UnknownDifficulty(s.into()))
+    }
+}
+
+/// Inclusive upper bounds on the Sudoku Explainer rating of the first five
+/// buckets, anything above the last bound is diabolical.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BucketThresholds(pub [f32; 5]);
+
+impl BucketThresholds {
+    pub fn bucket(&self, score: f32) -> Bucket {
+        let i = self.0.iter().position(|&upper| score <= upper).unwrap_or(5);
+        Bucket::all()[i]
+    }
+}
+
+impl Default for BucketThresholds {
+    /// The bands commonly used to label SE rated puzzles: hidden singles,
+    /// naked singles, intersections, subsets and basic fish and wings, up
+    /// to advanced uniqueness and chains, as fitted to
+    /// [`labeled_sample`](crate::calibration::labeled_sample) by
+    /// [`calibrate_thresholds`](crate::calibration::calibrate_thresholds).
+    fn default() -> Self {
+        Self([1.5, 2.3, 2.8, 4.4, 6.6])
+    }
+}
+
+/// A bucket together with the raw score it was derived from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Classification {
+    pub bucket: Bucket,
+    pub score: f32,
+}
+
+pub fn classify(sudoku: &Sudoku) -> Bucket {
+    classify_with(sudoku, &BucketThresholds::default()).bucket
+}
+
+pub fn classify_with(sudoku: &Sudoku, thresholds: &BucketThresholds) -> Classification {
+    let score = se_rating(sudoku).er;
+    Classification {
+        bucket: thresholds.bucket(score),
+        score,
+    }
+}
+
+/// Difficulty tiers for puzzles shown to people, a coarser split of the
+/// [`Bucket`]s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tier {
+    /// Beginner and easy puzzles.
+    Easy,
+    Medium,
+    /// Hard and expert puzzles.
+    Hard,
+    Diabolical,
+}
+
+impl Tier {
+    /// The tier of a [`Rating::score`], by its bucket under the default
+    /// [`BucketThresholds`].
+    pub fn from_score(score: f32) -> Self {
+        BucketThresholds::default().bucket(score).into()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tier::Easy => "easy",
+            Tier::Medium => "medium",
+            Tier::Hard => "hard",
+            Tier::Diabolical => "diabolical",
+        }
+    }
+}
+
+impl From<Bucket> for Tier {
+    fn from(bucket: Bucket) -> Self {
+        match bucket {
+            Bucket::Beginner | Bucket::Easy => Tier::Easy,
+            Bucket::Medium => Tier::Medium,
+            Bucket::Hard | Bucket::Expert => Tier::Hard,
+            Bucket::Diabolical => Tier::Diabolical,
+        }
+    }
+}
+
+/// Steps easier than this don't add to the score of a [`Rating`].
+const SINGLES: f32 = 2.3;
+
+/// How much each step beyond singles adds to the score of a [`Rating`], so
+/// that a puzzle needing a technique over and over rates above one needing
+/// it once.
+const STEP_WEIGHT: f32 = 0.02;
+
+/// The outcome of a simulated human solve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rating {
+    pub tier: Tier,
+    /// Sudoku Explainer difficulty of the hardest step, increased slightly
+    /// for each step beyond singles. [`SE_UNSOLVED`] if the techniques
+    /// don't suffice.
+    pub score: f32,
+    /// The hardest technique used.
+    pub hardest: Option<Technique>,
+    pub steps: usize,
+    pub solved: bool,
+}
+
+impl Sudoku {
+    /// Rates the puzzle by solving it with the techniques a person would
+    /// use, rather than by how much a backtracking solver has to guess.
+    /// Those assuming a unique solution only count for proper puzzles.
+    pub fn rate(&self) -> Rating {
+        let (solution, difficulties) = trace(self);
+        let hardest = max(&difficulties);
+        let technique = difficulties
+            .iter()
+            .position(|&d| d == hardest)
+            .map(|i| solution.steps[i].technique);
+        let advanced = difficulties.iter().filter(|&&d| d > SINGLES).count();
+        let score = if solution.solved {
+            hardest + STEP_WEIGHT * advanced as f32
+        } else {
+            SE_UNSOLVED
+        };
+        Rating {
+            tier: Tier::from_score(score),
+            score,
+            hardest: technique,
+            steps: difficulties.len(),
+            solved: solution.solved,
+        }
+    }
+
+    /// A cheap stand-in for `rate`: the guesses plus the backtracks the
+    /// solver needs to find the solution and rule out a second one. Zero
+    /// when singles suffice, so it works as a pre-filter for puzzles too
+    /// easy to be worth grading.
+    pub fn estimate_difficulty(&self) -> u64 {
+        let (_, stats) = SolverConfig::default().count_solutions_with_stats(self, 2);
+        stats.guesses + stats.backtracks
+    }
+}
+
+/// The ratings of a collection of puzzles, with the distribution over the
+/// tiers and the hardest techniques.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RatingReport {
+    /// The ratings in the order of the puzzles.
+    pub ratings: Vec<Rating>,
+    pub tiers: HashMap<Tier, usize>,
+    /// Puzzles by their hardest technique, puzzles without any step left
+    /// out.
+    pub techniques: HashMap<Technique, usize>,
+    /// Puzzles the techniques don't solve, also counted as diabolical.
+    pub unsolved: usize,
+}
+
+impl RatingReport {
+    pub fn count(&self, tier: Tier) -> usize {
+        self.tiers.get(&tier).copied().unwrap_or(0)
+    }
+
+    /// The fraction of the puzzles in `tier`, 0.0 for an empty report.
+    pub fn share(&self, tier: Tier) -> f64 {
+        match self.ratings.len() {
+            0 => 0.0,
+            n => self.count(tier) as f64 / n as f64,
+        }
+    }
+}
+
+/// Rates the puzzles as `Sudoku::rate`, spread over the available cores.
+pub fn rate_batch(puzzles: impl IntoIterator<Item = Sudoku>) -> RatingReport {
+    let puzzles: Vec<Sudoku> = puzzles.into_iter().collect();
+    let mut report = RatingReport {
+        ratings: par_map(&puzzles, Sudoku::rate),
+        ..RatingReport::default()
+    };
+    for rating in &report.ratings {
+        *report.tiers.entry(rating.tier).or_default() += 1;
+        if let Some(technique) = rating.hardest {
+            *report.techniques.entry(technique).or_default() += 1;
+        }
+        report.unsolved += !rating.solved as usize;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Sudoku {
+        s.replace('.', " ").parse().unwrap()
+    }
+
+    #[test]
+    fn test_rating() {
+        let easy = parse(
+            "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79",
+        );
+        let rating = se_rating(&easy);
+        assert!(rating.solved);
+        assert_eq!(rating.ed, 1.2);
+        assert!(rating.er <= 2.3);
+
+        let profile = rating_profile(&easy);
+        assert_eq!(profile.guess_free_steps, 51);
+        assert_eq!(profile.hardest(), rating.er);
+        let hardest = profile.hardest_step.unwrap();
+        assert_eq!(profile.difficulties[hardest], rating.er);
+        assert!(profile.spikiness() > 0.99);
+
+        assert_eq!(classify(&easy), Bucket::Beginner);
+
+        let rating = easy.rate();
+        assert_eq!(rating.tier, Tier::Easy);
+        assert_eq!(rating.score, profile.hardest());
+        assert_eq!(rating.steps, 51);
+
+        let rating = se_rating(&Sudoku::new());
+        assert!(!rating.solved);
+        assert_eq!(rating.er, SE_UNSOLVED);
+        assert_eq!(Sudoku::new().rate().tier, Tier::Diabolical);
+    }
+
+    #[test]
+    fn test_rate() {
+        let corpus = crate::Corpus::seventeen_sample();
+        let puzzle = corpus.puzzles.iter().last().unwrap();
+        let rating = puzzle.rate();
+        assert!(rating.solved);
+        assert_eq!(rating.tier, Tier::Hard);
+        assert_eq!(Tier::from_score(2.6), Tier::Medium);
+        assert_eq!(Tier::from_score(SE_UNSOLVED), Tier::Diabolical);
+        assert_eq!(Tier::from(Bucket::Expert), Tier::Hard);
+
+        // a unique rectangle is the hardest step of this proper puzzle
+        let puzzle: Sudoku =
+            "3.......7.7..4...2.6...7.5...9..3......5.8.2.13....6.4........341.9.......3.5...1"
+                .parse()
+                .unwrap();
+        let rating = puzzle.rate();
+        assert!(rating.solved);
+        assert_eq!(rating.hardest, Some(Technique::UniqueRectangle1));
+    }
+
+    #[test]
+    fn test_rate_batch() {
+        let mut puzzles: Vec<Sudoku> = crate::Corpus::seventeen_sample()
+            .puzzles
+            .iter()
+            .copied()
+            .collect();
+        puzzles.extend(crate::Corpus::top1465_sample().puzzles.iter().take(2));
+        let report = rate_batch(puzzles.iter().copied());
+        assert_eq!(report.ratings.len(), puzzles.len());
+        for (rating, puzzle) in report.ratings.iter().zip(&puzzles) {
+            assert_eq!(*rating, puzzle.rate());
+        }
+        assert_eq!(report.tiers.values().sum::<usize>(), puzzles.len());
+        assert!(report.count(Tier::Diabolical) >= report.unsolved);
+        let tiers = [Tier::Easy, Tier::Medium, Tier::Hard, Tier::Diabolical];
+        let shares: f64 = tiers.iter().map(|&tier| report.share(tier)).sum();
+        assert!((shares - 1.0).abs() < 1e-9);
+        assert!(report.techniques.values().sum::<usize>() <= puzzles.len());
+
+        let empty = rate_batch(Vec::new());
+        assert_eq!(empty.share(Tier::Easy), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_difficulty() {
+        let easy = parse(
+            "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79",
+        );
+        assert_eq!(easy.estimate_difficulty(), 0);
+        let corpus = crate::Corpus::top1465_sample();
+        let hard = corpus.puzzles.iter().next().unwrap();
+        assert!(hard.estimate_difficulty() > 0);
+    }
+
+    #[test]
+    fn test_bucket_thresholds() {
+        let thresholds = BucketThresholds::default();
+        assert_eq!(thresholds.bucket(1.2), Bucket::Beginner);
+        assert_eq!(thresholds.bucket(2.3), Bucket::Easy);
+        assert_eq!(thresholds.bucket(3.0), Bucket::Hard);
+        assert_eq!(thresholds.bucket(SE_UNSOLVED), Bucket::Diabolical);
+        let classification = classify_with(&Sudoku::new(), &thresholds);
+        assert_eq!(classification.score, SE_UNSOLVED);
+
+        for bucket in Bucket::all() {
+            assert_eq!(bucket.name().parse::<Bucket>().unwrap(), bucket);
+        }
+        assert!(matches!(
+            "easiest".parse::<Bucket>(),
+            Err(Error::UnknownDifficulty(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_weights() {
+        let weights = TechniqueWeights::default();
+        for &technique in Technique::all() {
+            let step = SolveStep {
+                technique,
+                unit: Some(Unit::Row(0)),
+                cells: Vec::new(),
+                digits: Vec::new(),
+                placements: Vec::new(),
+                eliminations: Vec::new(),
+            };
+            assert_eq!(weights.get(technique), se_difficulty(&step));
+        }
+    }
+
+    #[test]
+    fn test_rating_config() {
+        let mut config = RatingConfig::default();
+        assert_eq!(config.score(&Sudoku::new()), SE_UNSOLVED);
+        config.techniques = vec![Technique::NakedSingle, Technique::HiddenSingle];
+        config.weights.set(Technique::NakedSingle, 1.0);
+        config.weights.set(Technique::HiddenSingle, 3.0);
+        let easy = parse(
+            "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79",
+        );
+        // naked singles suffice when they are tried first
+        assert_eq!(config.score(&easy), 1.0);
+        config.techniques.reverse();
+        assert_eq!(config.score(&easy), 3.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rating_config_serde() {
+        let config = RatingConfig::default();
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<RatingConfig>(&json).unwrap(), config);
+    }
+}
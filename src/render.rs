@@ -0,0 +1,459 @@
+//! Text, HTML and LaTeX renderings of a grid, all configured by a
+//! [`GridStyle`].
+use crate::{Cell, Coord, Sudoku};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridStyle {
+    /// Written for cells without a value, and for missing candidates.
+    pub blank: char,
+    /// Separate the boxes with lines.
+    pub borders: bool,
+    /// Draw lines with box drawing characters instead of `-`, `|` and `+`.
+    pub unicode: bool,
+    /// Spaces on either side of a cell.
+    pub padding: usize,
+    /// Show the candidates of cells without a value in a 3x3 layout.
+    pub candidates: bool,
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self {
+            blank: '.',
+            borders: true,
+            unicode: false,
+            padding: 0,
+            candidates: false,
+        }
+    }
+}
+
+/// Line characters: horizontal, vertical and the crossings of the top,
+/// middle and bottom lines as left, center and right.
+struct Lines {
+    h: char,
+    v: char,
+    top: [char; 3],
+    mid: [char; 3],
+    bottom: [char; 3],
+}
+
+impl GridStyle {
+    fn lines(&self) -> Lines {
+        if self.unicode {
+            Lines {
+                h: '─',
+                v: '│',
+                top: ['┌', '┬', '┐'],
+                mid: ['├', '┼', '┤'],
+                bottom: ['└', '┴', '┘'],
+            }
+        } else {
+            Lines {
+                h: '-',
+                v: '|',
+                top: ['+'; 3],
+                mid: ['+'; 3],
+                bottom: ['+'; 3],
+            }
+        }
+    }
+
+    fn cell_width(&self) -> usize {
+        (if self.candidates { 3 } else { 1 }) + 2 * self.padding
+    }
+
+    /// Separator between two cells of the same box.
+    fn gap(&self) -> &'static str {
+        if self.candidates {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    /// The text lines of a cell without padding.
+    fn cell_lines(&self, cell: &Cell) -> Vec<String> {
+        let blank = self.blank.to_string();
+        if !self.candidates {
+            let text = cell.value().map_or(blank, |v| v.to_string());
+            return vec![text];
+        }
+        if let Some(value) = cell.value() {
+            return vec!["   ".into(), format!(" {} ", value), "   ".into()];
+        }
+        let mut lines = vec![String::new(); 3];
+        for (i, value) in (0..9).map(crate::Value).enumerate() {
+            let c = if cell.contains(value) {
+                (b'1' + value.0) as char
+            } else {
+                self.blank
+            };
+            lines[i / 3].push(c);
+        }
+        lines
+    }
+}
+
+/// What `render_color` picks out.
+#[cfg(feature = "color")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Highlight {
+    /// The puzzle the grid was filled from. Its values are drawn in bold
+    /// and the player's in blue; without it every value counts as given.
+    pub givens: Option<Sudoku>,
+    /// Draw values repeating in a unit in red.
+    pub conflicts: bool,
+    /// A cell drawn on a yellow background.
+    pub last_move: Option<Coord>,
+}
+
+/// The text renderers, `sgr` giving the ANSI SGR parameters of a cell or
+/// `None` to leave it undecorated.
+fn render_text(
+    sudoku: &Sudoku,
+    style: &GridStyle,
+    framed: bool,
+    sgr: &dyn Fn(Coord, &Cell) -> Option<String>,
+) -> String {
+    let lines = style.lines();
+    let box_width = 3 * style.cell_width() + 2 * style.gap().len();
+    let rule = |ends: [char; 3]| {
+        let mut line = String::new();
+        for b in 0..3 {
+            if b == 0 {
+                if framed {
+                    line.push(ends[0]);
+                }
+            } else {
+                line.push(ends[1]);
+            }
+            line.extend(std::iter::repeat_n(lines.h, box_width));
+        }
+        if framed {
+            line.push(ends[2]);
+        }
+        line.push('\n');
+        line
+    };
+    let pad = " ".repeat(style.padding);
+    let mut out = String::new();
+    if framed && style.borders {
+        out.push_str(&rule(lines.top));
+    }
+    for x in 0..9 {
+        if x > 0 && x % 3 == 0 && style.borders {
+            out.push_str(&rule(lines.mid));
+        }
+        let cells: Vec<(Cell, Vec<String>)> = (0..9)
+            .map(|y| {
+                let cell = *sudoku.get(x, y).unwrap();
+                (cell, style.cell_lines(&cell))
+            })
+            .collect();
+        for i in 0..cells[0].1.len() {
+            if framed && style.borders {
+                out.push(lines.v);
+            }
+            for (y, (cell, text_lines)) in cells.iter().enumerate() {
+                if y > 0 {
+                    if y % 3 == 0 && style.borders {
+                        out.push(lines.v);
+                    } else {
+                        out.push_str(style.gap());
+                    }
+                }
+                out.push_str(&pad);
+                let content = &text_lines[i];
+                match sgr(Coord::new(x, y), cell) {
+                    Some(sgr) => write!(out, "\x1b[{}m{}\x1b[0m", sgr, content).unwrap(),
+                    None => out.push_str(content),
+                }
+                out.push_str(&pad);
+            }
+            if framed && style.borders {
+                out.push(lines.v);
+            }
+            out.push('\n');
+        }
+    }
+    if framed && style.borders {
+        out.push_str(&rule(lines.bottom));
+    }
+    out
+}
+
+/// One line per row, with lines between the boxes if `style.borders` is
+/// set.
+pub fn render_plain(sudoku: &Sudoku, style: &GridStyle) -> String {
+    render_text(sudoku, style, false, &|_, _| None)
+}
+
+/// Like `render_plain`, with a frame around the grid.
+pub fn render_pretty(sudoku: &Sudoku, style: &GridStyle) -> String {
+    render_text(sudoku, style, true, &|_, _| None)
+}
+
+/// Like `render_pretty`, with values in bold and candidates dimmed using
+/// ANSI escape codes.
+pub fn render_ansi(sudoku: &Sudoku, style: &GridStyle) -> String {
+    render_text(sudoku, style, true, &|_, cell| {
+        Some(if cell.is_final() { "1" } else { "2" }.into())
+    })
+}
+
+/// Like `render_pretty`, coloring the cells as `highlight` says with
+/// ANSI escape codes.
+#[cfg(feature = "color")]
+pub fn render_color(sudoku: &Sudoku, highlight: &Highlight, style: &GridStyle) -> String {
+    let mut conflicts = [false; 81];
+    if highlight.conflicts {
+        for (a, b) in sudoku.conflicts() {
+            conflicts[a.index()] = true;
+            conflicts[b.index()] = true;
+        }
+    }
+    render_text(sudoku, style, true, &|coord, cell| {
+        let mut codes = Vec::new();
+        match cell.value() {
+            None => codes.push("2"),
+            Some(_) if conflicts[coord.index()] => codes.push("1;31"),
+            Some(_) if highlight.givens.is_some_and(|g| !g[coord].is_final()) => codes.push("34"),
+            Some(_) => codes.push("1"),
+        }
+        if highlight.last_move == Some(coord) {
+            codes.push("43");
+        }
+        Some(codes.join(";"))
+    })
+}
+
+/// A `<table class="sudoku">`. Cells get the class `given` if they have a
+/// value and `blank` otherwise, and `box-1` to `box-9` for their box. Cells
+/// starting a box also get `box-top` and `box-left` if `style.borders` is
+/// set, for the stylesheet to draw the lines; `style.unicode` doesn't
+/// apply.
+pub fn render_html(sudoku: &Sudoku, style: &GridStyle) -> String {
+    let mut out = String::from("<table class=\"sudoku\">\n");
+    for x in 0..9 {
+        out.push_str("<tr>");
+        for y in 0..9 {
+            let cell = sudoku.get(x, y).unwrap();
+            let given = if cell.is_final() { "given" } else { "blank" };
+            let block = format!("box-{}", Coord::new(x, y).block() + 1);
+            let mut classes = vec![given, &block];
+            if style.borders && x % 3 == 0 && x > 0 {
+                classes.push("box-top");
+            }
+            if style.borders && y % 3 == 0 && y > 0 {
+                classes.push("box-left");
+            }
+            write!(out, "<td class=\"{}\"", classes.join(" ")).unwrap();
+            if style.padding > 0 {
+                write!(out, " style=\"padding: 0 {}ch\"", style.padding).unwrap();
+            }
+            out.push('>');
+            match cell.value() {
+                Some(value) => write!(out, "{}", value).unwrap(),
+                None if style.candidates => {
+                    out.push_str("<div class=\"candidates\">");
+                    for line in style.cell_lines(cell) {
+                        write!(out, "<span>{}</span>", escape(&line)).unwrap();
+                    }
+                    out.push_str("</div>");
+                }
+                None => out.push_str(&escape(&style.blank.to_string())),
+            }
+            out.push_str("</td>");
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// A TikZ picture for puzzle books, one unit per cell with the origin at
+/// the bottom left. Boxes get thick rules if `style.borders` is set, blanks
+/// stay empty or show their candidates in a 3x3 layout if
+/// `style.candidates` is set; `style.blank`, `style.unicode` and
+/// `style.padding` don't apply.
+pub fn render_latex(sudoku: &Sudoku, style: &GridStyle) -> String {
+    let mut out = String::from("\\begin{tikzpicture}\n");
+    out.push_str("  \\draw[step=1] (0,0) grid (9,9);\n");
+    if style.borders {
+        out.push_str("  \\draw[step=3, very thick] (0,0) grid (9,9);\n");
+    }
+    for x in 0..9 {
+        for y in 0..9 {
+            let cell = sudoku.get(x, y).unwrap();
+            let (left, bottom) = (y as f32, (8 - x) as f32);
+            match cell.value() {
+                Some(value) => writeln!(
+                    out,
+                    "  \\node[font=\\Large] at ({}, {}) {{{}}};",
+                    left + 0.5,
+                    bottom + 0.5,
+                    value
+                )
+                .unwrap(),
+                None if style.candidates => {
+                    for value in cell.values() {
+                        let (row, col) = (value.0 / 3, value.0 % 3);
+                        writeln!(
+                            out,
+                            "  \\node[font=\\tiny] at ({:.3}, {:.3}) {{{}}};",
+                            left + (col as f32 + 0.5) / 3.0,
+                            bottom + 1.0 - (row as f32 + 0.5) / 3.0,
+                            value
+                        )
+                        .unwrap();
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+    out.push_str("\\end{tikzpicture}\n");
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Sudoku {
+    /// [`render_latex`] with the default style.
+    pub fn to_latex(&self) -> String {
+        render_latex(self, &GridStyle::default())
+    }
+
+    /// [`render_html`] with the default style.
+    pub fn to_html(&self) -> String {
+        render_html(self, &GridStyle::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle() -> Sudoku {
+        "53..7....\n\
+         6..195...\n\
+         .98....6.\n\
+         8...6...3\n\
+         4..8.3..1\n\
+         7...2...6\n\
+         .6....28.\n\
+         ...419..5\n\
+         ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_text() {
+        let style = GridStyle::default();
+        let plain = render_plain(&puzzle(), &style);
+        assert_eq!(plain.lines().next(), Some("53.|.7.|..."));
+        assert_eq!(plain.lines().nth(3), Some("---+---+---"));
+        let bare = GridStyle {
+            borders: false,
+            ..style
+        };
+        assert_eq!(render_plain(&puzzle(), &bare).lines().count(), 9);
+
+        let unicode = GridStyle {
+            unicode: true,
+            padding: 1,
+            ..style
+        };
+        let pretty = render_pretty(&puzzle(), &unicode);
+        let lines: Vec<&str> = pretty.lines().collect();
+        assert_eq!(lines[0], "┌─────────┬─────────┬─────────┐");
+        assert_eq!(lines[1], "│ 5  3  . │ .  7  . │ .  .  . │");
+        assert_eq!(lines.len(), 13);
+
+        let candidates = GridStyle {
+            candidates: true,
+            ..style
+        };
+        let mut sudoku = puzzle();
+        sudoku.get_mut(0, 2).unwrap().0 = 0b1_0000_0110;
+        let text = render_plain(&sudoku, &candidates);
+        assert_eq!(text.lines().count(), 29);
+        assert!(text.lines().next().unwrap().starts_with("        .23"));
+
+        let ansi = render_ansi(&puzzle(), &style);
+        assert!(ansi.contains("\x1b[1m5\x1b[0m"));
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn test_render_color() {
+        let givens = puzzle();
+        let mut grid = givens;
+        grid[Coord::new(0, 2)] = crate::Value::new(4).unwrap().into();
+        grid[Coord::new(0, 3)] = crate::Value::new(5).unwrap().into();
+        let highlight = Highlight {
+            givens: Some(givens),
+            conflicts: true,
+            last_move: Some(Coord::new(0, 3)),
+        };
+        let text = render_color(&grid, &highlight, &GridStyle::default());
+        let first = text.lines().nth(1).unwrap();
+        assert!(first.starts_with("|\x1b[1;31m5\x1b[0m\x1b[1m3\x1b[0m\x1b[34m4\x1b[0m|"));
+        assert!(first.contains("\x1b[1;31;43m5\x1b[0m"));
+        assert!(first.ends_with("\x1b[2m.\x1b[0m|"));
+
+        // without the puzzle every value is a given
+        let plain = render_color(&givens, &Highlight::default(), &GridStyle::default());
+        assert_eq!(plain, render_ansi(&givens, &GridStyle::default()));
+    }
+
+    #[test]
+    fn test_render_latex() {
+        let latex = render_latex(&puzzle(), &GridStyle::default());
+        let lines: Vec<&str> = latex.lines().collect();
+        assert_eq!(lines[0], "\\begin{tikzpicture}");
+        assert_eq!(lines[2], "  \\draw[step=3, very thick] (0,0) grid (9,9);");
+        assert_eq!(lines[3], "  \\node[font=\\Large] at (0.5, 8.5) {5};");
+        assert_eq!(lines.len(), 4 + 30);
+        assert_eq!(lines.last(), Some(&"\\end{tikzpicture}"));
+        assert_eq!(puzzle().to_latex(), latex);
+
+        let style = GridStyle {
+            candidates: true,
+            borders: false,
+            ..GridStyle::default()
+        };
+        let mut sudoku = puzzle();
+        sudoku.get_mut(0, 2).unwrap().0 = 0b1_0000_0010;
+        let latex = render_latex(&sudoku, &style);
+        assert!(!latex.contains("very thick"));
+        assert!(latex.contains("\\node[font=\\tiny] at (2.500, 8.833) {2};"));
+        assert!(latex.contains("\\node[font=\\tiny] at (2.833, 8.167) {9};"));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let style = GridStyle {
+            candidates: true,
+            ..GridStyle::default()
+        };
+        let html = render_html(&puzzle(), &style);
+        assert!(html.starts_with("<table class=\"sudoku\">\n<tr><td class=\"given box-1\">5</td>"));
+        assert!(html.contains("<td class=\"blank box-2 box-left\"><div class=\"candidates\">"));
+        assert_eq!(html.matches("<tr>").count(), 9);
+        assert_eq!(html.matches("given").count(), 30);
+        assert_eq!(html.matches("box-9").count(), 9);
+
+        let html = puzzle().to_html();
+        assert_eq!(html, render_html(&puzzle(), &GridStyle::default()));
+        assert!(!html.contains("candidates"));
+    }
+}
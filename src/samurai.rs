@@ -0,0 +1,237 @@
+//! Samurai sudoku: five 9x9 grids in a 21x21 square, the centre one sharing
+//! a corner box with each of the others.
+use crate::grid::{Rules, Search};
+use crate::symbols::SymbolSet;
+use crate::{Error, Sudoku};
+
+/// Width and height of the square the grids are laid out in.
+pub const SIDE: usize = 21;
+
+/// Top left cells of the grids: top left, top right, centre, bottom left
+/// and bottom right.
+pub const ORIGINS: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+/// Number of distinct cells, the shared boxes counted once.
+pub const CELLS: usize = 369;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Samurai {
+    /// Row-major digits of the 21x21 square, `0` for blanks and for the
+    /// cells outside of the grids.
+    cells: Vec<u8>,
+}
+
+/// Whether a cell of the square belongs to one of the grids.
+pub fn in_grid(row: usize, col: usize) -> bool {
+    ORIGINS
+        .iter()
+        .any(|&(top, left)| (top..top + 9).contains(&row) && (left..left + 9).contains(&col))
+}
+
+impl Samurai {
+    pub fn new() -> Self {
+        Self {
+            cells: vec![0; SIDE * SIDE],
+        }
+    }
+
+    /// Parses the cells of the grids row by row, ignoring whitespace, with
+    /// `.` or `0` for blanks. Cells outside of the grids are skipped, so the
+    /// format written by `Display` is read back unchanged.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Self::parse_with(s, &SymbolSet::digits())
+    }
+
+    pub fn parse_with(s: &str, symbols: &SymbolSet) -> Result<Self, Error> {
+        let mut digits = Vec::with_capacity(CELLS);
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            digits.push(if symbols.is_blank(c) {
+                0
+            } else {
+                match symbols.digit(c) {
+                    Some(d @ 1..=9) => d,
+                    _ => return Err(Error::InvalidSymbol(c)),
+                }
+            });
+        }
+        if digits.len() != CELLS {
+            return Err(Error::WrongLength {
+                expected: CELLS,
+                found: digits.len(),
+            });
+        }
+        let mut samurai = Self::new();
+        let positions = (0..SIDE * SIDE).filter(|&i| in_grid(i / SIDE, i % SIDE));
+        for (i, digit) in positions.zip(digits) {
+            samurai.cells[i] = digit;
+        }
+        Ok(samurai)
+    }
+
+    /// The digit at `row`, `col` of the square, `None` for blanks and
+    /// cells outside the grids.
+    pub fn get(&self, row: usize, col: usize) -> Option<u8> {
+        if row >= SIDE || col >= SIDE {
+            return None;
+        }
+        match self.cells[row * SIDE + col] {
+            0 => None,
+            digit => Some(digit),
+        }
+    }
+
+    /// Sets a cell of one of the grids.
+    pub fn set(&mut self, row: usize, col: usize, digit: Option<u8>) -> Result<(), Error> {
+        let digit = digit.unwrap_or(0);
+        if digit > 9 || row >= SIDE || col >= SIDE || !in_grid(row, col) {
+            return Err(Error::ValueOutOfRange);
+        }
+        self.cells[row * SIDE + col] = digit;
+        Ok(())
+    }
+
+    /// Grid `i` in the order of [`ORIGINS`].
+    pub fn grid(&self, i: usize) -> Sudoku {
+        let (top, left) = ORIGINS[i];
+        let mut digits = [0; 81];
+        for (j, digit) in digits.iter_mut().enumerate() {
+            *digit = self.cells[(top + j / 9) * SIDE + left + j % 9];
+        }
+        Sudoku::from_digits(&digits)
+    }
+
+    pub fn clues(&self) -> usize {
+        self.cells.iter().filter(|&&d| d != 0).count()
+    }
+
+    /// Rows, columns and boxes of all grids, each shared box once.
+    fn units() -> Vec<Vec<usize>> {
+        let mut units: Vec<Vec<usize>> = Vec::new();
+        for &(top, left) in &ORIGINS {
+            let at = |row: usize, col: usize| (top + row) * SIDE + left + col;
+            for i in 0..9 {
+                units.push((0..9).map(|col| at(i, col)).collect());
+                units.push((0..9).map(|row| at(row, i)).collect());
+                let (r, c) = ((i / 3) * 3, (i % 3) * 3);
+                let block: Vec<usize> = (0..9).map(|j| at(r + j / 3, c + j % 3)).collect();
+                if !units.contains(&block) {
+                    units.push(block);
+                }
+            }
+        }
+        units
+    }
+
+    fn search(&self) -> Option<Search> {
+        let cells = self.cells.clone();
+        Search::new(cells, 9, Self::units(), Rules::default(), true)
+    }
+
+    fn run(&self, limit: usize) -> Vec<Samurai> {
+        let mut solutions = Vec::new();
+        if let Some(mut search) = self.search() {
+            search.run(&mut solutions, limit, None);
+        }
+        solutions.into_iter().map(|cells| Self { cells }).collect()
+    }
+
+    /// Whether no digit repeats in a unit of any grid.
+    pub fn is_consistent(&self) -> bool {
+        self.search().is_some()
+    }
+
+    /// Whether all grids are full and no digit repeats in a unit.
+    pub fn is_valid(&self) -> bool {
+        self.clues() == CELLS && self.is_consistent()
+    }
+
+    /// Solves all grids at once, so that the digits of the shared boxes
+    /// constrain both grids they belong to.
+    pub fn solve(&self) -> Option<Samurai> {
+        self.run(1).pop()
+    }
+
+    /// Counts solutions, stopping at `limit`.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.run(limit).len()
+    }
+}
+
+impl Default for Samurai {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for Samurai {
+    /// One row of the square per line, with `.` for blanks and spaces
+    /// outside of the grids.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for row in 0..SIDE {
+            let mut line = String::with_capacity(SIDE);
+            for col in 0..SIDE {
+                line.push(match self.get(row, col) {
+                    _ if !in_grid(row, col) => ' ',
+                    Some(digit) => (b'0' + digit) as char,
+                    None => '.',
+                });
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Samurai {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samurai() {
+        let solution = Samurai::new().solve().unwrap();
+        assert!(solution.is_valid());
+        for i in 0..5 {
+            assert!(solution.grid(i).valid());
+        }
+        // the centre grid shares its corner boxes
+        assert_eq!(solution.get(6, 6), Some(solution.grid(0).digits()[60]));
+        assert_eq!(solution.to_string().lines().count(), SIDE);
+        assert_eq!(solution.to_string().parse::<Samurai>().unwrap(), solution);
+
+        // blank cells while the solution stays unique
+        let mut puzzle = solution.clone();
+        for i in (0..SIDE * SIDE).map(|i| i * 8 % (SIDE * SIDE)) {
+            let (row, col) = (i / SIDE, i % SIDE);
+            if let Some(digit) = puzzle.get(row, col) {
+                puzzle.set(row, col, None).unwrap();
+                if puzzle.count_solutions(2) != 1 {
+                    puzzle.set(row, col, Some(digit)).unwrap();
+                }
+            }
+        }
+        assert!(puzzle.clues() < CELLS / 2);
+        assert_eq!(puzzle.solve(), Some(solution.clone()));
+        assert!(puzzle.is_consistent() && !puzzle.is_valid());
+
+        let mut broken = puzzle;
+        broken.set(9, 6, solution.get(0, 0)).unwrap();
+        broken.set(9, 7, solution.get(0, 0)).unwrap();
+        assert!(!broken.is_consistent());
+        assert_eq!(broken.count_solutions(2), 0);
+
+        assert!(Samurai::new().set(0, 10, Some(1)).is_err());
+        assert_eq!(Samurai::new().get(0, SIDE), None);
+        assert!(matches!(
+            Samurai::parse("123"),
+            Err(Error::WrongLength { found: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+//! Sandwich sudoku: a 9x9 grid with clues outside of it, each the sum of
+//! the digits between the 1 and the 9 of its row or column.
+use crate::constraint::Sandwich;
+use crate::grid::Grid;
+use crate::Sudoku;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct SandwichSudoku {
+    pub givens: Sudoku,
+    /// The clue of each row, `None` for a row without one.
+    pub rows: [Option<u8>; 9],
+    pub cols: [Option<u8>; 9],
+}
+
+impl SandwichSudoku {
+    /// A puzzle without clues outside of the grid yet.
+    pub fn new(givens: Sudoku) -> Self {
+        Self {
+            givens,
+            rows: [None; 9],
+            cols: [None; 9],
+        }
+    }
+
+    /// A puzzle with every clue of a solution, and its givens.
+    pub fn from_solution(solution: &Sudoku, givens: Sudoku) -> Self {
+        let (rows, cols) = sums(solution);
+        Self { givens, rows, cols }
+    }
+
+    /// The givens with the clues as constraints.
+    pub fn grid(&self) -> Grid {
+        let mut grid = Grid::from(&self.givens);
+        for (i, (row, col)) in self.rows.iter().zip(&self.cols).enumerate() {
+            if let Some(sum) = row {
+                grid.add_constraint(Sandwich::row(i, *sum as u32, 9));
+            }
+            if let Some(sum) = col {
+                grid.add_constraint(Sandwich::col(i, *sum as u32, 9));
+            }
+        }
+        grid
+    }
+
+    /// Whether the givens repeat no digit in a unit and leave every clue
+    /// reachable.
+    pub fn is_consistent(&self) -> bool {
+        self.grid().is_consistent()
+    }
+
+    /// Whether `solution` is a full valid grid keeping the givens and
+    /// meeting every clue.
+    pub fn is_solution(&self, solution: &Sudoku) -> bool {
+        let (rows, cols) = sums(solution);
+        let met = |clues: &[Option<u8>; 9], sums: &[Option<u8>; 9]| {
+            clues
+                .iter()
+                .zip(sums)
+                .all(|(clue, sum)| clue.is_none() || clue == sum)
+        };
+        solution.valid()
+            && solution.is_completion_of(&self.givens)
+            && met(&self.rows, &rows)
+            && met(&self.cols, &cols)
+    }
+
+    pub fn solve(&self) -> Option<Sudoku> {
+        self.grid().solve().map(|grid| grid.to_sudoku())
+    }
+
+    /// Counts solutions, stopping at `limit`.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.grid().count_solutions(limit)
+    }
+}
+
+/// The sums between the 1 and the 9 of each row and column, `None` where
+/// either is missing.
+pub fn sums(sudoku: &Sudoku) -> ([Option<u8>; 9], [Option<u8>; 9]) {
+    let digits = sudoku.digits();
+    let sum = |line: [u8; 9]| {
+        let a = line.iter().position(|&d| d == 1)?;
+        let b = line.iter().position(|&d| d == 9)?;
+        Some(line[a.min(b) + 1..a.max(b)].iter().sum())
+    };
+    let rows = core::array::from_fn(|row| sum(core::array::from_fn(|col| digits[row * 9 + col])));
+    let cols = core::array::from_fn(|col| sum(core::array::from_fn(|row| digits[row * 9 + col])));
+    (rows, cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cell, Coord, Value};
+
+    #[test]
+    fn test_sandwich_sudoku() {
+        let solution: Sudoku = "534678912\
+             672195348\
+             198342567\
+             859761423\
+             426853791\
+             713924856\
+             961537284\
+             287419635\
+             345286179"
+            .parse()
+            .unwrap();
+        let (rows, cols) = sums(&solution);
+        assert_eq!(rows[0], Some(0));
+        assert_eq!(cols[0], Some(8 + 4 + 7));
+        assert_eq!(sums(&Sudoku::new()), ([None; 9], [None; 9]));
+
+        // the top band given, the rest left to the clues
+        let mut givens = solution;
+        for i in 27..81 {
+            givens[Coord::from_index(i)] = Cell::new();
+        }
+        let puzzle = SandwichSudoku::from_solution(&solution, givens);
+        assert!(puzzle.is_consistent());
+        assert!(puzzle.is_solution(&solution));
+        assert!(puzzle.is_solution(&puzzle.solve().unwrap()));
+        assert_eq!(SandwichSudoku::new(givens).count_solutions(2), 2);
+
+        let mut wrong = puzzle;
+        wrong.rows[0] = Some(1);
+        assert!(!wrong.is_solution(&solution));
+        let mut givens = Sudoku::new();
+        givens[Coord::new(0, 0)] = Value::new(1).unwrap().into();
+        givens[Coord::new(0, 1)] = Value::new(9).unwrap().into();
+        wrong.givens = givens;
+        wrong.rows[0] = Some(12);
+        assert!(!wrong.is_consistent());
+        assert_eq!(wrong.count_solutions(1), 0);
+    }
+}
@@ -0,0 +1,294 @@
+//! Solving by translating the grid into CNF, for puzzles built to defeat
+//! backtracking.
+use crate::verified::SolvedSudoku;
+use crate::{SolveOutcome, Sudoku, Unit, Value};
+
+/// A formula in conjunctive normal form. Literals are numbered like in
+/// DIMACS, variable `v` is `v` and its negation `-v`, counting from `1`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cnf {
+    pub vars: usize,
+    pub clauses: Vec<Vec<i32>>,
+}
+
+/// The variable stating that the cell at `x`, `y` has `value`.
+fn var(x: usize, y: usize, value: Value) -> i32 {
+    (x * 81 + y * 9 + value.0 as usize + 1) as i32
+}
+
+impl Cnf {
+    /// Encodes the rules, the clues and the candidates of `sudoku`.
+    pub fn from_sudoku(sudoku: &Sudoku) -> Self {
+        let values = || (0..9).map(Value);
+        let mut clauses = Vec::new();
+        for x in 0..9 {
+            for y in 0..9 {
+                let cell = sudoku.get(x, y).unwrap();
+                clauses.push(cell.values().map(|v| var(x, y, v)).collect());
+                for v in values().filter(|&v| !cell.contains(v)) {
+                    clauses.push(vec![-var(x, y, v)]);
+                }
+                for a in values() {
+                    for b in values().filter(|b| b.0 > a.0) {
+                        clauses.push(vec![-var(x, y, a), -var(x, y, b)]);
+                    }
+                }
+            }
+        }
+        for unit in Unit::all() {
+            for v in values() {
+                clauses.push(unit.cells().map(|(x, y)| var(x, y, v)).collect());
+                let cells: Vec<_> = unit.cells().collect();
+                for (i, &(ax, ay)) in cells.iter().enumerate() {
+                    for &(bx, by) in &cells[i + 1..] {
+                        clauses.push(vec![-var(ax, ay, v), -var(bx, by, v)]);
+                    }
+                }
+            }
+        }
+        Self { vars: 729, clauses }
+    }
+
+    /// The formula in the DIMACS format, for external solvers.
+    pub fn to_dimacs(&self) -> String {
+        let mut out = format!("p cnf {} {}\n", self.vars, self.clauses.len());
+        for clause in &self.clauses {
+            for lit in clause {
+                out.push_str(&lit.to_string());
+                out.push(' ');
+            }
+            out.push_str("0\n");
+        }
+        out
+    }
+}
+
+/// A SAT solver, returning the value of each variable of a satisfying
+/// assignment indexed from `0`.
+pub trait SatSolver {
+    fn solve(&mut self, cnf: &Cnf) -> Option<Vec<bool>>;
+}
+
+/// A small DPLL solver with unit propagation, branching on a literal of
+/// the shortest open clause.
+#[derive(Clone, Debug, Default)]
+pub struct Dpll {
+    assignment: Vec<Option<bool>>,
+    trail: Vec<usize>,
+    /// Clauses containing each literal, at `2 * var` for the positive and
+    /// `2 * var + 1` for the negative literal.
+    occurs: Vec<Vec<usize>>,
+}
+
+fn lit_index(lit: i32) -> usize {
+    let var = lit.unsigned_abs() as usize - 1;
+    2 * var + (lit < 0) as usize
+}
+
+impl Dpll {
+    fn value(&self, lit: i32) -> Option<bool> {
+        let value = self.assignment[lit.unsigned_abs() as usize - 1]?;
+        Some(value == (lit > 0))
+    }
+
+    fn assign(&mut self, lit: i32) {
+        let var = lit.unsigned_abs() as usize - 1;
+        self.assignment[var] = Some(lit > 0);
+        self.trail.push(var);
+    }
+
+    fn undo(&mut self, len: usize) {
+        for var in self.trail.drain(len..) {
+            self.assignment[var] = None;
+        }
+    }
+
+    /// Assigns `lit` and everything it implies, `false` on a conflict.
+    fn propagate(&mut self, cnf: &Cnf, lit: i32) -> bool {
+        match self.value(lit) {
+            Some(value) => return value,
+            None => self.assign(lit),
+        }
+        let mut queue = vec![lit];
+        while let Some(lit) = queue.pop() {
+            for i in 0..self.occurs[lit_index(-lit)].len() {
+                let clause = &cnf.clauses[self.occurs[lit_index(-lit)][i]];
+                let mut open = None;
+                let mut count = 0;
+                let mut satisfied = false;
+                for &l in clause {
+                    match self.value(l) {
+                        Some(true) => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            open = Some(l);
+                            count += 1;
+                        }
+                    }
+                }
+                match (satisfied, count, open) {
+                    (true, _, _) => {}
+                    (false, 0, _) => return false,
+                    (false, 1, Some(unit)) => {
+                        self.assign(unit);
+                        queue.push(unit);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// An unassigned literal of the open clause with the fewest unassigned
+    /// literals, or `None` if every clause is satisfied.
+    fn branch(&self, cnf: &Cnf) -> Option<i32> {
+        let mut best: Option<(usize, i32)> = None;
+        for clause in &cnf.clauses {
+            if clause.iter().any(|&l| self.value(l) == Some(true)) {
+                continue;
+            }
+            let mut open = clause.iter().filter(|&&l| self.value(l).is_none());
+            let first = match open.next() {
+                Some(&first) => first,
+                None => continue,
+            };
+            let count = 1 + open.count();
+            if best.is_none_or(|(best, _)| count < best) {
+                best = Some((count, first));
+            }
+        }
+        best.map(|(_, lit)| lit)
+    }
+
+    /// `Err` once `abort` returns `true`, checked at every decision.
+    fn search(&mut self, cnf: &Cnf, abort: &mut dyn FnMut() -> bool) -> Result<bool, ()> {
+        if abort() {
+            return Err(());
+        }
+        let lit = match self.branch(cnf) {
+            Some(lit) => lit,
+            None => return Ok(true),
+        };
+        for lit in [lit, -lit] {
+            let len = self.trail.len();
+            if self.propagate(cnf, lit) && self.search(cnf, abort)? {
+                return Ok(true);
+            }
+            self.undo(len);
+        }
+        Ok(false)
+    }
+
+    /// Like `SatSolver::solve`, `Err` once `abort` returns `true`.
+    fn solve_limited(
+        &mut self,
+        cnf: &Cnf,
+        abort: &mut dyn FnMut() -> bool,
+    ) -> Result<Option<Vec<bool>>, ()> {
+        self.assignment = vec![None; cnf.vars];
+        self.trail.clear();
+        self.occurs = vec![Vec::new(); 2 * cnf.vars];
+        for (i, clause) in cnf.clauses.iter().enumerate() {
+            if clause.is_empty() {
+                return Ok(None);
+            }
+            for &lit in clause {
+                self.occurs[lit_index(lit)].push(i);
+            }
+        }
+        for clause in &cnf.clauses {
+            if let [lit] = clause[..] {
+                if !self.propagate(cnf, lit) {
+                    return Ok(None);
+                }
+            }
+        }
+        if !self.search(cnf, abort)? {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.assignment.iter().map(|v| v.unwrap_or(false)).collect(),
+        ))
+    }
+}
+
+impl SatSolver for Dpll {
+    fn solve(&mut self, cnf: &Cnf) -> Option<Vec<bool>> {
+        self.solve_limited(cnf, &mut || false).ok()?
+    }
+}
+
+/// Solves with the embedded DPLL solver.
+pub fn solve_sat(sudoku: &Sudoku) -> Option<SolvedSudoku> {
+    solve_sat_with(sudoku, &mut Dpll::default())
+}
+
+/// Solves with any SAT solver.
+pub fn solve_sat_with<S: SatSolver>(sudoku: &Sudoku, solver: &mut S) -> Option<SolvedSudoku> {
+    decode(&solver.solve(&Cnf::from_sudoku(sudoku))?)
+}
+
+/// Like `solve_sat`, giving up once `abort` returns `true`, checked at
+/// every decision.
+pub(crate) fn solve_sat_limited(sudoku: &Sudoku, abort: &mut dyn FnMut() -> bool) -> SolveOutcome {
+    let cnf = Cnf::from_sudoku(sudoku);
+    match Dpll::default().solve_limited(&cnf, abort) {
+        Err(()) => SolveOutcome::Aborted,
+        Ok(model) => match model.as_deref().and_then(decode) {
+            Some(solution) => SolveOutcome::Solved(solution),
+            None => SolveOutcome::NoSolution,
+        },
+    }
+}
+
+/// The grid of a satisfying assignment.
+fn decode(model: &[bool]) -> Option<SolvedSudoku> {
+    let mut solution = Sudoku::new();
+    for x in 0..9 {
+        for y in 0..9 {
+            let value = (0..9)
+                .map(Value)
+                .find(|&v| model[var(x, y, v) as usize - 1])?;
+            solution.get_mut(x, y).unwrap().set(value);
+        }
+    }
+    SolvedSudoku::new(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_sat() {
+        for corpus in [
+            crate::Corpus::top1465_sample(),
+            crate::Corpus::seventeen_sample(),
+        ] {
+            for puzzle in corpus.puzzles.iter() {
+                let solution = solve_sat(puzzle).unwrap();
+                assert_eq!(
+                    solution.to_string(),
+                    crate::solve(puzzle).unwrap().to_string()
+                );
+            }
+        }
+
+        let mut sudoku = Sudoku::new();
+        sudoku.get_mut(0, 0).unwrap().set(Value(0));
+        sudoku.get_mut(0, 1).unwrap().set(Value(0));
+        assert!(solve_sat(&sudoku).is_none());
+    }
+
+    #[test]
+    fn test_dimacs() {
+        let cnf = Cnf::from_sudoku(&Sudoku::new());
+        let dimacs = cnf.to_dimacs();
+        assert!(dimacs.starts_with(&format!("p cnf 729 {}\n", cnf.clauses.len())));
+        assert_eq!(dimacs.lines().count(), cnf.clauses.len() + 1);
+    }
+}
@@ -0,0 +1,304 @@
+//! A search that runs a bounded number of nodes at a time, for callers
+//! that can't block until it finishes, such as a UI thread or a request
+//! with a deadline.
+//!
+//! It is the backtracking of `SolverConfig` with its stack kept between
+//! calls, propagating singles at each node and guessing at the cell with
+//! the fewest candidates, so it visits the same nodes as `solve`.
+use crate::solver::{Backtrack, SolverConfig};
+use crate::{SolveStats, Sudoku};
+
+#[derive(Clone, Copy, Debug)]
+pub enum SearchStatus {
+    /// The node budget ran out, call `step` again to continue.
+    Running,
+    /// Found a solution, call `step` again to look for the next one.
+    Solved(Sudoku),
+    /// There are no more solutions.
+    Exhausted,
+}
+
+/// A backtracking search that can be advanced a bounded number of nodes at
+/// a time. With the `serde` feature the state can be checkpointed and
+/// resumed later.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "checkpoint::Checkpoint", try_from = "checkpoint::Checkpoint")
+)]
+pub struct SearchState {
+    search: Backtrack,
+    /// A grid without empty cells that hasn't been reported yet.
+    complete: Option<Sudoku>,
+    nodes: u64,
+}
+
+impl SearchState {
+    pub fn new(sudoku: &Sudoku) -> Self {
+        let mut search = Backtrack::new(*sudoku);
+        let mut complete = None;
+        if search.current.is_some() && sudoku.0.iter().all(|cell| cell.is_final()) {
+            complete = search.current.take().map(|(sudoku, _)| sudoku);
+        }
+        Self {
+            search,
+            complete,
+            nodes: 0,
+        }
+    }
+
+    /// Number of nodes visited so far.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Visits at most `nodes` search nodes.
+    pub fn step(&mut self, nodes: usize) -> SearchStatus {
+        if let Some(sudoku) = self.complete.take() {
+            return SearchStatus::Solved(sudoku);
+        }
+        let mut budget = nodes;
+        let mut abort = || match budget.checked_sub(1) {
+            Some(left) => {
+                budget = left;
+                false
+            }
+            None => true,
+        };
+        let mut solution = None;
+        let mut found = |sudoku| {
+            solution = Some(sudoku);
+            false
+        };
+        let mut stats = SolveStats::default();
+        let config = SolverConfig::default();
+        self.search.run(&config, &mut found, &mut abort, &mut stats);
+        self.nodes += stats.nodes;
+        match solution {
+            Some(sudoku) => SearchStatus::Solved(sudoku),
+            None if self.search.is_finished() => SearchStatus::Exhausted,
+            None => SearchStatus::Running,
+        }
+    }
+}
+
+impl core::fmt::Debug for SearchState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SearchState")
+            .field("depth", &self.search.depth)
+            .field("complete", &self.complete)
+            .field("nodes", &self.nodes)
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod checkpoint {
+    use super::SearchState;
+    use crate::masks::UnitMasks;
+    use crate::solver::{Backtrack, Frame};
+    use crate::{Error, Sudoku};
+    use serde::{Deserialize, Serialize};
+    use std::convert::TryFrom;
+
+    /// A pending guess of the stack, the masks left out as they follow
+    /// from the grid.
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct Guess {
+        sudoku: Sudoku,
+        cell: usize,
+        untried: u16,
+    }
+
+    /// The stack of a `SearchState` as it is written out.
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct Checkpoint {
+        stack: Vec<Guess>,
+        current: Option<Sudoku>,
+        complete: Option<Sudoku>,
+        nodes: u64,
+    }
+
+    impl From<SearchState> for Checkpoint {
+        fn from(state: SearchState) -> Self {
+            let search = &state.search;
+            Self {
+                stack: search.stack[..search.depth]
+                    .iter()
+                    .map(|frame| Guess {
+                        sudoku: frame.sudoku,
+                        cell: frame.cell,
+                        untried: frame.untried,
+                    })
+                    .collect(),
+                current: search.current.map(|(sudoku, _)| sudoku),
+                complete: state.complete,
+                nodes: state.nodes,
+            }
+        }
+    }
+
+    /// Whether `grid` only narrows the candidates of the grid of `frame`
+    /// and gives its cell one of the digits already guessed.
+    fn follows(frame: &Frame, grid: &Sudoku) -> bool {
+        let cell = grid.0[frame.cell];
+        (0..81).all(|i| grid.0[i].0 & !frame.sudoku.0[i].0 == 0)
+            && cell.is_final()
+            && cell.0 & frame.untried == 0
+    }
+
+    impl TryFrom<Checkpoint> for SearchState {
+        type Error = Error;
+
+        /// Checks that the stack is one the search could have built, as
+        /// stepping trusts it.
+        fn try_from(checkpoint: Checkpoint) -> Result<Self, Error> {
+            let masks =
+                |sudoku: &Sudoku| UnitMasks::from_sudoku(sudoku).ok_or(Error::InvalidCheckpoint);
+            let mut search = Backtrack::new(Sudoku::new());
+            search.current = None;
+            if checkpoint.stack.len() > search.stack.len() {
+                return Err(Error::InvalidCheckpoint);
+            }
+            for (k, guess) in checkpoint.stack.iter().enumerate() {
+                let frame = Frame {
+                    sudoku: guess.sudoku,
+                    masks: masks(&guess.sudoku)?,
+                    cell: guess.cell,
+                    untried: guess.untried,
+                };
+                // the untried digits of an open cell, each still legal
+                let cell = guess
+                    .sudoku
+                    .0
+                    .get(guess.cell)
+                    .ok_or(Error::InvalidCheckpoint)?;
+                let legal = cell.0 & frame.masks.allowed(guess.cell / 9, guess.cell % 9);
+                if cell.is_final() || guess.untried & !legal != 0 {
+                    return Err(Error::InvalidCheckpoint);
+                }
+                if k > 0 && !follows(&search.stack[k - 1], &guess.sudoku) {
+                    return Err(Error::InvalidCheckpoint);
+                }
+                search.stack[k] = frame;
+            }
+            search.depth = checkpoint.stack.len();
+            if let Some(sudoku) = checkpoint.current {
+                let top = search.depth.checked_sub(1).map(|top| &search.stack[top]);
+                if top.is_some_and(|frame| !follows(frame, &sudoku)) {
+                    return Err(Error::InvalidCheckpoint);
+                }
+                search.current = Some((sudoku, masks(&sudoku)?));
+            }
+            if let Some(complete) = checkpoint.complete {
+                let full = complete.0.iter().all(|cell| cell.is_final());
+                if !full || masks(&complete).is_err() || !search.is_finished() {
+                    return Err(Error::InvalidCheckpoint);
+                }
+            }
+            Ok(Self {
+                search,
+                complete: checkpoint.complete,
+                nodes: checkpoint.nodes,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_state() {
+        let corpus = crate::Corpus::top1465_sample();
+        let sudoku = *corpus.puzzles.iter().next().unwrap();
+        let mut state = SearchState::new(&sudoku);
+        let mut polls = 0;
+        let solution = loop {
+            polls += 1;
+            match state.step(10) {
+                SearchStatus::Running => continue,
+                SearchStatus::Solved(solution) => break solution,
+                SearchStatus::Exhausted => panic!("no solution"),
+            }
+        };
+        assert!(polls > 1);
+        assert!(solution.valid());
+        // the same nodes as the solver up to the solution
+        let (expected, stats) = SolverConfig::default().solve_with_stats(&sudoku);
+        assert_eq!(solution, *expected.unwrap());
+        assert_eq!(state.nodes(), stats.nodes);
+        assert!(matches!(state.step(usize::MAX), SearchStatus::Exhausted));
+
+        let mut state = SearchState::new(&solution);
+        assert!(matches!(state.step(0), SearchStatus::Solved(_)));
+        assert!(matches!(state.step(1), SearchStatus::Exhausted));
+
+        // every solution of a puzzle with several, one at a time
+        let mut several = solution;
+        // the first two rows of this grid can be completed four ways
+        for cell in &mut several.0[..18] {
+            *cell = crate::Cell::new();
+        }
+        let mut state = SearchState::new(&several);
+        let mut solutions = Vec::new();
+        loop {
+            match state.step(1) {
+                SearchStatus::Running => {}
+                SearchStatus::Solved(solution) => solutions.push(solution),
+                SearchStatus::Exhausted => break,
+            }
+        }
+        assert_eq!(solutions.len(), 4);
+        let (_, stats) = SolverConfig::default().count_solutions_with_stats(&several, 10);
+        assert_eq!(state.nodes(), stats.nodes);
+        assert!(solutions.contains(&solution));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_search_state_serde() {
+        let mut state = SearchState::new(&Sudoku::new());
+        assert!(matches!(state.step(20), SearchStatus::Running));
+        let json = serde_json::to_string(&state).unwrap();
+        let mut resumed: SearchState = serde_json::from_str(&json).unwrap();
+        assert_eq!(resumed.nodes(), state.nodes());
+        match (state.step(1000), resumed.step(1000)) {
+            (SearchStatus::Solved(a), SearchStatus::Solved(b)) => {
+                assert_eq!(a.to_string(), b.to_string())
+            }
+            res => panic!("unexpected {:?}", res),
+        }
+
+        // checkpoints the search couldn't have written are refused
+        let mut state = SearchState::new(&Sudoku::new());
+        state.step(3);
+        let json: serde_json::Value = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["stack"].as_array().unwrap().len(), 3);
+        let refused = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut json = json.clone();
+            edit(&mut json);
+            serde_json::from_value::<SearchState>(json).is_err()
+        };
+        assert!(!refused(&|_| {}));
+        assert!(refused(&|json| json["stack"][0]["cell"] = 81.into()));
+        // the first guess is a filled cell of the second frame
+        let cell = json["stack"][0]["cell"].clone();
+        assert!(refused(&|json| json["stack"][1]["cell"] = cell.clone()));
+        assert!(refused(&|json| json["stack"][1]["untried"] = 0x1ff.into()));
+        assert!(refused(&|json| {
+            json["stack"][0]["sudoku"][0] = 1.into();
+            json["stack"][0]["sudoku"][1] = 1.into();
+        }));
+        assert!(refused(&|json| {
+            json["stack"].as_array_mut().unwrap().swap(0, 1);
+        }));
+        assert!(refused(&|json| {
+            json["current"] = json["stack"][0]["sudoku"].clone();
+        }));
+        let solution = serde_json::to_value(*crate::solve(&Sudoku::new()).unwrap()).unwrap();
+        assert!(refused(&|json| json["complete"] = solution.clone()));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{Cell, Coord, Sudoku, Value};
+use serde::de::{Error as _, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0 + 1)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let digit = u8::deserialize(deserializer)?;
+        Value::new(digit)
+            .map_err(|_| D::Error::invalid_value(Unexpected::Unsigned(digit.into()), &"a digit"))
+    }
+}
+
+/// Cells are serialized as their candidate bit mask.
+impl Serialize for Cell {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cell {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mask = u16::deserialize(deserializer)?;
+        if mask & !Cell::new().0 != 0 {
+            return Err(D::Error::invalid_value(
+                Unexpected::Unsigned(mask.into()),
+                &"a 9 bit candidate mask",
+            ));
+        }
+        Ok(Cell(mask))
+    }
+}
+
+impl Serialize for Coord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.row, self.col).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Coord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (row, col) = <(u8, u8)>::deserialize(deserializer)?;
+        if row >= 9 || col >= 9 {
+            return Err(D::Error::custom("coordinate out of range"));
+        }
+        Ok(Coord { row, col })
+    }
+}
+
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Sudoku {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cells = Vec::<Cell>::deserialize(deserializer)?;
+        if cells.len() != 81 {
+            return Err(D::Error::invalid_length(cells.len(), &"81 cells"));
+        }
+        let mut sudoku = Sudoku::new();
+        sudoku.0.copy_from_slice(&cells);
+        Ok(sudoku)
+    }
+}
@@ -0,0 +1,358 @@
+//! A small HTTP service over the library, one thread per connection.
+//!
+//! All endpoints take and return JSON, puzzles being lines of 81 cells:
+//!
+//! - `POST /solve` with `{"puzzle"}` returns `{"solution"}`.
+//! - `POST /generate` with `{"difficulty", "count", "seed", "symmetry"}`,
+//!   all optional, returns `{"puzzles"}`.
+//! - `POST /rate` with `{"puzzle"}` returns `{"tier", "score", "hardest",
+//!   "steps", "solved", "clues"}`.
+//!
+//! Errors are `{"error"}` with a 4xx status.
+use crate::rating::Bucket;
+use crate::{generate_batch_with, solve, Error, GenerateOptions, Sudoku};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Requests with larger bodies are refused.
+const MAX_BODY: usize = 64 * 1024;
+
+/// Requests with a longer request line are refused.
+const MAX_LINE: usize = 8 * 1024;
+
+/// Requests with more bytes of headers are refused.
+const MAX_HEADERS: usize = 32 * 1024;
+
+/// Most puzzles a single `/generate` request may ask for.
+const MAX_COUNT: usize = 100;
+
+pub struct Server {
+    listener: TcpListener,
+    timeout: Duration,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// How long a read from or a write to a client may take before the
+    /// connection is dropped, 10 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves requests until accepting a connection fails.
+    pub fn run(self) -> Result<(), Error> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            stream.set_read_timeout(Some(self.timeout))?;
+            stream.set_write_timeout(Some(self.timeout))?;
+            std::thread::spawn(move || {
+                // the client has gone away, there's no one to tell
+                let _ = handle(stream);
+            });
+        }
+        Ok(())
+    }
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::to_string(&body).expect("responses serialize; qed"),
+        }
+    }
+
+    fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message.to_string() }).to_string(),
+        }
+    }
+}
+
+fn handle(stream: TcpStream) -> Result<(), Error> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader) {
+        Ok(request) => route(&request.method, &request.path, &request.body),
+        Err(response) => response,
+    };
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        431 => "Request Header Fields Too Large",
+        _ => "Unprocessable Entity",
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+/// The request, or the response if it is malformed.
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let malformed = |_| Response::error(400, "malformed request");
+    let mut line = String::new();
+    if read_line(reader, MAX_LINE, &mut line).map_err(malformed)? > MAX_LINE {
+        return Err(Response::error(414, "request line too long"));
+    }
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(Response::error(400, "malformed request line")),
+    };
+    let mut length = 0;
+    let mut left = MAX_HEADERS;
+    loop {
+        let mut header = String::new();
+        let read = read_line(reader, left, &mut header).map_err(malformed)?;
+        if read > left {
+            return Err(Response::error(431, "headers too large"));
+        }
+        left -= read;
+        if read == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| Response::error(400, "invalid Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Response::error(413, "body too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(malformed)?;
+    Ok(Request { method, path, body })
+}
+
+/// Reads a line like `BufRead::read_line`, but at most one byte more than
+/// `limit`, so that a longer line shows as more than `limit` bytes read.
+fn read_line(reader: &mut impl BufRead, limit: usize, line: &mut String) -> std::io::Result<usize> {
+    reader.take(limit as u64 + 1).read_line(line)
+}
+
+#[derive(Deserialize)]
+struct PuzzleRequest {
+    puzzle: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct GenerateRequest {
+    difficulty: Option<String>,
+    count: Option<usize>,
+    seed: Option<u64>,
+    symmetry: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RateResponse {
+    tier: &'static str,
+    score: f32,
+    hardest: Option<&'static str>,
+    steps: usize,
+    solved: bool,
+    clues: usize,
+}
+
+fn route(method: &str, path: &str, body: &[u8]) -> Response {
+    if !matches!(path, "/solve" | "/generate" | "/rate") {
+        return Response::error(404, "no such endpoint");
+    }
+    if method != "POST" {
+        return Response::error(405, "use POST");
+    }
+    let result = match path {
+        "/solve" => parse_puzzle(body).and_then(|puzzle| match solve(&puzzle) {
+            Some(solution) => Ok(Response::ok(
+                serde_json::json!({ "solution": solution.to_line() }),
+            )),
+            None => Err(Response::error(422, "no solution")),
+        }),
+        "/rate" => parse_puzzle(body).map(|puzzle| {
+            let rating = puzzle.rate();
+            Response::ok(RateResponse {
+                tier: rating.tier.name(),
+                score: rating.score,
+                hardest: rating.hardest.map(|technique| technique.id()),
+                steps: rating.steps,
+                solved: rating.solved,
+                clues: puzzle.into_iter().count(),
+            })
+        }),
+        _ => generate(body),
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+fn parse_puzzle(body: &[u8]) -> Result<Sudoku, Response> {
+    let request: PuzzleRequest =
+        serde_json::from_slice(body).map_err(|err| Response::error(400, err))?;
+    request
+        .puzzle
+        .parse()
+        .map_err(|err: Error| Response::error(422, err))
+}
+
+fn generate(body: &[u8]) -> Result<Response, Response> {
+    let request: GenerateRequest = if body.is_empty() {
+        GenerateRequest::default()
+    } else {
+        serde_json::from_slice(body).map_err(|err| Response::error(400, err))?
+    };
+    let unknown = |err: Error| Response::error(422, err);
+    let difficulty = match request.difficulty.as_deref() {
+        None => Bucket::Medium,
+        Some(name) => name.parse().map_err(unknown)?,
+    };
+    let symmetry = match request.symmetry.as_deref() {
+        None => None,
+        Some(name) => Some(name.parse().map_err(unknown)?),
+    };
+    let count = request.count.unwrap_or(1);
+    if count > MAX_COUNT {
+        return Err(Response::error(
+            422,
+            format!("at most {} puzzles at once", MAX_COUNT),
+        ));
+    }
+    let options = GenerateOptions {
+        symmetry,
+        seed: request.seed,
+        ..GenerateOptions::default()
+    };
+    let puzzles: Vec<String> = generate_batch_with(count, difficulty, &options)
+        .iter()
+        .map(Sudoku::to_line)
+        .collect();
+    Ok(Response::ok(serde_json::json!({ "puzzles": puzzles })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const LINE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    fn post(addr: SocketAddr, path: &str, body: &str) -> String {
+        send(
+            addr,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: test\r\nContent-Length: {}\r\n\r\n{}",
+                path,
+                body.len(),
+                body
+            ),
+        )
+    }
+
+    fn send(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_server() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+
+        let body = format!("{{\"puzzle\":\"{}\"}}", LINE);
+        let solved = post(addr, "/solve", &body);
+        assert!(solved.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(solved.ends_with("{\"solution\":\"483921657967345821251876493548132976729564138136798245372689514814253769695417382\"}"));
+
+        let rated = post(addr, "/rate", &body);
+        assert!(rated.contains("{\"tier\":\"easy\","), "{}", rated);
+        assert!(rated.ends_with("\"clues\":32}"));
+
+        let generated = post(
+            addr,
+            "/generate",
+            "{\"difficulty\":\"easy\",\"count\":2,\"seed\":1}",
+        );
+        let json = generated.split("\r\n\r\n").nth(1).unwrap();
+        let json: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(json["puzzles"].as_array().unwrap().len(), 2);
+
+        assert!(post(addr, "/solve", "{}").starts_with("HTTP/1.1 400"));
+        assert!(post(addr, "/generate", "{\"count\":1000}").starts_with("HTTP/1.1 422"));
+        assert!(post(addr, "/nope", "").starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .timeout(Duration::from_millis(100));
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+
+        let path = "/".repeat(MAX_LINE);
+        let long = send(addr, &format!("POST {} HTTP/1.1\r\n\r\n", path));
+        assert!(long.starts_with("HTTP/1.1 414"), "{}", long);
+        let header = format!("X-Padding: {}\r\n", "x".repeat(1000));
+        let headers = send(
+            addr,
+            &format!("POST /solve HTTP/1.1\r\n{}\r\n", header.repeat(33)),
+        );
+        assert!(headers.starts_with("HTTP/1.1 431"), "{}", headers);
+        let ok = send(
+            addr,
+            &format!("POST /nope HTTP/1.1\r\n{}\r\n", header.repeat(30)),
+        );
+        assert!(ok.starts_with("HTTP/1.1 404"), "{}", ok);
+
+        // a client that never finishes its request is dropped
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"POST /solve HTTP/1.1\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+    }
+}
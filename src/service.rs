@@ -0,0 +1,190 @@
+//! Background puzzle generation on a pool of worker threads.
+use crate::generator::{reclue, ReclueOptions};
+use crate::rating::{se_rating, RatedPuzzle};
+use crate::Sudoku;
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::SeedableRng;
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GenerationRequest {
+    /// Identifies the request in events and for cancellation, should be
+    /// unique.
+    pub id: u64,
+    /// Range of acceptable Sudoku Explainer ratings.
+    pub min_rating: f32,
+    pub max_rating: f32,
+    /// Number of puzzles to generate.
+    pub count: usize,
+    /// Seed of the first attempt, each attempt uses the next seed.
+    pub seed: u64,
+    /// Number of attempts after which the request gives up.
+    pub attempts: usize,
+}
+
+impl GenerationRequest {
+    pub fn new(id: u64, min_rating: f32, max_rating: f32) -> Self {
+        Self {
+            id,
+            min_rating,
+            max_rating,
+            count: 1,
+            seed: id,
+            attempts: 10_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum GenerationEvent {
+    Puzzle {
+        id: u64,
+        puzzle: RatedPuzzle,
+    },
+    /// The request finished, was cancelled or ran out of attempts.
+    Done {
+        id: u64,
+        generated: usize,
+    },
+}
+
+/// A pool of generator threads. The workers exit once the service and all
+/// senders returned by `sender` are dropped.
+pub struct GenerationService {
+    requests: Sender<GenerationRequest>,
+    events: Receiver<GenerationEvent>,
+    cancelled: Arc<Mutex<HashSet<u64>>>,
+}
+
+impl GenerationService {
+    /// Starts `threads` workers, at least one.
+    pub fn new(threads: usize) -> Self {
+        let (requests, queue) = channel::<GenerationRequest>();
+        let (events_tx, events) = channel();
+        let queue = Arc::new(Mutex::new(queue));
+        let cancelled = Arc::new(Mutex::new(HashSet::new()));
+        for _ in 0..threads.max(1) {
+            let queue = queue.clone();
+            let events = events_tx.clone();
+            let cancelled = cancelled.clone();
+            std::thread::spawn(move || loop {
+                let request = match queue.lock().expect("worker panicked").recv() {
+                    Ok(request) => request,
+                    Err(_) => return,
+                };
+                let generated = run(&request, &events, &cancelled);
+                cancelled
+                    .lock()
+                    .expect("worker panicked")
+                    .remove(&request.id);
+                let done = GenerationEvent::Done {
+                    id: request.id,
+                    generated,
+                };
+                if events.send(done).is_err() {
+                    return;
+                }
+            });
+        }
+        Self {
+            requests,
+            events,
+            cancelled,
+        }
+    }
+
+    /// A channel for submitting requests from other threads.
+    pub fn sender(&self) -> Sender<GenerationRequest> {
+        self.requests.clone()
+    }
+
+    pub fn submit(&self, request: GenerationRequest) {
+        self.requests
+            .send(request)
+            .expect("workers only exit after the service is dropped; qed");
+    }
+
+    /// Stops working on a request, puzzles generated so far are still
+    /// delivered.
+    pub fn cancel(&self, id: u64) {
+        self.cancelled.lock().expect("worker panicked").insert(id);
+    }
+
+    pub fn events(&self) -> &Receiver<GenerationEvent> {
+        &self.events
+    }
+}
+
+fn run(
+    request: &GenerationRequest,
+    events: &Sender<GenerationEvent>,
+    cancelled: &Mutex<HashSet<u64>>,
+) -> usize {
+    let mut generated = 0;
+    for attempt in 0..request.attempts {
+        if generated == request.count {
+            break;
+        }
+        if cancelled
+            .lock()
+            .expect("worker panicked")
+            .contains(&request.id)
+        {
+            break;
+        }
+        let seed = request.seed.wrapping_add(attempt as u64);
+        let solution = Sudoku::fill_complete(&mut Xoshiro256PlusPlus::seed_from_u64(seed));
+        let puzzle = reclue(&solution, seed, &ReclueOptions::default());
+        let rating = se_rating(&puzzle);
+        if rating.er < request.min_rating || rating.er > request.max_rating {
+            continue;
+        }
+        let puzzle = RatedPuzzle { puzzle, rating };
+        if events
+            .send(GenerationEvent::Puzzle {
+                id: request.id,
+                puzzle,
+            })
+            .is_err()
+        {
+            break;
+        }
+        generated += 1;
+    }
+    generated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rating::SE_UNSOLVED;
+
+    #[test]
+    fn test_generation_service() {
+        let service = GenerationService::new(2);
+        let mut request = GenerationRequest::new(1, 0.0, SE_UNSOLVED);
+        request.count = 2;
+        service.submit(request);
+        let impossible = GenerationRequest::new(2, 20.0, 30.0);
+        service.sender().send(impossible).unwrap();
+        service.cancel(2);
+
+        let mut puzzles = 0;
+        let mut done = Vec::new();
+        while done.len() < 2 {
+            match service.events().recv().unwrap() {
+                GenerationEvent::Puzzle { id, puzzle } => {
+                    assert_eq!(id, 1);
+                    assert!(puzzle.puzzle.iter_empty().count() > 0);
+                    puzzles += 1;
+                }
+                GenerationEvent::Done { id, generated } => done.push((id, generated)),
+            }
+        }
+        done.sort_unstable();
+        assert_eq!(puzzles, 2);
+        assert_eq!(done, vec![(1, 2), (2, 0)]);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::rating::{se_rating, RatedPuzzle, SeRating};
+use crate::Sudoku;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+#[derive(Clone, Debug, Default)]
+pub struct PuzzleSet {
+    puzzles: Vec<Sudoku>,
+    /// Cached ratings, computed on demand.
+    ratings: Vec<Option<SeRating>>,
+}
+
+impl PuzzleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, sudoku: Sudoku) {
+        self.puzzles.push(sudoku);
+        self.ratings.push(None);
+    }
+
+    pub fn push_rated(&mut self, puzzle: RatedPuzzle) {
+        self.puzzles.push(puzzle.puzzle);
+        self.ratings.push(Some(puzzle.rating));
+    }
+
+    pub fn get(&self, i: usize) -> Option<&Sudoku> {
+        self.puzzles.get(i)
+    }
+
+    pub fn len(&self) -> usize {
+        self.puzzles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.puzzles.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Sudoku> {
+        self.puzzles.iter()
+    }
+
+    /// A puzzle picked at random, `None` if the set is empty.
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&Sudoku> {
+        self.puzzles.choose(rng)
+    }
+
+    /// The cached rating of a puzzle, if it has been rated.
+    pub fn rating(&self, i: usize) -> Option<SeRating> {
+        self.ratings.get(i).copied().flatten()
+    }
+
+    /// Rates the puzzles without a cached rating, spread over the available
+    /// cores.
+    pub fn rate_missing(&mut self) {
+        let missing: Vec<usize> = (0..self.len())
+            .filter(|&i| self.ratings[i].is_none())
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = missing.len().div_ceil(threads);
+        let puzzles = &self.puzzles;
+        let rated: Vec<(usize, SeRating)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = missing
+                .chunks(chunk)
+                .map(|indices| {
+                    scope.spawn(move || {
+                        indices
+                            .iter()
+                            .map(|&i| (i, se_rating(&puzzles[i])))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("rating doesn't panic; qed"))
+                .collect()
+        });
+        for (i, rating) in rated {
+            self.ratings[i] = Some(rating);
+        }
+    }
+
+    /// Puzzle indices from easiest to hardest, ties keeping their order.
+    fn difficulty_order(&mut self) -> Vec<usize> {
+        self.rate_missing();
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        let ratings = &self.ratings;
+        let er = |i: usize| ratings[i].map_or(0.0, |r| r.er);
+        order.sort_by(|&a, &b| er(a).total_cmp(&er(b)));
+        order
+    }
+
+    /// Orders the puzzles from easiest to hardest.
+    pub fn sort_by_difficulty(&mut self) {
+        let order = self.difficulty_order();
+        self.puzzles = order.iter().map(|&i| self.puzzles[i]).collect();
+        self.ratings = order.iter().map(|&i| self.ratings[i]).collect();
+    }
+
+    /// The position of each puzzle in difficulty order, `0` being the
+    /// easiest.
+    pub fn rank(&mut self) -> Vec<usize> {
+        let mut ranks = vec![0; self.len()];
+        for (rank, i) in self.difficulty_order().into_iter().enumerate() {
+            ranks[i] = rank;
+        }
+        ranks
+    }
+
+    /// The rating below or at which `p` percent of the puzzles lie.
+    pub fn percentile(&mut self, p: f32) -> Option<f32> {
+        let order = self.difficulty_order();
+        if order.is_empty() {
+            return None;
+        }
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * order.len() as f32).ceil() as usize;
+        let i = order[rank.saturating_sub(1)];
+        self.rating(i).map(|rating| rating.er)
+    }
+
+    /// Percentage of puzzles rated at most `score`.
+    pub fn percentile_of(&mut self, score: f32) -> f32 {
+        self.rate_missing();
+        if self.is_empty() {
+            return 0.0;
+        }
+        let below = (0..self.len())
+            .filter(|&i| self.rating(i).is_some_and(|r| r.er <= score))
+            .count();
+        below as f32 * 100.0 / self.len() as f32
+    }
+}
+
+impl std::iter::FromIterator<Sudoku> for PuzzleSet {
+    fn from_iter<I: IntoIterator<Item = Sudoku>>(iter: I) -> Self {
+        let puzzles: Vec<Sudoku> = iter.into_iter().collect();
+        Self {
+            ratings: vec![None; puzzles.len()],
+            puzzles,
+        }
+    }
+}
+
+impl IntoIterator for PuzzleSet {
+    type Item = Sudoku;
+    type IntoIter = std::vec::IntoIter<Sudoku>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.puzzles.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PuzzleSet {
+    type Item = &'a Sudoku;
+    type IntoIter = std::slice::Iter<'a, Sudoku>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.puzzles.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_by_difficulty() {
+        let easy: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let mut set: PuzzleSet = vec![Sudoku::new(), easy].into_iter().collect();
+        assert!(set.rating(0).is_none());
+        assert_eq!(set.rank(), vec![1, 0]);
+        assert!(set.rating(0).is_some());
+        assert_eq!(set.percentile(50.0), Some(set.rating(1).unwrap().er));
+        assert_eq!(set.percentile_of(5.0), 50.0);
+
+        set.sort_by_difficulty();
+        assert_eq!(set.get(0).unwrap().to_string(), easy.to_string());
+        assert!(!set.rating(1).unwrap().solved);
+    }
+}
@@ -0,0 +1,133 @@
+//! Short URL-safe codes for puzzles.
+//!
+//! A code is the unpadded URL-safe base64 of a version byte, the 81 bit
+//! mask of the givens in row-major order, least significant bit first, and
+//! the digits of the givens in the same order, four bits each with the
+//! first one in the high bits.
+use crate::{Error, Sudoku, Value};
+
+const VERSION: u8 = 1;
+const MASK_BYTES: usize = 11;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl Sudoku {
+    /// Encodes the givens, a typical puzzle taking about 35 characters.
+    pub fn to_share_code(&self) -> String {
+        let mut bytes = vec![VERSION];
+        let mut mask = [0u8; MASK_BYTES];
+        let mut digits = Vec::new();
+        for (i, cell) in self.0.iter().enumerate() {
+            if let Some(value) = cell.value() {
+                mask[i / 8] |= 1 << (i % 8);
+                digits.push(value.0);
+            }
+        }
+        bytes.extend_from_slice(&mask);
+        bytes.extend(
+            digits
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair.get(1).unwrap_or(&0)),
+        );
+        encode(&bytes)
+    }
+
+    /// Decodes a code of `to_share_code`, failing with
+    /// `Error::UnsupportedVersion` for codes of later releases.
+    pub fn from_share_code(code: &str) -> Result<Sudoku, Error> {
+        let bytes = decode(code).ok_or(Error::InvalidShareCode)?;
+        match bytes.first() {
+            Some(&VERSION) => {}
+            Some(&version) => return Err(Error::UnsupportedVersion(version as u32)),
+            None => return Err(Error::InvalidShareCode),
+        }
+        if bytes.len() < 1 + MASK_BYTES {
+            return Err(Error::InvalidShareCode);
+        }
+        let (mask, digits) = bytes[1..].split_at(MASK_BYTES);
+        let givens: Vec<usize> = (0..81)
+            .filter(|&i| mask[i / 8] & 1 << (i % 8) != 0)
+            .collect();
+        if mask[MASK_BYTES - 1] >> 1 != 0 || digits.len() != givens.len().div_ceil(2) {
+            return Err(Error::InvalidShareCode);
+        }
+        let mut sudoku = Sudoku::new();
+        for (n, &i) in givens.iter().enumerate() {
+            let digit = digits[n / 2] >> (4 - n % 2 * 4) & 0xf;
+            if digit > 8 {
+                return Err(Error::InvalidShareCode);
+            }
+            sudoku.0[i].set(Value(digit));
+        }
+        Ok(sudoku)
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            code.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    code
+}
+
+fn decode(code: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(code.len() * 3 / 4);
+    for chunk in code.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let sextet = ALPHABET.iter().position(|&a| a == c)? as u32;
+            n |= sextet << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_code() {
+        let corpus = crate::Corpus::seventeen_sample();
+        for puzzle in corpus.puzzles.iter() {
+            let code = puzzle.to_share_code();
+            assert!(code.len() <= 32, "{}", code);
+            assert_eq!(Sudoku::from_share_code(&code).unwrap(), *puzzle);
+        }
+        let empty = Sudoku::new().to_share_code();
+        assert_eq!(Sudoku::from_share_code(&empty).unwrap(), Sudoku::new());
+        let full = crate::solve(&Sudoku::new()).unwrap();
+        assert_eq!(
+            Sudoku::from_share_code(&full.to_share_code()).unwrap(),
+            *full
+        );
+
+        let code = corpus.puzzles.get(0).unwrap().to_share_code();
+        assert!(code.bytes().all(|c| ALPHABET.contains(&c)));
+        assert!(matches!(
+            Sudoku::from_share_code(&code[..code.len() - 2]),
+            Err(Error::InvalidShareCode)
+        ));
+        assert!(matches!(
+            Sudoku::from_share_code("not a code"),
+            Err(Error::InvalidShareCode)
+        ));
+        let later = format!("Ag{}", &code[2..]);
+        assert!(matches!(
+            Sudoku::from_share_code(&later),
+            Err(Error::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,123 @@
+//! Solving without blocking an async runtime. The search runs on a thread
+//! of its own and is awaited as a future, which works with any executor.
+use crate::{SolveOptions, SolveOutcome, SolverConfig, Sudoku};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Default)]
+struct Shared {
+    outcome: Option<SolveOutcome>,
+    waker: Option<Waker>,
+}
+
+/// A search in progress. Dropping the handle cancels the search, so a
+/// runtime dropping the future on timeout or shutdown frees the thread.
+pub struct SolveHandle {
+    shared: Arc<Mutex<Shared>>,
+    options: SolveOptions,
+}
+
+impl SolveHandle {
+    /// Stops the search, the future then resolves to
+    /// `SolveOutcome::Aborted` unless it finished first.
+    pub fn cancel(&self) {
+        self.options.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Future for SolveHandle {
+    type Output = SolveOutcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SolveOutcome> {
+        let mut shared = self.shared.lock().expect("the solver doesn't panic; qed");
+        match shared.outcome {
+            Some(outcome) => Poll::Ready(outcome),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for SolveHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+pub fn solve_async(sudoku: &Sudoku) -> SolveHandle {
+    SolverConfig::default().solve_async(sudoku, SolveOptions::default())
+}
+
+impl SolverConfig {
+    /// Starts `solve_with_options` on a new thread. The `cancel` flag of
+    /// `options` is the one `SolveHandle::cancel` sets.
+    pub fn solve_async(&self, sudoku: &Sudoku, options: SolveOptions) -> SolveHandle {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let handle = SolveHandle {
+            shared: shared.clone(),
+            options: options.clone(),
+        };
+        let (config, sudoku) = (*self, *sudoku);
+        std::thread::spawn(move || {
+            let outcome = config.solve_with_options(&sudoku, &options);
+            let mut shared = shared.lock().expect("polling doesn't panic; qed");
+            shared.outcome = Some(outcome);
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+        handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_async() {
+        let puzzle: Sudoku =
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+                .parse()
+                .unwrap();
+        match block_on(solve_async(&puzzle)) {
+            SolveOutcome::Solved(solution) => assert!(solution.is_completion_of(&puzzle)),
+            outcome => panic!("unexpected {:?}", outcome),
+        }
+
+        // cancelling resolves the future, with whatever finished first
+        let handle = SolverConfig::default().solve_async(&puzzle, SolveOptions::default());
+        handle.cancel();
+        assert!(matches!(
+            block_on(handle),
+            SolveOutcome::Aborted | SolveOutcome::Solved(_)
+        ));
+    }
+}
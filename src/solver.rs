@@ -0,0 +1,1099 @@
+#[cfg(feature = "std")]
+use crate::logic::{LogicState, LogicalSolver, SolveStep};
+use crate::masks::UnitMasks;
+use crate::verified::SolvedSudoku;
+#[cfg(feature = "std")]
+use crate::{peers, Coord};
+use crate::{Cell, Sudoku, Unit, Value};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::sync::mpsc::Sender;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::Instant;
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+#[cfg(feature = "std")]
+#[derive(Debug, Error)]
+pub enum SolveUniqueError {
+    #[error("puzzle has no solution")]
+    NoSolution,
+    #[error("puzzle has multiple solutions")]
+    MultipleSolutions(Box<Sudoku>, Box<Sudoku>),
+}
+
+/// Limits for a search, see `SolverConfig::solve_with_options`.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct SolveOptions {
+    pub timeout: Option<Duration>,
+    /// Number of search nodes, each one a propagation after a guess, a
+    /// decision of the SAT solver, a column covered by dancing links or a
+    /// step of the techniques.
+    pub max_nodes: Option<u64>,
+    /// Set from another thread to stop the search.
+    pub cancel: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "std")]
+impl SolveOptions {
+    /// A check for the limits to call at every node of a search, which
+    /// counts the nodes and returns `true` once a limit is hit. The timeout
+    /// runs from this call.
+    pub(crate) fn limits(&self) -> impl FnMut() -> bool + '_ {
+        let start = Instant::now();
+        let mut nodes = 0;
+        move || {
+            nodes += 1;
+            self.max_nodes.is_some_and(|max| nodes > max)
+                || self
+                    .timeout
+                    .is_some_and(|timeout| start.elapsed() > timeout)
+                || self.cancel.load(Ordering::Relaxed)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub enum SolveOutcome {
+    Solved(SolvedSudoku),
+    NoSolution,
+    /// The search stopped early because of a timeout, the node budget or
+    /// cancellation.
+    Aborted,
+}
+
+/// Which empty cell the backtracking solver guesses at next.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Heuristic {
+    /// The first one in row-major order.
+    FirstEmpty,
+    /// The one with the fewest candidates left.
+    MinimumRemaining,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverConfig {
+    pub heuristic: Heuristic,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            heuristic: Heuristic::MinimumRemaining,
+        }
+    }
+}
+
+impl SolverConfig {
+    pub fn solve(&self, sudoku: &Sudoku) -> Option<SolvedSudoku> {
+        let mut solution = None;
+        if consistent(sudoku) {
+            let mut found = |sudoku| {
+                solution = Some(sudoku);
+                false
+            };
+            self.backtrack(
+                *sudoku,
+                &mut found,
+                &mut || false,
+                &mut SolveStats::default(),
+            );
+        }
+        solution.map(SolvedSudoku::from_solver)
+    }
+
+    /// Like `solve`, also counting the work done.
+    #[cfg(feature = "std")]
+    pub fn solve_with_stats(&self, sudoku: &Sudoku) -> (Option<SolvedSudoku>, SolveStats) {
+        let start = Instant::now();
+        let mut stats = SolveStats::default();
+        let mut solution = None;
+        if consistent(sudoku) {
+            let mut found = |sudoku| {
+                solution = Some(sudoku);
+                false
+            };
+            self.backtrack(*sudoku, &mut found, &mut || false, &mut stats);
+        }
+        stats.duration = start.elapsed();
+        (solution.map(SolvedSudoku::from_solver), stats)
+    }
+
+    /// Like `count_solutions`, also counting the work done.
+    #[cfg(feature = "std")]
+    pub fn count_solutions_with_stats(&self, sudoku: &Sudoku, limit: usize) -> (usize, SolveStats) {
+        let start = Instant::now();
+        let mut stats = SolveStats::default();
+        let mut count = 0;
+        if limit > 0 && consistent(sudoku) {
+            let mut found = |_| {
+                count += 1;
+                count < limit
+            };
+            self.backtrack(*sudoku, &mut found, &mut || false, &mut stats);
+        }
+        stats.duration = start.elapsed();
+        (count, stats)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn solve_unique(&self, sudoku: &Sudoku) -> Result<SolvedSudoku, SolveUniqueError> {
+        let mut solutions = self.solutions(sudoku, 2);
+        match (solutions.pop(), solutions.pop()) {
+            (Some(second), Some(first)) => Err(SolveUniqueError::MultipleSolutions(
+                Box::new(first),
+                Box::new(second),
+            )),
+            (Some(solution), None) => Ok(SolvedSudoku::from_solver(solution)),
+            _ => Err(SolveUniqueError::NoSolution),
+        }
+    }
+
+    /// Enumerates up to `limit` solutions.
+    #[cfg(feature = "std")]
+    pub fn solutions(&self, sudoku: &Sudoku, limit: usize) -> Vec<Sudoku> {
+        let mut solutions = Vec::new();
+        if limit > 0 && consistent(sudoku) {
+            let mut found = |solution| {
+                solutions.push(solution);
+                solutions.len() < limit
+            };
+            self.backtrack(
+                *sudoku,
+                &mut found,
+                &mut || false,
+                &mut SolveStats::default(),
+            );
+        }
+        solutions
+    }
+
+    /// Counts solutions, stopping once `limit` are found.
+    pub fn count_solutions(&self, sudoku: &Sudoku, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 && consistent(sudoku) {
+            let mut found = |_| {
+                count += 1;
+                count < limit
+            };
+            self.backtrack(
+                *sudoku,
+                &mut found,
+                &mut || false,
+                &mut SolveStats::default(),
+            );
+        }
+        count
+    }
+
+    /// Like `solve`, giving up once `options` say so.
+    #[cfg(feature = "std")]
+    pub fn solve_with_options(&self, sudoku: &Sudoku, options: &SolveOptions) -> SolveOutcome {
+        self.solve_limited(sudoku, options, &mut SolveStats::default())
+    }
+
+    /// `solve_with_options` counting the work done in `stats`.
+    #[cfg(feature = "std")]
+    pub(crate) fn solve_limited(
+        &self,
+        sudoku: &Sudoku,
+        options: &SolveOptions,
+        stats: &mut SolveStats,
+    ) -> SolveOutcome {
+        if !consistent(sudoku) {
+            return SolveOutcome::NoSolution;
+        }
+        let start = Instant::now();
+        let mut abort = options.limits();
+        let mut solution = None;
+        let mut found = |sudoku| {
+            solution = Some(sudoku);
+            false
+        };
+        let finished = self.backtrack(*sudoku, &mut found, &mut abort, stats);
+        stats.duration = start.elapsed();
+        if !finished {
+            return SolveOutcome::Aborted;
+        }
+        match solution {
+            Some(solution) => SolveOutcome::Solved(SolvedSudoku::from_solver(solution)),
+            None => SolveOutcome::NoSolution,
+        }
+    }
+
+    /// `solve_with_stats` for each puzzle, the puzzles spread over the
+    /// available cores. The results are in the order of the puzzles.
+    #[cfg(feature = "std")]
+    pub fn solve_batch(&self, puzzles: impl IntoIterator<Item = Sudoku>) -> Vec<SolveResult> {
+        let puzzles: Vec<Sudoku> = puzzles.into_iter().collect();
+        par_map(&puzzles, |&puzzle| {
+            let (solution, stats) = self.solve_with_stats(&puzzle);
+            SolveResult {
+                puzzle,
+                solution,
+                stats,
+            }
+        })
+    }
+
+    /// Like `solve`, spreading the branches near the root over the rayon
+    /// thread pool. Finds the same solution as `solve`: once a branch has a
+    /// solution, the branches after it are aborted.
+    #[cfg(feature = "rayon")]
+    pub fn solve_parallel(&self, sudoku: &Sudoku) -> Option<SolvedSudoku> {
+        use rayon::prelude::*;
+
+        if !consistent(sudoku) {
+            return None;
+        }
+        let threads = rayon::current_num_threads();
+        // expand the tree breadth first, keeping the depth first order
+        let mut frontier = vec![*sudoku];
+        let mut expanded = true;
+        while expanded && frontier.len() < threads {
+            expanded = false;
+            let mut next = Vec::new();
+            for mut node in frontier {
+                if !propagate(&mut node) {
+                    continue;
+                }
+                match self.choose(&node) {
+                    Some(i) => {
+                        expanded = true;
+                        for value in node.0[i].values() {
+                            let mut child = node;
+                            child.0[i].set(value);
+                            next.push(child);
+                        }
+                    }
+                    None => next.push(node),
+                }
+            }
+            frontier = next;
+        }
+        // the first branch with a solution
+        let first = AtomicUsize::new(usize::MAX);
+        let solutions: Vec<Option<Sudoku>> = frontier
+            .par_iter()
+            .enumerate()
+            .map(|(k, node)| {
+                let mut solution = None;
+                let mut found = |sudoku| {
+                    solution = Some(sudoku);
+                    false
+                };
+                let mut abort = || first.load(Ordering::Relaxed) < k;
+                if consistent(node) {
+                    self.backtrack(*node, &mut found, &mut abort, &mut SolveStats::default());
+                }
+                if solution.is_some() {
+                    first.fetch_min(k, Ordering::Relaxed);
+                }
+                solution
+            })
+            .collect();
+        solutions
+            .into_iter()
+            .flatten()
+            .next()
+            .map(SolvedSudoku::from_solver)
+    }
+
+    /// The cell to guess at, `None` if every cell has a value.
+    fn choose(&self, sudoku: &Sudoku) -> Option<usize> {
+        let mut empty = (0..81).filter(|&i| !sudoku.0[i].is_final());
+        match self.heuristic {
+            Heuristic::FirstEmpty => empty.next(),
+            Heuristic::MinimumRemaining => empty.min_by_key(|&i| sudoku.0[i].len()),
+        }
+    }
+
+    /// Depth first search with an explicit stack, see [`Backtrack`]. Stops
+    /// when `found` returns `false`, or when `abort` is set, checked at
+    /// every node, then returning `false`.
+    fn backtrack(
+        &self,
+        sudoku: Sudoku,
+        found: &mut dyn FnMut(Sudoku) -> bool,
+        abort: &mut dyn FnMut() -> bool,
+        stats: &mut SolveStats,
+    ) -> bool {
+        Backtrack::new(sudoku).run(self, found, abort, stats)
+    }
+}
+
+/// The state of a depth first search between two nodes, so that it can
+/// stop and pick up again. A frame holds the grid and its unit masks
+/// before a guess, so undoing the guess and everything propagated from it
+/// is a copy, and the masks are updated with the guessed cell alone
+/// instead of rebuilt from the grid.
+#[derive(Clone, Copy)]
+pub(crate) struct Backtrack {
+    /// Every guess gives one more cell a value, so 81 frames suffice.
+    pub(crate) stack: [Frame; 81],
+    pub(crate) depth: usize,
+    /// The grid of the next node and its masks, `None` if the next node
+    /// is the next guess on the stack.
+    pub(crate) current: Option<(Sudoku, UnitMasks)>,
+}
+
+impl Backtrack {
+    /// A search of the grid, finished at once if a unit repeats a digit.
+    pub(crate) fn new(sudoku: Sudoku) -> Self {
+        let frame = Frame {
+            sudoku,
+            masks: UnitMasks::default(),
+            cell: 0,
+            untried: 0,
+        };
+        Self {
+            stack: [frame; 81],
+            depth: 0,
+            current: UnitMasks::from_sudoku(&sudoku).map(|masks| (sudoku, masks)),
+        }
+    }
+
+    /// Whether every node has been visited.
+    #[cfg(feature = "std")]
+    pub(crate) fn is_finished(&self) -> bool {
+        self.current.is_none() && self.depth == 0
+    }
+
+    /// Visits nodes until `found` returns `false` or every node has been
+    /// visited, or returns `false` once `abort`, called before each node,
+    /// returns `true`. A later call carries on after the last node.
+    pub(crate) fn run(
+        &mut self,
+        config: &SolverConfig,
+        found: &mut dyn FnMut(Sudoku) -> bool,
+        abort: &mut dyn FnMut() -> bool,
+        stats: &mut SolveStats,
+    ) -> bool {
+        loop {
+            let (mut current, mut masks) = match self.current.take() {
+                Some(node) => node,
+                None => match self.next_guess(stats) {
+                    Some(node) => node,
+                    None => return true,
+                },
+            };
+            if abort() {
+                self.current = Some((current, masks));
+                return false;
+            }
+            stats.nodes += 1;
+            // fill in the forced cells before guessing
+            if !propagate_with(&mut current, &mut masks) {
+                stats.backtracks += 1;
+                continue;
+            }
+            match config.choose(&current) {
+                Some(cell) => {
+                    // propagation left only the legal candidates
+                    self.stack[self.depth] = Frame {
+                        sudoku: current,
+                        masks,
+                        cell,
+                        untried: current.0[cell].0,
+                    };
+                    self.depth += 1;
+                    stats.max_depth = stats.max_depth.max(self.depth);
+                }
+                None => {
+                    if !found(current) {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The grid and masks after the next untried guess, dropping the
+    /// frames without one, `None` once there are none.
+    fn next_guess(&mut self, stats: &mut SolveStats) -> Option<(Sudoku, UnitMasks)> {
+        loop {
+            let frame = &mut self.stack[self.depth.checked_sub(1)?];
+            if frame.untried == 0 {
+                self.depth -= 1;
+                continue;
+            }
+            let guess = frame.untried & frame.untried.wrapping_neg();
+            frame.untried &= !guess;
+            let mut current = frame.sudoku;
+            current.0[frame.cell].0 = guess;
+            let mut masks = frame.masks;
+            let value = Value(guess.trailing_zeros() as u8);
+            masks.place(frame.cell / 9, frame.cell % 9, value);
+            stats.guesses += 1;
+            return Some((current, masks));
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Frame {
+    pub(crate) sudoku: Sudoku,
+    pub(crate) masks: UnitMasks,
+    pub(crate) cell: usize,
+    /// Candidates of the cell not guessed yet.
+    pub(crate) untried: u16,
+}
+
+/// The size of a guess on the stack of a backtracking search.
+#[cfg(feature = "std")]
+pub(crate) const FRAME_BYTES: usize = core::mem::size_of::<Frame>();
+
+/// Counters of a backtracking search.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SolveStats {
+    /// Propagations, one at the start and one after each guess.
+    pub nodes: u64,
+    pub guesses: u64,
+    /// Propagations that ran into a contradiction.
+    pub backtracks: u64,
+    /// Most guesses pending at once.
+    pub max_depth: usize,
+    /// Zero without the `std` feature.
+    pub duration: Duration,
+}
+
+/// A puzzle of `solve_batch` with its solution, if any.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolveResult {
+    pub puzzle: Sudoku,
+    pub solution: Option<SolvedSudoku>,
+    pub stats: SolveStats,
+}
+
+pub fn solve(sudoku: &Sudoku) -> Option<SolvedSudoku> {
+    SolverConfig::default().solve(sudoku)
+}
+
+#[cfg(feature = "std")]
+pub fn solve_with_stats(sudoku: &Sudoku) -> (Option<SolvedSudoku>, SolveStats) {
+    SolverConfig::default().solve_with_stats(sudoku)
+}
+
+#[cfg(feature = "std")]
+pub fn solve_unique(sudoku: &Sudoku) -> Result<SolvedSudoku, SolveUniqueError> {
+    SolverConfig::default().solve_unique(sudoku)
+}
+
+#[cfg(feature = "std")]
+pub fn solve_with_options(sudoku: &Sudoku, options: &SolveOptions) -> SolveOutcome {
+    SolverConfig::default().solve_with_options(sudoku, options)
+}
+
+/// `f` of each item, the items spread over the available cores. The results
+/// are in the order of the items.
+#[cfg(feature = "std")]
+pub(crate) fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    // hard puzzles take far longer than the rest, so the workers take one
+    // item at a time rather than a fixed share
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(i) {
+                            Some(item) => results.push((i, f(item))),
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("the workers don't panic; qed"))
+            .collect()
+    });
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(feature = "std")]
+pub fn solve_batch(puzzles: impl IntoIterator<Item = Sudoku>) -> Vec<SolveResult> {
+    SolverConfig::default().solve_batch(puzzles)
+}
+
+#[cfg(feature = "rayon")]
+pub fn solve_parallel(sudoku: &Sudoku) -> Option<SolvedSudoku> {
+    SolverConfig::default().solve_parallel(sudoku)
+}
+
+/// Enumerates up to `limit` solutions.
+#[cfg(feature = "std")]
+pub fn solutions(sudoku: &Sudoku, limit: usize) -> Vec<Sudoku> {
+    SolverConfig::default().solutions(sudoku, limit)
+}
+
+impl Sudoku {
+    /// Counts solutions, stopping once `limit` are found. Asking for at
+    /// most 2 tells apart puzzles without a solution, proper ones and
+    /// ambiguous ones.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        SolverConfig::default().count_solutions(self, limit)
+    }
+
+    /// Whether the puzzle has exactly one solution, stopping the search at
+    /// the second one.
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    /// The consequences of placing `value` at `coord`, replacing any value
+    /// there, without changing the grid.
+    #[cfg(feature = "std")]
+    pub fn try_place(&self, coord: Coord, value: Value) -> WhatIf {
+        let mut next = *self;
+        next[coord] = value.into();
+        let solutions = next.count_solutions(2);
+        let mut propagated = next;
+        if !propagate(&mut propagated) {
+            return WhatIf {
+                forced: Vec::new(),
+                contradiction: true,
+                solutions,
+            };
+        }
+        let forced = (0..81)
+            .map(Coord::from_index)
+            .filter(|&c| !next[c].is_final())
+            .filter_map(|c| propagated[c].value().map(|value| (c, value)))
+            .collect();
+        WhatIf {
+            forced,
+            contradiction: false,
+            solutions,
+        }
+    }
+
+    /// The empty cells whose value differs between two solutions, in
+    /// row-major order, empty for proper puzzles and ones without a
+    /// solution. Each cell is checked by searching for a solution avoiding
+    /// the digit of a first one, so this takes up to 81 searches however
+    /// many solutions there are.
+    #[cfg(feature = "std")]
+    pub fn solution_disagreements(&self) -> Vec<Coord> {
+        let first = match solutions(self, 1).pop() {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        let mut differs = [false; 81];
+        for i in 0..81 {
+            if differs[i] || self.0[i].is_final() {
+                continue;
+            }
+            let mut avoiding = *self;
+            avoiding.0[i].remove(first.0[i].value().expect("solutions are filled; qed"));
+            if let Some(other) = solutions(&avoiding, 1).pop() {
+                for (j, differ) in differs.iter_mut().enumerate() {
+                    *differ |= other.0[j] != first.0[j];
+                }
+            }
+        }
+        (0..81)
+            .filter(|&i| differs[i])
+            .map(Coord::from_index)
+            .collect()
+    }
+}
+
+/// What `Sudoku::try_place` found.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WhatIf {
+    /// The cells the move forces by singles, in row-major order.
+    pub forced: Vec<(Coord, Value)>,
+    /// The move repeats a digit in a unit, or singles lead from it to a
+    /// cell without candidates.
+    pub contradiction: bool,
+    /// The solutions left after the move, counting up to 2. Zero means the
+    /// move is wrong even without a contradiction found by singles, 2 that
+    /// the grid is still ambiguous.
+    pub solutions: usize,
+}
+
+#[cfg(feature = "std")]
+impl WhatIf {
+    /// Whether the grid has exactly one solution after the move.
+    pub fn is_unique(&self) -> bool {
+        self.solutions == 1
+    }
+}
+
+pub(crate) fn consistent(sudoku: &Sudoku) -> bool {
+    UnitMasks::from_sudoku(sudoku).is_some()
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn legal(sudoku: &Sudoku, x: usize, y: usize, value: Value) -> bool {
+    peers(x, y).all(|(px, py)| sudoku.get(px, py).unwrap().value() != Some(value))
+}
+
+/// Repeatedly applies naked and hidden singles. Returns `false` if a
+/// contradiction was found.
+#[cfg(feature = "std")]
+pub(crate) fn propagate(sudoku: &mut Sudoku) -> bool {
+    match UnitMasks::from_sudoku(sudoku) {
+        Some(mut masks) => propagate_with(sudoku, &mut masks),
+        None => false,
+    }
+}
+
+/// `propagate` with the masks of the cells with a value kept up to date,
+/// trusting them to be consistent.
+fn propagate_with(sudoku: &mut Sudoku, masks: &mut UnitMasks) -> bool {
+    loop {
+        let mut changed = false;
+        for i in 0..81 {
+            let (x, y) = (i / 9, i % 9);
+            let cell = &mut sudoku.0[i];
+            if cell.is_final() {
+                continue;
+            }
+            let candidates = cell.0 & masks.allowed(x, y);
+            if candidates != cell.0 {
+                cell.0 = candidates;
+                changed = true;
+            }
+            match cell.value() {
+                Some(value) => masks.place(x, y, value),
+                None if cell.is_empty() => return false,
+                None => {}
+            }
+        }
+        for unit in Unit::all() {
+            // digits with a place in the unit, and with more than one
+            let (mut once, mut twice) = (0, 0);
+            for (x, y) in unit.cells() {
+                let mask = sudoku.get(x, y).unwrap().0;
+                twice |= once & mask;
+                once |= mask;
+            }
+            if once != Cell::new().0 {
+                return false;
+            }
+            for value in (0..9).map(Value).filter(|v| v.mask() & !twice != 0) {
+                // gone if another digit was placed in its only cell
+                let place = unit
+                    .cells()
+                    .find(|&(x, y)| sudoku.get(x, y).unwrap().contains(value));
+                let (x, y) = match place {
+                    Some(place) => place,
+                    None => return false,
+                };
+                let cell = sudoku.get_mut(x, y).unwrap();
+                if cell.is_final() {
+                    continue;
+                }
+                if !masks.allows(x, y, value) {
+                    return false;
+                }
+                cell.set(value);
+                masks.place(x, y, value);
+                changed = true;
+            }
+        }
+        if !changed {
+            return true;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub enum SolveEvent {
+    /// A technique was applied.
+    Step(SolveStep),
+    /// The techniques ran out and a candidate is tried.
+    Guess(Coord, Value),
+    /// The guess at a cell led to a contradiction and is undone.
+    Backtrack(Coord),
+    Solved(Sudoku),
+}
+
+/// Solves with the logical techniques, guessing when they run out, and
+/// reports each step to `events`. Events are dropped once the receiver
+/// hangs up.
+#[cfg(feature = "std")]
+pub fn solve_with_events(sudoku: &Sudoku, events: Sender<SolveEvent>) -> Option<Sudoku> {
+    if !consistent(sudoku) {
+        return None;
+    }
+    let solver = LogicalSolver::default();
+    let state = LogicState::new(sudoku);
+    let solution = search_with_events(&solver, state, &events, &mut || false).ok()??;
+    let _ = events.send(SolveEvent::Solved(solution));
+    Some(solution)
+}
+
+/// The search of `solve_with_events`, `Err` once `abort` returns `true`,
+/// checked at every node and every step.
+#[cfg(feature = "std")]
+pub(crate) fn search_with_events(
+    solver: &LogicalSolver,
+    mut state: LogicState,
+    events: &Sender<SolveEvent>,
+    abort: &mut dyn FnMut() -> bool,
+) -> Result<Option<Sudoku>, ()> {
+    if abort() {
+        return Err(());
+    }
+    while !state.is_solved() {
+        if abort() {
+            return Err(());
+        }
+        match solver.next_step(&state) {
+            Some(step) => {
+                state.apply(&step);
+                let _ = events.send(SolveEvent::Step(step));
+            }
+            None => break,
+        }
+    }
+    if state.is_broken() {
+        return Ok(None);
+    }
+    if state.is_solved() {
+        return Ok(Some(*state.grid()).filter(|grid| grid.valid()));
+    }
+    let coord = (0..81)
+        .map(Coord::from_index)
+        .filter(|c| !state.is_placed(c.row(), c.col()))
+        .min_by_key(|c| state.grid().0[c.index()].len());
+    let coord = match coord {
+        Some(coord) => coord,
+        None => return Ok(None),
+    };
+    for value in state.grid().0[coord.index()].values() {
+        let _ = events.send(SolveEvent::Guess(coord, value));
+        let mut next = state;
+        next.place(coord, value);
+        if let Some(solution) = search_with_events(solver, next, events, abort)? {
+            return Ok(Some(solution));
+        }
+        let _ = events.send(SolveEvent::Backtrack(coord));
+    }
+    Ok(None)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_propagate() {
+        let mut sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        assert!(propagate(&mut sudoku));
+        assert!(sudoku.valid());
+    }
+
+    #[test]
+    fn test_search_masks() {
+        let hard = *crate::Corpus::top1465_sample().puzzles.get(0).unwrap();
+        let config = SolverConfig::default();
+        let mut search = Backtrack::new(hard);
+        let mut stats = SolveStats::default();
+        let mut solution = None;
+        loop {
+            // the masks carried along each guess match the grids
+            for frame in &search.stack[..search.depth] {
+                assert_eq!(UnitMasks::from_sudoku(&frame.sudoku), Some(frame.masks));
+            }
+            if let Some((sudoku, masks)) = search.current {
+                assert_eq!(UnitMasks::from_sudoku(&sudoku), Some(masks));
+            }
+            let mut nodes = 0;
+            let mut abort = || {
+                nodes += 1;
+                nodes > 5
+            };
+            let mut found = |sudoku| {
+                solution = Some(sudoku);
+                false
+            };
+            if search.run(&config, &mut found, &mut abort, &mut stats) {
+                break;
+            }
+        }
+        assert_eq!(
+            solution.unwrap().to_string(),
+            solve(&hard).unwrap().to_string()
+        );
+        assert!(stats.guesses > 0);
+    }
+
+    const PUZZLE: &str = "53..7....\n\
+         6..195...\n\
+         .98....6.\n\
+         8...6...3\n\
+         4..8.3..1\n\
+         7...2...6\n\
+         .6....28.\n\
+         ...419..5\n\
+         ....8..79";
+
+    #[test]
+    fn test_solve_corpus() {
+        let corpus = crate::Corpus::seventeen_sample();
+        for puzzle in corpus.puzzles.iter() {
+            let solution = solve_unique(puzzle).unwrap();
+            assert!(puzzle.iter_filled().all(|(coord, value)| {
+                solution.get(coord.row(), coord.col()).unwrap().value() == Some(value)
+            }));
+        }
+    }
+
+    #[test]
+    fn test_heuristics() {
+        let first = SolverConfig {
+            heuristic: Heuristic::FirstEmpty,
+        };
+        let mrv = SolverConfig::default();
+        for puzzle in crate::Corpus::top1465_sample().puzzles.iter() {
+            let a = first.solve_unique(puzzle).unwrap();
+            let b = mrv.solve_unique(puzzle).unwrap();
+            assert_eq!(a.to_string(), b.to_string());
+        }
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        let easy: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        let (solution, stats) = solve_with_stats(&easy);
+        assert!(solution.unwrap().is_completion_of(&easy));
+        // singles suffice
+        assert_eq!((stats.nodes, stats.guesses, stats.max_depth), (1, 0, 0));
+
+        let corpus = crate::Corpus::top1465_sample();
+        let hard = corpus.puzzles.iter().next().unwrap();
+        let (solution, stats) = solve_with_stats(hard);
+        assert_eq!(solution.unwrap().to_line(), solve(hard).unwrap().to_line());
+        assert_eq!(stats.nodes, stats.guesses + 1);
+        assert!(stats.backtracks <= stats.guesses && stats.max_depth > 0);
+
+        let mut broken = easy;
+        broken.get_mut(0, 2).unwrap().set(Value::new(5).unwrap());
+        assert_eq!(solve_with_stats(&broken).1.nodes, 0);
+    }
+
+    #[test]
+    fn test_try_place() {
+        let sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        let solution = solve(&sudoku).unwrap();
+        let corner = Coord::new(0, 2);
+        let right = solution[corner].value().unwrap();
+        let what_if = sudoku.try_place(corner, right);
+        assert!(!what_if.contradiction && what_if.is_unique());
+        // singles solve the puzzle from the start already
+        assert_eq!(what_if.forced.len(), 81 - 31);
+        assert!(!sudoku[corner].is_final());
+
+        let conflict = sudoku.try_place(corner, Value::new(5).unwrap());
+        assert!(conflict.contradiction && conflict.forced.is_empty());
+        assert_eq!(conflict.solutions, 0);
+
+        let open = Sudoku::new().try_place(corner, right);
+        assert!(!open.contradiction && open.forced.is_empty());
+        assert_eq!(open.solutions, 2);
+    }
+
+    #[test]
+    fn test_solution_disagreements() {
+        let sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        assert!(sudoku.solution_disagreements().is_empty());
+        let solution = solve(&sudoku).unwrap();
+
+        // with two digits cleared everywhere, swapping them solves it too
+        let (a, b) = (solution[Coord::new(0, 0)], solution[Coord::new(0, 1)]);
+        let mut open = *solution;
+        let cleared: Vec<Coord> = (0..9)
+            .flat_map(|row| (0..9).map(move |col| Coord::new(row, col)))
+            .filter(|&c| solution[c] == a || solution[c] == b)
+            .collect();
+        for &c in &cleared {
+            open[c] = Cell::new();
+        }
+        let disagreements = open.solution_disagreements();
+        assert_eq!(disagreements, cleared);
+        for &c in &disagreements {
+            let mut clued = open;
+            clued[c] = solution[c];
+            assert!(clued.solution_disagreements().len() < cleared.len());
+        }
+        assert!(Sudoku::new().solution_disagreements().len() == 81);
+    }
+
+    #[test]
+    fn test_solve_batch() {
+        let corpus = crate::Corpus::seventeen_sample();
+        let mut puzzles: Vec<Sudoku> = corpus.puzzles.iter().copied().collect();
+        let mut broken = puzzles[0];
+        broken.get_mut(0, 0).unwrap().set(Value::new(1).unwrap());
+        broken.get_mut(0, 1).unwrap().set(Value::new(1).unwrap());
+        puzzles.push(broken);
+        let results = solve_batch(puzzles.iter().copied());
+        assert_eq!(results.len(), puzzles.len());
+        for (result, puzzle) in results.iter().zip(&puzzles[..puzzles.len() - 1]) {
+            assert_eq!(result.puzzle, *puzzle);
+            assert!(result.solution.unwrap().is_completion_of(puzzle));
+            assert!(result.stats.nodes > 0);
+        }
+        assert_eq!(results.last().unwrap().solution, None);
+        assert!(solve_batch(Vec::new()).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_solve_parallel() {
+        for puzzle in crate::Corpus::top1465_sample().puzzles.iter() {
+            let parallel = solve_parallel(puzzle).unwrap();
+            assert_eq!(parallel.to_string(), solve(puzzle).unwrap().to_string());
+        }
+        let empty = solve_parallel(&Sudoku::new()).unwrap();
+        assert_eq!(
+            empty.to_string(),
+            solve(&Sudoku::new()).unwrap().to_string()
+        );
+
+        let mut sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        sudoku.get_mut(0, 2).unwrap().set(Value::new(5).unwrap());
+        assert!(solve_parallel(&sudoku).is_none());
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        assert_eq!(sudoku.count_solutions(5), 1);
+        assert_eq!(sudoku.count_solutions(0), 0);
+        assert_eq!(Sudoku::new().count_solutions(3), 3);
+
+        let mut broken = sudoku;
+        broken.get_mut(0, 2).unwrap().set(Value::new(5).unwrap());
+        assert_eq!(broken.count_solutions(2), 0);
+        assert!(sudoku.has_unique_solution());
+        assert!(!broken.has_unique_solution());
+        assert!(!Sudoku::new().has_unique_solution());
+    }
+
+    #[test]
+    fn test_solve_with_options() {
+        let puzzle = *crate::Corpus::top1465_sample().puzzles.get(0).unwrap();
+        let options = SolveOptions::default();
+        assert!(matches!(
+            solve_with_options(&puzzle, &options),
+            SolveOutcome::Solved(_)
+        ));
+        let budget = SolveOptions {
+            max_nodes: Some(1),
+            ..SolveOptions::default()
+        };
+        assert!(matches!(
+            solve_with_options(&puzzle, &budget),
+            SolveOutcome::Aborted
+        ));
+        options.cancel.store(true, Ordering::Relaxed);
+        assert!(matches!(
+            solve_with_options(&puzzle, &options),
+            SolveOutcome::Aborted
+        ));
+        let timeout = SolveOptions {
+            timeout: Some(Duration::from_secs(0)),
+            ..SolveOptions::default()
+        };
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(matches!(
+            solve_with_options(&puzzle, &timeout),
+            SolveOutcome::Aborted
+        ));
+
+        let mut broken = puzzle;
+        broken.get_mut(0, 1).unwrap().set(Value::new(4).unwrap());
+        assert!(matches!(
+            solve_with_options(&broken, &SolveOptions::default()),
+            SolveOutcome::NoSolution
+        ));
+    }
+
+    #[test]
+    fn test_solutions_limit() {
+        let found = solutions(&Sudoku::new(), 5);
+        assert_eq!(found.len(), 5);
+        for (i, solution) in found.iter().enumerate() {
+            assert!(solution.valid());
+            assert!(found[..i]
+                .iter()
+                .all(|other| other.to_string() != solution.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_solve_unique() {
+        let sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        let solution = solve_unique(&sudoku).unwrap();
+        assert!(solution.valid());
+        assert_eq!(
+            solution.to_string(),
+            "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179\n"
+        );
+    }
+
+    #[test]
+    fn test_solve_unique_multiple() {
+        match solve_unique(&Sudoku::new()) {
+            Err(SolveUniqueError::MultipleSolutions(first, second)) => {
+                assert!(first.valid());
+                assert!(second.valid());
+                assert_ne!(first.to_string(), second.to_string());
+            }
+            res => panic!("unexpected {:?}", res),
+        }
+
+        let mut sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        sudoku.get_mut(0, 2).unwrap().set(Value::new(5).unwrap());
+        assert!(matches!(
+            solve_unique(&sudoku),
+            Err(SolveUniqueError::NoSolution)
+        ));
+    }
+
+    #[test]
+    fn test_solve_with_events() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sudoku: Sudoku = PUZZLE.replace('.', " ").parse().unwrap();
+        let solution = solve_with_events(&sudoku, tx.clone()).unwrap();
+        assert_eq!(solution.to_string(), solve(&sudoku).unwrap().to_string());
+        let events: Vec<_> = rx.try_iter().collect();
+        assert_eq!(events.len(), 52);
+        assert!(matches!(events.last(), Some(SolveEvent::Solved(_))));
+
+        let solution = solve_with_events(&Sudoku::new(), tx).unwrap();
+        assert!(solution.valid());
+        assert!(rx
+            .try_iter()
+            .any(|event| matches!(event, SolveEvent::Guess(_, _))));
+    }
+}
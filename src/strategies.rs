@@ -0,0 +1,65 @@
+//! Grids for property tests. Each generator maps a seed to a grid, so they
+//! plug into any framework without this crate depending on one, with
+//! proptest for example `any::<u64>().prop_map(strategies::solved_grid)`.
+//! Shrinking the seed shrinks nothing in particular, but failures
+//! reproduce from the seed alone.
+use crate::generator::{reclue, ReclueOptions};
+use crate::rng::XorShift;
+use crate::verified::SolvedSudoku;
+use crate::{Cell, Sudoku};
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::SeedableRng;
+
+/// A complete valid grid.
+pub fn solved_grid(seed: u64) -> SolvedSudoku {
+    Sudoku::random_filled(&mut Xoshiro256PlusPlus::seed_from_u64(seed))
+}
+
+/// A consistent partial grid with at least one solution, with between 0
+/// and 64 cells of a solved grid cleared. It may have several solutions.
+pub fn solvable_puzzle(seed: u64) -> Sudoku {
+    let mut puzzle = *solved_grid(seed);
+    let mut rng = XorShift::new(seed);
+    let mut order: Vec<usize> = (0..81).collect();
+    rng.shuffle(&mut order);
+    for &i in &order[..rng.below(65)] {
+        puzzle.0[i] = Cell::new();
+    }
+    puzzle
+}
+
+/// A minimal puzzle with exactly one solution. Slower than the others,
+/// as removing each clue is checked with the solver.
+pub fn proper_puzzle(seed: u64) -> Sudoku {
+    reclue(&solved_grid(seed), seed, &ReclueOptions::default())
+}
+
+/// A grid where two peers have the same digit, so it has no solution.
+pub fn invalid_grid(seed: u64) -> Sudoku {
+    let mut grid = solvable_puzzle(seed);
+    let mut rng = XorShift::new(!seed);
+    let filled: Vec<_> = grid.iter_filled().collect();
+    let (coord, value) = filled[rng.below(filled.len())];
+    let peers: Vec<_> = coord.peers().collect();
+    grid[peers[rng.below(peers.len())]] = value.into();
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies() {
+        for seed in 0..20 {
+            let solved = solved_grid(seed);
+            assert!(solved.is_consistent() && solved.iter_filled().count() == 81);
+            let puzzle = solvable_puzzle(seed);
+            assert!(solved.is_completion_of(&puzzle));
+            assert!(!invalid_grid(seed).is_consistent());
+            assert_eq!(invalid_grid(seed).count_solutions(1), 0);
+        }
+        assert!(proper_puzzle(7).has_unique_solution());
+        assert_eq!(solved_grid(3), solved_grid(3));
+    }
+}
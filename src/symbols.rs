@@ -0,0 +1,165 @@
+//! Characters used to write the digits of a grid.
+use crate::{Error, Sudoku};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymbolSet {
+    /// The symbol of digit `n` is at index `n - 1`.
+    symbols: Vec<char>,
+    /// Characters read as blanks, the first one is written for blanks.
+    blanks: Vec<char>,
+}
+
+impl SymbolSet {
+    /// Creates a symbol set from the symbols of the digits in order and
+    /// the characters accepted as blanks.
+    pub fn new(symbols: &str, blanks: &str) -> Result<Self, Error> {
+        let symbols: Vec<char> = symbols.chars().collect();
+        let blanks: Vec<char> = blanks.chars().collect();
+        if blanks.is_empty() {
+            return Err(Error::WrongLength {
+                expected: 1,
+                found: 0,
+            });
+        }
+        for (i, c) in symbols.iter().chain(blanks.iter()).enumerate() {
+            if symbols.iter().chain(blanks.iter()).take(i).any(|d| d == c) {
+                return Err(Error::InvalidSymbol(*c));
+            }
+        }
+        Ok(Self { symbols, blanks })
+    }
+
+    /// `1` to `9`, with `.`, `0` or a space for blanks.
+    pub fn digits() -> Self {
+        Self::new("123456789", ". 0").expect("distinct symbols; qed")
+    }
+
+    /// `A` to `I`.
+    pub fn letters() -> Self {
+        Self::new("ABCDEFGHI", ". ").expect("distinct symbols; qed")
+    }
+
+    /// `0` to `9` and `A` to `F`, for 16x16 grids.
+    pub fn hex() -> Self {
+        Self::new("0123456789ABCDEF", ". ").expect("distinct symbols; qed")
+    }
+
+    /// `1` to `9` followed by `A` to `Z`, enough for 36x36 grids.
+    pub fn alphanumeric() -> Self {
+        Self::new("123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ", ".0 ").expect("distinct symbols; qed")
+    }
+
+    /// Number of digits that have a symbol.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn blank(&self) -> char {
+        self.blanks[0]
+    }
+
+    pub fn is_blank(&self, c: char) -> bool {
+        self.blanks.contains(&c)
+    }
+
+    /// The symbol of a digit counting from `1`.
+    pub fn symbol(&self, digit: u8) -> Option<char> {
+        self.symbols.get((digit as usize).checked_sub(1)?).copied()
+    }
+
+    /// The digit of a symbol counting from `1`. Letters also match in the
+    /// other case unless that is a symbol of its own.
+    pub fn digit(&self, c: char) -> Option<u8> {
+        let exact = self.symbols.iter().position(|&s| s == c);
+        let position =
+            exact.or_else(|| self.symbols.iter().position(|s| s.eq_ignore_ascii_case(&c)))?;
+        Some(position as u8 + 1)
+    }
+}
+
+impl Default for SymbolSet {
+    fn default() -> Self {
+        Self::digits()
+    }
+}
+
+impl Sudoku {
+    /// Parses one row per line like `from_str`, reading digits and blanks
+    /// with `symbols`.
+    pub fn parse_with(string: &str, symbols: &SymbolSet) -> Result<Self, Error> {
+        let mut sudoku = Sudoku::new();
+        for (x, row) in string.lines().enumerate() {
+            for (y, c) in row.chars().enumerate() {
+                let cell = sudoku.get_mut(x, y).ok_or(Error::WrongLength {
+                    expected: 81,
+                    found: x * 9 + y + 1,
+                })?;
+                if symbols.is_blank(c) {
+                    continue;
+                }
+                match symbols.digit(c) {
+                    Some(digit @ 1..=9) => cell.set(crate::Value(digit - 1)),
+                    _ => return Err(Error::InvalidSymbol(c)),
+                }
+            }
+        }
+        Ok(sudoku)
+    }
+
+    /// Formats one row per line like `to_string`, writing digits and
+    /// blanks with `symbols`.
+    pub fn to_string_with(&self, symbols: &SymbolSet) -> String {
+        let mut string = String::with_capacity(90);
+        for row in self.0.chunks(9) {
+            for cell in row {
+                let c = cell
+                    .value()
+                    .and_then(|value| symbols.symbol(value.0 + 1))
+                    .unwrap_or_else(|| symbols.blank());
+                string.push(c);
+            }
+            string.push('\n');
+        }
+        string
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_set() {
+        let letters = SymbolSet::letters();
+        assert_eq!(letters.digit('c'), Some(3));
+        assert_eq!(letters.symbol(9), Some('I'));
+        assert_eq!(letters.symbol(0), None);
+        assert_eq!(SymbolSet::hex().digit('0'), Some(1));
+        assert!(matches!(
+            SymbolSet::new("AB", "A"),
+            Err(Error::InvalidSymbol('A'))
+        ));
+
+        let puzzle: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let written = puzzle.to_string_with(&letters);
+        assert_eq!(&written[..10], "EC..G....\n");
+        let read = Sudoku::parse_with(&written, &letters).unwrap();
+        assert_eq!(read.to_string(), puzzle.to_string());
+        assert!(Sudoku::parse_with("12x", &SymbolSet::digits()).is_err());
+    }
+}
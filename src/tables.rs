@@ -0,0 +1,91 @@
+//! Cell indices of the units and peers, computed at compile time.
+
+/// The row-major cell indices of the rows, the columns and the blocks, in
+/// the order of `Unit::all`.
+pub(crate) const UNITS: [[u8; 9]; 27] = units();
+
+/// The 20 peers of each cell: the rest of its row, then of its column, then
+/// the cells of its block in neither.
+pub(crate) const PEERS: [[u8; 20]; 81] = peers();
+
+const fn units() -> [[u8; 9]; 27] {
+    let mut units = [[0; 9]; 27];
+    let mut i = 0;
+    while i < 9 {
+        let mut j = 0;
+        while j < 9 {
+            units[i][j] = (i * 9 + j) as u8;
+            units[9 + i][j] = (j * 9 + i) as u8;
+            units[18 + i][j] = (((i / 3) * 3 + j / 3) * 9 + (i % 3) * 3 + j % 3) as u8;
+            j += 1;
+        }
+        i += 1;
+    }
+    units
+}
+
+const fn peers() -> [[u8; 20]; 81] {
+    let mut peers = [[0; 20]; 81];
+    let mut cell = 0;
+    while cell < 81 {
+        let (row, col) = (cell / 9, cell % 9);
+        let mut n = 0;
+        let mut other = 0;
+        while other < 81 {
+            if other != cell && other / 9 == row {
+                peers[cell][n] = other as u8;
+                n += 1;
+            }
+            other += 1;
+        }
+        other = 0;
+        while other < 81 {
+            if other != cell && other % 9 == col {
+                peers[cell][n] = other as u8;
+                n += 1;
+            }
+            other += 1;
+        }
+        other = 0;
+        while other < 81 {
+            let (r, c) = (other / 9, other % 9);
+            if r / 3 == row / 3 && c / 3 == col / 3 && r != row && c != col {
+                peers[cell][n] = other as u8;
+                n += 1;
+            }
+            other += 1;
+        }
+        cell += 1;
+    }
+    peers
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{Coord, Unit};
+
+    #[test]
+    fn test_tables() {
+        for (unit, cells) in Unit::all().zip(UNITS.iter()) {
+            for (i, &cell) in cells.iter().enumerate() {
+                let (x, y) = unit.cell(i);
+                assert_eq!(cell as usize, x * 9 + y);
+            }
+        }
+        for (i, peers) in PEERS.iter().enumerate() {
+            let coord = Coord::from_index(i);
+            let mut peers = peers.to_vec();
+            peers.sort();
+            peers.dedup();
+            assert_eq!(peers.len(), 20);
+            assert!(peers.iter().all(|&p| {
+                let peer = Coord::from_index(p as usize);
+                peer != coord
+                    && (peer.row() == coord.row()
+                        || peer.col() == coord.col()
+                        || peer.block() == coord.block())
+            }));
+        }
+    }
+}
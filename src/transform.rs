@@ -0,0 +1,382 @@
+use crate::rng::XorShift;
+use crate::{Cell, Error, Sudoku, Value};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Quarter {
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Axis {
+    /// Flips the grid upside down.
+    Horizontal,
+    /// Flips the grid left to right.
+    Vertical,
+}
+
+/// A bijection of the digits `1..=9`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct DigitPermutation([u8; 9]);
+
+impl DigitPermutation {
+    /// Creates the permutation mapping digit `i + 1` to `digits[i]`.
+    pub fn new(digits: [u8; 9]) -> Result<Self, Error> {
+        let mut seen = 0u16;
+        for &digit in &digits {
+            seen |= Value::new(digit)?.mask();
+        }
+        if seen != Cell::new().0 {
+            return Err(Error::InvalidPermutation);
+        }
+        Ok(Self(digits))
+    }
+
+    pub fn identity() -> Self {
+        Self([1, 2, 3, 4, 5, 6, 7, 8, 9])
+    }
+
+    /// A permutation picked by `seed`, the same seed always picking the
+    /// same one.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut digits = Self::identity().0;
+        XorShift::new(seed).shuffle(&mut digits);
+        Self(digits)
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut digits = [0; 9];
+        for (i, &digit) in self.0.iter().enumerate() {
+            digits[digit as usize - 1] = i as u8 + 1;
+        }
+        Self(digits)
+    }
+
+    pub fn apply(&self, value: Value) -> Value {
+        Value(self.0[value.0 as usize] - 1)
+    }
+
+    fn apply_cell(&self, cell: Cell) -> Cell {
+        let mask = cell
+            .values()
+            .fold(0, |mask, value| mask | self.apply(value).mask());
+        Cell(mask)
+    }
+}
+
+impl Sudoku {
+    /// Replaces every digit, and every candidate, by its image under `perm`.
+    pub fn relabel(&self, perm: &DigitPermutation) -> Sudoku {
+        let mut sudoku = *self;
+        for cell in sudoku.0.iter_mut() {
+            *cell = perm.apply_cell(*cell);
+        }
+        sudoku
+    }
+
+    /// Builds a new grid, taking the cell at `(x, y)` from `source(x, y)`.
+    fn remap(&self, source: impl Fn(usize, usize) -> (usize, usize)) -> Sudoku {
+        let mut sudoku = Sudoku::new();
+        for x in 0..9 {
+            for y in 0..9 {
+                let (sx, sy) = source(x, y);
+                *sudoku.get_mut(x, y).unwrap() = *self.get(sx, sy).unwrap();
+            }
+        }
+        sudoku
+    }
+
+    pub fn rotated(&self, quarter: Quarter) -> Sudoku {
+        match quarter {
+            Quarter::Cw90 => self.remap(|x, y| (8 - y, x)),
+            Quarter::Cw180 => self.remap(|x, y| (8 - x, 8 - y)),
+            Quarter::Cw270 => self.remap(|x, y| (y, 8 - x)),
+        }
+    }
+
+    pub fn mirrored(&self, axis: Axis) -> Sudoku {
+        match axis {
+            Axis::Horizontal => self.remap(|x, y| (8 - x, y)),
+            Axis::Vertical => self.remap(|x, y| (x, 8 - y)),
+        }
+    }
+
+    pub fn transposed(&self) -> Sudoku {
+        self.remap(|x, y| (y, x))
+    }
+
+    /// Swaps two rows of the same band, `Error::DifferentBands` if they're
+    /// in different ones, which would break the blocks.
+    pub fn rows_swapped(&self, a: usize, b: usize) -> Result<Sudoku, Error> {
+        within(a, b, 9, |i| (i, 0))?;
+        same_band(a, b)?;
+        Ok(self.remap(|x, y| (swap(x, a, b), y)))
+    }
+
+    /// Swaps two columns of the same stack, `Error::DifferentBands` if
+    /// they're in different ones.
+    pub fn cols_swapped(&self, a: usize, b: usize) -> Result<Sudoku, Error> {
+        within(a, b, 9, |i| (0, i))?;
+        same_band(a, b)?;
+        Ok(self.remap(|x, y| (x, swap(y, a, b))))
+    }
+
+    /// Swaps two bands, the rows `3 * a..3 * a + 3` with those of `b`.
+    pub fn bands_swapped(&self, a: usize, b: usize) -> Result<Sudoku, Error> {
+        within(a, b, 3, |i| (i * 3, 0))?;
+        Ok(self.remap(|x, y| (swap(x / 3, a, b) * 3 + x % 3, y)))
+    }
+
+    /// Swaps two stacks, the columns `3 * a..3 * a + 3` with those of `b`.
+    pub fn stacks_swapped(&self, a: usize, b: usize) -> Result<Sudoku, Error> {
+        within(a, b, 3, |i| (0, i * 3))?;
+        Ok(self.remap(|x, y| (x, swap(y / 3, a, b) * 3 + y % 3)))
+    }
+
+    /// An equivalent grid picked by `seed`: the digits relabeled, the
+    /// bands, stacks and the rows and columns within them shuffled and the
+    /// grid transposed or not. The same seed always picks the same grid.
+    pub fn scrambled(&self, seed: u64) -> Sudoku {
+        let mut rng = XorShift::new(seed);
+        let mut order = || {
+            let mut outer = [0, 1, 2];
+            rng.shuffle(&mut outer);
+            let mut lines = [0; 9];
+            for (i, &band) in outer.iter().enumerate() {
+                let mut inner = [0, 1, 2];
+                rng.shuffle(&mut inner);
+                for (j, &line) in inner.iter().enumerate() {
+                    lines[i * 3 + j] = band * 3 + line;
+                }
+            }
+            lines
+        };
+        let (rows, cols) = (order(), order());
+        let transpose = rng.below(2) == 1;
+        let perm = DigitPermutation::from_seed(rng.next());
+        let sudoku = self.remap(|x, y| (rows[x], cols[y])).relabel(&perm);
+        if transpose {
+            sudoku.transposed()
+        } else {
+            sudoku
+        }
+    }
+
+    /// Flips the grid over the diagonal from the top right to the bottom
+    /// left, the last of the eight symmetries of the square with the
+    /// rotations, the mirrors and `transposed`.
+    pub fn anti_transposed(&self) -> Sudoku {
+        self.remap(|x, y| (8 - y, 8 - x))
+    }
+}
+
+/// Checks that `a` and `b` are below `n`, `cell` giving the first cell of
+/// the line or band for the error.
+fn within(
+    a: usize,
+    b: usize,
+    n: usize,
+    cell: impl Fn(usize) -> (usize, usize),
+) -> Result<(), Error> {
+    match [a, b].iter().find(|&&i| i >= n) {
+        Some(&i) => {
+            let (row, col) = cell(i);
+            Err(Error::OutOfBounds { row, col })
+        }
+        None => Ok(()),
+    }
+}
+
+/// Checks that lines `a` and `b` are in the same band or stack.
+fn same_band(a: usize, b: usize) -> Result<(), Error> {
+    if a / 3 != b / 3 {
+        return Err(Error::DifferentBands { a, b });
+    }
+    Ok(())
+}
+
+/// `i` with `a` and `b` exchanged.
+fn swap(i: usize, a: usize, b: usize) -> usize {
+    if i == a {
+        b
+    } else if i == b {
+        a
+    } else {
+        i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform() {
+        let sudoku: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        let rotated = sudoku.rotated(Quarter::Cw90);
+        assert!(rotated.valid());
+        assert_eq!(rotated.to_string().lines().next(), Some("329748165"));
+        assert_eq!(
+            rotated.rotated(Quarter::Cw270).to_string(),
+            sudoku.to_string()
+        );
+        assert_eq!(
+            rotated.rotated(Quarter::Cw90).to_string(),
+            sudoku.rotated(Quarter::Cw180).to_string()
+        );
+        let mirrored = sudoku.mirrored(Axis::Horizontal);
+        assert_eq!(mirrored.to_string().lines().next(), Some("345286179"));
+        let mirrored = sudoku.mirrored(Axis::Vertical);
+        assert_eq!(mirrored.to_string().lines().next(), Some("219876435"));
+        assert_eq!(
+            sudoku.transposed().to_string(),
+            sudoku
+                .rotated(Quarter::Cw90)
+                .mirrored(Axis::Vertical)
+                .to_string()
+        );
+        let anti = sudoku.anti_transposed();
+        assert!(anti.valid());
+        assert_eq!(anti.get(0, 0), sudoku.get(8, 8));
+        assert_eq!(
+            anti.to_string(),
+            sudoku.transposed().rotated(Quarter::Cw180).to_string()
+        );
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let sudoku: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        let line = sudoku.to_line();
+        let rotated = sudoku.rotated(Quarter::Cw90);
+        assert_ne!(rotated.to_line(), line);
+        assert_eq!(rotated.get(0, 8), sudoku.get(0, 0));
+        let full = rotated
+            .rotated(Quarter::Cw90)
+            .rotated(Quarter::Cw90)
+            .rotated(Quarter::Cw90);
+        assert_eq!(full.to_line(), line);
+        assert_eq!(sudoku.transposed().get(1, 0), sudoku.get(0, 1));
+        assert_eq!(sudoku.transposed().transposed().to_line(), line);
+        assert_eq!(
+            sudoku.mirrored(Axis::Horizontal).get(8, 0),
+            sudoku.get(0, 0)
+        );
+        assert_eq!(
+            sudoku
+                .mirrored(Axis::Horizontal)
+                .mirrored(Axis::Horizontal)
+                .to_line(),
+            line
+        );
+        assert_eq!(sudoku.mirrored(Axis::Vertical).get(0, 8), sudoku.get(0, 0));
+        assert_eq!(
+            sudoku
+                .mirrored(Axis::Vertical)
+                .mirrored(Axis::Vertical)
+                .to_line(),
+            line
+        );
+        assert_eq!(
+            sudoku.transposed().mirrored(Axis::Vertical).to_line(),
+            rotated.to_line()
+        );
+        for transformed in [
+            rotated,
+            sudoku.transposed(),
+            sudoku.mirrored(Axis::Horizontal),
+            sudoku.mirrored(Axis::Vertical),
+        ] {
+            assert!(transformed.has_unique_solution());
+        }
+    }
+
+    #[test]
+    fn test_permutations() {
+        let sudoku: Sudoku = "534678912\n\
+             672195348\n\
+             198342567\n\
+             859761423\n\
+             426853791\n\
+             713924856\n\
+             961537284\n\
+             287419635\n\
+             345286179"
+            .parse()
+            .unwrap();
+        let rows = sudoku.rows_swapped(0, 2).unwrap();
+        assert!(rows.valid());
+        assert_eq!(rows.to_string().lines().next(), Some("198342567"));
+        let cols = sudoku.cols_swapped(3, 5).unwrap();
+        assert!(cols.valid());
+        assert_eq!(cols.to_string().lines().next(), Some("534876912"));
+        let bands = sudoku.bands_swapped(0, 2).unwrap();
+        assert_eq!(bands.to_string().lines().next(), Some("961537284"));
+        let stacks = sudoku.stacks_swapped(1, 2).unwrap();
+        assert!(stacks.valid());
+        assert_eq!(stacks.to_string().lines().next(), Some("534912678"));
+        assert!(matches!(
+            sudoku.rows_swapped(2, 3),
+            Err(Error::DifferentBands { a: 2, b: 3 })
+        ));
+        assert!(matches!(
+            sudoku.cols_swapped(8, 9),
+            Err(Error::OutOfBounds { row: 0, col: 9 })
+        ));
+        assert!(matches!(
+            sudoku.stacks_swapped(0, 3),
+            Err(Error::OutOfBounds { row: 0, col: 9 })
+        ));
+
+        let scrambled = sudoku.scrambled(7);
+        assert!(scrambled.valid());
+        assert_eq!(scrambled.to_line(), sudoku.scrambled(7).to_line());
+        assert_ne!(scrambled.to_line(), sudoku.scrambled(8).to_line());
+    }
+
+    #[test]
+    fn test_relabel() {
+        assert!(matches!(
+            DigitPermutation::new([1, 1, 3, 4, 5, 6, 7, 8, 9]),
+            Err(Error::InvalidPermutation)
+        ));
+        assert!(DigitPermutation::new([0, 2, 3, 4, 5, 6, 7, 8, 9]).is_err());
+        let perm = DigitPermutation::new([2, 3, 1, 4, 5, 6, 7, 8, 9]).unwrap();
+        assert_eq!(perm.inverse().inverse(), perm);
+
+        let mut sudoku = Sudoku::new();
+        sudoku.get_mut(0, 0).unwrap().set(Value::new(1).unwrap());
+        sudoku.get_mut(0, 1).unwrap().remove(Value::new(2).unwrap());
+        let relabeled = sudoku.relabel(&perm);
+        assert_eq!(relabeled.get(0, 0).unwrap().value(), Value::new(2).ok());
+        assert!(!relabeled
+            .get(0, 1)
+            .unwrap()
+            .contains(Value::new(3).unwrap()));
+        let restored = relabeled.relabel(&perm.inverse());
+        assert_eq!(restored.to_string(), sudoku.to_string());
+        assert_eq!(restored.get(0, 1), sudoku.get(0, 1));
+    }
+}
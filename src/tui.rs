@@ -0,0 +1,356 @@
+//! A game in the terminal: move around the grid with the arrow keys or
+//! `hjkl`, enter digits, toggle pencil marks with `n`, undo and redo with
+//! `u` and `r`, ask for a hint with `?` and quit with `q`. Conflicting
+//! cells are highlighted as they are entered.
+use crate::game::Game;
+use crate::{Coord, Error, Sudoku, Value};
+use std::io::{Read, Write};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Digit(u8),
+    Clear,
+    /// Switches between entering digits and pencil marks.
+    Notes,
+    Undo,
+    Redo,
+    Hint,
+    Quit,
+}
+
+/// The keys of a read from the terminal, unknown ones dropped.
+fn keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let key = match bytes[i] {
+            0x1b if bytes.get(i + 1) == Some(&b'[') && i + 2 < bytes.len() => {
+                i += 2;
+                match bytes[i] {
+                    b'A' => Some(Key::Up),
+                    b'B' => Some(Key::Down),
+                    b'C' => Some(Key::Right),
+                    b'D' => Some(Key::Left),
+                    // delete is `ESC [ 3 ~`
+                    b'3' if bytes.get(i + 1) == Some(&b'~') => {
+                        i += 1;
+                        Some(Key::Clear)
+                    }
+                    _ => None,
+                }
+            }
+            b'k' => Some(Key::Up),
+            b'j' => Some(Key::Down),
+            b'l' => Some(Key::Right),
+            b'h' => Some(Key::Left),
+            b @ b'1'..=b'9' => Some(Key::Digit(b - b'0')),
+            b'0' | b'.' | b' ' | 0x7f | 0x08 => Some(Key::Clear),
+            b'n' => Some(Key::Notes),
+            b'u' => Some(Key::Undo),
+            b'r' => Some(Key::Redo),
+            b'?' => Some(Key::Hint),
+            // ctrl-c arrives as a byte in raw mode
+            b'q' | 0x03 => Some(Key::Quit),
+            _ => None,
+        };
+        keys.extend(key);
+        i += 1;
+    }
+    keys
+}
+
+/// A game with the state of the screen.
+struct Play {
+    game: Game,
+    cursor: Coord,
+    noting: bool,
+    /// Cells of the last hint.
+    hint: Vec<Coord>,
+    message: String,
+}
+
+impl Play {
+    fn new(puzzle: &Sudoku) -> Self {
+        Self {
+            game: Game::new(*puzzle),
+            cursor: Coord::new(0, 0),
+            noting: false,
+            hint: Vec::new(),
+            message: String::new(),
+        }
+    }
+
+    /// The givens and the entries.
+    fn grid(&self) -> Sudoku {
+        *self.game.grid().sudoku()
+    }
+
+    /// Whether each cell has the value of a cell it sees.
+    fn conflicts(&self) -> [bool; 81] {
+        let mut conflicts = [false; 81];
+        for (a, b) in self.grid().conflicts() {
+            conflicts[a.index()] = true;
+            conflicts[b.index()] = true;
+        }
+        conflicts
+    }
+
+    /// Applies a key, `false` once the game is over.
+    fn handle(&mut self, key: Key) -> bool {
+        self.hint.clear();
+        self.message.clear();
+        let (row, col) = (self.cursor.row(), self.cursor.col());
+        let coord = self.cursor;
+        let given = self.game.is_given(coord);
+        match key {
+            Key::Up => self.cursor = Coord::new((row + 8) % 9, col),
+            Key::Down => self.cursor = Coord::new((row + 1) % 9, col),
+            Key::Left => self.cursor = Coord::new(row, (col + 8) % 9),
+            Key::Right => self.cursor = Coord::new(row, (col + 1) % 9),
+            Key::Notes => self.noting = !self.noting,
+            Key::Digit(_) | Key::Clear if given => self.message = "That's a given.".into(),
+            Key::Digit(digit) => {
+                let value = Value::new(digit).expect("keys are digits from 1 to 9; qed");
+                if self.noting {
+                    self.game.toggle_note(coord, value);
+                } else {
+                    self.game.place(coord, value);
+                }
+            }
+            // a digit first, then the marks of the empty cell
+            Key::Clear if self.grid()[coord].is_final() => {
+                self.game.clear(coord);
+            }
+            Key::Clear => {
+                for value in self.game.grid().notes(coord) {
+                    self.game.toggle_note(coord, value);
+                }
+            }
+            Key::Undo if !self.game.undo() => self.message = "Nothing to undo.".into(),
+            Key::Redo if !self.game.redo() => self.message = "Nothing to redo.".into(),
+            Key::Undo | Key::Redo => {}
+            Key::Hint if self.conflicts().contains(&true) => {
+                self.message = "Fix the conflicts first.".into()
+            }
+            Key::Hint => match self.grid().next_hint() {
+                Some(hint) => {
+                    self.hint = hint.cells.clone();
+                    self.hint
+                        .extend(hint.placements.iter().map(|&(coord, _)| coord));
+                    self.message = hint.explanation;
+                }
+                None => self.message = "No hint available.".into(),
+            },
+            Key::Quit => return false,
+        }
+        if matches!(key, Key::Digit(_) | Key::Redo) && self.game.is_solved() {
+            self.message = "Solved!".into();
+        }
+        true
+    }
+
+    /// The screen, lines ending in `\r\n` for a terminal in raw mode. Cells
+    /// are three lines of three characters to fit the pencil marks.
+    fn render(&self) -> String {
+        let conflicts = self.conflicts();
+        let border = |left: &str, middle: &str, right: &str| {
+            let line = vec!["─".repeat(13); 3].join(middle);
+            format!("{}{}{}\r\n", left, line, right)
+        };
+        let mut out = String::from("\x1b[H\x1b[2J");
+        out.push_str(&border("┌", "┬", "┐"));
+        for row in 0..9 {
+            for line in 0..3 {
+                out.push('│');
+                for col in 0..9 {
+                    let coord = Coord::new(row, col);
+                    let i = coord.index();
+                    let value = self.grid()[coord].value();
+                    let mut style = Vec::new();
+                    if self.game.is_given(coord) {
+                        style.push("1");
+                    } else if value.is_some() {
+                        style.push("36");
+                    } else {
+                        style.push("2");
+                    }
+                    if conflicts[i] {
+                        style.push("31");
+                    }
+                    if self.hint.contains(&coord) {
+                        style.push("43");
+                    }
+                    if coord == self.cursor {
+                        style.push("7");
+                    }
+                    let text: String = match value {
+                        Some(value) if line == 1 => format!(" {} ", value),
+                        Some(_) => "   ".into(),
+                        None => (1..=3)
+                            .map(|k| {
+                                let value = Value::new(line as u8 * 3 + k).unwrap();
+                                if self.game.grid().notes(coord).contains(value) {
+                                    char::from(b'0' + line as u8 * 3 + k)
+                                } else {
+                                    ' '
+                                }
+                            })
+                            .collect(),
+                    };
+                    out.push_str(&format!(" \x1b[{}m{}\x1b[0m", style.join(";"), text));
+                    out.push_str(if col % 3 == 2 { " │" } else { "" });
+                }
+                out.push_str("\r\n");
+            }
+            if row == 8 {
+                out.push_str(&border("└", "┴", "┘"));
+            } else if row % 3 == 2 {
+                out.push_str(&border("├", "┼", "┤"));
+            }
+        }
+        let mode = if self.noting {
+            "pencil marks"
+        } else {
+            "digits"
+        };
+        out.push_str(&format!(
+            "entering {}, n to switch, u/r to undo/redo, ? for a hint, q to quit\r\n",
+            mode
+        ));
+        out.push_str(&self.message);
+        out
+    }
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped.
+struct Terminal {
+    original: libc::termios,
+}
+
+impl Terminal {
+    fn raw() -> Result<Self, Error> {
+        let mut original = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        print!("\x1b[?1049h\x1b[?25l");
+        Ok(Self { original })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Plays the puzzle on the terminal until the player quits. Returns the
+/// grid as left, the givens with the digits entered.
+pub fn play(puzzle: &Sudoku) -> Result<Sudoku, Error> {
+    let terminal = Terminal::raw()?;
+    let mut play = Play::new(puzzle);
+    let mut stdout = std::io::stdout();
+    let mut buf = [0; 32];
+    'game: loop {
+        stdout.write_all(play.render().as_bytes())?;
+        stdout.flush()?;
+        let read = std::io::stdin().read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        for key in keys(&buf[..read]) {
+            if !play.handle(key) {
+                break 'game;
+            }
+        }
+    }
+    drop(terminal);
+    Ok(play.grid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    #[test]
+    fn test_keys() {
+        assert_eq!(
+            keys(b"\x1b[A\x1b[Dj5\x1b[3~nur?x\x1bq"),
+            vec![
+                Key::Up,
+                Key::Left,
+                Key::Down,
+                Key::Digit(5),
+                Key::Clear,
+                Key::Notes,
+                Key::Undo,
+                Key::Redo,
+                Key::Hint,
+                Key::Quit
+            ]
+        );
+    }
+
+    #[test]
+    fn test_play() {
+        let puzzle: Sudoku = LINE.parse().unwrap();
+        let mut play = Play::new(&puzzle);
+        let corner = Coord::new(0, 0);
+        assert!(play.handle(Key::Digit(5)));
+        assert_eq!(play.grid()[corner].value(), Value::new(5).ok());
+        // the 3 in the first row
+        assert!(play.handle(Key::Digit(3)));
+        let conflicts = play.conflicts();
+        assert!(conflicts[0] && conflicts[2]);
+        assert!(play.handle(Key::Hint));
+        assert_eq!(play.message, "Fix the conflicts first.");
+
+        play.handle(Key::Clear);
+        play.handle(Key::Notes);
+        play.handle(Key::Digit(4));
+        play.handle(Key::Digit(8));
+        assert_eq!(play.grid()[corner].value(), None);
+        assert!(play.render().contains(" \x1b[2;7m4  \x1b[0m"));
+        play.handle(Key::Undo);
+        assert_eq!(play.game.grid().notes(corner).len(), 1);
+        play.handle(Key::Redo);
+        play.handle(Key::Clear);
+        assert!(play.game.grid().notes(corner).is_empty());
+        play.handle(Key::Right);
+        play.handle(Key::Right);
+        play.handle(Key::Digit(1));
+        assert_eq!(play.message, "That's a given.");
+        play.handle(Key::Hint);
+        assert!(!play.hint.is_empty());
+
+        let solution = crate::solve(&puzzle).unwrap();
+        let mut play = Play::new(&puzzle);
+        for i in 0..81 {
+            if !puzzle.0[i].is_final() {
+                play.cursor = Coord::new(i / 9, i % 9);
+                let value = solution.get(i / 9, i % 9).unwrap().value().unwrap();
+                play.handle(Key::Digit(value.0 + 1));
+            }
+        }
+        assert_eq!(play.message, "Solved!");
+        play.handle(Key::Undo);
+        assert!(!play.game.is_solved());
+        play.handle(Key::Redo);
+        assert_eq!(play.message, "Solved!");
+        assert_eq!(play.grid().to_line(), solution.to_line());
+    }
+}
@@ -0,0 +1,107 @@
+//! Grids whose properties have been checked once, so that they don't have
+//! to be checked again.
+#[cfg(feature = "std")]
+use crate::solver::{solve_unique, SolveUniqueError};
+use crate::Sudoku;
+
+/// A complete grid without conflicts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SolvedSudoku(Sudoku);
+
+impl SolvedSudoku {
+    /// Returns `None` unless every cell has a value and no unit repeats one.
+    pub fn new(sudoku: Sudoku) -> Option<Self> {
+        if sudoku.valid() {
+            Some(Self(sudoku))
+        } else {
+            None
+        }
+    }
+
+    /// Wraps a grid found by the solver, which only places legal values.
+    pub(crate) fn from_solver(sudoku: Sudoku) -> Self {
+        debug_assert!(sudoku.valid());
+        Self(sudoku)
+    }
+
+    pub fn as_sudoku(&self) -> &Sudoku {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Sudoku {
+        self.0
+    }
+}
+
+impl core::ops::Deref for SolvedSudoku {
+    type Target = Sudoku;
+
+    fn deref(&self) -> &Sudoku {
+        &self.0
+    }
+}
+
+impl core::fmt::Display for SolvedSudoku {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A puzzle with exactly one solution.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ProperPuzzle {
+    puzzle: Sudoku,
+    solution: SolvedSudoku,
+}
+
+#[cfg(feature = "std")]
+impl ProperPuzzle {
+    pub fn new(puzzle: Sudoku) -> Result<Self, SolveUniqueError> {
+        let solution = solve_unique(&puzzle)?;
+        Ok(Self { puzzle, solution })
+    }
+
+    pub fn as_sudoku(&self) -> &Sudoku {
+        &self.puzzle
+    }
+
+    pub fn solution(&self) -> &SolvedSudoku {
+        &self.solution
+    }
+
+    pub fn into_inner(self) -> Sudoku {
+        self.puzzle
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verified() {
+        let puzzle: Sudoku = "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79"
+            .replace('.', " ")
+            .parse()
+            .unwrap();
+        assert!(SolvedSudoku::new(puzzle).is_none());
+        let proper = ProperPuzzle::new(puzzle).unwrap();
+        assert_eq!(proper.as_sudoku().to_string(), puzzle.to_string());
+        let solution = proper.solution().into_inner();
+        assert!(SolvedSudoku::new(solution).is_some());
+
+        assert!(matches!(
+            ProperPuzzle::new(Sudoku::new()),
+            Err(SolveUniqueError::MultipleSolutions(_, _))
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+//! Bindings for running in a browser, built for `wasm32-unknown-unknown`
+//! and wrapped into a JavaScript module by `wasm-bindgen`:
+//!
+//! ```text
+//! cargo rustc --release --features wasm --target wasm32-unknown-unknown --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/sudoku.wasm
+//! ```
+//!
+//! Puzzles are passed as strings in any format `Sudoku` parses and come
+//! back as 81 characters on a line. Errors are thrown as `Error`s.
+use crate::rating::Bucket;
+use crate::{generate_with, Coord, GenerateOptions, Sudoku, Value};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Serialize)]
+struct RateResult {
+    tier: &'static str,
+    score: f32,
+    hardest: Option<&'static str>,
+    steps: usize,
+    solved: bool,
+    clues: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct HintResult {
+    technique: &'static str,
+    explanation: String,
+    cells: Vec<String>,
+    placements: Vec<(String, u8)>,
+    eliminations: Vec<(String, u8)>,
+}
+
+/// Normalizes a puzzle to a line.
+#[wasm_bindgen]
+pub fn parse(puzzle: &str) -> Result<String, JsError> {
+    Ok(puzzle.trim().parse::<Sudoku>()?.to_line())
+}
+
+/// The solution of a puzzle.
+#[wasm_bindgen]
+pub fn solve(puzzle: &str) -> Result<String, JsError> {
+    match crate::solve(&puzzle.trim().parse()?) {
+        Some(solution) => Ok(solution.to_line()),
+        None => Err(JsError::new("no solution")),
+    }
+}
+
+/// A new puzzle of a difficulty from `"beginner"` to `"diabolical"`,
+/// random unless a `seed` is passed, as a `BigInt`.
+#[wasm_bindgen]
+pub fn generate(difficulty: &str, seed: Option<u64>) -> Result<String, JsError> {
+    let bucket: Bucket = difficulty.parse()?;
+    let options = GenerateOptions {
+        seed,
+        ..GenerateOptions::default()
+    };
+    Ok(generate_with(bucket, &options).to_line())
+}
+
+/// The rating of a puzzle as `{tier, score, hardest, steps, solved,
+/// clues}`, `hardest` the id of the technique or `null`.
+#[wasm_bindgen]
+pub fn rate(puzzle: &str) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(&rating(
+        &puzzle.trim().parse()?,
+    ))?)
+}
+
+/// The next step as `{technique, explanation, cells, placements,
+/// eliminations}` with cells like `"r1c2"` and the others as pairs of a
+/// cell and a digit, or `null` if there is none.
+#[wasm_bindgen]
+pub fn hint(puzzle: &str) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(&next_hint(
+        &puzzle.trim().parse()?,
+    ))?)
+}
+
+fn rating(sudoku: &Sudoku) -> RateResult {
+    let rating = sudoku.rate();
+    RateResult {
+        tier: rating.tier.name(),
+        score: rating.score,
+        hardest: rating.hardest.map(|technique| technique.id()),
+        steps: rating.steps,
+        solved: rating.solved,
+        clues: sudoku.into_iter().count(),
+    }
+}
+
+fn next_hint(sudoku: &Sudoku) -> Option<HintResult> {
+    let digits = |pairs: &[(Coord, Value)]| {
+        pairs
+            .iter()
+            .map(|(coord, value)| (coord.to_string(), value.0 + 1))
+            .collect()
+    };
+    sudoku.next_hint().map(|hint| HintResult {
+        technique: hint.technique.id(),
+        cells: hint.cells.iter().map(Coord::to_string).collect(),
+        placements: digits(&hint.placements),
+        eliminations: digits(&hint.eliminations),
+        explanation: hint.explanation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    #[test]
+    fn test_exports() {
+        assert!(solve(LINE).unwrap().starts_with("483921657"));
+        assert_eq!(parse(&LINE.replace('.', "0")).unwrap(), LINE);
+        assert_eq!(generate("easy", Some(42)).unwrap().len(), 81);
+        assert_eq!(
+            generate("easy", Some(42)).unwrap(),
+            generate("easy", Some(42)).unwrap()
+        );
+
+        let sudoku: Sudoku = LINE.parse().unwrap();
+        let rating = rating(&sudoku);
+        assert_eq!(rating.tier, "easy");
+        assert_eq!(rating.clues, 32);
+        let hint = next_hint(&sudoku).unwrap();
+        assert!(hint.placements[0].0.starts_with('r'), "{:?}", hint);
+        assert!(next_hint(&crate::solve(&sudoku).unwrap()).is_none());
+    }
+}